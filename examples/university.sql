@@ -0,0 +1,10 @@
+-- the university data as relational tables, build the database with:
+--     sqlite3 university_data.db < university.sql
+CREATE TABLE staff (name TEXT PRIMARY KEY, position TEXT NOT NULL);
+INSERT INTO staff VALUES ('John', 'professor'), ('Mary', 'professor'), ('Ava', 'student');
+
+CREATE TABLE lecture (professor TEXT NOT NULL, course TEXT NOT NULL);
+INSERT INTO lecture VALUES ('John', 'DB2'), ('Mary', 'AI');
+
+CREATE TABLE enrolment (student TEXT NOT NULL, course TEXT NOT NULL);
+INSERT INTO enrolment VALUES ('Ava', 'DB2'), ('Bob', 'AI'), ('Mary', 'DB2');
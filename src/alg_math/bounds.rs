@@ -53,12 +53,13 @@ fn find_bound_complex_switcher(
     tolerance: f64,
     m_scale: f64,
     _b_translate: f64,
+    method: Method,
     use_concurrency: bool,
 ) -> Option<f64> {
     if use_concurrency {
-        find_bound_complex_concurrency(matrix, tolerance, m_scale, _b_translate)
+        find_bound_complex_concurrency(matrix, tolerance, m_scale, _b_translate, method)
     } else {
-        find_bound_complex_linear(matrix, tolerance, m_scale, _b_translate)
+        find_bound_complex_linear(matrix, tolerance, m_scale, _b_translate, method)
     }
 }
 
@@ -67,6 +68,7 @@ fn find_bound_complex_concurrency(
     tolerance: f64,
     m_scale: f64,
     _b_translate: f64,
+    method: Method,
 ) -> Option<f64> {
     // I love to keep values these values such that I can stop calling for them
     let rows = matrix.nrows();
@@ -269,7 +271,7 @@ fn find_bound_complex_concurrency(
                 &mut polynomial,
                 polynomial_length,
                 tolerance,
-                Method::CauchyCubic,
+                method,
             );
 
             // println!("    bound found is: {}", bound_found);
@@ -303,6 +305,7 @@ fn find_bound_complex_linear(
     tolerance: f64,
     m_scale: f64,
     _b_translate: f64,
+    method: Method,
 ) -> Option<f64> {
     // I love to keep values these values such that I can stop calling for them
     let rows = matrix.nrows();
@@ -510,7 +513,7 @@ fn find_bound_complex_linear(
                     &mut polynomial,
                     polynomial_length,
                     tolerance,
-                    Method::CauchyCubic,
+                    method,
                 );
 
                 /*
@@ -574,6 +577,7 @@ pub fn find_bound_complex_wrapper(
     tolerance: f64,
     m_scale: f64,
     b_translate: f64,
+    method: Method,
     use_concurrency: bool,
 ) -> Option<f64> {
     let nsquared = v.len();
@@ -600,6 +604,7 @@ pub fn find_bound_complex_wrapper(
             tolerance,
             m_scale,
             b_translate,
+            method,
             use_concurrency,
         )
     }
@@ -607,3 +612,15 @@ pub fn find_bound_complex_wrapper(
 
 // type for the numeric adjusters
 pub type Adjusters = (f64, f64, f64);
+
+/// how the bound for stabilized results is found: interpolating polynomials with the Fast
+/// Fourier Transform and bounding their roots, or from the spectral radius of the matrix
+/// (see 'spectral.rs')
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BoundAlgorithm {
+    Interpolation,
+    Spectral,
+}
+
+// the adjusters, the method used to bound the roots of the polynomials and the algorithm
+pub type BoundSettings = (Adjusters, Method, BoundAlgorithm);
@@ -25,8 +25,6 @@ use std::ops::Deref;
 
 use crate::alg_math::interface::{DataHolder, DataItem, Oracle};
 
-use rustoner::kb::knowledge_base::AggrFn;
-
 /// Building the conflict matrix depend on three structures:
 ///     - a Filter, that iterates over each subset of a given
 ///       dataholder in an increasing size way
@@ -324,6 +322,7 @@ impl Builder {
     }
 
     pub fn build_matrix<
+        F: FnMut(Vec<f64>) -> f64,
         DI: DataItem,
         DH: DataHolder + DataHolder<DI = DI>,
         O: Oracle + Oracle<DH = DH>,
@@ -332,7 +331,7 @@ impl Builder {
         dh: &DH,
         ora: &O,
         credibility_vector: &[f64],
-        aggf: &AggrFn,
+        aggf: &mut F,
         conflict_limit: Option<usize>,
     ) -> Vec<f64> {
         /*
@@ -379,6 +378,7 @@ impl Builder {
     //       optimization: see the condition that allow for deduction of I values
     //                     before computation
     pub fn build_values<
+        F: FnMut(Vec<f64>) -> f64,
         DI: DataItem,
         DH: DataHolder + DataHolder<DI = DI>,
         O: Oracle + Oracle<DH = DH>,
@@ -387,7 +387,7 @@ impl Builder {
         dh: &DH,
         oracle: &O,
         credibility_vector: &[f64],
-        aggf: &AggrFn,
+        aggf: &mut F,
         conflict_limit: Option<usize>,
     ) {
         // initialize or reinitialize the builder struct
@@ -495,7 +495,7 @@ impl Builder {
                                         (_, _) => {
                                             // find the value of B
                                             let aggf_b = compute_aggregation_from_filter(
-                                                &aggf,
+                                                aggf,
                                                 credibility_vector,
                                                 &filter,
                                             );
@@ -538,8 +538,8 @@ impl Builder {
 
 // this function is not really associated to an specific struct, but to all, thus better
 // to make it independent
-pub fn compute_aggregation_from_filter(
-    aggf: &AggrFn,
+pub fn compute_aggregation_from_filter<F: FnMut(Vec<f64>) -> f64>(
+    aggf: &mut F,
     credibility_vector: &[f64],
     filter: &[bool],
 ) -> f64 {
@@ -26,4 +26,6 @@ pub mod bounds;
 pub mod interface;
 pub mod matrix_building;
 pub mod polynomial_roots;
+pub mod rational;
+pub mod spectral;
 pub mod utilities;
@@ -1,13 +1,34 @@
-use roots::{find_roots_cubic, find_roots_quartic, Roots};
+use roots::{find_roots_cubic, Roots};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Method {
     CauchyOriginal,
     CauchySquare,
     CauchyCubic,
     CauchyQuad,
+    Fujiwara,
+    Lagrange,
+    Kojima,
+    LagrangeZassenhaus,
+    // every method is computed and the tightest valid bound is kept
+    Auto,
 }
 
+/// every method that computes a bound by itself, that is everything but 'Auto'
+pub const BOUND_METHODS: [Method; 8] = [
+    Method::CauchyOriginal,
+    Method::CauchySquare,
+    Method::CauchyCubic,
+    Method::CauchyQuad,
+    Method::Fujiwara,
+    Method::Lagrange,
+    Method::Kojima,
+    Method::LagrangeZassenhaus,
+];
+
+// the largest real root of a quartic is bisected at most this many times
+const QUARTIC_BISECTIONS: usize = 200;
+
 // pub const PRINCIPAL_CUBIC_ROOT: (f64, f64) = (-0.5_f64, 0.5_f64 * 1.73205080757_f64);
 
 /*
@@ -78,9 +99,14 @@ pub fn find_bound_on_polynomial_roots(
             */
 
             // if the polynomial has degree 3 or less we use original cauchy regardless of the
-            // method chosen
+            // method chosen, the other Cauchy methods look at the last three coefficients
+
+            let is_cauchy = matches!(
+                method,
+                Method::CauchySquare | Method::CauchyCubic | Method::CauchyQuad
+            );
 
-            if real_degree < 4 {
+            if real_degree < 4 && is_cauchy {
                 return find_bound_on_polynomial_roots_cauchy_original(
                     polynomial,
                     max_coeff,
@@ -88,7 +114,7 @@ pub fn find_bound_on_polynomial_roots(
                 );
             }
 
-            match method {
+            let bound = match method {
                 Method::CauchyOriginal => find_bound_on_polynomial_roots_cauchy_original(
                     polynomial,
                     max_coeff,
@@ -105,6 +131,33 @@ pub fn find_bound_on_polynomial_roots(
                     max_coeff,
                     real_degree,
                 ),
+                Method::Fujiwara => {
+                    find_bound_on_polynomial_roots_fujiwara(polynomial, max_coeff, real_degree)
+                }
+                Method::Lagrange => {
+                    find_bound_on_polynomial_roots_lagrange(polynomial, max_coeff, real_degree)
+                }
+                Method::Kojima => {
+                    find_bound_on_polynomial_roots_kojima(polynomial, max_coeff, real_degree)
+                }
+                Method::LagrangeZassenhaus => find_bound_on_polynomial_roots_lagrange_zassenhaus(
+                    polynomial,
+                    max_coeff,
+                    real_degree,
+                ),
+                Method::Auto => find_bound_on_polynomial_roots_auto(
+                    polynomial,
+                    max_coeff,
+                    real_degree,
+                    tolerance,
+                ),
+            };
+
+            // Kojima can't always give a bound, Cauchy always does
+            if bound.is_finite() {
+                bound
+            } else {
+                find_bound_on_polynomial_roots_cauchy_original(polynomial, max_coeff, real_degree)
             }
         }
     }
@@ -218,14 +271,267 @@ pub fn find_bound_on_polynomial_roots_cauchy_quadratic(
     let b = 3_f64 - a_n1;
     let c = 3_f64 - 2_f64 * a_n1 - a_n2;
     let d = 1_f64 - a_n1 - a_n2 - a_n3;
-    let r = find_roots_quartic(a, b, c, d, -bound);
 
-    1_f64
-        + (match r {
-            Roots::One(root) => root[0],
-            Roots::Two(root) => (root[0].max(root[1])),
-            Roots::Three(root) => (root[0].max(root[1].max(root[2]))),
-            Roots::Four(root) => (root[0].max(root[1].max(root[2].max(root[3])))),
-            _ => 0_f64,
+    1_f64 + largest_real_root_quartic(a, b, c, d, -bound)
+}
+
+// the largest real root of a x^4 + b x^3 + c x^2 + d x + e, 0 if there is none
+// ('roots::find_roots_quartic' prints its intermediate values to stderr and loses the root
+// when e is large), the quartic is monotonic between the roots of its derivative, a sign
+// change between two of them is bisected, from the right
+fn largest_real_root_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> f64 {
+    let value = |x: f64| (((a * x + b) * x + c) * x + d) * x + e;
+
+    // no root past the Cauchy bound
+    let limit = 1_f64
+        + [b, c, d, e]
+            .iter()
+            .map(|coeff| (coeff / a).abs())
+            .fold(0_f64, f64::max);
+
+    let mut ends: Vec<f64> = find_roots_cubic(4_f64 * a, 3_f64 * b, 2_f64 * c, d)
+        .as_ref()
+        .iter()
+        .cloned()
+        .filter(|x| x.abs() < limit)
+        .collect();
+    ends.push(limit);
+    ends.push(-limit);
+    ends.sort_by(|x, y| y.partial_cmp(x).unwrap_or(std::cmp::Ordering::Equal));
+
+    for window in ends.windows(2) {
+        let (mut high, mut low) = (window[0], window[1]);
+        let high_is_positive = value(high) >= 0_f64;
+
+        if value(low) == 0_f64 {
+            return low;
+        }
+
+        if high_is_positive == (value(low) >= 0_f64) {
+            continue;
+        }
+
+        for _ in 0..QUARTIC_BISECTIONS {
+            let middle = 0.5 * (low + high);
+
+            if middle <= low || middle >= high {
+                break;
+            }
+
+            if (value(middle) >= 0_f64) == high_is_positive {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+
+        return 0.5 * (low + high);
+    }
+
+    0_f64
+}
+
+/*
+   the following bounds are classical bounds on the modulus of the roots, here applied to
+   q(x) = a_n x^n - sum of |a_i| x^i over the negative a_i, for x > 0 we have p(x) >= q(x)
+   and q has only one positive root (one change of sign), past it q and thus p are positive
+*/
+
+// the terms (|a_i| / a_n)^(1 / (n - i)) for the negative coefficients, a_0 is divided by
+// 'first_divisor' first
+fn negative_coefficient_terms(
+    polynomial: &[f64],
+    max_coeff: f64,
+    real_degree: usize,
+    first_divisor: f64,
+) -> Vec<f64> {
+    polynomial
+        .iter()
+        .take(real_degree)
+        .enumerate()
+        .filter(|(_, coeff)| **coeff < 0_f64)
+        .map(|(i, coeff)| {
+            let divisor = if i == 0 { first_divisor } else { 1_f64 };
+            (-(*coeff) / (max_coeff * divisor)).powf(1_f64 / ((real_degree - i) as f64))
         })
+        .collect()
+}
+
+pub fn find_bound_on_polynomial_roots_fujiwara(
+    polynomial: &[f64],
+    max_coeff: f64,
+    real_degree: usize,
+) -> f64 {
+    // 2 max (|a_i| / a_n)^(1 / (n - i)), with a_0 halved
+    let terms = negative_coefficient_terms(polynomial, max_coeff, real_degree, 2_f64);
+
+    2_f64 * terms.iter().cloned().fold(0_f64, f64::max)
+}
+
+pub fn find_bound_on_polynomial_roots_lagrange(
+    polynomial: &[f64],
+    max_coeff: f64,
+    real_degree: usize,
+) -> f64 {
+    // Lagrange bound for positive roots: the sum of the two largest terms
+    let mut terms = negative_coefficient_terms(polynomial, max_coeff, real_degree, 1_f64);
+    terms.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+    terms.iter().take(2).sum::<f64>()
+}
+
+pub fn find_bound_on_polynomial_roots_lagrange_zassenhaus(
+    polynomial: &[f64],
+    max_coeff: f64,
+    real_degree: usize,
+) -> f64 {
+    // 2 max (|a_i| / a_n)^(1 / (n - i))
+    let terms = negative_coefficient_terms(polynomial, max_coeff, real_degree, 1_f64);
+
+    2_f64 * terms.iter().cloned().fold(0_f64, f64::max)
+}
+
+pub fn find_bound_on_polynomial_roots_kojima(
+    polynomial: &[f64],
+    max_coeff: f64,
+    real_degree: usize,
+) -> f64 {
+    /*
+       Kojima: max(2 |a_(n-1) / a_n|, ..., 2 |a_1 / a_2|, |a_0 / a_1|), it bounds every
+       root of p but needs every coefficient to be non zero, otherwise it is infinite
+    */
+    if polynomial
+        .iter()
+        .take(real_degree)
+        .any(|coeff| *coeff == 0_f64)
+    {
+        return f64::INFINITY;
+    }
+
+    let mut bound = 0_f64;
+
+    for i in 1..=real_degree {
+        let upper = if i == real_degree {
+            max_coeff
+        } else {
+            polynomial[i]
+        };
+        let factor = if i == 1 { 1_f64 } else { 2_f64 };
+
+        bound = bound.max(factor * (polynomial[i - 1] / upper).abs());
+    }
+
+    bound
+}
+
+/*
+   a bound B is valid when p has no root past it, shifting p to p(x + B) this is true when the
+   shifted coefficients don't change sign (Descartes' rule of signs), the converse doesn't hold
+   thus some true bounds can be rejected, but an accepted one is never wrong
+   the shift by B is done on q(y) = p(B y) / (a_n B^n), then by 1: q(y + 1) = p(B y + B) / ...
+   has the same signs, the coefficients stay near 1 instead of growing as B^n and a shifted
+   coefficient is only compared with the rounding error of the sum that made it
+*/
+pub fn is_valid_bound(
+    polynomial: &[f64],
+    max_coeff: f64,
+    real_degree: usize,
+    bound: f64,
+    tolerance: f64,
+) -> bool {
+    if !bound.is_finite() || bound < 0_f64 {
+        return false;
+    }
+
+    if bound == 0_f64 {
+        // p(y) itself, positive past 0 when its coefficients are
+        return polynomial
+            .iter()
+            .take(real_degree)
+            .all(|coeff| *coeff >= -tolerance * max_coeff.abs().max(1_f64));
+    }
+
+    // q_i = a_i B^i / (a_n B^n), from the leading coefficient down
+    let mut scaled: Vec<f64> = vec![0_f64; real_degree + 1];
+    let mut power = 1_f64;
+    scaled[real_degree] = 1_f64;
+
+    for i in (0..real_degree).rev() {
+        power /= bound;
+        scaled[i] = polynomial[i] / max_coeff * power;
+    }
+
+    // Taylor shift by 1, the same additions on the absolute values give the error scale
+    let mut shifted = scaled.clone();
+    let mut magnitudes: Vec<f64> = scaled.iter().map(|coeff| coeff.abs()).collect();
+
+    for i in 0..real_degree {
+        for j in (i..real_degree).rev() {
+            shifted[j] += shifted[j + 1];
+            magnitudes[j] += magnitudes[j + 1];
+        }
+    }
+
+    shifted
+        .iter()
+        .zip(magnitudes.iter())
+        .all(|(coeff, magnitude)| *coeff >= -tolerance * magnitude)
+}
+
+pub fn find_bound_on_polynomial_roots_auto(
+    polynomial: &[f64],
+    max_coeff: f64,
+    real_degree: usize,
+    tolerance: f64,
+) -> f64 {
+    // the Cauchy bound is always valid, as is the default method (Cauchy cubic), the
+    // tightest of both is kept when nothing tighter can be verified
+    let mut best =
+        find_bound_on_polynomial_roots_cauchy_original(polynomial, max_coeff, real_degree);
+
+    if real_degree >= 4 {
+        best = best.min(find_bound_on_polynomial_roots_cauchy_cubic(
+            polynomial,
+            max_coeff,
+            real_degree,
+        ));
+    }
+
+    for method in BOUND_METHODS.iter() {
+        let bound = match method {
+            Method::CauchySquare | Method::CauchyCubic | Method::CauchyQuad if real_degree < 4 => {
+                continue
+            }
+            Method::CauchySquare => {
+                find_bound_on_polynomial_roots_cauchy_square(polynomial, max_coeff, real_degree)
+            }
+            Method::CauchyCubic => {
+                find_bound_on_polynomial_roots_cauchy_cubic(polynomial, max_coeff, real_degree)
+            }
+            Method::CauchyQuad => {
+                find_bound_on_polynomial_roots_cauchy_quadratic(polynomial, max_coeff, real_degree)
+            }
+            Method::Fujiwara => {
+                find_bound_on_polynomial_roots_fujiwara(polynomial, max_coeff, real_degree)
+            }
+            Method::Lagrange => {
+                find_bound_on_polynomial_roots_lagrange(polynomial, max_coeff, real_degree)
+            }
+            Method::Kojima => {
+                find_bound_on_polynomial_roots_kojima(polynomial, max_coeff, real_degree)
+            }
+            Method::LagrangeZassenhaus => find_bound_on_polynomial_roots_lagrange_zassenhaus(
+                polynomial,
+                max_coeff,
+                real_degree,
+            ),
+            Method::CauchyOriginal | Method::Auto => continue,
+        };
+
+        if bound < best && is_valid_bound(polynomial, max_coeff, real_degree, bound, tolerance) {
+            best = bound;
+        }
+    }
+
+    best
 }
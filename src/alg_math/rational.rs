@@ -0,0 +1,646 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   Arbitrary precision integers and rationals, only what the exact ranking needs.
+   Integers are a sign and a magnitude in base 2^32, the least significant digit first and
+   no trailing zero (zero is the empty magnitude).
+   Rationals are always reduced with a positive denominator, thus two equal rationals
+   have the same representation.
+*/
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// =================================================================================================
+// MAGNITUDES
+
+type Magnitude = Vec<u32>;
+
+fn trim(m: &mut Magnitude) {
+    while let Some(0) = m.last() {
+        m.pop();
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+
+    for (x, y) in a.iter().rev().zip(b.iter().rev()) {
+        if x != y {
+            return x.cmp(y);
+        }
+    }
+
+    Ordering::Equal
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Magnitude {
+    let mut result: Magnitude = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0_u64;
+
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+
+    result
+}
+
+// a - b, a must be the bigger one
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Magnitude {
+    let mut result: Magnitude = Vec::with_capacity(a.len());
+    let mut borrow = 0_i64;
+
+    for (i, x) in a.iter().enumerate() {
+        let mut difference = *x as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+
+        if difference < 0 {
+            difference += 1 << 32;
+            borrow = 1;
+        } else {
+            borrow = 0;
+        }
+
+        result.push(difference as u32);
+    }
+
+    trim(&mut result);
+    result
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Magnitude {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    let mut result: Magnitude = vec![0; a.len() + b.len()];
+
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0_u64;
+
+        for (j, y) in b.iter().enumerate() {
+            let current = result[i + j] as u64 + (*x as u64) * (*y as u64) + carry;
+            result[i + j] = current as u32;
+            carry = current >> 32;
+        }
+
+        result[i + b.len()] = carry as u32;
+    }
+
+    trim(&mut result);
+    result
+}
+
+// quotient and remainder by a single digit
+fn divrem_small(a: &[u32], divisor: u32) -> (Magnitude, u32) {
+    let mut quotient: Magnitude = vec![0; a.len()];
+    let mut remainder = 0_u64;
+
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+
+    trim(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+fn bit_length(a: &[u32]) -> usize {
+    match a.last() {
+        Option::None => 0,
+        Some(last) => (a.len() - 1) * 32 + (32 - last.leading_zeros() as usize),
+    }
+}
+
+fn bit(a: &[u32], index: usize) -> bool {
+    (a[index / 32] >> (index % 32)) & 1 == 1
+}
+
+fn shift_left(a: &[u32], shift: usize) -> Magnitude {
+    if a.is_empty() {
+        return Vec::new();
+    }
+
+    let (digits, bits) = (shift / 32, shift % 32);
+    let mut result: Magnitude = vec![0; digits];
+
+    if bits == 0 {
+        result.extend_from_slice(a);
+    } else {
+        let mut carry = 0_u32;
+
+        for x in a {
+            result.push((x << bits) | carry);
+            carry = x >> (32 - bits);
+        }
+
+        result.push(carry);
+    }
+
+    trim(&mut result);
+    result
+}
+
+// quotient and remainder, binary long division (the divisor is not zero)
+fn divrem_magnitude(a: &[u32], b: &[u32]) -> (Magnitude, Magnitude) {
+    if cmp_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+
+    if b.len() == 1 {
+        let (quotient, remainder) = divrem_small(a, b[0]);
+        let mut remainder = vec![remainder];
+        trim(&mut remainder);
+
+        return (quotient, remainder);
+    }
+
+    let length = bit_length(a);
+    let mut quotient: Magnitude = vec![0; a.len()];
+    let mut remainder: Magnitude = Vec::new();
+
+    for index in (0..length).rev() {
+        remainder = shift_left(&remainder, 1);
+
+        if bit(a, index) {
+            if remainder.is_empty() {
+                remainder.push(1);
+            } else {
+                remainder[0] |= 1;
+            }
+        }
+
+        if cmp_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            quotient[index / 32] |= 1 << (index % 32);
+        }
+    }
+
+    trim(&mut quotient);
+    (quotient, remainder)
+}
+
+// END OF MAGNITUDES
+// =================================================================================================
+
+/// an integer of any size
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Magnitude,
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt {
+            negative: false,
+            magnitude: Vec::new(),
+        }
+    }
+
+    fn from_parts(negative: bool, mut magnitude: Magnitude) -> BigInt {
+        trim(&mut magnitude);
+        let negative = negative && !magnitude.is_empty();
+
+        BigInt {
+            negative,
+            magnitude,
+        }
+    }
+
+    pub fn from_u64(n: u64) -> BigInt {
+        BigInt::from_parts(false, vec![n as u32, (n >> 32) as u32])
+    }
+
+    pub fn from_i64(n: i64) -> BigInt {
+        let magnitude = BigInt::from_u64(n.unsigned_abs()).magnitude;
+        BigInt::from_parts(n < 0, magnitude)
+    }
+
+    /// a decimal integer, digits only with an optional sign
+    pub fn parse_decimal(s: &str) -> Option<BigInt> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            Option::None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Option::None;
+        }
+
+        let mut magnitude: Magnitude = Vec::new();
+
+        for c in digits.chars() {
+            let digit = c.to_digit(10).unwrap();
+            magnitude = add_magnitude(&mul_magnitude(&magnitude, &[10]), &[digit]);
+            trim(&mut magnitude);
+        }
+
+        Some(BigInt::from_parts(negative, magnitude))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_parts(false, self.magnitude.clone())
+    }
+
+    /// quotient (truncated) and remainder (of the sign of self), None when dividing by zero
+    pub fn divrem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return Option::None;
+        }
+
+        let (quotient, remainder) = divrem_magnitude(&self.magnitude, &other.magnitude);
+
+        Some((
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+
+        while !b.is_zero() {
+            let (_, remainder) = a.divrem(&b).unwrap();
+            a = b;
+            b = remainder;
+        }
+
+        a
+    }
+
+    pub fn bit_length(&self) -> usize {
+        bit_length(&self.magnitude)
+    }
+
+    fn shift_left(&self, shift: usize) -> BigInt {
+        BigInt::from_parts(self.negative, shift_left(&self.magnitude, shift))
+    }
+
+    fn to_f64(&self) -> f64 {
+        let value = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0_f64, |acc, digit| acc * 4294967296_f64 + *digit as f64);
+
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // nine decimal digits at a time
+        let mut chunks: Vec<u32> = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = divrem_small(&magnitude, 1_000_000_000);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+
+        let mut s = String::new();
+
+        if self.negative {
+            s.push('-');
+        }
+
+        s.push_str(&chunks.last().unwrap().to_string());
+
+        for chunk in chunks.iter().rev().skip(1) {
+            s.push_str(&format!("{:09}", chunk));
+        }
+
+        write!(f, "{}", s)
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            BigInt::from_parts(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            )
+        } else if cmp_magnitude(&self.magnitude, &other.magnitude) != Ordering::Less {
+            BigInt::from_parts(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            )
+        } else {
+            BigInt::from_parts(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            )
+        }
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitude(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+/// a reduced fraction of integers of any size
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rational {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+
+impl Rational {
+    /// None when the denominator is zero
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Option<Rational> {
+        if denominator.is_zero() {
+            return Option::None;
+        }
+
+        let gcd = numerator.gcd(&denominator);
+        let (mut numerator, _) = numerator.divrem(&gcd)?;
+        let (mut denominator, _) = denominator.divrem(&gcd)?;
+
+        if denominator.is_negative() {
+            numerator = -&numerator;
+            denominator = -&denominator;
+        }
+
+        Some(Rational {
+            numerator,
+            denominator,
+        })
+    }
+
+    pub fn from_integer(n: i64) -> Rational {
+        Rational {
+            numerator: BigInt::from_i64(n),
+            denominator: BigInt::from_u64(1),
+        }
+    }
+
+    pub fn zero() -> Rational {
+        Rational::from_integer(0)
+    }
+
+    pub fn one() -> Rational {
+        Rational::from_integer(1)
+    }
+
+    /// a decimal number as written: '-12.0625' is -193/16, no exponent
+    pub fn parse_decimal(s: &str) -> Option<Rational> {
+        let s = s.trim();
+        let (integer, fraction) = match s.find('.') {
+            Option::None => (s, ""),
+            Some(point) => (&s[..point], &s[(point + 1)..]),
+        };
+
+        let digits = format!("{}{}", integer, fraction);
+        let numerator = BigInt::parse_decimal(&digits)?;
+        let denominator = BigInt::parse_decimal(&format!("1{}", "0".repeat(fraction.len())))?;
+
+        Rational::new(numerator, denominator)
+    }
+
+    /// The rational written by the shortest decimal that gives back the same float, this is
+    /// what was written in the files: 0.1 is 1/10 and not the closest binary fraction.
+    /// None for infinite and not a number values.
+    pub fn from_f64_decimal(x: f64) -> Option<Rational> {
+        if !x.is_finite() {
+            return Option::None;
+        }
+
+        // Display of floats never uses an exponent
+        Rational::parse_decimal(&format!("{}", x))
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+
+    pub fn abs(&self) -> Rational {
+        Rational {
+            numerator: self.numerator.abs(),
+            denominator: self.denominator.clone(),
+        }
+    }
+
+    /// the closest float, up to the last bit
+    pub fn to_f64(&self) -> f64 {
+        if self.is_zero() {
+            return 0_f64;
+        }
+
+        // a quotient of 64 significant bits, then scaled back
+        let shift = 64 + self.denominator.bit_length() as i64 - self.numerator.bit_length() as i64;
+
+        let (quotient, _) = if shift >= 0 {
+            self.numerator
+                .shift_left(shift as usize)
+                .divrem(&self.denominator)
+                .unwrap()
+        } else {
+            self.numerator
+                .divrem(&self.denominator.shift_left((-shift) as usize))
+                .unwrap()
+        };
+
+        quotient.to_f64() * 2_f64.powi(-shift as i32)
+    }
+
+    /// the decimal expansion with 'digits' digits after the point (truncated)
+    pub fn to_decimal_string(&self, digits: usize) -> String {
+        let (integer, remainder) = self.numerator.abs().divrem(&self.denominator).unwrap();
+        let mut s = String::new();
+
+        if self.numerator.is_negative() {
+            s.push('-');
+        }
+
+        s.push_str(&integer.to_string());
+
+        if digits > 0 {
+            s.push('.');
+
+            let ten = BigInt::from_u64(10);
+            let mut remainder = remainder;
+
+            for _ in 0..digits {
+                let (digit, rest) = (&remainder * &ten).divrem(&self.denominator).unwrap();
+                s.push_str(&digit.to_string());
+                remainder = rest;
+            }
+        }
+
+        s
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.denominator == BigInt::from_u64(1) {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // denominators are positive
+        (&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        Rational::new(
+            &(&self.numerator * &other.denominator) + &(&other.numerator * &self.denominator),
+            &self.denominator * &other.denominator,
+        )
+        .unwrap()
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            numerator: -&self.numerator,
+            denominator: self.denominator.clone(),
+        }
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &Rational) -> Rational {
+        self + &(-other)
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::new(
+            &self.numerator * &other.numerator,
+            &self.denominator * &other.denominator,
+        )
+        .unwrap()
+    }
+}
+
+impl Div for &Rational {
+    type Output = Option<Rational>;
+
+    /// None when dividing by zero
+    fn div(self, other: &Rational) -> Option<Rational> {
+        Rational::new(
+            &self.numerator * &other.denominator,
+            &self.denominator * &other.numerator,
+        )
+    }
+}
@@ -0,0 +1,223 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   An alternative to the interpolation of 'bounds.rs', no polynomial is built, the bound
+   comes from the spectral radius of the matrix and from the powers of the matrix applied
+   to the unit vector.
+
+   For a > rho(A) the solution of (a*1 - A) x = (1,...,1) is the Neumann series
+       x(a) = sum_k A^k 1 / a^(k+1)
+   thus for two facts i and j, with d_k = (A^k 1)_i - (A^k 1)_j and k0 the first k with
+   d_k != 0, the sign of x_i - x_j is the sign of d_k0 as soon as the first term wins over
+   the rest of the series.
+
+   With B = |A| and a positive vector v such that B v <= r v (Collatz-Wielandt: r is then an
+   upper bound of rho(B) >= rho(A)) in the weighted norm |y|_v = max |y_i| / v_i we have
+   |A^m y|_v <= r^m |y|_v, the rest of the series is smaller than
+       (v_i + v_j) |A^(k0+1) 1|_v / (a^k0 (a - r))
+   and the order of i and j doesn't change past
+       a = r + (v_i + v_j) |A^(k0+1) 1|_v / |d_k0|
+
+   Only the powers A^k 1 are needed, one product by the matrix each, which makes this
+   method interesting for big sparse matrices.
+*/
+
+use rayon::prelude::*;
+use std::cmp::Ordering;
+
+// maximum number of iterations of the power method
+const SPECTRAL_ITERATIONS: usize = 1000;
+// the power method stops when the upper bound doesn't move more than this (relative)
+const SPECTRAL_CONVERGENCE: f64 = 0.000001;
+// components of the positive vector are kept above this value (the vector has max 1)
+const SPECTRAL_FLOOR: f64 = 0.000001;
+// two values of A^k 1 closer than this (relative to the biggest) are taken as equal
+const SPECTRAL_TOLERANCE: f64 = 0.000000001;
+
+// the matrix by rows, only the non zero entries are kept
+type SparseRows = Vec<Vec<(usize, f64)>>;
+
+/// builds the rows of the square matrix stored in v (row major, as the system solved), the
+/// diagonal is ignored as in the interpolation
+fn sparse_rows(v: &[f64], n: usize) -> SparseRows {
+    (0..n)
+        .map(|row| {
+            (0..n)
+                .filter(|col| *col != row && v[row * n + *col] != 0_f64)
+                .map(|col| (col, v[row * n + col]))
+                .collect::<Vec<(usize, f64)>>()
+        })
+        .collect()
+}
+
+fn multiply(
+    rows: &[Vec<(usize, f64)>],
+    x: &[f64],
+    absolute: bool,
+    use_concurrency: bool,
+) -> Vec<f64> {
+    let row_product = |row: &Vec<(usize, f64)>| -> f64 {
+        row.iter()
+            .map(|(col, value)| {
+                if absolute {
+                    value.abs() * x[*col]
+                } else {
+                    value * x[*col]
+                }
+            })
+            .sum()
+    };
+
+    if use_concurrency {
+        rows.par_iter().map(row_product).collect()
+    } else {
+        rows.iter().map(row_product).collect()
+    }
+}
+
+fn weighted_norm(x: &[f64], weights: &[f64]) -> f64 {
+    x.iter()
+        .zip(weights)
+        .map(|(value, weight)| value.abs() / weight)
+        .fold(0_f64, f64::max)
+}
+
+/// An upper bound on the spectral radius of |A| (thus of A) with the positive vector that
+/// certifies it (|A| v <= r v, max of v is 1), found with the power method.
+pub fn spectral_radius_upper_bound(
+    rows: &[Vec<(usize, f64)>],
+    use_concurrency: bool,
+) -> (f64, Vec<f64>) {
+    let n = rows.len();
+    let mut vector: Vec<f64> = vec![1.; n];
+    let mut best_bound = f64::INFINITY;
+    let mut best_vector = vector.clone();
+
+    for _ in 0..SPECTRAL_ITERATIONS {
+        let product = multiply(rows, &vector, true, use_concurrency);
+
+        // Collatz-Wielandt, valid for any positive vector
+        let bound = product
+            .iter()
+            .zip(&vector)
+            .map(|(p, v)| p / v)
+            .fold(0_f64, f64::max);
+
+        let converged = (best_bound - bound).abs() <= SPECTRAL_CONVERGENCE * bound;
+
+        if bound < best_bound {
+            best_bound = bound;
+            best_vector = vector.clone();
+        }
+
+        let max_value = product.iter().cloned().fold(0_f64, f64::max);
+
+        if converged || max_value == 0_f64 {
+            break;
+        }
+
+        vector = product
+            .iter()
+            .map(|p| (p / max_value).max(SPECTRAL_FLOOR))
+            .collect();
+    }
+
+    (best_bound, best_vector)
+}
+
+/// The bound past which the order of the solution of (a*1 - A) x = (1,...,1) doesn't
+/// change, A is the square matrix stored in v.
+/// The answer is scaled by 'm_scale' to stay away from the spectral radius.
+pub fn find_bound_spectral_wrapper(
+    v: Vec<f64>,
+    m_scale: f64,
+    use_concurrency: bool,
+) -> Option<f64> {
+    let nsquared = v.len();
+    let n = (nsquared as f64).sqrt() as usize;
+
+    if n * n != nsquared {
+        // always verify the array can be casted to an square matrix
+        println!(
+            "not an square matrix can be formed, {} is not a perfect square!",
+            nsquared
+        );
+        return Option::None;
+    }
+
+    let rows = sparse_rows(&v, n);
+    let (radius, weights) = spectral_radius_upper_bound(&rows, use_concurrency);
+
+    // if the matrix is null the bound is 1, as with the interpolation
+    if radius == 0_f64 {
+        return Some(1_f64);
+    }
+
+    // the facts still to be separated, in the order of the solution for big a, each group is
+    // a set of facts with the same A^k 1 up to the current k
+    let mut groups: Vec<Vec<usize>> = vec![(0..n).collect()];
+    let mut power: Vec<f64> = vec![1.; n];
+    let mut bound = radius;
+
+    // by Cayley-Hamilton facts not separated after n steps are never separated
+    for _ in 0..=n {
+        if groups.iter().all(|group| group.len() < 2) {
+            break;
+        }
+
+        let next_power = multiply(&rows, &power, false, use_concurrency);
+        let tail = 2_f64 * weighted_norm(&next_power, &weights);
+        let max_value = power.iter().fold(0_f64, |a, b| a.max(b.abs()));
+        let tolerance = SPECTRAL_TOLERANCE * max_value.max(f64::MIN_POSITIVE);
+
+        let mut new_groups: Vec<Vec<usize>> = Vec::new();
+
+        for mut group in groups {
+            if group.len() < 2 {
+                new_groups.push(group);
+                continue;
+            }
+
+            group.sort_by(|i, j| power[*j].partial_cmp(&power[*i]).unwrap_or(Ordering::Equal));
+
+            let mut current: Vec<usize> = vec![group[0]];
+
+            for pair in group.windows(2) {
+                let gap = power[pair[0]] - power[pair[1]];
+
+                if gap > tolerance {
+                    // every fact before is separated from every fact after by at least gap
+                    bound = bound.max(radius + tail / gap);
+                    new_groups.push(current);
+                    current = Vec::new();
+                }
+
+                current.push(pair[1]);
+            }
+
+            new_groups.push(current);
+        }
+
+        groups = new_groups;
+        power = next_power;
+    }
+
+    Some(bound * m_scale)
+}
@@ -330,6 +330,99 @@ pub fn median(data: &[f64]) -> Option<f64> {
     }
 }
 
+/// rank correlation of two sequences of the same length, in [-1, 1]:
+/// 1 when they order the elements the same way, -1 when in reverse order,
+/// ties are accounted (tau-b), None if there is nothing to compare
+pub fn kendall_tau(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() || a.len() < 2 {
+        return None;
+    }
+
+    let mut concordant = 0_f64;
+    let mut discordant = 0_f64;
+    let mut ties_a = 0_f64;
+    let mut ties_b = 0_f64;
+
+    for i in 0..a.len() {
+        for j in (i + 1)..a.len() {
+            let da = a[i] - a[j];
+            let db = b[i] - b[j];
+
+            match (da == 0., db == 0.) {
+                (true, true) => (),
+                (true, false) => ties_a += 1.,
+                (false, true) => ties_b += 1.,
+                (false, false) if (da > 0.) == (db > 0.) => concordant += 1.,
+                (false, false) => discordant += 1.,
+            }
+        }
+    }
+
+    let denominator =
+        ((concordant + discordant + ties_a) * (concordant + discordant + ties_b)).sqrt();
+
+    if denominator == 0. {
+        None
+    } else {
+        Some((concordant - discordant) / denominator)
+    }
+}
+
+/// position of each element once sorted (from 1), tied elements share the mean
+/// of their positions (e.g. [0.5, 0.2, 0.5] -> [2.5, 1, 2.5])
+pub fn fractional_ranks(v: &[f64]) -> Vec<f64> {
+    let mut indices = (0..v.len()).collect::<Vec<usize>>();
+    indices.sort_by(|a, b| v[*a].partial_cmp(&v[*b]).unwrap_or(Ordering::Equal));
+
+    let mut ranks: Vec<f64> = vec![0.; v.len()];
+    let mut start = 0;
+
+    while start < indices.len() {
+        let mut end = start;
+        while end + 1 < indices.len() && v[indices[end + 1]] == v[indices[start]] {
+            end += 1;
+        }
+
+        // positions start..=end, counted from 1
+        let rank = (start + end) as f64 / 2. + 1.;
+        for index in &indices[start..=end] {
+            ranks[*index] = rank;
+        }
+
+        start = end + 1;
+    }
+
+    ranks
+}
+
+/// spearman rank correlation, the pearson correlation of the fractional ranks,
+/// None if there is nothing to compare
+pub fn spearman_rho(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() || a.len() < 2 {
+        return None;
+    }
+
+    let ranks_a = fractional_ranks(a);
+    let ranks_b = fractional_ranks(b);
+    let mean = (a.len() as f64 + 1.) / 2.;
+
+    let mut covariance = 0_f64;
+    let mut variance_a = 0_f64;
+    let mut variance_b = 0_f64;
+
+    for (ra, rb) in ranks_a.iter().zip(ranks_b.iter()) {
+        covariance += (ra - mean) * (rb - mean);
+        variance_a += (ra - mean) * (ra - mean);
+        variance_b += (rb - mean) * (rb - mean);
+    }
+
+    if variance_a == 0. || variance_b == 0. {
+        None
+    } else {
+        Some(covariance / (variance_a * variance_b).sqrt())
+    }
+}
+
 // TODO: find a way to generalize to every numeric type: integer, float, complex...
 pub fn null_vector(v: &[i8]) -> bool {
     for item in v {
@@ -2,6 +2,7 @@ mod alg_math;
 
 mod benching;
 mod dl_lite;
+mod graph_maker;
 mod helper;
 mod interface;
 mod kb;
@@ -15,6 +16,7 @@ use crate::kb::knowledge_base::{ABox, TBox, TBoxItem};
 use crate::kb::types::FileType;
 
 use crate::alg_math::bounds::find_bound_complex_wrapper;
+use crate::alg_math::polynomial_roots::Method;
 use crate::alg_math::utilities::UpperTriangle;
 
 use rand::Rng;
@@ -208,6 +210,7 @@ pub fn bench_bound_finding_one_line(
             TOLERANCE,
             M_SCALER,
             B_TRANSLATE,
+            Method::CauchyCubic,
             use_concurrency,
         );
         // println!("bound is {:?}", b);
@@ -0,0 +1,352 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+mod dl_lite;
+mod graph_maker;
+mod interface;
+mod kb;
+
+use crate::dl_lite::node::{ItemDllite, Mod};
+
+use pad::PadStr;
+use std::collections::HashSet;
+use std::time::Instant;
+
+// identifiers of the base symbols, 0 and 1 are bottom and top
+const IDENTIFIERS: [usize; 4] = [2, 3, 17, 1 << 33];
+
+// number of items cloned, sorted and hashed for the timings
+const TIMING_SIZE: usize = 200_000;
+
+pub fn main() {
+    println!("{}", vec!["="; 40].join(""));
+    println!("benching\n - compact items against the former boxed representation\n - both give the same answers, see the tests of 'dl_lite/node.rs'");
+    println!("{}", vec!["="; 40].join(""));
+
+    let olds = all_items();
+    let news = olds.iter().map(to_compact).collect::<Vec<_>>();
+
+    println!("    items: {}", olds.len());
+
+    // timings: clone, sort and hash a big vector of items
+    let old_big = olds
+        .iter()
+        .cycle()
+        .take(TIMING_SIZE)
+        .cloned()
+        .collect::<Vec<_>>();
+    let new_big = news
+        .iter()
+        .cycle()
+        .take(TIMING_SIZE)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    println!(
+        "    {} {} {} gain",
+        "operation".pad_to_width(12),
+        "boxed".pad_to_width(12),
+        "compact".pad_to_width(12),
+    );
+
+    let (old_time, new_time) = (
+        time(|| old_big.clone().len()),
+        time(|| new_big.clone().len()),
+    );
+    print_timing("clone", old_time, new_time);
+
+    let (old_time, new_time) = (
+        time(|| {
+            let mut v = old_big.clone();
+            v.sort();
+            v.len()
+        }),
+        time(|| {
+            let mut v = new_big.clone();
+            v.sort();
+            v.len()
+        }),
+    );
+    print_timing("sort", old_time, new_time);
+
+    let (old_time, new_time) = (
+        time(|| old_big.iter().collect::<HashSet<_>>().len()),
+        time(|| new_big.iter().collect::<HashSet<_>>().len()),
+    );
+    print_timing("hash", old_time, new_time);
+}
+
+// every well formed item over a few identifiers, as built by the parser
+fn all_items() -> Vec<old_representation::ItemDllite> {
+    use old_representation::ItemDllite as Old;
+
+    let mut items: Vec<Old> = vec![Old::B, Old::T];
+
+    for n in IDENTIFIERS.iter() {
+        let r = Old::R(*n);
+        let r_inv = Old::X(Mod::I, Box::new(r.clone()));
+        let c = Old::C(*n);
+
+        let roles = vec![r.clone(), r_inv.clone()];
+        let concepts = vec![
+            c,
+            Old::X(Mod::E, Box::new(r)),
+            Old::X(Mod::E, Box::new(r_inv)),
+        ];
+
+        for item in roles.into_iter().chain(concepts) {
+            items.push(Old::X(Mod::N, Box::new(item.clone())));
+            items.push(item);
+        }
+
+        items.push(Old::N(*n));
+    }
+
+    items
+}
+
+fn to_compact(old: &old_representation::ItemDllite) -> ItemDllite {
+    use old_representation::ItemDllite as Old;
+
+    match old {
+        Old::B => ItemDllite::B,
+        Old::T => ItemDllite::T,
+        Old::R(n) => ItemDllite::role(*n),
+        Old::C(n) => ItemDllite::concept(*n),
+        Old::N(n) => ItemDllite::nominal(*n),
+        Old::X(m, bn) => ItemDllite::modified(*m, to_compact(bn)),
+    }
+}
+
+fn time<F: Fn() -> usize>(f: F) -> f64 {
+    let now = Instant::now();
+    let mut total: usize = 0;
+
+    for _ in 0..5 {
+        total += f();
+    }
+
+    // keep the result alive
+    assert!(total > 0);
+
+    now.elapsed().as_secs_f64() / 5.
+}
+
+fn print_timing(operation: &str, old_time: f64, new_time: f64) {
+    println!(
+        "    {} {} {} {:.1}x",
+        operation.pad_to_width(12),
+        format!("{:.6}s", old_time).pad_to_width(12),
+        format!("{:.6}s", new_time).pad_to_width(12),
+        old_time / new_time,
+    );
+}
+
+// the boxed enum used before the compact items, trimmed to what is timed
+#[allow(dead_code)]
+mod old_representation {
+    use std::fmt;
+
+    use crate::kb::types::DLType;
+    use std::cmp::Ordering;
+    use std::ops::Deref;
+
+    use crate::dl_lite::node::Mod;
+
+    #[derive(PartialEq, Eq, Debug, Hash, Clone)]
+    pub enum ItemDllite {
+        B,                       // bottom
+        T,                       // top
+        R(usize),                // base role
+        C(usize),                // base concept
+        N(usize),                // nominal
+        X(Mod, Box<ItemDllite>), // complex type of items (e.g. inverse role or negated concept)
+    }
+
+    impl fmt::Display for ItemDllite {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ItemDllite::B => write!(f, "<B>"),
+                ItemDllite::T => write!(f, "<T>"),
+                ItemDllite::R(n) => write!(f, "r({})", n),
+                ItemDllite::C(n) => write!(f, "c({})", n),
+                ItemDllite::N(n) => write!(f, "n({})", n),
+                ItemDllite::X(m, bn) => match m {
+                    Mod::N => write!(f, "-{}", *((*bn).deref())),
+                    Mod::I => write!(f, "{}^-", *((*bn).deref())),
+                    Mod::E => write!(f, "E{}", *((*bn).deref())),
+                },
+            }
+        }
+    }
+
+    impl PartialOrd for ItemDllite {
+        /// compares self to other, with the following rule:
+        /// concepts always before roles and roles always before nominals
+        /// for basic constructs the identifier decides precedence
+        /// for complex constructs:
+        /// concept: bottom before base concept before exists quantifier before negation before top
+        /// role: base before inverse before negation
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            if self == other {
+                Some(Ordering::Equal)
+            } else {
+                if (self.t().is_concept_type() && !other.t().is_concept_type())
+                    || (self.t().is_role_type() && other.t().is_nominal_type())
+                {
+                    Some(Ordering::Less)
+                } else if (self.t().is_nominal_type() && !other.t().is_nominal_type())
+                    || (self.t().is_role_type() && other.t().is_concept_type())
+                {
+                    Some(Ordering::Greater)
+                } else if DLType::all_concepts(self.t(), other.t()) {
+                    if self.t() == DLType::Bottom || other.t() == DLType::Top {
+                        Some(Ordering::Less)
+                    } else if self.t() == DLType::Top || other.t() == DLType::Bottom {
+                        Some(Ordering::Greater)
+                    } else {
+                        match self.t() {
+                            DLType::BaseConcept => match other.t() {
+                                DLType::BaseConcept => self.n().partial_cmp(&other.n()),
+                                DLType::ExistsConcept | DLType::NegatedConcept => {
+                                    Some(Ordering::Less)
+                                }
+                                _ => Option::None,
+                            },
+                            DLType::ExistsConcept => match other.t() {
+                                DLType::BaseConcept => Some(Ordering::Greater),
+                                DLType::NegatedConcept => Some(Ordering::Less),
+                                DLType::ExistsConcept => match (self, other) {
+                                    (
+                                        ItemDllite::X(Mod::E, bnself),
+                                        ItemDllite::X(Mod::E, bnother),
+                                    ) => bnself.partial_cmp(bnother),
+                                    (_, _) => Option::None,
+                                },
+                                _ => Option::None,
+                            },
+                            DLType::NegatedConcept => match other.t() {
+                                DLType::BaseConcept | DLType::ExistsConcept => {
+                                    Some(Ordering::Greater)
+                                }
+                                DLType::NegatedConcept => match (self, other) {
+                                    (
+                                        ItemDllite::X(Mod::N, bnself),
+                                        ItemDllite::X(Mod::N, bnother),
+                                    ) => bnself.partial_cmp(bnother),
+                                    (_, _) => Option::None,
+                                },
+                                _ => Option::None,
+                            },
+                            _ => Option::None,
+                        }
+                    }
+                } else if DLType::all_roles(self.t(), other.t()) {
+                    match self.t() {
+                        DLType::BaseRole => match other.t() {
+                            DLType::BaseRole => self.n().partial_cmp(&other.n()),
+                            DLType::InverseRole | DLType::NegatedRole => Some(Ordering::Less),
+                            _ => Option::None,
+                        },
+                        DLType::InverseRole => match other.t() {
+                            DLType::BaseRole => Some(Ordering::Greater),
+                            DLType::NegatedRole => Some(Ordering::Less),
+                            DLType::InverseRole => match (self, other) {
+                                (ItemDllite::X(Mod::I, bnself), ItemDllite::X(Mod::I, bnother)) => {
+                                    bnself.partial_cmp(bnother)
+                                }
+                                (_, _) => Option::None,
+                            },
+                            _ => Option::None,
+                        },
+                        DLType::NegatedRole => match other.t() {
+                            DLType::BaseRole | DLType::InverseRole => Some(Ordering::Greater),
+                            DLType::NegatedRole => match (self, other) {
+                                (ItemDllite::X(Mod::N, bnself), ItemDllite::X(Mod::N, bnother)) => {
+                                    bnself.partial_cmp(bnother)
+                                }
+                                (_, _) => Option::None,
+                            },
+                            _ => Option::None,
+                        },
+                        _ => Option::None,
+                    }
+                } else if DLType::all_nominals(self.t(), other.t()) {
+                    // forcibly all nominals...
+                    self.n().partial_cmp(&other.n())
+                } else {
+                    Option::None
+                }
+            }
+        }
+    }
+
+    impl Ord for ItemDllite {
+        /*
+           The real function is defined in the PartialOrd trait.
+        */
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.partial_cmp(other).unwrap()
+        }
+    }
+
+    impl ItemDllite {
+        /// Every Item as a type (a DLType).
+        pub fn t(&self) -> DLType {
+            // they have to be well formed, otherwise it will fail
+            match self {
+                ItemDllite::B => DLType::Bottom,
+                ItemDllite::T => DLType::Top,
+                ItemDllite::C(_) => DLType::BaseConcept,
+                ItemDllite::R(_) => DLType::BaseRole,
+                ItemDllite::N(_) => DLType::Nominal,
+                ItemDllite::X(t, bn) => match (t, bn.deref()) {
+                    // if the item is not well formed is not the job of this function to detect it
+                    (Mod::N, ItemDllite::R(_)) | (Mod::N, ItemDllite::X(Mod::I, _)) => {
+                        DLType::NegatedRole
+                    }
+                    (Mod::N, ItemDllite::C(_)) | (Mod::N, ItemDllite::X(Mod::E, _)) => {
+                        DLType::NegatedConcept
+                    }
+                    (Mod::I, _) => DLType::InverseRole,
+                    (Mod::E, _) => DLType::ExistsConcept,
+                    (_, _) => {
+                        println!("incorrect format for node: {}", &self);
+                        std::process::exit(exitcode::DATAERR)
+                    }
+                },
+            }
+        }
+
+        /// returns the identifier of the Item
+        /// top is always 1 and bottom is always 0,values are reserved
+        pub fn n(&self) -> usize {
+            /*
+                if you try to write a parser for rustoner be aware that this values
+                are always reserved
+            */
+            match self {
+                ItemDllite::T => 1,
+                ItemDllite::B => 0,
+                ItemDllite::C(n) | ItemDllite::R(n) | ItemDllite::N(n) => *n,
+                ItemDllite::X(_, bn) => (*bn).n(),
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ mod alg_math;
 
 mod benching;
 mod dl_lite;
+mod graph_maker;
 mod helper;
 mod interface;
 mod kb;
@@ -25,7 +26,10 @@ use crate::kb::aggr_functions::*;
 use crate::kb::knowledge_base::{ABox, TBox, TBoxItem};
 use crate::kb::types::FileType;
 
-use crate::alg_math::bounds::{find_bound_complex_wrapper, Adjusters};
+use crate::alg_math::bounds::{
+    find_bound_complex_wrapper, Adjusters, BoundAlgorithm, BoundSettings,
+};
+use crate::alg_math::polynomial_roots::Method;
 
 use pad::PadStr;
 use std::cmp::Ordering;
@@ -328,8 +332,13 @@ pub fn bench_ontology_related() {
                     onto.conflict_matrix_refs_only(onto.abox().unwrap(), false);
 
                 let adjusters: Adjusters = (TOLERANCE, M_SCALER, B_TRANSLATE);
+                let bound_settings: BoundSettings = (
+                    adjusters,
+                    Method::CauchyCubic,
+                    BoundAlgorithm::Interpolation,
+                );
                 let (_before_matrix, _virtual_to_real, _conflict_type) =
-                    rank_abox(&onto, &mut abox, false, aggr, adjusters, false, true);
+                    rank_abox(&onto, &mut abox, false, aggr, bound_settings, false, true);
 
                 rankab_time = now.elapsed().as_secs_f64();
 
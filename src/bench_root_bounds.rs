@@ -0,0 +1,259 @@
+mod alg_math;
+
+mod dl_lite;
+mod graph_maker;
+mod helper;
+mod interface;
+mod kb;
+
+use crate::dl_lite::ontology::OntologyDllite;
+
+use crate::helper::normalize_vector;
+
+use crate::kb::aggr_functions::AGGR_SUM;
+use crate::kb::knowledge_base::ABox;
+
+use crate::alg_math::bounds::find_bound_complex_wrapper;
+use crate::alg_math::polynomial_roots::{Method, BOUND_METHODS};
+use crate::alg_math::spectral::find_bound_spectral_wrapper;
+use crate::alg_math::utilities::solve_system_wrapper_only_id_mod;
+
+use crate::interface::utilities::{get_filetype, parse_name_from_filename};
+
+use pad::PadStr;
+use rand::Rng;
+use std::time::Instant;
+
+// constants for the bound computing, the same as the ones of the reasoner
+const TOLERANCE: f64 = 0.0000000000000001;
+const M_SCALER: f64 = 1.1;
+const B_TRANSLATE: f64 = 1.;
+
+// each bound is computed this many times and the mean time is kept
+const REPETITIONS: usize = 20;
+
+// sizes and density of the random sparse matrices comparing interpolation and spectral bounds
+const RANDOM_SIZES: [usize; 4] = [10, 20, 40, 80];
+const RANDOM_DENSITY: f64 = 0.1;
+
+// two values closer than this (relative to the biggest) are taken as a tie
+const AGREEMENT_TOLERANCE: f64 = 0.000000000001;
+
+// the examples with conflicts, as (tbox, abox)
+const EXAMPLES: [(&str, &str); 6] = [
+    ("examples/university_tbox", "examples/university_abox"),
+    ("examples/university_tbox", "examples/university_abox2"),
+    (
+        "examples/university_tbox",
+        "examples/university_abox_scaled",
+    ),
+    ("examples/university_tbox", "examples/testab"),
+    ("examples/university_tbox", "examples/testab6"),
+    ("examples/university_tbox", "examples/testab7"),
+];
+
+pub fn main() {
+    println!("{}", vec!["="; 40].join(""));
+    println!("benching\n - bounds on polynomial roots over the aggregation matrices of the examples\n - smaller bounds are tighter\n - interpolation against spectral bounds, time and agreement of the rankings");
+    println!("{}", vec!["="; 40].join(""));
+
+    let mut methods = BOUND_METHODS.to_vec();
+    methods.push(Method::Auto);
+
+    for (path_tbox, path_abox) in EXAMPLES.iter() {
+        let aggr_matrix_op = aggregation_matrix_of_example(path_tbox, path_abox);
+
+        match aggr_matrix_op {
+            Option::None => println!(" -- {}: no conflicts, no bound to find", path_abox),
+            Some(aggr_matrix) => {
+                let n = (aggr_matrix.len() as f64).sqrt() as usize;
+                println!(" -- {} ({} assertions in conflict)", path_abox, n);
+
+                for method in &methods {
+                    let (bound_op, time) = bench_method(&aggr_matrix, *method);
+                    let bound_str = match bound_op {
+                        Option::None => String::from("none"),
+                        Some(bound) => format!("{:.6}", bound),
+                    };
+
+                    println!(
+                        "    {} bound: {} mean time: {:.6}s",
+                        format!("{:?}", method).pad_to_width(20),
+                        bound_str.pad_to_width(12),
+                        time
+                    );
+                }
+
+                let (bound_op, time) = bench_spectral(&aggr_matrix);
+                println!(
+                    "    {} bound: {} mean time: {:.6}s",
+                    "Spectral".pad_to_width(20),
+                    format!("{:.6}", bound_op.unwrap_or(f64::NAN)).pad_to_width(12),
+                    time
+                );
+                print_agreement(&aggr_matrix);
+            }
+        }
+    }
+
+    // bigger random sparse matrices
+    let mut rng = rand::thread_rng();
+
+    for size in RANDOM_SIZES.iter() {
+        let aggr_matrix = (0..(size * size))
+            .map(|index| {
+                if index % (size + 1) != 0 && rng.gen::<f64>() < RANDOM_DENSITY {
+                    rng.gen_range(-1_f64..1_f64)
+                } else {
+                    0_f64
+                }
+            })
+            .collect::<Vec<f64>>();
+
+        println!(
+            " -- random matrix of size {} (density {})",
+            size, RANDOM_DENSITY
+        );
+
+        let (bound_op, time) = bench_method(&aggr_matrix, Method::CauchyCubic);
+        println!(
+            "    {} bound: {} mean time: {:.6}s",
+            "Interpolation".pad_to_width(20),
+            format!("{:.6}", bound_op.unwrap_or(f64::NAN)).pad_to_width(12),
+            time
+        );
+
+        let (bound_op, time) = bench_spectral(&aggr_matrix);
+        println!(
+            "    {} bound: {} mean time: {:.6}s",
+            "Spectral".pad_to_width(20),
+            format!("{:.6}", bound_op.unwrap_or(f64::NAN)).pad_to_width(12),
+            time
+        );
+        print_agreement(&aggr_matrix);
+    }
+}
+
+// the rankings given by both bounds should order the facts the same way
+fn print_agreement(aggr_matrix: &[f64]) {
+    let n = (aggr_matrix.len() as f64).sqrt() as usize;
+    let interpolation_op = find_bound_complex_wrapper(
+        aggr_matrix.to_vec(),
+        TOLERANCE,
+        M_SCALER,
+        B_TRANSLATE,
+        Method::CauchyCubic,
+        false,
+    );
+    let spectral_op = find_bound_spectral_wrapper(aggr_matrix.to_vec(), M_SCALER, false);
+
+    if let (Some(interpolation), Some(spectral)) = (interpolation_op, spectral_op) {
+        let mut interpolation_rank: Vec<f64> = vec![0.; n];
+        let mut spectral_rank: Vec<f64> = vec![0.; n];
+
+        solve_system_wrapper_only_id_mod(aggr_matrix, &mut interpolation_rank, interpolation);
+        solve_system_wrapper_only_id_mod(aggr_matrix, &mut spectral_rank, spectral);
+
+        let (discordant, compared) = discordant_pairs(&interpolation_rank, &spectral_rank);
+        println!(
+            "    agreement: {} discordant pairs of the {} pairs both rankings separate",
+            discordant, compared
+        );
+    }
+}
+
+// the matrix on which 'rank_abox' looks for a bound, none if the abox has no conflict
+fn aggregation_matrix_of_example(path_tbox: &str, path_abox: &str) -> Option<Vec<f64>> {
+    let verbose = false;
+
+    let mut onto = OntologyDllite::new(parse_name_from_filename(path_tbox).to_string());
+    let tb_filetype = get_filetype(path_tbox);
+    onto.add_symbols_from_file(path_tbox, tb_filetype, verbose);
+    onto.add_tbis_from_file(path_tbox, tb_filetype, verbose);
+    onto.new_abox_from_file_quantum(path_abox, get_filetype(path_abox), verbose);
+    onto.generate_cln(false, verbose, 0_i8);
+
+    let mut abq = onto.abox().unwrap().clone();
+
+    // the same normalization as the ranking
+    let mut credibilities = abq
+        .items()
+        .iter()
+        .map(|x| x.credibility())
+        .collect::<Vec<f64>>();
+    normalize_vector(&mut credibilities);
+
+    for (abiq, credibility) in abq.items_mut().iter_mut().zip(&credibilities) {
+        abiq.set_credibility(*credibility);
+    }
+
+    let (before_matrix, _, virtual_to_real) = onto.conflict_matrix_refs_only(&abq, verbose);
+    let (done_matrix, _, _, _) = OntologyDllite::from_conflict_to_clean_matrix(&before_matrix)?;
+
+    if done_matrix.is_empty() {
+        Option::None
+    } else {
+        Some(OntologyDllite::compute_aggregation_matrix(
+            &abq,
+            &done_matrix,
+            &virtual_to_real,
+            AGGR_SUM,
+            verbose,
+        ))
+    }
+}
+
+fn bench_method(aggr_matrix: &[f64], method: Method) -> (Option<f64>, f64) {
+    let mut bound_op: Option<f64> = Option::None;
+    let now = Instant::now();
+
+    for _ in 0..REPETITIONS {
+        bound_op = find_bound_complex_wrapper(
+            aggr_matrix.to_vec(),
+            TOLERANCE,
+            M_SCALER,
+            B_TRANSLATE,
+            method,
+            false,
+        );
+    }
+
+    (bound_op, now.elapsed().as_secs_f64() / REPETITIONS as f64)
+}
+
+// pairs ordered in opposite ways by a and b, among the pairs neither of them ties, the bigger
+// the bound the closer the values, near ties are rounding errors
+fn discordant_pairs(a: &[f64], b: &[f64]) -> (usize, usize) {
+    let tolerance =
+        |values: &[f64]| AGREEMENT_TOLERANCE * values.iter().fold(0_f64, |acc, x| acc.max(x.abs()));
+    let (tolerance_a, tolerance_b) = (tolerance(a), tolerance(b));
+    let mut discordant: usize = 0;
+    let mut compared: usize = 0;
+
+    for i in 0..a.len() {
+        for j in (i + 1)..a.len() {
+            let (diff_a, diff_b) = (a[i] - a[j], b[i] - b[j]);
+
+            if diff_a.abs() > tolerance_a && diff_b.abs() > tolerance_b {
+                compared += 1;
+
+                if diff_a * diff_b < 0_f64 {
+                    discordant += 1;
+                }
+            }
+        }
+    }
+
+    (discordant, compared)
+}
+
+fn bench_spectral(aggr_matrix: &[f64]) -> (Option<f64>, f64) {
+    let mut bound_op: Option<f64> = Option::None;
+    let now = Instant::now();
+
+    for _ in 0..REPETITIONS {
+        bound_op = find_bound_spectral_wrapper(aggr_matrix.to_vec(), M_SCALER, false);
+    }
+
+    (bound_op, now.elapsed().as_secs_f64() / REPETITIONS as f64)
+}
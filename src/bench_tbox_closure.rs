@@ -0,0 +1,190 @@
+mod dl_lite;
+mod graph_maker;
+mod interface;
+mod kb;
+
+use crate::dl_lite::node::ItemDllite;
+use crate::dl_lite::ontology::OntologyDllite;
+use crate::dl_lite::tbox::TBDllite;
+use crate::dl_lite::tbox_item::TbiDllite;
+
+use crate::kb::knowledge_base::TBox;
+
+use crate::interface::utilities::{get_filetype, parse_name_from_filename};
+
+use pad::PadStr;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+// the tboxes generated by 'benchmark_files/generate_all_files.py', one directory by tbox
+const ONTO_PATH: &str = "new_onto/";
+
+// the examples, as a tbox file
+const EXAMPLES: [&str; 3] = [
+    "examples/university_tbox",
+    "examples/are_men_mortals",
+    "examples/are_men_mortals_contradiction",
+];
+
+// sizes (number of axioms) of the tboxes generated here, with the same shape as the ones of
+// the script: forests of roles and concepts, some 'EXISTS' and some negated inclusions
+const GENERATED_SIZES: [usize; 4] = [50, 100, 200, 300];
+const ROLE_PROPORTION: f64 = 0.2;
+const EXISTS_POSSIBILITY: f64 = 0.2;
+const CONFLICT_POSSIBILITY: f64 = 0.1;
+const SEED: u64 = 2021;
+
+// each closure is computed this many times and the mean time is kept
+const REPETITIONS: usize = 3;
+
+pub fn main() {
+    println!("{}", vec!["="; 40].join(""));
+    println!("benching\n - closures of tboxes: pairwise fixpoint against semi-naive evaluation\n - both closures must be the same");
+    println!("{}", vec!["="; 40].join(""));
+
+    let mut tboxes: Vec<(String, TBDllite)> = Vec::new();
+
+    for path in EXAMPLES.iter() {
+        tboxes.push((path.to_string(), tbox_from_file(path)));
+    }
+
+    // the generated ones, if the script was run
+    if let Ok(entries) = fs::read_dir(ONTO_PATH) {
+        let mut paths = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join("tbox.txt"))
+            .filter(|path| path.exists())
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        for path in paths {
+            let path = path.to_str().unwrap().to_string();
+            let tbox = tbox_from_file(&path);
+            tboxes.push((path, tbox));
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(SEED);
+
+    for size in GENERATED_SIZES.iter() {
+        tboxes.push((
+            format!("generated tbox of size {}", size),
+            generate_tbox(*size, &mut rng),
+        ));
+    }
+
+    println!(
+        "    {} {} {} {} {} {} same",
+        "tbox".pad_to_width(48),
+        "axioms".pad_to_width(8),
+        "closure".pad_to_width(10),
+        "pairwise".pad_to_width(12),
+        "semi-naive".pad_to_width(12),
+        "gain".pad_to_width(8),
+    );
+
+    for (name, tbox) in &tboxes {
+        for negative_closure in [true, false].iter() {
+            let (pairwise, pairwise_time) = bench_closure(tbox, *negative_closure, true);
+            let (semi_naive, semi_naive_time) = bench_closure(tbox, *negative_closure, false);
+
+            println!(
+                "    {} {} {} {} {} {} {}",
+                format!(
+                    "{} ({})",
+                    name,
+                    if *negative_closure { "neg" } else { "pos" }
+                )
+                .pad_to_width(48),
+                tbox.len().to_string().pad_to_width(8),
+                semi_naive.len().to_string().pad_to_width(10),
+                format!("{:.6}s", pairwise_time).pad_to_width(12),
+                format!("{:.6}s", semi_naive_time).pad_to_width(12),
+                format!("{:.1}x", pairwise_time / semi_naive_time).pad_to_width(8),
+                same_items(&pairwise, &semi_naive),
+            );
+        }
+    }
+}
+
+fn tbox_from_file(path: &str) -> TBDllite {
+    let verbose = false;
+    let mut onto = OntologyDllite::new(parse_name_from_filename(path).to_string());
+    let filetype = get_filetype(path);
+
+    if Path::new(path).exists() {
+        onto.add_symbols_from_file(path, filetype, verbose);
+        onto.add_tbis_from_file(path, filetype, verbose);
+    }
+
+    onto.tbox().clone()
+}
+
+// a role hierarchy and a concept hierarchy, each new symbol is put under an older one
+fn generate_tbox(size: usize, rng: &mut StdRng) -> TBDllite {
+    let mut tbox = TBDllite::new();
+    let roles = ((size as f64 * ROLE_PROPORTION) as usize).max(2);
+    let concepts = size - roles + 1;
+
+    // identifiers 0 and 1 are bottom and top
+    let role = |n: usize| ItemDllite::role(n + 2);
+    let concept = |n: usize| ItemDllite::concept(roles + n + 2);
+
+    for n in 1..roles {
+        let parent = rng.gen_range(0..n);
+        tbox.add(TbiDllite::new(role(n), role(parent), 0).unwrap());
+    }
+
+    for n in 1..concepts {
+        let parent = rng.gen_range(0..n);
+
+        let lside = if rng.gen::<f64>() < EXISTS_POSSIBILITY {
+            let r = role(rng.gen_range(0..roles));
+
+            if rng.gen::<bool>() {
+                r.exists().unwrap()
+            } else {
+                r.inverse().unwrap().exists().unwrap()
+            }
+        } else {
+            concept(n)
+        };
+
+        let rside = if rng.gen::<f64>() < CONFLICT_POSSIBILITY {
+            concept(parent).negate()
+        } else {
+            concept(parent)
+        };
+
+        tbox.add(TbiDllite::new(lside, rside, 0).unwrap());
+    }
+
+    tbox
+}
+
+fn bench_closure(tbox: &TBDllite, negative_closure: bool, pairwise: bool) -> (TBDllite, f64) {
+    let mut closure = TBDllite::new();
+    let now = Instant::now();
+
+    for _ in 0..REPETITIONS {
+        closure = if pairwise {
+            tbox.cln_completion_pairwise(negative_closure, false, false)
+        } else {
+            tbox.cln_completion(negative_closure, false, false)
+        };
+    }
+
+    (closure, now.elapsed().as_secs_f64() / REPETITIONS as f64)
+}
+
+fn same_items(a: &TBDllite, b: &TBDllite) -> bool {
+    let mut a = a.items().clone();
+    let mut b = b.items().clone();
+    a.sort();
+    b.sort();
+
+    a == b
+}
@@ -17,7 +17,7 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see https://www.gnu.org/licenses/.
 */
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::sync::{Arc, Mutex};
 
@@ -28,7 +28,8 @@ use crate::dl_lite::helpers_and_utilities::{
     complete_helper_add_if_necessary_general, complete_helper_dump_from_mutex_temporal_to_current,
 };
 
-use crate::dl_lite::node::ItemDllite;
+use crate::dl_lite::node::{ItemDllite, Mod, Unpacked};
+use crate::dl_lite::obda::subsumees;
 use crate::dl_lite::rule::{dl_lite_abox_rule_one, dl_lite_abox_rule_three, dl_lite_abox_rule_two};
 use crate::dl_lite::string_formatter::abiq_in_vec_of_vec;
 use crate::dl_lite::tbox::TBDllite;
@@ -50,6 +51,27 @@ pub struct AbqDllite {
     length: usize,
 }
 
+/// Indexes of an ABox, built by 'AbqDllite::index': the assertions mentioning each nominal
+/// and the assertions of each predicate (see 'AbqDllite::predicates_of').
+/// Assertions are given by their position, the indexes are worthless once the ABox changes.
+#[derive(Debug, Clone, Default)]
+pub struct AbqIndex {
+    by_nominal: HashMap<ItemDllite, Vec<usize>>,
+    by_predicate: HashMap<ItemDllite, Vec<usize>>,
+}
+
+impl AbqIndex {
+    pub fn by_nominal(&self, nominal: &ItemDllite) -> &[usize] {
+        self.by_nominal.get(nominal).map_or(&[], |v| v.as_slice())
+    }
+
+    pub fn by_predicate(&self, predicate: &ItemDllite) -> &[usize] {
+        self.by_predicate
+            .get(predicate)
+            .map_or(&[], |v| v.as_slice())
+    }
+}
+
 /// Implementation of the ABox trait for the materialization of
 /// ABox in the dl_lite_r setting.
 impl ABox for AbqDllite {
@@ -136,6 +158,10 @@ impl AbqDllite {
         }
     }
 
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
     /// Gets a mutable reference to an ABox item in self, at index 'index'.
     /// Wrapped in an Option, it will return None if nothing is found and index
     /// 'index'.
@@ -157,6 +183,92 @@ impl AbqDllite {
         abq
     }
 
+    /// Removes abiq from self, returns false if it was not present.
+    pub fn remove(&mut self, abiq: &AbiqDllite) -> bool {
+        match self.items.iter().position(|present| present == abiq) {
+            Some(index) => {
+                self.items.remove(index);
+                self.length -= 1;
+                self.completed = false;
+                true
+            }
+            Option::None => false,
+        }
+    }
+
+    /// Merges several ABoxes into a new one named 'name', keeping the provenance:
+    /// an assertion without sources gets the name of the ABox it comes from.
+    /// An assertion present in several ABoxes appears once, see 'add_with_provenance'.
+    pub fn merge(name: &str, aboxes: &[&AbqDllite]) -> AbqDllite {
+        let mut merged = AbqDllite::new(name);
+
+        for abox in aboxes {
+            let abox_name = abox.name();
+
+            for abiq in abox.items() {
+                let mut new_abiq = abiq.clone();
+
+                if new_abiq.evidence().is_empty() {
+                    new_abiq.add_source(&abox_name);
+                }
+
+                merged.add_with_provenance(new_abiq);
+            }
+        }
+
+        merged
+    }
+
+    /// Adds abiq to self, if it is already present nothing new is added but the
+    /// sources of both are joined and the highest credibility is kept.
+    /// Returns true if abiq was not present.
+    pub fn add_with_provenance(&mut self, abiq: AbiqDllite) -> bool {
+        match self.items.iter().position(|present| present == &abiq) {
+            Some(index) => {
+                let present = &mut self.items[index];
+
+                for (source, credibility) in abiq.evidence() {
+                    present.add_evidence(source, *credibility);
+                }
+
+                if abiq.credibility() > present.credibility() {
+                    present.set_credibility(abiq.credibility());
+                }
+
+                false
+            }
+            Option::None => self.add(abiq),
+        }
+    }
+
+    /// The sources of the assertions in self, in order of appearance.
+    pub fn sources(&self) -> Vec<String> {
+        let mut sources: Vec<String> = Vec::new();
+
+        for abiq in &self.items {
+            for source in abiq.sources() {
+                if !sources.contains(&source) {
+                    sources.push(source);
+                }
+            }
+        }
+
+        sources
+    }
+
+    /// A conflict is across sources when no single source asserts all of its
+    /// members, members without provenance never make a conflict across sources.
+    pub fn is_cross_source_conflict(members: &[&AbiqDllite]) -> bool {
+        if members.is_empty() || members.iter().any(|m| m.sources().is_empty()) {
+            false
+        } else {
+            !members[0]
+                .sources()
+                .iter()
+                .any(|source| members.iter().all(|m| m.has_source(source)))
+        }
+    }
+
     /// Will create an ABox from self and a vector of indexes.
     /// Literally copies each ABox item at an index present in  self.
     /// A name is necessary to construct the new ABox.
@@ -179,6 +291,170 @@ impl AbqDllite {
         }
     }
 
+    /// The predicate under which 'item' is indexed: negations and inverses are dropped,
+    /// 'NOT EXISTS r^-' is indexed as 'EXISTS r^-' and 'r^-' as 'r'.
+    pub fn predicate_key(item: &ItemDllite) -> ItemDllite {
+        match item.unpack() {
+            Unpacked::X(Mod::N, bn) | Unpacked::X(Mod::I, bn) => AbqDllite::predicate_key(&bn),
+            _ => *item,
+        }
+    }
+
+    /// The predicates an assertion can be found under, a role assertion (a,b):r is also
+    /// a:EXISTS r and b:EXISTS r^-, as in 'is_inconsistent_refs_only'.
+    pub fn predicates_of(abiq: &AbiqDllite) -> Vec<ItemDllite> {
+        let symbol = abiq.abi().symbol();
+        let mut predicates = vec![AbqDllite::predicate_key(symbol)];
+
+        if symbol.t().is_role_type() && !symbol.is_purely_negated() {
+            let exists_role = symbol.exists();
+            let exists_role_inversed = symbol.inverse().and_then(|r| r.exists());
+
+            for exists in exists_role.iter().chain(exists_role_inversed.iter()) {
+                predicates.push(AbqDllite::predicate_key(exists));
+            }
+        }
+
+        predicates
+    }
+
+    /// Builds the nominal and predicate indexes of self.
+    pub fn index(&self) -> AbqIndex {
+        let mut index = AbqIndex::default();
+
+        for (position, abiq) in self.items.iter().enumerate() {
+            for nominal in abiq.abi().decompact_nominals_refs() {
+                let positions = index.by_nominal.entry(*nominal).or_default();
+
+                // (a,a):r mentions a only once
+                if positions.last() != Some(&position) {
+                    positions.push(position);
+                }
+            }
+
+            for predicate in AbqDllite::predicates_of(abiq) {
+                let positions = index.by_predicate.entry(predicate).or_default();
+
+                if positions.last() != Some(&position) {
+                    positions.push(position);
+                }
+            }
+        }
+
+        index
+    }
+
+    /// The pairs (i, j), i < j, of assertions that can be in conflict or imply one another
+    /// with respect to the closures 'tbs': they share a nominal and their predicates appear
+    /// together in some axiom (a predicate always goes with itself, for a:A and a:NOT A),
+    /// or one of them, or its negation, is inconsistent on its own (e.g. an unsatisfiable
+    /// concept is implied by anything), these are paired with every other assertion.
+    /// Any other pair is consistent and independent, 'is_inconsistent_refs_only' needs both
+    /// assertions on the same nominal and one side of an axiom matched by each.
+    /// Each assertion only meets the assertions on its nominals, or of the related predicates
+    /// when those are fewer, on sparse aboxes this is close to linear.
+    pub fn candidate_pairs(&self, tbs: &[&TBDllite]) -> Vec<(usize, usize)> {
+        let index = self.index();
+        let length = self.items.len();
+
+        // related predicates, in both directions
+        let mut related: HashMap<ItemDllite, HashSet<ItemDllite>> = HashMap::new();
+
+        for tb in tbs {
+            for tbi in tb.items() {
+                let lside = AbqDllite::predicate_key(tbi.lside());
+                let rside = AbqDllite::predicate_key(tbi.rside());
+
+                related.entry(lside).or_default().insert(rside);
+                related.entry(rside).or_default().insert(lside);
+            }
+        }
+
+        let predicates = self
+            .items
+            .iter()
+            .map(AbqDllite::predicates_of)
+            .collect::<Vec<Vec<ItemDllite>>>();
+
+        let related_to = |i: usize| -> HashSet<ItemDllite> {
+            let mut to: HashSet<ItemDllite> = HashSet::new();
+
+            for predicate in &predicates[i] {
+                to.insert(*predicate);
+
+                if let Some(others) = related.get(predicate) {
+                    to.extend(others.iter().cloned());
+                }
+            }
+
+            to
+        };
+
+        let mut pairs: HashSet<(usize, usize)> = HashSet::new();
+
+        for (i, abiq) in self.items.iter().enumerate() {
+            let negated = abiq.negate();
+            let alone_is_inconsistent = tbs.iter().any(|tb| {
+                AbqDllite::is_inconsistent_refs_only(vec![abiq], tb, false).0
+                    || AbqDllite::is_inconsistent_refs_only(vec![&negated], tb, false).0
+            });
+
+            if alone_is_inconsistent {
+                for j in (0..length).filter(|j| *j != i) {
+                    pairs.insert((usize::min(i, j), usize::max(i, j)));
+                }
+
+                continue;
+            }
+
+            let related_i = related_to(i);
+            let nominals_i = abiq.abi().decompact_nominals_refs();
+
+            let by_nominals = nominals_i
+                .iter()
+                .map(|nominal| index.by_nominal(nominal).len())
+                .sum::<usize>();
+            let by_predicates = related_i
+                .iter()
+                .map(|predicate| index.by_predicate(predicate).len())
+                .sum::<usize>();
+
+            let mut add_if_candidate = |j: usize| {
+                if j > i
+                    && predicates[j].iter().any(|p| related_i.contains(p))
+                    && self.items[j]
+                        .abi()
+                        .decompact_nominals_refs()
+                        .iter()
+                        .any(|nominal| nominals_i.contains(nominal))
+                {
+                    pairs.insert((i, j));
+                }
+            };
+
+            if by_nominals <= by_predicates {
+                for nominal in &nominals_i {
+                    index
+                        .by_nominal(nominal)
+                        .iter()
+                        .for_each(|j| add_if_candidate(*j));
+                }
+            } else {
+                for predicate in &related_i {
+                    index
+                        .by_predicate(predicate)
+                        .iter()
+                        .for_each(|j| add_if_candidate(*j));
+                }
+            }
+        }
+
+        let mut pairs = pairs.into_iter().collect::<Vec<(usize, usize)>>();
+        pairs.sort_unstable();
+
+        pairs
+    }
+
     // TODO: verify this
     pub fn items_by_ref(&self) -> Vec<&AbiqDllite> {
         let index = (0..self.len()).collect::<Vec<usize>>();
@@ -236,7 +512,7 @@ impl AbqDllite {
             // get left, right and negated right side
             let left = tbi.lside();
             let right = tbi.rside();
-            let right_negated = right.clone().negate(); // gets the right side negation
+            let right_negated = right.negate(); // gets the right side negation
 
             for i in 0..self_length {
                 // test against each member of self
@@ -363,18 +639,17 @@ impl AbqDllite {
                     let nominals = abiq.abi().decompact_nominals_refs();
                     let role_name = abiq.abi().symbol();
 
-                    let role_inversed = role_name.clone().inverse().unwrap();
+                    let role_inversed = role_name.inverse().unwrap();
                     let exists_role_inversed = role_inversed.exists().unwrap();
 
-                    let exists_role = role_name.clone().exists().unwrap();
+                    let exists_role = role_name.exists().unwrap();
 
                     /*
                        TODO: come back here and see why my logic of the 'for_completion' item is bad
                     */
-                    let new_ca_exists =
-                        AbiDllite::new_ca(exists_role, nominals[0].clone(), true).unwrap();
+                    let new_ca_exists = AbiDllite::new_ca(exists_role, *nominals[0], true).unwrap();
                     let new_ca_exists_inverse =
-                        AbiDllite::new_ca(exists_role_inversed, nominals[1].clone(), true).unwrap();
+                        AbiDllite::new_ca(exists_role_inversed, *nominals[1], true).unwrap();
 
                     let new_abiq = AbiqDllite::new(
                         new_ca_exists,
@@ -503,18 +778,17 @@ impl AbqDllite {
                 let nominals = abiq.abi().decompact_nominals_refs();
                 let role_name = abiq.abi().symbol();
 
-                let role_inversed = role_name.clone().inverse().unwrap();
+                let role_inversed = role_name.inverse().unwrap();
                 let exists_role_inversed = role_inversed.exists().unwrap();
 
-                let exists_role = role_name.clone().exists().unwrap();
+                let exists_role = role_name.exists().unwrap();
 
                 /*
                    TODO: come back here and see why my logic of the 'for_completion' item is bad
                 */
-                let new_ca_exists =
-                    AbiDllite::new_ca(exists_role, nominals[0].clone(), true).unwrap();
+                let new_ca_exists = AbiDllite::new_ca(exists_role, *nominals[0], true).unwrap();
                 let new_ca_exists_inverse =
-                    AbiDllite::new_ca(exists_role_inversed, nominals[1].clone(), true).unwrap();
+                    AbiDllite::new_ca(exists_role_inversed, *nominals[1], true).unwrap();
 
                 let new_abiq = AbiqDllite::new(
                     new_ca_exists,
@@ -921,9 +1195,14 @@ impl AbqDllite {
                             }
                         };
 
+                    /*
+                       the rules only read the first item, pairing an item with each other one
+                       applies them to both, alone in the abox an item is paired with itself
+                    */
+                    let alone = length == 1;
                     let mut apply_to_two_items =
                         |item: &AbiqDllite, current_item: &AbiqDllite, tbi: &TbiDllite| {
-                            if item != current_item {
+                            if item != current_item || alone {
                                 for rule_index in &rules_used {
                                     let rule: &AbRule<T, A> = rules[rule_index - 1];
                                     let rule_ord = rule_ordinal[rule_index - 1];
@@ -1062,6 +1341,36 @@ impl AbqDllite {
         get_max_level_abstract(self.items())
     }
 
+    /// The answers to a basic concept or role (e.g. 'A', 'EXISTS INV r', 'r') over self,
+    /// one individual for a concept and two for a role. The query is rewritten as for
+    /// the mappings, see 'obda::subsumees'.
+    pub fn answer(&self, query: &ItemDllite, tbox: &TBDllite) -> Vec<Vec<ItemDllite>> {
+        let mut answers: Vec<Vec<ItemDllite>> = Vec::new();
+
+        for item in subsumees(query, tbox) {
+            for abiq in &self.items {
+                let answer = match (abiq.abi(), item.unpack()) {
+                    (AbiDllite::CA(c, a), _) if c == &item => vec![*a],
+                    (AbiDllite::RA(r, a, _), Unpacked::X(Mod::E, role)) if r == &role => {
+                        vec![*a]
+                    }
+                    (AbiDllite::RA(r, _, b), Unpacked::X(Mod::E, role)) if r.is_inverse(&role) => {
+                        vec![*b]
+                    }
+                    (AbiDllite::RA(r, a, b), _) if r == &item => vec![*a, *b],
+                    (AbiDllite::RA(r, a, b), _) if r.is_inverse(&item) => vec![*b, *a],
+                    (_, _) => continue,
+                };
+
+                if !answers.contains(&answer) {
+                    answers.push(answer);
+                }
+            }
+        }
+
+        answers
+    }
+
     pub fn get_abis_by_level(
         &self,
         only_conflicts: bool,
@@ -132,14 +132,14 @@ impl AbiDllite {
     pub fn negate(&self) -> AbiDllite {
         match self {
             AbiDllite::CA(c, a) => {
-                let c_neg = c.clone().negate();
+                let c_neg = c.negate();
 
-                AbiDllite::new_ca(c_neg, a.clone(), true).unwrap()
+                AbiDllite::new_ca(c_neg, *a, true).unwrap()
             }
             AbiDllite::RA(r, a, b) => {
-                let r_neg = r.clone().negate();
+                let r_neg = r.negate();
 
-                AbiDllite::new_ra(r_neg, a.clone(), b.clone(), true).unwrap()
+                AbiDllite::new_ra(r_neg, *a, *b, true).unwrap()
             }
         }
     }
@@ -38,6 +38,9 @@ use std::hash::{Hash, Hasher};
 /// The first, 'credibility', is the amount of trust a user put in an
 /// assertion (which default to one if none is provided).
 /// The second, 'value',  is computed by the abox ranking algorithm.
+/// The 'sources' are where the assertion comes from (an ABox or a source tag),
+/// each one with the credibility it gave to the assertion, see 'dl_lite/trust.rs'
+/// for how they are combined.
 #[derive(Debug, Clone)]
 pub struct AbiqDllite {
     abi: AbiDllite, // role or concept assertion
@@ -45,6 +48,7 @@ pub struct AbiqDllite {
     value: Option<f64>,
     level: usize,
     impliers: Vec<(CR, Vec<TbiDllite>, Vec<AbiqDllite>)>,
+    sources: Vec<(String, f64)>,
 }
 
 // TODO: this might introduce some unseen problems... :(
@@ -212,6 +216,7 @@ impl AbiqDllite {
             value,
             level,
             impliers,
+            sources: Vec::new(),
         }
     }
 
@@ -239,6 +244,41 @@ impl AbiqDllite {
         self.abi.is_trivial()
     }
 
+    /// The names of the sources of self.
+    pub fn sources(&self) -> Vec<String> {
+        self.sources.iter().map(|(s, _)| s.clone()).collect()
+    }
+
+    /// The sources of self with the credibility each one gave.
+    pub fn evidence(&self) -> &Vec<(String, f64)> {
+        &self.sources
+    }
+
+    /// Adds source to the provenance of self with the credibility of self,
+    /// returns false if it was already there.
+    pub fn add_source(&mut self, source: &str) -> bool {
+        self.add_evidence(source, self.credibility)
+    }
+
+    /// Adds source to the provenance of self with its own credibility,
+    /// a source already present keeps the highest credibility and false is returned.
+    pub fn add_evidence(&mut self, source: &str, credibility: f64) -> bool {
+        match self.sources.iter_mut().find(|(s, _)| s == source) {
+            Some(present) => {
+                present.1 = f64::max(present.1, credibility);
+                false
+            }
+            Option::None => {
+                self.sources.push((source.to_string(), credibility));
+                true
+            }
+        }
+    }
+
+    pub fn has_source(&self, source: &str) -> bool {
+        self.sources.iter().any(|(s, _)| s == source)
+    }
+
     /// Checks if self and other have the same constants (in the same order).
     pub fn same_nominal(&self, other: &Self) -> bool {
         self.abi.same_nominal(&other.abi)
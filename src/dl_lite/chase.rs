@@ -0,0 +1,453 @@
+/*
+UMONS 2021
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::abox_item::AbiDllite;
+use crate::dl_lite::abox_item_quantum::AbiqDllite;
+use crate::dl_lite::node::{ItemDllite, Mod, Unpacked};
+use crate::dl_lite::string_formatter::abiq_to_string;
+use crate::dl_lite::tbox::TBDllite;
+use crate::kb::knowledge_base::{ABox, Implier, Item, LeveledItem, SymbolDict};
+use crate::kb::types::{DLType, CR};
+
+/*
+   the completion of an abox only derives atomic consequences, a fact 'a: EXISTS r'
+   says that some 'b' exists with '(a,b): r' but nobody creates that 'b',
+   here we create it, it is a labelled null: a nominal that does not come from the abox,
+   doing this until nothing new appear gives the canonical model, which can be infinite
+   (think of 'A < EXISTS r' and 'EXISTS r^- < A'), thus we bound it by depth
+*/
+
+/// Every labelled null is named with this prefix followed by a number,
+/// the name is still a valid nominal name for the native format.
+pub const NULL_PREFIX: &str = "_null_";
+
+/// A (prefix of the) canonical model of an ABox with respect to a TBox.
+/// The ABox holds the completed assertions plus the witnessing role
+/// assertions created by the chase, the symbols are the original ones
+/// extended with the labelled nulls.
+/// Each null keeps its depth: a null created for a named individual has
+/// depth 1, a null created for a null of depth d has depth d + 1.
+#[derive(PartialEq, Debug, Clone)]
+pub struct CanonicalModelDllite {
+    abox: AbqDllite,
+    symbols: SymbolDict,
+    nulls: HashMap<usize, usize>,
+    max_depth: usize,
+    truncated: bool,
+}
+
+impl fmt::Display for CanonicalModelDllite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<CM({}, nulls: {}, depth: {}, truncated: {})>{}",
+            self.abox.name(),
+            self.nulls.len(),
+            self.max_depth,
+            self.truncated,
+            &self.abox
+        )
+    }
+}
+
+impl CanonicalModelDllite {
+    /// Runs the chase on abq.
+    /// args:
+    /// - abq: the ABox to chase, it is completed first
+    /// - tbox: the TBox to use, it should be the positive closure
+    ///   (see 'OntologyDllite::generate_cln')
+    /// - symbols: the symbols of the ontology, they are not modified, a copy
+    ///   is extended with the nulls
+    /// - max_depth: nulls deeper than this are not created
+    /// - deduction_tree: keep track of the impliers as in 'AbqDllite::complete'
+    ///
+    /// The chase does not verify consistency, an inconsistent ABox has no model,
+    /// check it before with 'AbqDllite::is_inconsistent_refs_only'.
+    pub fn chase(
+        abq: &AbqDllite,
+        tbox: &TBDllite,
+        symbols: &SymbolDict,
+        max_depth: usize,
+        deduction_tree: bool,
+        verbose: bool,
+    ) -> CanonicalModelDllite {
+        let mut new_symbols = symbols.clone();
+        let mut nulls: HashMap<usize, usize> = HashMap::new();
+        let mut truncated = false;
+
+        // new nominals must not collide with any symbol
+        let mut current_id = symbols.values().map(|(id, _)| *id).max().unwrap_or(1) + 1;
+        // and their names neither, a name already taken is skipped
+        let mut null_number: usize = 0;
+
+        let mut current = abq.complete(tbox, deduction_tree, verbose);
+        let mut round: usize = 0;
+
+        loop {
+            let mut witnesses: Vec<AbiqDllite> = Vec::new();
+
+            for abiq in current.items() {
+                if let AbiDllite::CA(c, a) = abiq.abi() {
+                    let r = match c.unpack() {
+                        Unpacked::X(Mod::E, r) => r,
+                        _ => continue,
+                    };
+
+                    if CanonicalModelDllite::has_witness(current.items(), &r, a) {
+                        continue;
+                    }
+
+                    // individuals from the abox are at depth 0
+                    let depth = nulls.get(&a.n()).map_or(1, |d| d + 1);
+
+                    if depth > max_depth {
+                        truncated = true;
+                        continue;
+                    }
+
+                    let null = ItemDllite::new(Some(current_id), DLType::Nominal).unwrap();
+                    let null_name = loop {
+                        null_number += 1;
+                        let name = format!("{}{}", NULL_PREFIX, null_number);
+
+                        if !new_symbols.contains_key(&name) {
+                            break name;
+                        }
+                    };
+
+                    new_symbols.insert(null_name, (current_id, DLType::Nominal));
+                    nulls.insert(current_id, depth);
+                    current_id += 1;
+
+                    // inverse roles are written with the base role and the nominals swapped
+                    let abi = match r.unpack() {
+                        Unpacked::X(Mod::I, base) => AbiDllite::new_ra(base, null, *a, true),
+                        _ => AbiDllite::new_ra(r, *a, null, true),
+                    };
+
+                    if let Some(some_abi) = abi {
+                        let mut witness = AbiqDllite::new(
+                            some_abi,
+                            Some(abiq.credibility()),
+                            Option::None,
+                            abiq.level() + 1,
+                        );
+
+                        if deduction_tree {
+                            witness.add_to_implied_by((CR::Fourth, vec![], vec![abiq.clone()]));
+                        }
+
+                        if verbose {
+                            println!(
+                                " -- CanonicalModel::chase: round {}, {} witnessed by {}",
+                                round, abiq, &witness
+                            );
+                        }
+
+                        witnesses.push(witness);
+                    }
+                }
+            }
+
+            if witnesses.is_empty() {
+                break;
+            }
+
+            for witness in witnesses {
+                current.add(witness);
+            }
+
+            // the new role assertions have consequences of their own
+            current = current.complete(tbox, deduction_tree, verbose);
+            round += 1;
+        }
+
+        let new_name = format!("{}_chased", abq.name());
+        let mut chased = AbqDllite::from_vec(&new_name, current.items().clone());
+        chased.sort();
+
+        if verbose {
+            println!(
+                " -- CanonicalModel::chase: done after {} rounds, {} nulls created, truncated: {}",
+                round,
+                nulls.len(),
+                truncated
+            );
+        }
+
+        CanonicalModelDllite {
+            abox: chased,
+            symbols: new_symbols,
+            nulls,
+            max_depth,
+            truncated,
+        }
+    }
+
+    /// The chased ABox, nulls are ordinary nominals in it.
+    pub fn abox(&self) -> &AbqDllite {
+        &self.abox
+    }
+
+    /// The symbols of the ontology plus the nulls.
+    pub fn symbols(&self) -> &SymbolDict {
+        &self.symbols
+    }
+
+    /// The identifiers of the labelled nulls and their depth.
+    pub fn nulls(&self) -> &HashMap<usize, usize> {
+        &self.nulls
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// True if some existential was left without a witness because of the depth
+    /// bound, in that case the ABox is only a prefix of the canonical model.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn is_null(&self, node: &ItemDllite) -> bool {
+        match node.unpack() {
+            Unpacked::N(n) => self.nulls.contains_key(&n),
+            _ => false,
+        }
+    }
+
+    /// Checks if some nominal of abiq is a labelled null.
+    pub fn contains_null(&self, abiq: &AbiqDllite) -> bool {
+        abiq.abi()
+            .decompact_nominals_refs()
+            .iter()
+            .any(|node| self.is_null(node))
+    }
+
+    /// Only the assertions over base concepts and base roles, this is the
+    /// materialized dataset, what you want to export.
+    /// Role assertions over inverse roles are turned into the base role.
+    pub fn materialized(&self) -> AbqDllite {
+        let new_name = format!("{}_materialized", self.abox.name());
+        let mut materialized = AbqDllite::new(&new_name);
+
+        for abiq in self.abox.items() {
+            let abi_op = match abiq.abi() {
+                AbiDllite::CA(c, _) if c.t() == DLType::BaseConcept => Some(abiq.abi().clone()),
+                AbiDllite::RA(r, a, b) => match r.unpack() {
+                    Unpacked::R(_) => Some(abiq.abi().clone()),
+                    Unpacked::X(Mod::I, base) => AbiDllite::new_ra(base, *b, *a, true),
+                    _ => Option::None,
+                },
+                _ => Option::None,
+            };
+
+            if let Some(abi) = abi_op {
+                materialized.add(AbiqDllite::new(
+                    abi,
+                    Some(abiq.credibility()),
+                    abiq.value(),
+                    abiq.level(),
+                ));
+            }
+        }
+
+        materialized.sort();
+        materialized
+    }
+
+    /// Native format of the materialized ABox, assertions involving nulls are
+    /// marked with a comment so they can be told apart, the output can be parsed
+    /// back as any other abox file.
+    pub fn to_native_string(&self) -> String {
+        let to_native = true;
+        let materialized = self.materialized();
+        let mut s = String::new();
+
+        s.push_str(&format!(
+            "// canonical model of '{}', depth {}, {} labelled nulls ('{}n')\n",
+            self.abox.name(),
+            self.max_depth,
+            self.nulls.len(),
+            NULL_PREFIX
+        ));
+
+        if self.truncated {
+            s.push_str(
+                "// WARNING: the chase was stopped by the depth bound, this is only a prefix\n",
+            );
+        }
+
+        s.push_str("BEGINABOX\n");
+
+        for abiq in materialized.items() {
+            if let Some(abiq_string) = abiq_to_string(abiq, &self.symbols, to_native) {
+                s.push_str(&abiq_string);
+
+                if self.contains_null(abiq) {
+                    s.push_str(" // labelled null");
+                }
+
+                s.push('\n');
+            }
+        }
+
+        s.push_str("ENDABOX\n");
+        s
+    }
+
+    /// Human readable view of the whole chased ABox, labelled nulls are
+    /// printed between brackets with their depth (e.g. '[_null_1 @1]').
+    pub fn to_pretty_string(&self) -> String {
+        let to_native = false;
+
+        // the view uses its own symbols so that nulls stand out
+        let mut view_symbols = self.symbols.clone();
+        for (name, (id, _)) in self.symbols.iter() {
+            if let Some(depth) = self.nulls.get(id) {
+                let marked = format!("[{} @{}]", name, depth);
+                view_symbols.remove(name);
+                view_symbols.insert(marked, (*id, DLType::Nominal));
+            }
+        }
+
+        let mut s = String::from("[\n");
+
+        for abiq in self.abox.items() {
+            if abiq.is_trivial() {
+                continue;
+            }
+
+            if let Some(abiq_string) = abiq_to_string(abiq, &view_symbols, to_native) {
+                s.push_str(&format!("  {},\n", abiq_string));
+            }
+        }
+
+        s.push(']');
+        s
+    }
+
+    // an existential 'a: EXISTS r' is witnessed by '(a,b): r' or by '(b,a): s' when r = s^-
+    fn has_witness(items: &[AbiqDllite], r: &ItemDllite, a: &ItemDllite) -> bool {
+        items.iter().any(|abiq| match abiq.abi() {
+            AbiDllite::RA(s, b, c) => (s == r && b == a) || (s.is_inverse(r) && c == a),
+            _ => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dl_lite::string_formatter::{string_to_abiq, string_to_tbi};
+    use crate::kb::knowledge_base::TBox;
+
+    // the canonical model of the assertions over the axioms, symbols are declared here
+    fn chased(axioms: &[&str], assertions: &[&str], max_depth: usize) -> CanonicalModelDllite {
+        let mut symbols: SymbolDict = HashMap::new();
+
+        for (id, name) in ["A", "B", "C"].iter().enumerate() {
+            symbols.insert(name.to_string(), (id + 2, DLType::BaseConcept));
+        }
+        for (id, name) in ["r", "s"].iter().enumerate() {
+            symbols.insert(name.to_string(), (id + 5, DLType::BaseRole));
+        }
+
+        let mut tbox = TBDllite::new();
+
+        for axiom in axioms {
+            for tbi in string_to_tbi(axiom, &symbols).unwrap() {
+                tbox.add(tbi);
+            }
+        }
+
+        let mut abq = AbqDllite::new("test");
+        let mut current_id: usize = 7;
+
+        for assertion in assertions {
+            let (parsed, next_id) = string_to_abiq(assertion, &mut symbols, current_id, false);
+            let (abiq, new_symbols) = parsed.unwrap();
+
+            symbols.extend(new_symbols);
+            current_id = next_id;
+            abq.add(abiq);
+        }
+
+        CanonicalModelDllite::chase(&abq, &tbox, &symbols, max_depth, false, false)
+    }
+
+    fn assertions(model: &CanonicalModelDllite) -> Vec<String> {
+        model
+            .materialized()
+            .items()
+            .iter()
+            .filter_map(|abiq| abiq_to_string(abiq, model.symbols(), true))
+            .collect()
+    }
+
+    #[test]
+    fn single_assertion_is_chased() {
+        // nothing to pair 'a: A' with, the rules are still applied to it
+        let model = chased(&["A < B", "A < EXISTS r"], &["a: A"], 3);
+
+        assert_eq!(
+            assertions(&model),
+            vec!["a : A, 1", "a : B, 1", "a, _null_1: r, 1"]
+        );
+        assert_eq!(model.nulls().values().collect::<Vec<_>>(), vec![&1]);
+        assert!(!model.is_truncated());
+    }
+
+    #[test]
+    fn existential_chain_is_chased() {
+        let model = chased(
+            &["A < EXISTS r", "EXISTS INV r < C", "C < EXISTS s"],
+            &["a: A"],
+            3,
+        );
+        let mut depths = model.nulls().values().cloned().collect::<Vec<usize>>();
+        depths.sort_unstable();
+
+        assert_eq!(
+            assertions(&model),
+            vec![
+                "a : A, 1",
+                "_null_1 : C, 1",
+                "a, _null_1: r, 1",
+                "_null_1, _null_2: s, 1"
+            ]
+        );
+        assert_eq!(depths, vec![1, 2]);
+        assert!(!model.is_truncated());
+
+        // one step less and the second null is not created
+        let model = chased(
+            &["A < EXISTS r", "EXISTS INV r < C", "C < EXISTS s"],
+            &["a: A"],
+            1,
+        );
+
+        assert_eq!(model.nulls().len(), 1);
+        assert!(model.is_truncated());
+    }
+}
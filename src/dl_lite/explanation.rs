@@ -0,0 +1,253 @@
+/*
+UMONS 2021
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+use std::cmp::Ordering;
+
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::abox_item_quantum::AbiqDllite;
+use crate::dl_lite::ontology::OntologyDllite;
+use crate::dl_lite::string_formatter::{abi_to_string, abiq_to_string, tbi_to_string};
+use crate::dl_lite::tbox_item::TbiDllite;
+use crate::kb::knowledge_base::{ABox, SymbolDict};
+use crate::kb::types::ConflictType;
+
+/*
+   why did an assertion get its value? the ranking solves a linear system where each
+   assertion is pushed up by the ones implying it and down by the ones contradicting it,
+   the value of an assertion decomposes as a base plus the contribution of each of them,
+   for the contradictions the negative inclusions of the tbox behind them are kept
+*/
+
+/// An assertion acting on the value of the explained one: what it adds to the
+/// value (negative for a conflict) and, for a conflict, the negative inclusions
+/// that make both assertions inconsistent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Influence {
+    abiq: AbiqDllite,
+    contribution: f64,
+    inclusions: Vec<TbiDllite>,
+}
+
+impl Influence {
+    pub fn abiq(&self) -> &AbiqDllite {
+        &self.abiq
+    }
+
+    pub fn contribution(&self) -> f64 {
+        self.contribution
+    }
+
+    pub fn inclusions(&self) -> &Vec<TbiDllite> {
+        &self.inclusions
+    }
+
+    pub fn is_conflict(&self) -> bool {
+        self.contribution < 0.
+    }
+}
+
+/// Why an assertion got its value: the base of its value and the influences
+/// on it sorted from the strongest, for a self conflicting assertion the
+/// negative inclusions it violates alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankExplanation {
+    abiq: AbiqDllite,
+    conflict_type: ConflictType,
+    base: Option<f64>,
+    influences: Vec<Influence>,
+    inclusions: Vec<TbiDllite>,
+}
+
+impl RankExplanation {
+    /// explain the assertion at 'index' in a ranked abox, the decomposition comes from
+    /// the system solved by the ranking: the base of the value and what each other
+    /// assertion (by index) adds to it
+    pub fn new(
+        onto: &OntologyDllite,
+        abq: &AbqDllite,
+        index: usize,
+        conflict_type: ConflictType,
+        decomposition_op: Option<(f64, Vec<(usize, f64)>)>,
+    ) -> Option<RankExplanation> {
+        let abiq = abq.get(index)?;
+        let tbox = onto.cln(false);
+
+        // a self conflicting assertion is alone to blame
+        let inclusions = if conflict_type == ConflictType::SelfConflict {
+            let (_, detailed) = AbqDllite::is_inconsistent_refs_only_detailed(vec![abiq], tbox);
+            collect_inclusions(detailed)
+        } else {
+            Vec::new()
+        };
+
+        let mut influences: Vec<Influence> = Vec::new();
+        let base = decomposition_op.as_ref().map(|(b, _)| *b);
+
+        if let Some((_, contributions)) = decomposition_op {
+            for (other_index, contribution) in contributions {
+                if let Some(other) = abq.get(other_index) {
+                    let inclusions = if contribution < 0. {
+                        let (_, detailed) =
+                            AbqDllite::is_inconsistent_refs_only_detailed(vec![abiq, other], tbox);
+                        collect_inclusions(detailed)
+                    } else {
+                        Vec::new()
+                    };
+
+                    influences.push(Influence {
+                        abiq: other.clone(),
+                        contribution,
+                        inclusions,
+                    });
+                }
+            }
+        }
+
+        // the strongest influences first
+        influences.sort_by(|a, b| {
+            b.contribution
+                .abs()
+                .partial_cmp(&a.contribution.abs())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        Some(RankExplanation {
+            abiq: abiq.clone(),
+            conflict_type,
+            base,
+            influences,
+            inclusions,
+        })
+    }
+
+    pub fn abiq(&self) -> &AbiqDllite {
+        &self.abiq
+    }
+
+    pub fn conflict_type(&self) -> ConflictType {
+        self.conflict_type
+    }
+
+    pub fn base(&self) -> Option<f64> {
+        self.base
+    }
+
+    pub fn influences(&self) -> &Vec<Influence> {
+        &self.influences
+    }
+
+    pub fn conflicts(&self) -> Vec<&Influence> {
+        self.influences.iter().filter(|i| i.is_conflict()).collect()
+    }
+
+    pub fn inclusions(&self) -> &Vec<TbiDllite> {
+        &self.inclusions
+    }
+
+    /// a readable explanation, only the 'limit' strongest influences are shown
+    pub fn to_string_with_symbols(&self, symbols: &SymbolDict, limit: usize) -> String {
+        let to_native = false;
+        let mut s = format!(
+            " -- explanation of {}\n",
+            abiq_to_string(&self.abiq, symbols, to_native).unwrap_or_default()
+        );
+
+        match self.conflict_type {
+            ConflictType::Clean => {
+                s.push_str("    clean: no other assertion contradicts or implies it\n");
+            }
+            ConflictType::SelfConflict => {
+                s.push_str("    self conflicting, it violates alone:\n");
+
+                for tbi in &self.inclusions {
+                    s.push_str(&format!(
+                        "        {}\n",
+                        tbi_to_string(tbi, symbols).unwrap_or_default()
+                    ));
+                }
+            }
+            ConflictType::Conflict => {
+                if let Some(base) = self.base {
+                    s.push_str(&format!("    base value: {:.6}\n", base));
+                }
+
+                for influence in self.influences.iter().take(limit) {
+                    let kind = if influence.is_conflict() {
+                        "contradicted by"
+                    } else {
+                        "implied by"
+                    };
+
+                    s.push_str(&format!(
+                        "    {:+.6} {} {} (credibility {})\n",
+                        influence.contribution,
+                        kind,
+                        abi_to_string(influence.abiq.abi(), symbols).unwrap_or_default(),
+                        influence.abiq.credibility(),
+                    ));
+
+                    for tbi in &influence.inclusions {
+                        s.push_str(&format!(
+                            "        because of {}\n",
+                            tbi_to_string(tbi, symbols).unwrap_or_default()
+                        ));
+                    }
+                }
+
+                if self.influences.len() > limit {
+                    s.push_str(&format!(
+                        "    ... and {} weaker influences\n",
+                        self.influences.len() - limit
+                    ));
+                }
+            }
+        }
+
+        s
+    }
+}
+
+// the tbox items found by a detailed inconsistency check, without repetitions
+fn collect_inclusions(
+    detailed: Option<Vec<(Option<&TbiDllite>, Vec<&AbiqDllite>)>>,
+) -> Vec<TbiDllite> {
+    let mut inclusions: Vec<TbiDllite> = Vec::new();
+
+    for (tbi, _) in detailed.unwrap_or_default().iter() {
+        if let Some(tbi) = tbi {
+            if !inclusions.contains(tbi) {
+                inclusions.push((*tbi).clone());
+            }
+        }
+    }
+
+    inclusions
+}
+
+/// Index of the assertion written as 's' (e.g. 'John : Student' or 'John, DB2 : teaches'),
+/// spaces don't matter.
+pub fn find_assertion(abq: &AbqDllite, s: &str, symbols: &SymbolDict) -> Option<usize> {
+    let wanted: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+
+    abq.items().iter().position(|abiq| {
+        abi_to_string(abiq.abi(), symbols)
+            .map(|a| a.chars().filter(|c| !c.is_whitespace()).collect::<String>() == wanted)
+            .unwrap_or(false)
+    })
+}
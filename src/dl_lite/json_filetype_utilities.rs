@@ -199,7 +199,7 @@ pub fn parse_value_to_tbi(
                             (_, Err(e2)) => Err(Error::new(ErrorKind::InvalidData, e2.to_string())),
                             (Ok(ls), Ok(rs)) => {
                                 let level: usize = 0;
-                                let new_tbi_op = TbiDllite::new(ls.clone(), rs.clone(), level);
+                                let new_tbi_op = TbiDllite::new(*ls, *rs, level);
 
                                 match new_tbi_op {
                                     Some(new_tbi) => Ok(new_tbi),
@@ -24,13 +24,18 @@ along with this program.  If not, see https://www.gnu.org/licenses/.
 pub mod abox;
 pub mod abox_item;
 pub mod abox_item_quantum;
+pub mod chase;
+pub mod explanation;
 pub mod helpers_and_utilities;
 pub mod json_filetype_utilities;
 pub mod native_filetype_utilities;
 pub mod node;
+pub mod obda;
 pub mod ontology;
 pub mod rule;
+pub mod sqlite_interface;
 pub mod string_formatter;
 pub mod tbox;
 pub mod tbox_item;
+pub mod trust;
 pub mod utilities;
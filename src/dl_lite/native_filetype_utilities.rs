@@ -22,6 +22,8 @@ use crate::dl_lite::string_formatter::{
     abiq_to_string, string_to_abiq, string_to_symbol, string_to_tbi, tbi_to_string, PS,
 };
 use crate::dl_lite::tbox::TBDllite;
+use crate::dl_lite::tbox_item::TbiDllite;
+use crate::dl_lite::trust::{split_source_tags, SOURCE_TAG};
 use crate::interface::utilities::parse_name_from_filename;
 use crate::kb::types::DLType;
 use std::collections::HashMap;
@@ -226,6 +228,30 @@ pub fn parse_tbox_native(
     symbols: &SymbolDict,
     verbose: bool,
 ) -> io::Result<TBDllite> {
+    let checked = false;
+
+    read_tbox_native(filename, symbols, checked, verbose).map(|(tb, _)| tb)
+}
+
+/// As 'parse_tbox_native' but a line of the tbox that can't be parsed is an error giving
+/// its number instead of being skipped. The tbox keeps one copy of an axiom written several
+/// times, the other copies are given with the line of the first one (lines count from 1).
+pub fn parse_tbox_native_checked(
+    filename: &str,
+    symbols: &SymbolDict,
+    verbose: bool,
+) -> io::Result<(TBDllite, Vec<(TbiDllite, usize)>)> {
+    let checked = true;
+
+    read_tbox_native(filename, symbols, checked, verbose)
+}
+
+fn read_tbox_native(
+    filename: &str,
+    symbols: &SymbolDict,
+    checked: bool,
+    verbose: bool,
+) -> io::Result<(TBDllite, Vec<(TbiDllite, usize)>)> {
     let file_result = File::open(filename);
 
     match file_result {
@@ -247,6 +273,11 @@ pub fn parse_tbox_native(
 
             let mut tb = TBDllite::new();
 
+            // the line of the first copy of each axiom
+            let mut first_lines: HashMap<TbiDllite, usize> = HashMap::new();
+            let mut repeated: Vec<(TbiDllite, usize)> = Vec::new();
+            let mut line_number: usize = 0;
+
             let mut buffer = String::new();
 
             loop {
@@ -257,6 +288,7 @@ pub fn parse_tbox_native(
                             break;
                         } else {
                             let line = &buffer;
+                            line_number += 1;
 
                             if verbose {
                                 println!(
@@ -331,7 +363,17 @@ pub fn parse_tbox_native(
                                         if !(&tbi_vec).is_empty() {
                                             while !(&tbi_vec).is_empty() {
                                                 let tbi = tbi_vec.pop().unwrap();
-                                                tb.add(tbi);
+
+                                                match first_lines.get(&tbi) {
+                                                    Some(first) => {
+                                                        repeated.push((tbi, *first));
+                                                    }
+                                                    Option::None => {
+                                                        first_lines
+                                                            .insert(tbi.clone(), line_number);
+                                                        tb.add(tbi);
+                                                    }
+                                                }
                                             }
                                         }
                                     }
@@ -339,6 +381,16 @@ pub fn parse_tbox_native(
                                         if verbose {
                                             println!(" -- native_utilities::parse_tbox_native: couldn't parse: {}", &e);
                                         }
+
+                                        if checked && !not_ignored.is_empty() {
+                                            return Err(Error::new(
+                                                ErrorKind::InvalidData,
+                                                format!(
+                                                    "line {} can't be parsed: {}",
+                                                    line_number, e
+                                                ),
+                                            ));
+                                        }
                                     }
                                 }
                             } else if verbose {
@@ -369,7 +421,7 @@ pub fn parse_tbox_native(
 
                 Result::Err(new_error)
             } else {
-                Result::Ok(tb)
+                Result::Ok((tb, repeated))
             }
         }
     }
@@ -403,6 +455,28 @@ pub fn tbox_to_native_string(
     Some(res)
 }
 
+// nominals come with the abox and Top and Bottom are always there, only concepts
+// and roles are written
+pub fn symbols_to_native_string(symbols: &SymbolDict) -> String {
+    let mut names: Vec<(&String, &DLType)> = symbols.iter().map(|(k, (_, t))| (k, t)).collect();
+    names.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut res = String::from("BEGINSYMBOL\n");
+
+    for (name, t) in names {
+        let type_in_string = match t {
+            DLType::BaseConcept => "concept",
+            DLType::BaseRole => "role",
+            _ => continue,
+        };
+
+        res.push_str(&format!("{}: {}\n", type_in_string, name));
+    }
+
+    res.push_str("ENDSYMBOL\n");
+    res
+}
+
 pub fn find_bound_of_symbols(symbols: &SymbolDict) -> (usize, usize) {
     if symbols.is_empty() {
         (0, 0)
@@ -523,13 +597,26 @@ pub fn parse_abox_native_quantum(
                                     }
                                 }
 
+                                // source tags are at the end
+                                let (not_ignored, source_tags) = split_source_tags(not_ignored);
+
                                 let (parsed_result, current_id_result) =
                                     string_to_abiq(not_ignored, symbols, current_id, false); // parsing from file should be a new abox
                                 current_id = current_id_result;
 
                                 match parsed_result {
-                                    Ok((abi, mut to_be_added)) => {
-                                        ab.add(abi);
+                                    Ok((mut abi, mut to_be_added)) => {
+                                        if source_tags.is_empty() {
+                                            // a repeated fact keeps its first credibility
+                                            ab.add(abi);
+                                        } else {
+                                            for source in source_tags {
+                                                abi.add_source(source);
+                                            }
+
+                                            // the same fact from several sources is kept once
+                                            ab.add_with_provenance(abi);
+                                        }
 
                                         if !to_be_added.is_empty() {
                                             while !(&to_be_added).is_empty() {
@@ -591,11 +678,38 @@ pub fn abox_to_native_string_quantum(
 
     for abi in abox.items() {
         if !(abi.is_trivial() && dont_write_trivial) {
-            let abi_str_op = abiq_to_string(abi, symbols, to_native);
+            // provenance is written as source tags, each source with the credibility
+            // it gave, not the combined one, thus a line for each credibility
+            let mut groups: Vec<(f64, Vec<&str>)> = Vec::new();
+
+            for (source, credibility) in abi.evidence() {
+                match groups.iter_mut().find(|(c, _)| c == credibility) {
+                    Some((_, sources)) => sources.push(source),
+                    Option::None => groups.push((*credibility, vec![source])),
+                }
+            }
 
-            if let Some(some_abi_str) = abi_str_op {
-                res.push_str(some_abi_str.as_str());
-                res.push('\n');
+            if groups.is_empty() {
+                groups.push((abi.credibility(), Vec::new()));
+            }
+
+            for (credibility, sources) in groups {
+                let mut abi_line = abi.clone();
+                abi_line.set_credibility(credibility);
+
+                let abi_str_op = abiq_to_string(&abi_line, symbols, to_native);
+
+                if let Some(some_abi_str) = abi_str_op {
+                    res.push_str(some_abi_str.as_str());
+
+                    for source in sources {
+                        res.push(' ');
+                        res.push(SOURCE_TAG);
+                        res.push_str(source);
+                    }
+
+                    res.push('\n');
+                }
             }
         }
     }
@@ -603,3 +717,45 @@ pub fn abox_to_native_string_quantum(
     res.push_str("ENDABOX\n");
     Some(res)
 }
+
+// the blocks of a native file, each one has its parser
+const NATIVE_BLOCKS: [&str; 4] = ["SYMBOL", "TBOX", "ABOX", "SOURCES"];
+
+/// A line of a native file: its number (from 0), the block it is in, what is parsed
+/// and its comment (without the '//'). The 'BEGIN' and 'END' lines are in no block.
+pub type NativeLine<'a> = (usize, Option<&'static str>, &'a str, Option<&'a str>);
+
+/// Splits the text of a native file in lines, as the parsers read them.
+pub fn native_lines(text: &str) -> Vec<NativeLine<'_>> {
+    let mut lines: Vec<NativeLine> = Vec::new();
+    let mut current: Option<&'static str> = Option::None;
+
+    for (number, line) in text.lines().enumerate() {
+        let (content, comment) = match line.find("//") {
+            Some(index) => (&line[..index], Some(&line[index + 2..])),
+            Option::None => (line, Option::None),
+        };
+        let marker = content.trim();
+
+        let begins = NATIVE_BLOCKS
+            .iter()
+            .find(|block| marker.strip_prefix("BEGIN") == Some(**block));
+        let ends = NATIVE_BLOCKS
+            .iter()
+            .find(|block| marker.strip_prefix("END") == Some(**block));
+
+        match (begins, ends) {
+            (Some(block), _) => {
+                current = Some(*block);
+                lines.push((number, Option::None, content, comment));
+            }
+            (_, Some(_)) => {
+                current = Option::None;
+                lines.push((number, Option::None, content, comment));
+            }
+            _ => lines.push((number, current, content, comment)),
+        }
+    }
+
+    lines
+}
@@ -20,14 +20,16 @@ along with this program.  If not, see https://www.gnu.org/licenses/.
 /// The ItemDllite struct is the materialization of the Item trait present in
 /// kb module 'knowledge_base.rs' file, it is the basic construct for ontologies
 /// objects.
-/// The inner structure follows a basic enum construction:
+/// The inner structure follows a basic enum construction (see 'Unpacked'):
 /// - a bottom object
 /// - a top object
 /// - a role object with an identifier (a integer)
 /// - a concept object with an identifier (a integer)
 /// - a nominal object with an identifier (a integer)
 /// - a complex object with a modifier (inverse, negated, exists quantifier)
-///   and a reference to a child object.
+///   and a child object.
+///
+/// Items are packed in a single integer, cloning one never allocates.
 /*
 TODO: I'm making a big choice here, top will be the negated one, that way we respect that no
       negations are present in the left hand
@@ -37,7 +39,6 @@ use std::fmt;
 use crate::kb::knowledge_base::Item;
 use crate::kb::types::DLType;
 use std::cmp::Ordering;
-use std::ops::Deref;
 
 #[derive(PartialEq, Eq, Debug, Hash, Copy, Clone)]
 pub enum Mod {
@@ -46,28 +47,70 @@ pub enum Mod {
     E, // exists
 }
 
-#[derive(PartialEq, Eq, Debug, Hash, Clone)]
-pub enum ItemDllite {
-    B,                       // bottom
-    T,                       // top
-    R(usize),                // base role
-    C(usize),                // base concept
-    N(usize),                // nominal
-    X(Mod, Box<ItemDllite>), // complex type of items (e.g. inverse role or negated concept)
+/*
+   packed layout of an item, from the lowest bits:
+   - bits 0..40: identifier of the base symbol (0 for bottom, 1 for top)
+   - bits 40..43: kind of the base symbol: bottom, top, role, concept or nominal
+   - bits 43..46: number of modifiers on the base symbol
+   - bits 46..60: the modifiers, two bits each, the innermost first
+   the modifiers of an item are always cleared past its number of modifiers, thus two items
+   are equal exactly when their integers are
+*/
+const ID_BITS: u64 = 40;
+const ID_MASK: u64 = (1 << ID_BITS) - 1;
+const KIND_SHIFT: u64 = 40;
+const KIND_MASK: u64 = 0b111;
+const DEPTH_SHIFT: u64 = 43;
+const DEPTH_MASK: u64 = 0b111;
+const MODS_SHIFT: u64 = 46;
+const MAX_DEPTH: u64 = 7;
+
+const KIND_BOTTOM: u64 = 0;
+const KIND_TOP: u64 = 1;
+const KIND_ROLE: u64 = 2;
+const KIND_CONCEPT: u64 = 3;
+const KIND_NOMINAL: u64 = 4;
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone)]
+pub struct ItemDllite(u64);
+
+/// An item with its outermost layer opened, to match on it.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Unpacked {
+    B,                  // bottom
+    T,                  // top
+    R(usize),           // base role
+    C(usize),           // base concept
+    N(usize),           // nominal
+    X(Mod, ItemDllite), // complex type of items (e.g. inverse role or negated concept)
+}
+
+impl fmt::Debug for ItemDllite {
+    // the same output as the former enum
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unpack() {
+            Unpacked::B => write!(f, "B"),
+            Unpacked::T => write!(f, "T"),
+            Unpacked::R(n) => write!(f, "R({})", n),
+            Unpacked::C(n) => write!(f, "C({})", n),
+            Unpacked::N(n) => write!(f, "N({})", n),
+            Unpacked::X(m, bn) => write!(f, "X({:?}, {:?})", m, bn),
+        }
+    }
 }
 
 impl fmt::Display for ItemDllite {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ItemDllite::B => write!(f, "<B>"),
-            ItemDllite::T => write!(f, "<T>"),
-            ItemDllite::R(n) => write!(f, "r({})", n),
-            ItemDllite::C(n) => write!(f, "c({})", n),
-            ItemDllite::N(n) => write!(f, "n({})", n),
-            ItemDllite::X(m, bn) => match m {
-                Mod::N => write!(f, "-{}", *((*bn).deref())),
-                Mod::I => write!(f, "{}^-", *((*bn).deref())),
-                Mod::E => write!(f, "E{}", *((*bn).deref())),
+        match self.unpack() {
+            Unpacked::B => write!(f, "<B>"),
+            Unpacked::T => write!(f, "<T>"),
+            Unpacked::R(n) => write!(f, "r({})", n),
+            Unpacked::C(n) => write!(f, "c({})", n),
+            Unpacked::N(n) => write!(f, "n({})", n),
+            Unpacked::X(m, bn) => match m {
+                Mod::N => write!(f, "-{}", bn),
+                Mod::I => write!(f, "{}^-", bn),
+                Mod::E => write!(f, "E{}", bn),
             },
         }
     }
@@ -83,6 +126,9 @@ impl PartialOrd for ItemDllite {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         if self == other {
             Some(Ordering::Equal)
+        } else if let (Some(key_self), Some(key_other)) = (self.order_key(), other.order_key()) {
+            // well formed items, the rule below is already in the key
+            Some(key_self.cmp(&key_other))
         } else {
             if (self.t().is_concept_type() && !other.t().is_concept_type())
                 || (self.t().is_role_type() && other.t().is_nominal_type())
@@ -107,9 +153,9 @@ impl PartialOrd for ItemDllite {
                         DLType::ExistsConcept => match other.t() {
                             DLType::BaseConcept => Some(Ordering::Greater),
                             DLType::NegatedConcept => Some(Ordering::Less),
-                            DLType::ExistsConcept => match (self, other) {
-                                (ItemDllite::X(Mod::E, bnself), ItemDllite::X(Mod::E, bnother)) => {
-                                    bnself.partial_cmp(bnother)
+                            DLType::ExistsConcept => match (self.unpack(), other.unpack()) {
+                                (Unpacked::X(Mod::E, bnself), Unpacked::X(Mod::E, bnother)) => {
+                                    bnself.partial_cmp(&bnother)
                                 }
                                 (_, _) => Option::None,
                             },
@@ -117,9 +163,9 @@ impl PartialOrd for ItemDllite {
                         },
                         DLType::NegatedConcept => match other.t() {
                             DLType::BaseConcept | DLType::ExistsConcept => Some(Ordering::Greater),
-                            DLType::NegatedConcept => match (self, other) {
-                                (ItemDllite::X(Mod::N, bnself), ItemDllite::X(Mod::N, bnother)) => {
-                                    bnself.partial_cmp(bnother)
+                            DLType::NegatedConcept => match (self.unpack(), other.unpack()) {
+                                (Unpacked::X(Mod::N, bnself), Unpacked::X(Mod::N, bnother)) => {
+                                    bnself.partial_cmp(&bnother)
                                 }
                                 (_, _) => Option::None,
                             },
@@ -138,9 +184,9 @@ impl PartialOrd for ItemDllite {
                     DLType::InverseRole => match other.t() {
                         DLType::BaseRole => Some(Ordering::Greater),
                         DLType::NegatedRole => Some(Ordering::Less),
-                        DLType::InverseRole => match (self, other) {
-                            (ItemDllite::X(Mod::I, bnself), ItemDllite::X(Mod::I, bnother)) => {
-                                bnself.partial_cmp(bnother)
+                        DLType::InverseRole => match (self.unpack(), other.unpack()) {
+                            (Unpacked::X(Mod::I, bnself), Unpacked::X(Mod::I, bnother)) => {
+                                bnself.partial_cmp(&bnother)
                             }
                             (_, _) => Option::None,
                         },
@@ -148,9 +194,9 @@ impl PartialOrd for ItemDllite {
                     },
                     DLType::NegatedRole => match other.t() {
                         DLType::BaseRole | DLType::InverseRole => Some(Ordering::Greater),
-                        DLType::NegatedRole => match (self, other) {
-                            (ItemDllite::X(Mod::N, bnself), ItemDllite::X(Mod::N, bnother)) => {
-                                bnself.partial_cmp(bnother)
+                        DLType::NegatedRole => match (self.unpack(), other.unpack()) {
+                            (Unpacked::X(Mod::N, bnself), Unpacked::X(Mod::N, bnother)) => {
+                                bnself.partial_cmp(&bnother)
                             }
                             (_, _) => Option::None,
                         },
@@ -181,18 +227,16 @@ impl Item for ItemDllite {
     /// Every Item as a type (a DLType).
     fn t(&self) -> DLType {
         // they have to be well formed, otherwise it will fail
-        match self {
-            ItemDllite::B => DLType::Bottom,
-            ItemDllite::T => DLType::Top,
-            ItemDllite::C(_) => DLType::BaseConcept,
-            ItemDllite::R(_) => DLType::BaseRole,
-            ItemDllite::N(_) => DLType::Nominal,
-            ItemDllite::X(t, bn) => match (t, bn.deref()) {
+        match self.unpack() {
+            Unpacked::B => DLType::Bottom,
+            Unpacked::T => DLType::Top,
+            Unpacked::C(_) => DLType::BaseConcept,
+            Unpacked::R(_) => DLType::BaseRole,
+            Unpacked::N(_) => DLType::Nominal,
+            Unpacked::X(t, bn) => match (t, bn.unpack()) {
                 // if the item is not well formed is not the job of this function to detect it
-                (Mod::N, ItemDllite::R(_)) | (Mod::N, ItemDllite::X(Mod::I, _)) => {
-                    DLType::NegatedRole
-                }
-                (Mod::N, ItemDllite::C(_)) | (Mod::N, ItemDllite::X(Mod::E, _)) => {
+                (Mod::N, Unpacked::R(_)) | (Mod::N, Unpacked::X(Mod::I, _)) => DLType::NegatedRole,
+                (Mod::N, Unpacked::C(_)) | (Mod::N, Unpacked::X(Mod::E, _)) => {
                     DLType::NegatedConcept
                 }
                 (Mod::I, _) => DLType::InverseRole,
@@ -205,18 +249,11 @@ impl Item for ItemDllite {
         }
     }
 
-    /// Returns a reference to the base node, recursive function
+    /// Returns the base node
     /// (e.g. 'NOT EXISTS INV eats' -> 'eats'
     /// but also 'eats' -> 'eats').
-    fn base(node: &ItemDllite) -> &Self {
-        match node {
-            ItemDllite::B => &ItemDllite::B,
-            ItemDllite::T => &ItemDllite::T,
-            ItemDllite::C(_) => node,
-            ItemDllite::R(_) => node,
-            ItemDllite::N(_) => node,
-            ItemDllite::X(_, bn) => ItemDllite::base(bn),
-        }
+    fn base(node: &ItemDllite) -> Self {
+        ItemDllite(node.0 & (ID_MASK | (KIND_MASK << KIND_SHIFT)))
     }
 
     /// tries to return the depth-th child of the current node
@@ -227,17 +264,13 @@ impl Item for ItemDllite {
     /// ('A INTER B', depth: 2) -> None
     /// ('NOT A', depth: 1) -> Some(['A'])
     /// )
-    fn child(node: Option<&ItemDllite>, depth: usize) -> Option<Vec<&Self>> {
+    fn child(node: Option<&ItemDllite>, depth: usize) -> Option<Vec<Self>> {
         match node {
             Option::None => Option::None,
-            Some(n) => match (n, depth) {
-                (_, 0) => Some(vec![node.unwrap()]),
-                (ItemDllite::B, _)
-                | (ItemDllite::T, _)
-                | (ItemDllite::C(_), _)
-                | (ItemDllite::R(_), _)
-                | (ItemDllite::N(_), _) => Option::None, // there is no child for the bases types
-                (ItemDllite::X(_, bn), _) => ItemDllite::child(Some(&bn), depth - 1),
+            Some(n) => match (n.unpack(), depth) {
+                (_, 0) => Some(vec![*n]),
+                (Unpacked::X(_, bn), _) => ItemDllite::child(Some(&bn), depth - 1),
+                (_, _) => Option::None, // there is no child for the bases types
             },
         }
     }
@@ -245,11 +278,127 @@ impl Item for ItemDllite {
     /// returns true if self is equal to Top (which is the negation of bottom, the base type)
     /// or if self is effectively a negated construct
     fn is_negated(&self) -> bool {
-        matches!(self, ItemDllite::T | ItemDllite::X(Mod::N, _))
+        *self == ItemDllite::T || self.outer_mod() == Some(Mod::N)
     }
 }
 
 impl ItemDllite {
+    pub const B: ItemDllite = ItemDllite(KIND_BOTTOM << KIND_SHIFT);
+    pub const T: ItemDllite = ItemDllite((KIND_TOP << KIND_SHIFT) | 1);
+
+    fn from_base(kind: u64, n: usize) -> ItemDllite {
+        if n as u64 > ID_MASK {
+            println!("identifier too big for an item: {}", n);
+            std::process::exit(exitcode::DATAERR)
+        }
+
+        ItemDllite((kind << KIND_SHIFT) | n as u64)
+    }
+
+    pub fn role(n: usize) -> ItemDllite {
+        ItemDllite::from_base(KIND_ROLE, n)
+    }
+
+    pub fn concept(n: usize) -> ItemDllite {
+        ItemDllite::from_base(KIND_CONCEPT, n)
+    }
+
+    pub fn nominal(n: usize) -> ItemDllite {
+        ItemDllite::from_base(KIND_NOMINAL, n)
+    }
+
+    fn depth(&self) -> u64 {
+        (self.0 >> DEPTH_SHIFT) & DEPTH_MASK
+    }
+
+    /*
+       the place of a well formed item in the order of 'partial_cmp', read from the bits: the
+       rank of its shape (kind and modifiers) above its identifier, None for any other shape
+    */
+    fn order_key(&self) -> Option<u64> {
+        let kind = (self.0 >> KIND_SHIFT) & KIND_MASK;
+        let depth = self.depth();
+        let mods = self.0 >> MODS_SHIFT;
+
+        // modifiers innermost first, two bits each: 1 negation, 2 inverse, 3 exists
+        let rank: u64 = match (kind, depth, mods) {
+            (KIND_BOTTOM, 0, _) => 0,
+            (KIND_CONCEPT, 0, _) => 1,
+            (KIND_ROLE, 1, 0b11) => 2,     // EXISTS r
+            (KIND_ROLE, 2, 0b1110) => 3,   // EXISTS INV r
+            (KIND_CONCEPT, 1, 0b01) => 4,  // NOT c
+            (KIND_ROLE, 2, 0b0111) => 5,   // NOT EXISTS r
+            (KIND_ROLE, 3, 0b011110) => 6, // NOT EXISTS INV r
+            (KIND_TOP, 0, _) => 7,
+            (KIND_ROLE, 0, _) => 8,
+            (KIND_ROLE, 1, 0b10) => 9,    // INV r
+            (KIND_ROLE, 1, 0b01) => 10,   // NOT r
+            (KIND_ROLE, 2, 0b0110) => 11, // NOT INV r
+            (KIND_NOMINAL, 0, _) => 12,
+            (_, _, _) => return Option::None,
+        };
+
+        Some((rank << ID_BITS) | (self.0 & ID_MASK))
+    }
+
+    fn outer_mod(&self) -> Option<Mod> {
+        match self.depth() {
+            0 => Option::None,
+            depth => match (self.0 >> (MODS_SHIFT + 2 * (depth - 1))) & 0b11 {
+                1 => Some(Mod::N),
+                2 => Some(Mod::I),
+                _ => Some(Mod::E),
+            },
+        }
+    }
+
+    /// puts the modifier m over child, as 'X(m, child)' in the former enum
+    pub fn modified(m: Mod, child: ItemDllite) -> ItemDllite {
+        let depth = child.depth();
+
+        if depth == MAX_DEPTH {
+            println!("too many modifiers for an item: {}", child);
+            std::process::exit(exitcode::DATAERR)
+        }
+
+        let bits: u64 = match m {
+            Mod::N => 1,
+            Mod::I => 2,
+            Mod::E => 3,
+        };
+
+        let without_depth = child.0 & !(DEPTH_MASK << DEPTH_SHIFT);
+
+        ItemDllite(
+            without_depth | ((depth + 1) << DEPTH_SHIFT) | (bits << (MODS_SHIFT + 2 * depth)),
+        )
+    }
+
+    /// opens the outermost layer of self
+    pub fn unpack(&self) -> Unpacked {
+        match self.outer_mod() {
+            Some(m) => {
+                let depth = self.depth();
+                let without_depth = self.0
+                    & !(DEPTH_MASK << DEPTH_SHIFT)
+                    & !(0b11 << (MODS_SHIFT + 2 * (depth - 1)));
+
+                Unpacked::X(m, ItemDllite(without_depth | ((depth - 1) << DEPTH_SHIFT)))
+            }
+            Option::None => {
+                let n = (self.0 & ID_MASK) as usize;
+
+                match (self.0 >> KIND_SHIFT) & KIND_MASK {
+                    KIND_BOTTOM => Unpacked::B,
+                    KIND_TOP => Unpacked::T,
+                    KIND_ROLE => Unpacked::R(n),
+                    KIND_CONCEPT => Unpacked::C(n),
+                    _ => Unpacked::N(n),
+                }
+            }
+        }
+    }
+
     /// creates a new ItemDllite from the specification
     /// an integer n as identifier and a type t
     /// the integer is wrapped in a Option type to allow for border cases
@@ -258,9 +407,9 @@ impl ItemDllite {
         match (n, t) {
             (_, DLType::Bottom) => Some(ItemDllite::B),
             (_, DLType::Top) => Some(ItemDllite::T),
-            (Option::Some(n), DLType::BaseConcept) => Some(ItemDllite::C(n)),
-            (Option::Some(n), DLType::BaseRole) => Some(ItemDllite::R(n)),
-            (Option::Some(n), DLType::Nominal) => Some(ItemDllite::N(n)),
+            (Option::Some(n), DLType::BaseConcept) => Some(ItemDllite::concept(n)),
+            (Option::Some(n), DLType::BaseRole) => Some(ItemDllite::role(n)),
+            (Option::Some(n), DLType::Nominal) => Some(ItemDllite::nominal(n)),
             (_, _) => Option::None,
         }
     }
@@ -272,16 +421,11 @@ impl ItemDllite {
             if you try to write a parser for rustoner be aware that this values
             are always reserved
         */
-        match self {
-            ItemDllite::T => 1,
-            ItemDllite::B => 0,
-            ItemDllite::C(n) | ItemDllite::R(n) | ItemDllite::N(n) => *n,
-            ItemDllite::X(_, bn) => (*bn).n(),
-        }
+        (self.0 & ID_MASK) as usize
     }
 
     pub fn is_purely_negated(&self) -> bool {
-        matches!(self, ItemDllite::X(Mod::N, _))
+        self.outer_mod() == Some(Mod::N)
     }
 
     /// tries to build an Exists concept from self, will fail if self is not
@@ -290,20 +434,20 @@ impl ItemDllite {
     /// 'NOT teaches' -> None
     /// 'Human' is a concept: 'Human' -> None
     pub fn exists(self) -> Option<Self> {
-        match (&self).t() {
-            DLType::BaseRole | DLType::InverseRole => Some(ItemDllite::X(Mod::E, Box::new(self))),
+        match self.t() {
+            DLType::BaseRole | DLType::InverseRole => Some(ItemDllite::modified(Mod::E, self)),
             _ => Option::None,
         }
     }
 
-    /// build a new ItemDllite struct with self as a child and a
+    /// build a new ItemDllite with self as a child and a
     /// negation (NOT) modifier
     pub fn negate(self) -> Self {
-        match self {
-            ItemDllite::X(Mod::N, bn) => *bn,
-            ItemDllite::B => ItemDllite::T,
-            ItemDllite::T => ItemDllite::B,
-            _ => ItemDllite::X(Mod::N, Box::new(self)),
+        match self.unpack() {
+            Unpacked::X(Mod::N, bn) => bn,
+            Unpacked::B => ItemDllite::T,
+            Unpacked::T => ItemDllite::B,
+            _ => ItemDllite::modified(Mod::N, self),
         }
     }
 
@@ -311,14 +455,14 @@ impl ItemDllite {
     /// e.g. ('NOT Human', 'Human') -> true
     /// ('NOT Human', 'Mortal') -> false
     pub fn is_negation(&self, other: &ItemDllite) -> bool {
-        match (self, other) {
+        match (self.unpack(), other.unpack()) {
             // bottom and top
-            (ItemDllite::B, ItemDllite::T) | (ItemDllite::T, ItemDllite::B) => true,
+            (Unpacked::B, Unpacked::T) | (Unpacked::T, Unpacked::B) => true,
             // if both are negated return false
-            (ItemDllite::X(Mod::N, _), ItemDllite::X(Mod::N, _)) => false,
+            (Unpacked::X(Mod::N, _), Unpacked::X(Mod::N, _)) => false,
             // if one is negated compare its child with the other
-            (ItemDllite::X(Mod::N, bn), _) => bn.deref() == other,
-            (_, ItemDllite::X(Mod::N, bn)) => self == bn.deref(),
+            (Unpacked::X(Mod::N, bn), _) => bn == *other,
+            (_, Unpacked::X(Mod::N, bn)) => *self == bn,
             // anything else is false
             (_, _) => false,
         }
@@ -328,9 +472,9 @@ impl ItemDllite {
     /// which is a base role or an inverse role,
     /// a negated role, a concept or a nominal are all not invertible
     pub fn inverse(self) -> Option<Self> {
-        match self {
-            ItemDllite::R(_) => Some(ItemDllite::X(Mod::I, Box::new(self))),
-            ItemDllite::X(Mod::I, bn) => Some(*bn),
+        match self.unpack() {
+            Unpacked::R(_) => Some(ItemDllite::modified(Mod::I, self)),
+            Unpacked::X(Mod::I, bn) => Some(bn),
             _ => Option::None,
         }
     }
@@ -338,10 +482,248 @@ impl ItemDllite {
     /// check if other is the inverse of self, this implicitly
     /// implies that both other and self are roles
     pub fn is_inverse(&self, other: &Self) -> bool {
-        match (self, other) {
-            (ItemDllite::X(Mod::I, bn), _) => bn.deref() == other,
-            (_, ItemDllite::X(Mod::I, bn)) => self == bn.deref(),
+        match (self.unpack(), other.unpack()) {
+            (Unpacked::X(Mod::I, bn), _) => bn == *other,
+            (_, Unpacked::X(Mod::I, bn)) => *self == bn,
             (_, _) => false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+
+    // identifiers of the base symbols, 0 and 1 are bottom and top
+    const IDENTIFIERS: [usize; 4] = [2, 3, 17, 1 << 33];
+
+    // the boxed enum used before the packed items, trimmed to what is checked
+    #[derive(PartialEq, Eq, Debug, Hash, Clone)]
+    enum Boxed {
+        B,
+        T,
+        R(usize),
+        C(usize),
+        N(usize),
+        X(Mod, Box<Boxed>),
+    }
+
+    impl fmt::Display for Boxed {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Boxed::B => write!(f, "<B>"),
+                Boxed::T => write!(f, "<T>"),
+                Boxed::R(n) => write!(f, "r({})", n),
+                Boxed::C(n) => write!(f, "c({})", n),
+                Boxed::N(n) => write!(f, "n({})", n),
+                Boxed::X(Mod::N, bn) => write!(f, "-{}", bn),
+                Boxed::X(Mod::I, bn) => write!(f, "{}^-", bn),
+                Boxed::X(Mod::E, bn) => write!(f, "E{}", bn),
+            }
+        }
+    }
+
+    impl Boxed {
+        fn t(&self) -> DLType {
+            match self {
+                Boxed::B => DLType::Bottom,
+                Boxed::T => DLType::Top,
+                Boxed::C(_) => DLType::BaseConcept,
+                Boxed::R(_) => DLType::BaseRole,
+                Boxed::N(_) => DLType::Nominal,
+                Boxed::X(Mod::N, bn) => match **bn {
+                    Boxed::R(_) | Boxed::X(Mod::I, _) => DLType::NegatedRole,
+                    _ => DLType::NegatedConcept,
+                },
+                Boxed::X(Mod::I, _) => DLType::InverseRole,
+                Boxed::X(Mod::E, _) => DLType::ExistsConcept,
+            }
+        }
+
+        fn n(&self) -> usize {
+            match self {
+                Boxed::B => 0,
+                Boxed::T => 1,
+                Boxed::C(n) | Boxed::R(n) | Boxed::N(n) => *n,
+                Boxed::X(_, bn) => bn.n(),
+            }
+        }
+
+        // the rule of the former 'partial_cmp', for well formed items
+        fn order(&self, other: &Boxed) -> Ordering {
+            // concepts, then roles, then nominals
+            let family = |item: &Boxed| match item.t() {
+                t if t.is_concept_type() => 0,
+                t if t.is_role_type() => 1,
+                _ => 2,
+            };
+            // the types inside a family
+            let rank = |item: &Boxed| match item.t() {
+                DLType::Bottom | DLType::BaseConcept | DLType::BaseRole | DLType::Nominal => 0,
+                DLType::ExistsConcept | DLType::InverseRole => 1,
+                _ => 2,
+            };
+
+            match (self, other) {
+                _ if self == other => Ordering::Equal,
+                _ if family(self) != family(other) => family(self).cmp(&family(other)),
+                (Boxed::B, _) | (_, Boxed::T) => Ordering::Less,
+                (Boxed::T, _) | (_, Boxed::B) => Ordering::Greater,
+                _ if rank(self) != rank(other) => rank(self).cmp(&rank(other)),
+                (Boxed::X(_, bn_self), Boxed::X(_, bn_other)) => bn_self.order(bn_other),
+                (_, _) => self.n().cmp(&other.n()),
+            }
+        }
+
+        fn negate(self) -> Boxed {
+            match self {
+                Boxed::X(Mod::N, bn) => *bn,
+                Boxed::B => Boxed::T,
+                Boxed::T => Boxed::B,
+                _ => Boxed::X(Mod::N, Box::new(self)),
+            }
+        }
+
+        fn inverse(self) -> Option<Boxed> {
+            match self {
+                Boxed::R(_) => Some(Boxed::X(Mod::I, Box::new(self))),
+                Boxed::X(Mod::I, bn) => Some(*bn),
+                _ => Option::None,
+            }
+        }
+
+        fn exists(self) -> Option<Boxed> {
+            match self.t() {
+                DLType::BaseRole | DLType::InverseRole => Some(Boxed::X(Mod::E, Box::new(self))),
+                _ => Option::None,
+            }
+        }
+
+        fn base(&self) -> &Boxed {
+            match self {
+                Boxed::X(_, bn) => bn.base(),
+                _ => self,
+            }
+        }
+
+        fn child(&self, depth: usize) -> Option<&Boxed> {
+            match (self, depth) {
+                (_, 0) => Some(self),
+                (Boxed::X(_, bn), _) => bn.child(depth - 1),
+                (_, _) => Option::None,
+            }
+        }
+
+        fn is_negation(&self, other: &Boxed) -> bool {
+            match (self, other) {
+                (Boxed::B, Boxed::T) | (Boxed::T, Boxed::B) => true,
+                (Boxed::X(Mod::N, _), Boxed::X(Mod::N, _)) => false,
+                (Boxed::X(Mod::N, bn), _) => **bn == *other,
+                (_, Boxed::X(Mod::N, bn)) => *self == **bn,
+                (_, _) => false,
+            }
+        }
+
+        fn is_inverse(&self, other: &Boxed) -> bool {
+            match (self, other) {
+                (Boxed::X(Mod::I, bn), _) => **bn == *other,
+                (_, Boxed::X(Mod::I, bn)) => *self == **bn,
+                (_, _) => false,
+            }
+        }
+    }
+
+    fn packed(boxed: &Boxed) -> ItemDllite {
+        match boxed {
+            Boxed::B => ItemDllite::B,
+            Boxed::T => ItemDllite::T,
+            Boxed::R(n) => ItemDllite::role(*n),
+            Boxed::C(n) => ItemDllite::concept(*n),
+            Boxed::N(n) => ItemDllite::nominal(*n),
+            Boxed::X(m, bn) => ItemDllite::modified(*m, packed(bn)),
+        }
+    }
+
+    // every well formed item over a few identifiers, as built by the parser
+    fn all_items() -> Vec<Boxed> {
+        let mut items: Vec<Boxed> = vec![Boxed::B, Boxed::T];
+
+        for n in IDENTIFIERS {
+            let r = Boxed::R(n);
+            let r_inv = Boxed::X(Mod::I, Box::new(r.clone()));
+            let concepts = vec![
+                Boxed::C(n),
+                Boxed::X(Mod::E, Box::new(r.clone())),
+                Boxed::X(Mod::E, Box::new(r_inv.clone())),
+            ];
+
+            for item in vec![r, r_inv].into_iter().chain(concepts) {
+                items.push(Boxed::X(Mod::N, Box::new(item.clone())));
+                items.push(item);
+            }
+
+            items.push(Boxed::N(n));
+        }
+
+        items
+    }
+
+    fn hash_of(item: &ItemDllite) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn operations_match_the_boxed_items() {
+        for boxed in all_items() {
+            let item = packed(&boxed);
+
+            assert_eq!(format!("{}", item), format!("{}", boxed));
+            assert_eq!(item.t(), boxed.t(), "type of {}", boxed);
+            assert_eq!(item.n(), boxed.n(), "identifier of {}", boxed);
+            assert_eq!(
+                item.is_negated(),
+                matches!(boxed, Boxed::T | Boxed::X(Mod::N, _))
+            );
+            assert_eq!(ItemDllite::base(&item), packed(boxed.base()));
+            assert_eq!(item.negate(), packed(&boxed.clone().negate()));
+            assert_eq!(item.inverse(), boxed.clone().inverse().as_ref().map(packed));
+            assert_eq!(item.exists(), boxed.clone().exists().as_ref().map(packed));
+
+            for depth in 0..5 {
+                assert_eq!(
+                    ItemDllite::child(Some(&item), depth),
+                    boxed.child(depth).map(|child| vec![packed(child)]),
+                    "child {} of {}",
+                    depth,
+                    boxed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pairs_match_the_boxed_items() {
+        let boxed_items = all_items();
+        let items = boxed_items.iter().map(packed).collect::<Vec<_>>();
+
+        for (boxed_i, item_i) in boxed_items.iter().zip(&items) {
+            for (boxed_j, item_j) in boxed_items.iter().zip(&items) {
+                let names = format!("{} and {}", boxed_i, boxed_j);
+
+                assert_eq!(item_i.cmp(item_j), boxed_i.order(boxed_j), "{}", names);
+                assert_eq!(item_i == item_j, boxed_i == boxed_j, "{}", names);
+                assert!(item_i != item_j || hash_of(item_i) == hash_of(item_j));
+                assert_eq!(item_i.is_negation(item_j), boxed_i.is_negation(boxed_j));
+                assert_eq!(item_i.is_inverse(item_j), boxed_i.is_inverse(boxed_j));
+            }
+        }
+
+        let distinct = items.iter().collect::<HashSet<_>>().len();
+        assert_eq!(distinct, boxed_items.len());
+    }
+}
@@ -0,0 +1,745 @@
+/*
+UMONS 2021
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+
+use rusqlite::{Connection, Error, Result};
+
+use crate::dl_lite::node::{ItemDllite, Mod, Unpacked};
+use crate::dl_lite::string_formatter::{node_to_string, string_to_node};
+use crate::dl_lite::tbox::TBDllite;
+use crate::dl_lite::tbox_item::TbiDllite;
+use crate::kb::knowledge_base::{Item, SymbolDict, TBox, TBoxItem};
+use crate::kb::types::DLType;
+
+/*
+   ontology based data access: the abox is never built, assertions live in a relational
+   database and a mapping tells which sql query gives the instances of each symbol,
+   a concept is mapped to a query with one column, a role to a query with two columns
+   (the subject and the object)
+   asking for the instances of a concept or role is done by rewriting it with the positive
+   inclusions of the tbox into a union of the mapping queries, thus the database does all
+   the work, the same goes for the consistency: each negative inclusion 'X < NOT Y' becomes
+   a violation query intersecting the rewriting of 'X' and 'Y', the abox is inconsistent
+   if and only if one of them has an answer
+*/
+
+/// Every violation found: the negative inclusion and the individuals (or pairs of
+/// individuals) that break it.
+pub type Violations = Vec<(TbiDllite, Vec<Vec<String>>)>;
+
+/// Maps a base concept or a base role to a sql query. The query must return one
+/// column for a concept and two columns for a role, each row is an assertion.
+#[derive(PartialEq, Debug, Clone)]
+pub struct MappingDllite {
+    item: ItemDllite,
+    query: String,
+}
+
+impl fmt::Display for MappingDllite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} <- {}>", &self.item, &self.query)
+    }
+}
+
+impl MappingDllite {
+    /// Only base concepts and base roles can be mapped, anything else returns None.
+    pub fn new(item: ItemDllite, query: &str) -> Option<MappingDllite> {
+        match item.t() {
+            DLType::BaseConcept | DLType::BaseRole => Some(MappingDllite {
+                item,
+                query: query.trim().to_string(),
+            }),
+            _ => Option::None,
+        }
+    }
+
+    pub fn item(&self) -> &ItemDllite {
+        &self.item
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The number of columns the query must return.
+    pub fn arity(&self) -> usize {
+        if self.item.t() == DLType::BaseRole {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// A set of mappings over one database, the entry point to rewrite queries
+/// and to verify consistency without loading an ABox.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ObdaDllite {
+    name: String,
+    mappings: Vec<MappingDllite>,
+}
+
+impl fmt::Display for ObdaDllite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.mappings.is_empty() {
+            write!(f, "<OBDA({})>[]", &self.name)
+        } else {
+            let mut s = format!("<OBDA({})>[\n", &self.name);
+
+            for mapping in &self.mappings {
+                s.push_str(format!("    {}\n", mapping).as_str());
+            }
+            s.push(']');
+
+            write!(f, "{}", s)
+        }
+    }
+}
+
+impl ObdaDllite {
+    pub fn new(name: &str) -> ObdaDllite {
+        ObdaDllite {
+            name: name.to_string(),
+            mappings: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mappings(&self) -> &Vec<MappingDllite> {
+        &self.mappings
+    }
+
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Adds a mapping, a symbol can be mapped by several queries, their
+    /// answers are joined.
+    pub fn add(&mut self, mapping: MappingDllite) -> bool {
+        if self.mappings.contains(&mapping) {
+            false
+        } else {
+            self.mappings.push(mapping);
+            true
+        }
+    }
+
+    /// Verifies that every query can be run against the database and returns
+    /// the expected number of columns.
+    pub fn check(&self, conn: &Connection, verbose: bool) -> Result<()> {
+        for mapping in &self.mappings {
+            let stmt = conn.prepare(mapping.query())?;
+
+            if stmt.column_count() != mapping.arity() {
+                return Err(Error::InvalidParameterName(format!(
+                    "the query '{}' must return {} column(s), it returns {}",
+                    mapping.query(),
+                    mapping.arity(),
+                    stmt.column_count()
+                )));
+            }
+
+            if verbose {
+                println!(" -- ObdaDllite::check: {} is correct", mapping);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rewrites a basic concept or role (e.g. 'A', 'EXISTS r', 'INV r') into a sql query
+    /// over the mappings, the tbox can be the tbox of the ontology or its positive closure,
+    /// negative inclusions are ignored.
+    /// The query returns a column 'x' for a concept and two columns 'x' and 'y' for a role,
+    /// values are casted to text and sorted.
+    /// Returns None if the item can not be queried (e.g. a negated concept).
+    pub fn rewrite(&self, query: &ItemDllite, tbox: &TBDllite) -> Option<String> {
+        let columns = ObdaDllite::columns(query)?;
+        let union = self.union_for_item(query, tbox)?;
+
+        Some(format!(
+            "{}SELECT DISTINCT {} FROM ({}) WHERE {} ORDER BY {}",
+            self.common_table_expressions(),
+            columns,
+            union,
+            ObdaDllite::not_null(query),
+            columns
+        ))
+    }
+
+    /// Rewrites the negative inclusion tbi ('X < NOT Y') into a query whose answers are
+    /// the individuals (or pairs) that are both instances of 'X' and 'Y'.
+    /// Returns None if tbi is not a negative inclusion.
+    pub fn violation_query(&self, tbi: &TbiDllite, tbox: &TBDllite) -> Option<String> {
+        let lside = tbi.lside();
+        let rside = match tbi.rside().unpack() {
+            Unpacked::X(Mod::N, bn) => bn,
+            _ => return Option::None,
+        };
+
+        let columns = ObdaDllite::columns(lside)?;
+        let left_union = self.union_for_item(lside, tbox)?;
+        let right_union = self.union_for_item(&rside, tbox)?;
+
+        Some(format!(
+            "{}SELECT {} FROM ({}) WHERE {} INTERSECT SELECT {} FROM ({}) ORDER BY {}",
+            self.common_table_expressions(),
+            columns,
+            left_union,
+            ObdaDllite::not_null(lside),
+            columns,
+            right_union,
+            columns
+        ))
+    }
+
+    /// Answers the query on the database, each row has one value for a concept
+    /// and two values for a role.
+    pub fn answer(
+        &self,
+        conn: &Connection,
+        query: &ItemDllite,
+        tbox: &TBDllite,
+        verbose: bool,
+    ) -> Result<Vec<Vec<String>>> {
+        match self.rewrite(query, tbox) {
+            Some(sql) => {
+                if verbose {
+                    println!(" -- ObdaDllite::answer: rewritten query: {}", &sql);
+                }
+
+                ObdaDllite::run(conn, &sql, Option::None)
+            }
+            Option::None => Err(Error::InvalidParameterName(format!(
+                "can't rewrite the query: {}",
+                query
+            ))),
+        }
+    }
+
+    /// Runs the violation query of each negative inclusion of tb_negative (this should
+    /// be the negative closure, see 'OntologyDllite::generate_cln'), tb_positive is used
+    /// to rewrite both sides.
+    /// If detailed is false it stops at the first violation and only says if the data
+    /// is inconsistent, as 'AbqDllite::is_inconsistent_refs_only' does.
+    pub fn is_inconsistent(
+        &self,
+        conn: &Connection,
+        tb_positive: &TBDllite,
+        tb_negative: &TBDllite,
+        detailed: bool,
+        verbose: bool,
+    ) -> Result<(bool, Option<Violations>)> {
+        let mut violations: Violations = Vec::new();
+
+        for tbi in tb_negative.items() {
+            if let Some(sql) = self.violation_query(tbi, tb_positive) {
+                if verbose {
+                    println!(" -- ObdaDllite::is_inconsistent: violation query: {}", &sql);
+                }
+
+                let limit = if detailed { Option::None } else { Some(1) };
+                let rows = ObdaDllite::run(conn, &sql, limit)?;
+
+                if !rows.is_empty() {
+                    if !detailed {
+                        return Ok((true, Option::None));
+                    }
+
+                    violations.push((tbi.clone(), rows));
+                }
+            }
+        }
+
+        if detailed {
+            Ok((!violations.is_empty(), Some(violations)))
+        } else {
+            Ok((false, Option::None))
+        }
+    }
+
+    /// Writes the mappings in the native format, the one read by 'parse_mappings_native'.
+    pub fn to_native_string(&self, symbols: &SymbolDict) -> Option<String> {
+        let mut s = String::from("BEGINMAPPING\n");
+
+        for mapping in &self.mappings {
+            let item = node_to_string(mapping.item(), symbols, String::new())?;
+            s.push_str(format!("{} <- {}\n", item, mapping.query()).as_str());
+        }
+
+        s.push_str("ENDMAPPING\n");
+
+        Some(s)
+    }
+
+    // every mapping is a named subquery, 'm0', 'm1', ..., with its columns renamed
+    fn common_table_expressions(&self) -> String {
+        if self.mappings.is_empty() {
+            return String::new();
+        }
+
+        let ctes: Vec<String> = self
+            .mappings
+            .iter()
+            .enumerate()
+            .map(|(index, mapping)| {
+                let columns = if mapping.arity() == 2 { "x, y" } else { "x" };
+                format!("m{}({}) AS ({})", index, columns, mapping.query())
+            })
+            .collect();
+
+        format!("WITH {} ", ctes.join(", "))
+    }
+
+    // the union over the mappings of every subsumee of item
+    fn union_for_item(&self, item: &ItemDllite, tbox: &TBDllite) -> Option<String> {
+        let is_role = item.t().is_role_type();
+
+        if item.is_negated() || (!is_role && !item.t().is_concept_type()) {
+            return Option::None;
+        }
+
+        let mut selects: Vec<String> = Vec::new();
+
+        for subsumee in subsumees(item, tbox) {
+            for (index, mapping) in self.mappings.iter().enumerate() {
+                if let Some(select) = ObdaDllite::select_for_mapping(&subsumee, mapping, index) {
+                    if !selects.contains(&select) {
+                        selects.push(select);
+                    }
+                }
+            }
+        }
+
+        if selects.is_empty() {
+            // nothing is mapped, the answer is empty
+            if is_role {
+                selects.push(String::from("SELECT NULL AS x, NULL AS y WHERE 0"));
+            } else {
+                selects.push(String::from("SELECT NULL AS x WHERE 0"));
+            }
+        }
+
+        Some(selects.join(" UNION "))
+    }
+
+    // how the mapping (the 'index'-th one) contributes to the basic item,
+    // None if it does not
+    fn select_for_mapping(
+        item: &ItemDllite,
+        mapping: &MappingDllite,
+        index: usize,
+    ) -> Option<String> {
+        let (x, y) = ("CAST(x AS TEXT)", "CAST(y AS TEXT)");
+
+        let columns = match item.unpack() {
+            Unpacked::C(_) | Unpacked::R(_) if item == mapping.item() => {
+                if mapping.arity() == 2 {
+                    format!("{} AS x, {} AS y", x, y)
+                } else {
+                    format!("{} AS x", x)
+                }
+            }
+            Unpacked::X(Mod::I, bn) if bn == *mapping.item() => {
+                format!("{} AS x, {} AS y", y, x)
+            }
+            Unpacked::X(Mod::E, bn) => match bn.unpack() {
+                Unpacked::R(_) if bn == *mapping.item() => format!("{} AS x", x),
+                Unpacked::X(Mod::I, base) if base == *mapping.item() => {
+                    format!("{} AS x", y)
+                }
+                _ => return Option::None,
+            },
+            _ => return Option::None,
+        };
+
+        Some(format!("SELECT {} FROM m{}", columns, index))
+    }
+
+    fn columns(item: &ItemDllite) -> Option<&'static str> {
+        if item.t().is_role_type() {
+            Some("x, y")
+        } else if item.t().is_concept_type() {
+            Some("x")
+        } else {
+            Option::None
+        }
+    }
+
+    fn not_null(item: &ItemDllite) -> &'static str {
+        if item.t().is_role_type() {
+            "x IS NOT NULL AND y IS NOT NULL"
+        } else {
+            "x IS NOT NULL"
+        }
+    }
+
+    fn run(conn: &Connection, sql: &str, limit: Option<usize>) -> Result<Vec<Vec<String>>> {
+        let mut stmt = conn.prepare(sql)?;
+        let column_count = stmt.column_count();
+        let mut rows = stmt.query([])?;
+        let mut answers: Vec<Vec<String>> = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            let mut answer: Vec<String> = Vec::new();
+
+            for index in 0..column_count {
+                answer.push(row.get(index)?);
+            }
+
+            answers.push(answer);
+
+            if limit.is_some_and(|l| answers.len() >= l) {
+                break;
+            }
+        }
+
+        Ok(answers)
+    }
+}
+
+/// Every basic concept (or role) that is subsumed by item with respect to the positive
+/// inclusions of tbox, item included.
+/// A role inclusion 'r < s' also gives 'INV r < INV s', 'EXISTS r < EXISTS s' and
+/// 'EXISTS INV r < EXISTS INV s', thus the closure is not needed, the tbox is enough.
+pub fn subsumees(item: &ItemDllite, tbox: &TBDllite) -> Vec<ItemDllite> {
+    let mut found: Vec<ItemDllite> = vec![*item];
+    let mut index: usize = 0;
+
+    while index < found.len() {
+        let current = found[index];
+        index += 1;
+
+        let mut new_items: Vec<ItemDllite> = Vec::new();
+
+        for tbi in tbox.items() {
+            if tbi.is_negative_inclusion() {
+                continue;
+            }
+
+            match current.unpack() {
+                Unpacked::X(Mod::E, role) => {
+                    if let Some(sub_role) = sub_role_from_inclusion(tbi, &role) {
+                        new_items.push(ItemDllite::modified(Mod::E, sub_role));
+                    }
+                }
+                _ if current.t().is_role_type() => {
+                    if let Some(sub_role) = sub_role_from_inclusion(tbi, &current) {
+                        new_items.push(sub_role);
+                    }
+                }
+                _ => (),
+            }
+
+            if tbi.rside() == &current {
+                new_items.push(*tbi.lside());
+            }
+        }
+
+        for new_item in new_items {
+            if !found.contains(&new_item) {
+                found.push(new_item);
+            }
+        }
+    }
+
+    found
+}
+
+// for 'r < s' gives 'r' if role is 's' and 'INV r' if role is 'INV s'
+fn sub_role_from_inclusion(tbi: &TbiDllite, role: &ItemDllite) -> Option<ItemDllite> {
+    let (lside, rside) = (tbi.lside(), tbi.rside());
+
+    if !rside.t().is_role_type() {
+        Option::None
+    } else if rside == role {
+        Some(*lside)
+    } else if rside.is_inverse(role) {
+        lside.inverse()
+    } else {
+        Option::None
+    }
+}
+
+/// Parses a mapping file, it is a native file with a mapping section:
+/// BEGINMAPPING
+/// Person <- SELECT name FROM person
+/// teaches <- SELECT professor, course FROM lecture
+/// ENDMAPPING
+/// A query can go on the next lines, any line without '<-' continues the previous query.
+/// Symbols must be known (they come from the tbox), comments start with '//', a '//'
+/// inside a quoted sql literal (e.g. 'http://...') is part of the query.
+pub fn parse_mappings_native(
+    filename: &str,
+    symbols: &SymbolDict,
+    verbose: bool,
+) -> io::Result<ObdaDllite> {
+    let file = File::open(filename)?;
+    let reader = BufReader::new(file);
+
+    let mut obda = ObdaDllite::new(filename);
+    let mut begin_mapping_encountered = false;
+
+    // the mapping being read, its query can span several lines
+    let mut current: Option<(ItemDllite, String)> = Option::None;
+
+    for line in reader.lines() {
+        let line = line?;
+        let not_ignored = without_comment(&line).trim();
+
+        if verbose {
+            println!(
+                " -- obda::parse_mappings_native: trying to parse: {}",
+                &line
+            );
+        }
+
+        if not_ignored.is_empty() {
+            continue;
+        }
+
+        if not_ignored == "BEGINMAPPING" {
+            begin_mapping_encountered = true;
+            continue;
+        }
+
+        if !begin_mapping_encountered {
+            continue;
+        }
+
+        if not_ignored == "ENDMAPPING" {
+            break;
+        }
+
+        match not_ignored.split_once("<-") {
+            Some((item_s, query)) => {
+                if let Some((item, query)) = current.take() {
+                    add_parsed_mapping(&mut obda, item, &query)?;
+                }
+
+                let item = string_to_node(item_s.trim(), symbols)?;
+                current = Some((item, query.trim().to_string()));
+            }
+            Option::None => match current.as_mut() {
+                Some((_, query)) => {
+                    query.push(' ');
+                    query.push_str(not_ignored);
+                }
+                Option::None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("not a mapping: {}", not_ignored),
+                    ))
+                }
+            },
+        }
+    }
+
+    if let Some((item, query)) = current.take() {
+        add_parsed_mapping(&mut obda, item, &query)?;
+    }
+
+    if verbose {
+        println!(
+            " -- obda::parse_mappings_native: {} mappings found",
+            obda.len()
+        );
+    }
+
+    Ok(obda)
+}
+
+// the line up to the first '//' that is not inside a quoted literal
+fn without_comment(line: &str) -> &str {
+    let mut quote: Option<char> = Option::None;
+    let mut previous = ' ';
+
+    for (index, c) in line.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = Option::None,
+            Some(_) => (),
+            Option::None if c == '\'' || c == '"' => quote = Some(c),
+            Option::None if c == '/' && previous == '/' => return &line[..index - 1],
+            Option::None => (),
+        }
+
+        previous = c;
+    }
+
+    line
+}
+
+fn add_parsed_mapping(obda: &mut ObdaDllite, item: ItemDllite, query: &str) -> io::Result<()> {
+    match MappingDllite::new(item, query) {
+        Some(mapping) => {
+            obda.add(mapping);
+            Ok(())
+        }
+        Option::None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("only base concepts and roles can be mapped: {}", query),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dl_lite::native_filetype_utilities::{parse_symbols_native, parse_tbox_native};
+    use crate::dl_lite::string_formatter::string_to_tbi;
+
+    // the university example: its tbox, its mappings and its data in an in-memory database
+    fn university() -> (SymbolDict, TBDllite, ObdaDllite, Connection) {
+        let examples = format!("{}/examples", env!("CARGO_MANIFEST_DIR"));
+        let tbox_file = format!("{}/university_tbox", examples);
+
+        let symbols = parse_symbols_native(&tbox_file, false).unwrap();
+        let tbox = parse_tbox_native(&tbox_file, &symbols, false).unwrap();
+        let obda = parse_mappings_native(
+            &format!("{}/university_mappings", examples),
+            &symbols,
+            false,
+        )
+        .unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../../examples/university.sql"))
+            .unwrap();
+
+        (symbols, tbox, obda, conn)
+    }
+
+    fn node(s: &str, symbols: &SymbolDict) -> ItemDllite {
+        string_to_node(s, symbols).unwrap()
+    }
+
+    fn names(rows: &[Vec<String>]) -> Vec<String> {
+        rows.iter().map(|row| row.join(", ")).collect()
+    }
+
+    #[test]
+    fn comments_are_not_cut_inside_literals() {
+        assert_eq!(
+            without_comment("A <- SELECT a FROM t // all"),
+            "A <- SELECT a FROM t "
+        );
+        assert_eq!(without_comment("// a comment"), "");
+        assert_eq!(
+            without_comment("A <- SELECT a FROM t WHERE url = 'http://b.org' // c"),
+            "A <- SELECT a FROM t WHERE url = 'http://b.org' "
+        );
+        assert_eq!(
+            without_comment("A <- SELECT \"x//y\" FROM t"),
+            "A <- SELECT \"x//y\" FROM t"
+        );
+    }
+
+    #[test]
+    fn mappings_are_checked_against_the_database() {
+        let (symbols, _, mut obda, conn) = university();
+        assert_eq!(obda.len(), 4);
+        assert!(obda.check(&conn, false).is_ok());
+
+        // a role needs two columns
+        let wrong = MappingDllite::new(node("teaches", &symbols), "SELECT name FROM staff");
+        obda.add(wrong.unwrap());
+        assert!(obda.check(&conn, false).is_err());
+
+        // only base concepts and roles can be mapped
+        assert!(MappingDllite::new(node("EXISTS teaches", &symbols), "SELECT 1").is_none());
+    }
+
+    #[test]
+    fn queries_are_rewritten_with_the_tbox() {
+        let (symbols, tbox, obda, conn) = university();
+
+        let persons = obda
+            .answer(&conn, &node("Person", &symbols), &tbox, false)
+            .unwrap();
+        assert_eq!(names(&persons), ["Ava", "Bob", "John", "Mary"]);
+
+        let courses = obda
+            .answer(&conn, &node("Course", &symbols), &tbox, false)
+            .unwrap();
+        assert_eq!(names(&courses), ["AI", "DB2"]);
+
+        let taught_by = obda
+            .answer(&conn, &node("INV teaches", &symbols), &tbox, false)
+            .unwrap();
+        assert_eq!(names(&taught_by), ["AI, Mary", "DB2, John"]);
+
+        // 'Course' has no mapping of its own, only the roles give its instances
+        let without_tbox = obda
+            .answer(&conn, &node("Course", &symbols), &TBDllite::new(), false)
+            .unwrap();
+        assert!(without_tbox.is_empty());
+        assert!(obda.rewrite(&node("NOT Person", &symbols), &tbox).is_none());
+    }
+
+    #[test]
+    fn violations_are_found_by_the_database() {
+        let (symbols, tbox, obda, conn) = university();
+        let negative_closure = tbox.cln_completion(true, false, false);
+
+        // only negative inclusions give a violation query
+        let positive = &string_to_tbi("Student < Person", &symbols).unwrap()[0];
+        assert!(obda.violation_query(positive, &tbox).is_none());
+
+        let negative = &string_to_tbi("Student < NOT Professor", &symbols).unwrap()[0];
+        let sql = obda.violation_query(negative, &tbox).unwrap();
+        let rows = ObdaDllite::run(&conn, &sql, Option::None).unwrap();
+        assert_eq!(names(&rows), ["Mary"]);
+
+        let (inconsistent, violations) = obda
+            .is_inconsistent(&conn, &tbox, &negative_closure, false, false)
+            .unwrap();
+        assert!(inconsistent);
+        assert!(violations.is_none());
+
+        let (inconsistent, violations) = obda
+            .is_inconsistent(&conn, &tbox, &negative_closure, true, false)
+            .unwrap();
+        assert!(inconsistent);
+        let violations = violations.unwrap();
+        assert!(!violations.is_empty());
+        assert!(violations
+            .iter()
+            .all(|(_, rows)| rows.iter().all(|row| row.contains(&"Mary".to_string()))));
+
+        // without Mary attending a course the data is consistent
+        conn.execute("DELETE FROM enrolment WHERE student = 'Mary'", [])
+            .unwrap();
+        let (inconsistent, violations) = obda
+            .is_inconsistent(&conn, &tbox, &negative_closure, true, false)
+            .unwrap();
+        assert!(!inconsistent);
+        assert!(violations.unwrap().is_empty());
+    }
+}
@@ -22,24 +22,34 @@ use crate::dl_lite::abox_item::AbiDllite;
 use crate::dl_lite::abox_item_quantum::AbiqDllite;
 use crate::dl_lite::json_filetype_utilities::{parse_symbols_json, parse_tbox_json};
 use crate::dl_lite::native_filetype_utilities::{
-    abox_to_native_string_quantum, parse_abox_native_quantum, parse_symbols_native,
-    parse_tbox_native,
+    abox_to_native_string_quantum, find_bound_of_symbols, parse_abox_native_quantum,
+    parse_symbols_native, parse_tbox_native,
 };
-use crate::dl_lite::node::{ItemDllite, Mod};
+use crate::dl_lite::node::{ItemDllite, Mod, Unpacked};
+use crate::dl_lite::sqlite_interface::{
+    abox_from_db, abox_names_from_db, add_abox_to_db, add_basic_tables_to_db,
+    add_ontology_name_to_db, add_ranking_to_db, add_sources_to_db, add_symbols_from_db,
+    add_symbols_to_db, add_tbis_from_db, add_tbis_to_db, ontology_name_from_db, open_db,
+    parse_abox_sqlite, parse_symbols_sqlite, parse_tbox_sqlite, sources_from_db, RankingRun,
+    DB_CLN_NEGATIVE, DB_CLN_POSITIVE, DB_TBOX,
+};
+use crate::dl_lite::string_formatter::string_to_abiq;
 use crate::dl_lite::tbox::TBDllite;
 use crate::dl_lite::tbox_item::TbiDllite;
+use crate::dl_lite::trust::parse_sources_native;
 
-use crate::kb::knowledge_base::{ABox, ABoxItem, AggrFn, SymbolDict, TBox, TBoxItem};
-use crate::kb::types::DLType;
+use crate::kb::knowledge_base::{ABox, ABoxItem, SourceDict, SymbolDict, TBox, TBoxItem};
 use crate::kb::types::FileType;
+use crate::kb::types::{ConflictType, DLType};
 
-use crate::interface::utilities::write_str_to_file;
+use crate::interface::utilities::{parse_name_from_filename, write_str_to_file};
 
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 
 use rayon::prelude::*;
+use rusqlite::{Connection, Result};
 
 /*
 some types to avoid large names
@@ -50,12 +60,26 @@ type CleanMatrixDictOpt = Option<(
     HashMap<usize, usize>,
     Option<(usize, usize)>,
 )>;
-type ConflictMatrixDict = (
+pub type ConflictMatrixDict = (
     Vec<i8>,
     HashMap<usize, Option<usize>>,
     HashMap<usize, usize>,
 );
 
+// the same assertions with the same credibilities and sources, in the same order
+fn same_assertions(ab: &AbqDllite, other: &AbqDllite) -> bool {
+    ab.len() == other.len()
+        && ab
+            .items()
+            .iter()
+            .zip(other.items())
+            .all(|(abiq, other_abiq)| {
+                abiq == other_abiq
+                    && abiq.credibility() == other_abiq.credibility()
+                    && abiq.evidence() == other_abiq.evidence()
+            })
+}
+
 /*
 an ontology model
     - name is the name of the ontology
@@ -64,6 +88,11 @@ an ontology model
     - number_of_symbols is the current number of symbols
     - number_of_tbi is the current number of tbi
     - latest_id is higher number present in the symbols dictionary
+    - db_path is the database the ontology was loaded from, if any, aboxes
+      and closures are read from it only when asked
+    - aboxes are all the aboxes loaded, each one with its own name, current_abox is
+      the name of the one tasks work on, the last loaded unless told otherwise
+    - trust is the trust declared for each source of assertions
  */
 
 #[derive(PartialEq, Clone, Debug)]
@@ -73,7 +102,10 @@ pub struct OntologyDllite {
     tbox: TBDllite,
     cln_positive: TBDllite,
     cln_negative: TBDllite,
-    current_abox: Option<AbqDllite>,
+    aboxes: Vec<AbqDllite>,
+    current_abox: Option<String>,
+    trust: SourceDict,
+    db_path: Option<String>,
 }
 
 impl fmt::Display for OntologyDllite {
@@ -96,12 +128,12 @@ impl fmt::Display for OntologyDllite {
         formatted = format!("----<TBox>\n{}\n", &self.tbox_to_string(&self.tbox, false));
         s.push_str(formatted.as_str());
 
-        // add the tbox
-        if self.current_abox.is_some() {
+        // add the aboxes
+        for ab in &self.aboxes {
             formatted = format!(
                 "----<ABox({})>\n{}\n",
-                self.current_abox.as_ref().unwrap().name(),
-                &self.abox_to_string_quantum(&self.current_abox.as_ref().unwrap())
+                ab.name(),
+                &self.abox_to_string_quantum(ab)
             );
             s.push_str(formatted.as_str());
         }
@@ -132,7 +164,10 @@ impl OntologyDllite {
             tbox: TBDllite::new(),
             cln_negative: TBDllite::new(),
             cln_positive: TBDllite::new(),
+            aboxes: Vec::new(),
             current_abox: Option::None,
+            trust: HashMap::new(),
+            db_path: Option::None,
         }
     }
 
@@ -147,13 +182,167 @@ impl OntologyDllite {
         }
     }
 
+    /// The abox tasks work on, the last one added unless 'set_current_abox' was used.
     pub fn abox(&self) -> Option<&AbqDllite> {
         match &self.current_abox {
             Option::None => Option::None,
-            Some(ab) => Some(ab),
+            Some(name) => self.abox_by_name(name),
+        }
+    }
+
+    pub fn abox_by_name(&self, name: &str) -> Option<&AbqDllite> {
+        self.aboxes.iter().find(|ab| ab.name() == name)
+    }
+
+    pub fn abox_names(&self) -> Vec<String> {
+        self.aboxes.iter().map(|ab| ab.name()).collect()
+    }
+
+    pub fn aboxes(&self) -> &Vec<AbqDllite> {
+        &self.aboxes
+    }
+
+    /// The trust declared for each source, see 'dl_lite/trust.rs'.
+    pub fn trust(&self) -> &SourceDict {
+        &self.trust
+    }
+
+    pub fn set_trust(&mut self, source: &str, value: f64) {
+        self.trust.insert(source.to_string(), value);
+    }
+
+    /// Adds the source declarations of a file, a source already declared is updated.
+    pub fn add_trust_from_file(&mut self, filename: &str, filetype: FileType, verbose: bool) {
+        let trust_result = match filetype {
+            FileType::Native => parse_sources_native(filename, verbose),
+            FileType::Sqlite => open_db(filename, verbose)
+                .and_then(|conn| sources_from_db(&conn))
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())),
+            FileType::Json => {
+                if verbose {
+                    println!("the json parser is not yet implemented");
+                }
+
+                return;
+            }
+        };
+
+        match trust_result {
+            Err(e) => {
+                if verbose {
+                    println!("couldn't read the sources from {}: {}", filename, &e);
+                }
+            }
+            Ok(trust) => self.trust.extend(trust),
+        }
+    }
+
+    /// Makes the abox named 'name' the current one, returns false if there is none.
+    pub fn set_current_abox(&mut self, name: &str) -> bool {
+        if self.abox_by_name(name).is_some() {
+            self.current_abox = Some(name.to_string());
+            true
+        } else {
+            false
         }
     }
 
+    /// Adds ab to the ontology and makes it the current abox, returns the name it is
+    /// known by. If the name is taken by the same assertions they are replaced, if it is
+    /// taken by other assertions ab is renamed 'name_2' ('name_3' if taken, and so on),
+    /// an abox is never lost.
+    pub fn add_abox(&mut self, mut ab: AbqDllite) -> String {
+        let mut name = ab.name();
+
+        if let Some(index) = self
+            .aboxes
+            .iter()
+            .position(|present| present.name() == name)
+        {
+            if same_assertions(&self.aboxes[index], &ab) {
+                self.aboxes[index] = ab;
+                self.current_abox = Some(name.clone());
+                return name;
+            }
+
+            let mut number = 2;
+
+            while self.abox_by_name(&format!("{}_{}", name, number)).is_some() {
+                number += 1;
+            }
+
+            name = format!("{}_{}", name, number);
+            ab.set_name(&name);
+        }
+
+        self.aboxes.push(ab);
+        self.current_abox = Some(name.clone());
+        name
+    }
+
+    // adds an abox read from 'origin', says so if it had to be renamed
+    fn add_abox_from(&mut self, ab: AbqDllite, origin: &str) {
+        let asked = ab.name();
+        let given = self.add_abox(ab);
+
+        if given != asked {
+            println!(
+                "warning: an abox named '{}' is already loaded, the one of {} is named '{}'",
+                asked, origin, given
+            );
+        }
+    }
+
+    /// Removes the abox named 'name', if it was the current abox the last one
+    /// remaining becomes current.
+    pub fn remove_abox(&mut self, name: &str) -> Option<AbqDllite> {
+        let index = self.aboxes.iter().position(|ab| ab.name() == name)?;
+        let ab = self.aboxes.remove(index);
+
+        if self.current_abox.as_deref() == Some(name) {
+            self.current_abox = self.aboxes.last().map(|last| last.name());
+        }
+
+        Some(ab)
+    }
+
+    /// Merges the aboxes named in 'names' (all of them if empty) into a new abox
+    /// 'merged_name', each assertion keeps the names of the aboxes it comes from,
+    /// see 'AbqDllite::merge'. Returns None if a name is unknown.
+    pub fn merge_aboxes(&self, names: &[&str], merged_name: &str) -> Option<AbqDllite> {
+        let to_merge: Vec<&AbqDllite> = if names.is_empty() {
+            self.aboxes.iter().collect()
+        } else {
+            let mut to_merge: Vec<&AbqDllite> = Vec::new();
+
+            for name in names {
+                to_merge.push(self.abox_by_name(name)?);
+            }
+
+            to_merge
+        };
+
+        Some(AbqDllite::merge(merged_name, &to_merge))
+    }
+
+    /// Conflicts of abq whose members come from different sources, abq is usually
+    /// the result of 'merge_aboxes'.
+    /// The negative closure must have been generated before, see 'generate_cln'.
+    pub fn cross_abox_conflicts<'a>(
+        &'a self,
+        abq: &'a AbqDllite,
+    ) -> Vec<(Option<&'a TbiDllite>, Vec<&'a AbiqDllite>)> {
+        let detailed = true;
+        let (_, contradictions_op) =
+            AbqDllite::is_inconsistent_refs_only(abq.items_by_ref(), &self.cln_negative, detailed);
+
+        contradictions_op
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, members)| AbqDllite::is_cross_source_conflict(members))
+            .collect()
+    }
+
     // ------------------------------------------------------------------------
     // modifications of the ontology
 
@@ -172,6 +361,46 @@ impl OntologyDllite {
         }
     }
 
+    /// Adds a concept, role or nominal named 'name', returns false if the name is taken.
+    pub fn declare_symbol(&mut self, name: &str, t: DLType) -> bool {
+        let mut new_symbols: SymbolDict = HashMap::new();
+        new_symbols.insert(name.to_string(), (0, t));
+
+        self.add_symbol(&new_symbols, name)
+    }
+
+    /// Removes tbi from the tbox, the closures are not updated.
+    pub fn remove_tbi(&mut self, tbi: &TbiDllite) -> bool {
+        self.tbox.remove(tbi)
+    }
+
+    /// Removes abi from the current abox, returns false if it was not there.
+    pub fn remove_abi(&mut self, abi: &AbiqDllite) -> bool {
+        match &self.current_abox {
+            Option::None => false,
+            Some(name) => match self.aboxes.iter_mut().find(|ab| &ab.name() == name) {
+                Some(abox) => abox.remove(abi),
+                Option::None => false,
+            },
+        }
+    }
+
+    /// Parses an assertion written as in a native file, the new nominals are added
+    /// to the symbols.
+    pub fn abiq_from_string(&mut self, s: &str) -> std::io::Result<AbiqDllite> {
+        let (_, id_bound) = find_bound_of_symbols(&self.symbols);
+        let for_completion = false;
+
+        let (parsed_result, _) = string_to_abiq(s, &mut self.symbols, id_bound + 1, for_completion);
+        let (abiq, to_be_added) = parsed_result?;
+
+        for (name, (id, dltype)) in to_be_added {
+            self.symbols.insert(name, (id, dltype));
+        }
+
+        Ok(abiq)
+    }
+
     /*
     pub fn add_abis_from_vec(&mut self, v: &Vec<AbiqDllite>) {
         match self.current_abox.as_mut() {
@@ -197,6 +426,7 @@ impl OntologyDllite {
             FileType::Native => {
                 parse_symbols_native(filename, verbose) // don't like this :/ (this is a smiley face)
             }
+            FileType::Sqlite => parse_symbols_sqlite(filename, verbose),
         };
         match new_symbols_result {
             Err(error) => {
@@ -218,6 +448,7 @@ impl OntologyDllite {
             let tb_result = match filetype {
                 FileType::Json => parse_tbox_json(filename, &self.symbols, verbose),
                 FileType::Native => parse_tbox_native(filename, &self.symbols, verbose),
+                FileType::Sqlite => parse_tbox_sqlite(filename, &self.symbols, verbose),
             };
             match tb_result {
                 Err(error) => {
@@ -239,6 +470,19 @@ impl OntologyDllite {
         filename: &str,
         filetype: FileType,
         verbose: bool,
+    ) {
+        self.new_named_abox_from_file_quantum(filename, filetype, Option::None, verbose)
+    }
+
+    /// Adds the abox in filename under the name 'name_op', the abox becomes the current one.
+    /// Without a name a file gives its name to the abox and from a database
+    /// the last stored abox is read, with a name the abox of that name is read.
+    pub fn new_named_abox_from_file_quantum(
+        &mut self,
+        filename: &str,
+        filetype: FileType,
+        name_op: Option<&str>,
+        verbose: bool,
     ) {
         if !self.symbols.is_empty() {
             match filetype {
@@ -258,8 +502,49 @@ impl OntologyDllite {
                                 println!("couldn't parse abox from file: {}", filename);
                             }
                         }
+                        Ok(mut ab) => {
+                            if let Some(name) = name_op {
+                                ab.set_name(name);
+                            }
+
+                            // the file can declare the trust of its sources
+                            self.add_trust_from_file(filename, filetype, verbose);
+                            self.add_abox_from(ab, filename);
+                        }
+                    }
+                }
+                // the database the ontology comes from, only the abox asked is read
+                FileType::Sqlite if self.db_path.as_deref() == Some(filename) => {
+                    let name_op = name_op
+                        .map(|name| name.to_string())
+                        .or_else(|| self.abox_names_in_db(verbose).pop());
+
+                    let read = match &name_op {
+                        Some(name) => self.new_abox_from_db(name, verbose),
+                        Option::None => false,
+                    };
+
+                    if !read && verbose {
+                        println!("couldn't read abox from database {}", filename);
+                    }
+                }
+                FileType::Sqlite => {
+                    // without a name the last stored abox is taken
+                    let ab_result =
+                        parse_abox_sqlite(filename, name_op, &mut self.symbols, verbose);
+
+                    match ab_result {
+                        Err(error) => {
+                            if verbose {
+                                println!(
+                                    "couldn't read abox from database {}: {}",
+                                    filename, error
+                                );
+                            }
+                        }
                         Ok(ab) => {
-                            self.current_abox = Some(ab);
+                            self.add_trust_from_file(filename, filetype, verbose);
+                            self.add_abox_from(ab, filename);
                         }
                     }
                 }
@@ -315,9 +600,10 @@ impl OntologyDllite {
 
     pub fn add_abi(&mut self, abi: &AbiqDllite) {
         // you must have created a new abox
-        if self.current_abox.is_some() {
-            let abox = self.current_abox.as_mut().unwrap();
-            abox.add(abi.clone());
+        if let Some(name) = &self.current_abox {
+            if let Some(abox) = self.aboxes.iter_mut().find(|ab| &ab.name() == name) {
+                abox.add(abi.clone());
+            }
         }
     }
 
@@ -360,6 +646,16 @@ impl OntologyDllite {
     // for the positive closure pass a positive number
     // and for both pass 0
     pub fn generate_cln(&mut self, deduction_tree: bool, verbose: bool, positive_or_negative: i8) {
+        // an ontology read from a database takes the closures stored with its tbox, the
+        // stored impliers can be missing thus not for a deduction tree
+        if !deduction_tree && self.db_path.is_some() && self.cln_from_db(verbose) {
+            if verbose {
+                println!(" -- closures read from the database");
+            }
+
+            return;
+        }
+
         match positive_or_negative.cmp(&0) {
             Ordering::Less => {
                 self.cln_negative = self.tbox.cln_completion(true, deduction_tree, verbose)
@@ -471,7 +767,6 @@ impl OntologyDllite {
             // now the matrix
             let virtual_length = virtual_to_real.len();
             let mut matrix: Vec<i8> = vec![0; virtual_length * virtual_length];
-            let mut already_computed: HashMap<(usize, usize), i8> = HashMap::new();
 
             // conflicts are always binary at this point
             /*
@@ -480,88 +775,90 @@ impl OntologyDllite {
             a => -b with [a,b] is inconsistent
 
             normally we can't have both, we weed out the self conflicting nodes...
+
+            only pairs sharing a nominal with predicates related by the closures can be
+            in any of these cases, the indexes of the abox give them without looking at
+            every pair, an item inconsistent on its own or by its negation goes with all
              */
-            for i in 0..virtual_length {
-                let real_index_i = virtual_to_real.get(&i).unwrap();
+            let candidates = abq.candidate_pairs(&[self.cln(false), self.cln(true)]);
 
-                let abiq_i = abq.get(*real_index_i).unwrap();
+            if verbose {
+                println!(
+                    " -- Ontology::conflict_matrix: {} pairs of items to analyse",
+                    candidates.len()
+                );
+            }
+
+            for (real_index_i, real_index_j) in candidates {
+                let (i, j) = match (
+                    real_to_virtual.get(&real_index_i),
+                    real_to_virtual.get(&real_index_j),
+                ) {
+                    (Some(Some(i)), Some(Some(j))) => (*i, *j),
+                    // self conflicting items are not in the matrix
+                    (_, _) => continue,
+                };
+
+                let abiq_i = abq.get(real_index_i).unwrap();
+                let abiq_j = abq.get(real_index_j).unwrap();
 
                 if verbose {
                     println!(
-                        " -- Ontology::conflict_matrix: filling column {} for item {}",
-                        i, abiq_i
+                        " -- Ontology::conflict_matrix: analysing items {} and {}",
+                        abiq_i, abiq_j
                     );
                 }
 
-                for j in 0..virtual_length {
-                    // no need of same element analysis
-                    if i != j {
-                        if already_computed.contains_key(&(i, j))
-                            && already_computed.get(&(i, j)).unwrap() == &(-1)
-                        {
-                            matrix[virtual_length * i + j] = -1;
-                            already_computed.insert((j, i), -1);
+                // first analyse if (i) and (j) are in conflict, in both ways
 
-                            if verbose {
-                                println!(" -- Ontology::conflict_matrix: already found negative coefficient for index: ({}, {}), passing", i, j);
-                            }
-                        } else {
-                            let real_index_j = virtual_to_real.get(&j).unwrap();
+                /*
+                   again, for this is the negative closure that is needed
+                */
 
-                            let abiq_j = abq.get(*real_index_j).unwrap();
-
-                            // first analyse if (i) implies (not j)
-
-                            /*
-                               again, for this is the negative closure that is needed
-                            */
-
-                            let mut deduction_found = false;
-                            let (abox_is_inconsistent, _) = AbqDllite::is_inconsistent_refs_only(
-                                vec![abiq_i, abiq_j],
-                                self.cln(false),
-                                false,
-                            );
-
-                            if abox_is_inconsistent {
-                                if verbose {
-                                    println!(" -- Ontology::conflict_matrix: setting position ({}, {}) to -1", j, i);
-                                }
-
-                                matrix[virtual_length * j + i] = -1;
-                                already_computed.insert((i, j), -1);
-                                deduction_found = true;
-                            }
+                let (abox_is_inconsistent, _) = AbqDllite::is_inconsistent_refs_only(
+                    vec![abiq_i, abiq_j],
+                    self.cln(false),
+                    false,
+                );
 
-                            // if not deduction was found then
-                            // check if (i) implies (j)
+                if abox_is_inconsistent {
+                    if verbose {
+                        println!(
+                            " -- Ontology::conflict_matrix: setting positions ({}, {}) and ({}, {}) to -1",
+                            j, i, i, j
+                        );
+                    }
 
-                            /*
-                               now we use the positive closure, that have all consequences
-                            */
+                    matrix[virtual_length * j + i] = -1;
+                    matrix[virtual_length * i + j] = -1;
+                } else {
+                    // if not deduction was found then
+                    // check if (i) implies (j) and if (j) implies (i)
 
-                            if !deduction_found {
-                                let abiq_j_neg = abiq_j.negate();
+                    /*
+                       now we use the positive closure, that have all consequences
+                    */
 
-                                let (abox_is_inconsistent, _) =
-                                    AbqDllite::is_inconsistent_refs_only(
-                                        vec![abiq_i, &abiq_j_neg],
-                                        self.cln(true),
-                                        false,
-                                    );
+                    for (from, to, abiq_from, abiq_to) in
+                        [(i, j, abiq_i, abiq_j), (j, i, abiq_j, abiq_i)]
+                    {
+                        let abiq_to_neg = abiq_to.negate();
 
-                                if abox_is_inconsistent {
-                                    if verbose {
-                                        println!(
-                                            " -- Ontology::conflict_matrix: setting position ({}, {}) to 1",
-                                            j, i
-                                        );
-                                    }
+                        let (abox_is_inconsistent, _) = AbqDllite::is_inconsistent_refs_only(
+                            vec![abiq_from, &abiq_to_neg],
+                            self.cln(true),
+                            false,
+                        );
 
-                                    matrix[virtual_length * j + i] = 1;
-                                    already_computed.insert((i, j), 1);
-                                }
+                        if abox_is_inconsistent {
+                            if verbose {
+                                println!(
+                                    " -- Ontology::conflict_matrix: setting position ({}, {}) to 1",
+                                    to, from
+                                );
                             }
+
+                            matrix[virtual_length * to + from] = 1;
                         }
                     }
                 }
@@ -908,11 +1205,12 @@ impl OntologyDllite {
     // here we compute the A matrix
     // remember: a*1 - b*A = c*(1,...,1)
     // (Vec<i8>, HashMap<usize, Option<usize>>, HashMap<usize, usize>)
-    pub fn compute_aggregation_matrix(
+    // the aggregate is any operator, an AggrFn or a boxed (maybe stateful) one
+    pub fn compute_aggregation_matrix<F: FnMut(Vec<f64>) -> f64>(
         abq: &AbqDllite,
         matrix: &[i8],
         virtual_to_real: &HashMap<usize, usize>,
-        aggr: AggrFn,
+        mut aggr: F,
         verbose: bool,
     ) -> Vec<f64> {
         let matrix_len = matrix.len();
@@ -1093,10 +1391,10 @@ impl OntologyDllite {
         mut left_current: String,
         mut right_current: String,
     ) -> String {
-        match node {
-            ItemDllite::T => String::from("Top"),    // format!("{}", node),
-            ItemDllite::B => String::from("Bottom"), // format!("{}", node),
-            ItemDllite::N(n) | ItemDllite::R(n) | ItemDllite::C(n) => {
+        match node.unpack() {
+            Unpacked::T => String::from("Top"),    // format!("{}", node),
+            Unpacked::B => String::from("Bottom"), // format!("{}", node),
+            Unpacked::N(n) | Unpacked::R(n) | Unpacked::C(n) => {
                 // find the name
                 let mut name_found = false;
                 let mut name: String = String::new();
@@ -1104,7 +1402,7 @@ impl OntologyDllite {
                 for symbol in &self.symbols {
                     let (a, (b, _c)) = symbol;
 
-                    if b == n {
+                    if *b == n {
                         name_found = true;
                         name = a.clone();
                     }
@@ -1114,14 +1412,14 @@ impl OntologyDllite {
                     name = String::from("<NAME NOT FOUND>");
                 }
 
-                match node {
-                    ItemDllite::N(_) => format!("{}{}{}", left_current, name, right_current),
-                    ItemDllite::R(_) => format!("{}{}{}", left_current, name, right_current),
-                    ItemDllite::C(_) => format!("{}{}{}", left_current, name, right_current),
+                match node.unpack() {
+                    Unpacked::N(_) => format!("{}{}{}", left_current, name, right_current),
+                    Unpacked::R(_) => format!("{}{}{}", left_current, name, right_current),
+                    Unpacked::C(_) => format!("{}{}{}", left_current, name, right_current),
                     _ => String::from("you shouldn't be here"),
                 }
             }
-            ItemDllite::X(m, bn) => {
+            Unpacked::X(m, bn) => {
                 let left_addition = match m {
                     Mod::N => "-",
                     Mod::I => "(",
@@ -1137,7 +1435,7 @@ impl OntologyDllite {
                 left_current = format!("{}{}", left_current, left_addition);
                 right_current = format!("{}{}", right_addition, right_current);
 
-                self.node_to_string_helper(bn, left_current, right_current)
+                self.node_to_string_helper(&bn, left_current, right_current)
             }
         }
     }
@@ -1320,7 +1618,7 @@ impl OntologyDllite {
         match filetype {
             FileType::Native => {
                 let abox_as_string_op = abox_to_native_string_quantum(
-                    &self.current_abox.as_ref().unwrap(),
+                    self.abox().unwrap(),
                     &self.symbols,
                     dont_write_trivial,
                 );
@@ -1344,22 +1642,59 @@ impl OntologyDllite {
     // this part is the sqlite interface
     // ------------------------------------------------------------------------------------------
 
-    /*
-    pub fn populate_db(&mut self, conn: &Connection, verbose: bool) -> bool {
-        // sort your tbis before
-        self.sort();
+    /// Writes everything the ontology has to the database behind conn: its name,
+    /// the symbols, the tbox, the sources, both closures if they were computed and
+    /// every abox loaded.
+    /// What was stored before under the same names is replaced.
+    pub fn populate_db(&self, conn: &Connection, verbose: bool) -> bool {
+        let populate = || -> Result<()> {
+            add_basic_tables_to_db(conn, verbose)?;
+            add_ontology_name_to_db(&self.name, conn)?;
+            add_symbols_to_db(&self.symbols, conn, verbose)?;
+            add_tbis_to_db(&self.symbols, self.tbox.items(), DB_TBOX, conn, verbose)?;
+            add_sources_to_db(&self.trust, conn, verbose)?;
+
+            if !self.cln_positive.is_empty() {
+                add_tbis_to_db(
+                    &self.symbols,
+                    self.cln_positive.items(),
+                    DB_CLN_POSITIVE,
+                    conn,
+                    verbose,
+                )?;
+            }
 
-        add_basic_tables_to_db(&conn, verbose);
-        add_symbols_to_db(&self.symbols, &conn, verbose);
-        add_tbis_to_db(self.symbols(), self.tbox.items(), &conn, verbose);
-        true
-    }
+            if !self.cln_negative.is_empty() {
+                add_tbis_to_db(
+                    &self.symbols,
+                    self.cln_negative.items(),
+                    DB_CLN_NEGATIVE,
+                    conn,
+                    verbose,
+                )?;
+            }
 
-     */
+            for ab in &self.aboxes {
+                add_abox_to_db(&self.symbols, ab, conn, verbose)?;
+            }
 
-    /*
-    pub fn initiate_from_db(filename: &str, verbose: bool) -> Result<Ontology_DLlite> {
-        let conn_res = Connection::open(filename);
+            Ok(())
+        };
+
+        match populate() {
+            Err(e) => {
+                println!("couldn't populate the database: {}", &e);
+                false
+            }
+            Ok(_) => true,
+        }
+    }
+
+    /// Creates an ontology from a database, only the symbols and the tbox are read,
+    /// closures and aboxes are read later on demand
+    /// (see 'cln_from_db' and 'new_abox_from_db').
+    pub fn initiate_from_db(filename: &str, verbose: bool) -> Result<OntologyDllite> {
+        let conn_res = open_db(filename, verbose);
 
         match conn_res {
             Err(e) => {
@@ -1369,17 +1704,269 @@ impl OntologyDllite {
                 Err(e)
             }
             Ok(conn) => {
-                let tb_name = parse_name_from_filename(filename);
+                let name = ontology_name_from_db(&conn)
+                    .unwrap_or_else(|_| parse_name_from_filename(filename).to_string());
+
+                let mut onto = OntologyDllite::new(name);
 
-                let mut onto = Ontology_DLlite::new(String::from(tb_name));
+                add_symbols_from_db(&mut onto.symbols, &conn, verbose)?;
+                add_tbis_from_db(&onto.symbols, &mut onto.tbox, DB_TBOX, &conn, verbose)?;
+                // databases older than the trust model have no sources
+                onto.trust = sources_from_db(&conn).unwrap_or_default();
 
-                let _symbol_res = add_symbols_from_db(&mut onto.symbols, &conn, verbose);
-                let _tbis_res = add_tbis_from_db(&onto.symbols, &mut onto.tbox, &conn, verbose);
+                onto.db_path = Some(filename.to_string());
 
                 Ok(onto)
             }
         }
     }
 
-     */
+    /// Reads the closures stored in the database the ontology comes from, this avoids
+    /// computing them again. Returns true if both closures were found and the tbox is
+    /// still the one stored with them.
+    pub fn cln_from_db(&mut self, verbose: bool) -> bool {
+        match self.db_connection(verbose) {
+            Option::None => false,
+            Some(conn) => {
+                let mut stored_tbox = TBDllite::new();
+
+                if add_tbis_from_db(&self.symbols, &mut stored_tbox, DB_TBOX, &conn, verbose)
+                    .is_err()
+                    || stored_tbox.len() != self.tbox.len()
+                    || !self
+                        .tbox
+                        .items()
+                        .iter()
+                        .all(|tbi| stored_tbox.contains(tbi))
+                {
+                    return false;
+                }
+
+                let mut cln_positive = TBDllite::new();
+                let mut cln_negative = TBDllite::new();
+
+                let positive_res = add_tbis_from_db(
+                    &self.symbols,
+                    &mut cln_positive,
+                    DB_CLN_POSITIVE,
+                    &conn,
+                    verbose,
+                );
+                let negative_res = add_tbis_from_db(
+                    &self.symbols,
+                    &mut cln_negative,
+                    DB_CLN_NEGATIVE,
+                    &conn,
+                    verbose,
+                );
+
+                match (positive_res, negative_res) {
+                    (Ok(_), Ok(_)) if !cln_positive.is_empty() && !cln_negative.is_empty() => {
+                        self.cln_positive = cln_positive;
+                        self.cln_negative = cln_negative;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    /// Names of the aboxes stored in the database the ontology comes from.
+    pub fn abox_names_in_db(&self, verbose: bool) -> Vec<String> {
+        match self.db_connection(verbose) {
+            Option::None => Vec::new(),
+            Some(conn) => abox_names_from_db(&conn).unwrap_or_default(),
+        }
+    }
+
+    /// Makes the abox named 'name' in the database the current abox.
+    pub fn new_abox_from_db(&mut self, name: &str, verbose: bool) -> bool {
+        match self.db_connection(verbose) {
+            Option::None => false,
+            Some(conn) => match abox_from_db(&self.symbols, name, &conn, verbose) {
+                Err(e) => {
+                    if verbose {
+                        println!("couldn't read abox {} from database: {}", name, &e);
+                    }
+                    false
+                }
+                Ok(ab) => {
+                    let origin = self.db_path.clone().unwrap_or_default();
+                    self.add_abox_from(ab, &origin);
+                    true
+                }
+            },
+        }
+    }
+
+    /// Stores a ranked abox and the metadata of the run that ranked it,
+    /// returns the id given to the run.
+    pub fn ranking_to_db(
+        &self,
+        conn: &Connection,
+        abq: &AbqDllite,
+        conflict_type: &HashMap<usize, ConflictType>,
+        run: &RankingRun,
+        verbose: bool,
+    ) -> Option<i64> {
+        let store = || -> Result<i64> {
+            add_basic_tables_to_db(conn, verbose)?;
+            add_abox_to_db(&self.symbols, abq, conn, verbose)?;
+            add_ranking_to_db(abq, conflict_type, run, conn, verbose)
+        };
+
+        match store() {
+            Err(e) => {
+                println!("couldn't store the ranking: {}", &e);
+                Option::None
+            }
+            Ok(run_id) => Some(run_id),
+        }
+    }
+
+    fn db_connection(&self, verbose: bool) -> Option<Connection> {
+        match &self.db_path {
+            Option::None => {
+                if verbose {
+                    println!("this ontology does not come from a database");
+                }
+                Option::None
+            }
+            Some(path) => match open_db(path, verbose) {
+                Err(e) => {
+                    if verbose {
+                        println!("an error occurred: {}", &e);
+                    }
+                    Option::None
+                }
+                Ok(conn) => Some(conn),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dl_lite::string_formatter::string_to_tbi;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const CONCEPTS: usize = 8;
+    const ROLES: usize = 4;
+    const NOMINALS: usize = 6;
+
+    fn basic(rng: &mut StdRng) -> String {
+        if rng.gen::<f64>() < 0.6 {
+            format!("C{}", rng.gen_range(0..CONCEPTS))
+        } else {
+            let inverse = if rng.gen::<bool>() { "INV " } else { "" };
+            format!("EXISTS {}r{}", inverse, rng.gen_range(0..ROLES))
+        }
+    }
+
+    // a small random ontology, with its closures, and a random abox on it
+    fn random_ontology(seed: u64) -> (OntologyDllite, AbqDllite) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut onto = OntologyDllite::new(format!("random_{}", seed));
+
+        for c in 0..CONCEPTS {
+            onto.declare_symbol(&format!("C{}", c), DLType::BaseConcept);
+        }
+        for r in 0..ROLES {
+            onto.declare_symbol(&format!("r{}", r), DLType::BaseRole);
+        }
+
+        for _ in 0..rng.gen_range(6..15) {
+            let negation = if rng.gen::<f64>() < 0.3 { "NOT " } else { "" };
+            let axiom = if rng.gen::<f64>() < 0.8 {
+                format!("{} < {}{}", basic(&mut rng), negation, basic(&mut rng))
+            } else {
+                let inverse = if rng.gen::<f64>() < 0.4 { "INV " } else { "" };
+                format!(
+                    "r{} < {}{}r{}",
+                    rng.gen_range(0..ROLES),
+                    negation,
+                    inverse,
+                    rng.gen_range(0..ROLES)
+                )
+            };
+
+            if let Ok(tbis) = string_to_tbi(&axiom, onto.symbols()) {
+                onto.add_tbis_from_vec(&tbis);
+            }
+        }
+
+        onto.generate_cln(false, false, 0);
+
+        let mut abq = AbqDllite::new("random");
+
+        for _ in 0..rng.gen_range(5..13) {
+            let assertion = if rng.gen::<f64>() < 0.65 {
+                format!(
+                    "n{} : C{}",
+                    rng.gen_range(0..NOMINALS),
+                    rng.gen_range(0..CONCEPTS)
+                )
+            } else {
+                format!(
+                    "n{}, n{} : r{}",
+                    rng.gen_range(0..NOMINALS),
+                    rng.gen_range(0..NOMINALS),
+                    rng.gen_range(0..ROLES)
+                )
+            };
+
+            abq.add(onto.abiq_from_string(&assertion).unwrap());
+        }
+
+        (onto, abq)
+    }
+
+    // the matrix by every ordered pair of items, what the candidate pairs must give
+    fn pairwise_matrix(onto: &OntologyDllite, abq: &AbqDllite) -> Vec<i8> {
+        let (_, real_to_virtual, virtual_to_real) = onto.conflict_matrix_refs_only(abq, false);
+        let length = virtual_to_real.len();
+        let mut matrix: Vec<i8> = vec![0; length * length];
+
+        for i in 0..length {
+            for j in (0..length).filter(|j| *j != i) {
+                let abiq_i = abq.get(virtual_to_real[&i]).unwrap();
+                let abiq_j = abq.get(virtual_to_real[&j]).unwrap();
+                let abiq_j_neg = abiq_j.negate();
+
+                if AbqDllite::is_inconsistent_refs_only(
+                    vec![abiq_i, abiq_j],
+                    onto.cln(false),
+                    false,
+                )
+                .0
+                {
+                    matrix[length * j + i] = -1;
+                } else if AbqDllite::is_inconsistent_refs_only(
+                    vec![abiq_i, &abiq_j_neg],
+                    onto.cln(true),
+                    false,
+                )
+                .0
+                {
+                    matrix[length * j + i] = 1;
+                }
+            }
+        }
+
+        assert_eq!(real_to_virtual.len(), abq.len());
+        matrix
+    }
+
+    #[test]
+    fn candidate_pairs_give_the_pairwise_matrix() {
+        for seed in 0..200 {
+            let (onto, abq) = random_ontology(seed);
+            let (matrix, _, _) = onto.conflict_matrix_refs_only(&abq, false);
+
+            assert_eq!(matrix, pairwise_matrix(&onto, &abq), "seed {}", seed);
+        }
+    }
 }
@@ -59,12 +59,12 @@ pub fn dl_lite_abox_rule_one(
                     true => Some(v),
                     false => {
                         // first create a: Er
-                        let er = r.clone().exists().unwrap(); // this should work, r is a base role
-                        let a_er = AbiDllite::new_ca(er, a.clone(), true).unwrap(); // rules are always applied for completion
+                        let er = r.exists().unwrap(); // this should work, r is a base role
+                        let a_er = AbiDllite::new_ca(er, *a, true).unwrap(); // rules are always applied for completion
 
                         // secondly create b:Er^-
-                        let erinv = r.clone().inverse().unwrap().exists().unwrap();
-                        let b_erinv = AbiDllite::new_ca(erinv, b.clone(), true).unwrap();
+                        let erinv = r.inverse().unwrap().exists().unwrap();
+                        let b_erinv = AbiDllite::new_ca(erinv, *b, true).unwrap();
 
                         let mut a_er_q =
                             AbiqDllite::new(a_er, Option::None, Option::None, big_level + 1);
@@ -104,7 +104,7 @@ pub fn dl_lite_abox_rule_two(
             AbiDllite::CA(_, _) => Option::None,
             AbiDllite::RA(r, a, b) => {
                 if r == tbi.lside() {
-                    let new_ra = AbiDllite::new_ra(tbi.rside().clone(), a.clone(), b.clone(), true);
+                    let new_ra = AbiDllite::new_ra(*tbi.rside(), *a, *b, true);
 
                     let mut new_ra_q =
                         AbiqDllite::new(new_ra.unwrap(), Option::None, Option::None, big_level + 1);
@@ -144,7 +144,7 @@ pub fn dl_lite_abox_rule_three(
             AbiDllite::RA(_, _, _) => Option::None,
             AbiDllite::CA(c, a) => {
                 if c == tbi.lside() {
-                    let new_ca = AbiDllite::new_ca(tbi.rside().clone(), a.clone(), true);
+                    let new_ca = AbiDllite::new_ca(*tbi.rside(), *a, true);
 
                     let mut new_ca_q =
                         AbiqDllite::new(new_ca.unwrap(), Option::None, Option::None, big_level + 1);
@@ -286,9 +286,9 @@ pub fn dl_lite_closure_negative_one(
 
         // pick the matching pattern
         if y == lside2 && rside2.is_purely_negated() {
-            new_tbi_op = TbiDllite::new(x.clone(), rside2.clone(), level);
+            new_tbi_op = TbiDllite::new(*x, *rside2, level);
         } else {
-            new_tbi_op = TbiDllite::new(x.clone(), lside2.clone().negate(), level);
+            new_tbi_op = TbiDllite::new(*x, lside2.negate(), level);
         }
 
         // be sure that the tbi was built
@@ -307,19 +307,19 @@ pub fn dl_lite_closure_negative_two(
 
     if DLType::all_roles(tbi1.lside().t(), tbi1.rside().t()) && tbi2.rside().is_purely_negated() {
         if tbi2.lside().t() == DLType::ExistsConcept
-            && ItemDllite::child(Some(tbi2.lside()), 1).unwrap()[0] == tbi1.rside()
+            && ItemDllite::child(Some(tbi2.lside()), 1).unwrap()[0] == *tbi1.rside()
         {
-            let new_item = tbi1.lside().clone().exists().unwrap(); // the clone instruction is necessary because exists
-                                                                   // consumes the item
-            let new_tbi_op = TbiDllite::new(new_item, tbi2.rside().clone(), level);
+            let new_item = tbi1.lside().exists().unwrap(); // the clone instruction is necessary because exists
+                                                           // consumes the item
+            let new_tbi_op = TbiDllite::new(new_item, *tbi2.rside(), level);
 
             output_new_tbis_for_rules(new_tbi_op, deduction_tree, CR::Second, tbi1, tbi2)
         } else if ItemDllite::child(Some(tbi2.rside()), 1).unwrap()[0].t() == DLType::ExistsConcept
-            && ItemDllite::child(Some(tbi2.rside()), 2).unwrap()[0] == tbi1.rside()
+            && ItemDllite::child(Some(tbi2.rside()), 2).unwrap()[0] == *tbi1.rside()
         {
-            let new_item = tbi1.lside().clone().exists().unwrap(); // the clone instruction is necessary because exists
-                                                                   // consumes the item
-            let new_tbi_op = TbiDllite::new(new_item, tbi2.lside().clone().negate(), level);
+            let new_item = tbi1.lside().exists().unwrap(); // the clone instruction is necessary because exists
+                                                           // consumes the item
+            let new_tbi_op = TbiDllite::new(new_item, tbi2.lside().negate(), level);
 
             output_new_tbis_for_rules(new_tbi_op, deduction_tree, CR::Second, tbi1, tbi2)
         } else {
@@ -340,20 +340,20 @@ pub fn dl_lite_closure_negative_three(
     if DLType::all_roles(tbi1.lside().t(), tbi1.rside().t()) && tbi2.rside().is_purely_negated() {
         let second_child_lside2 = ItemDllite::child(Some(tbi2.lside()), 2);
 
-        if second_child_lside2.is_some() && second_child_lside2.unwrap()[0] == tbi1.rside() {
-            let new_item = tbi1.lside().clone().inverse().unwrap().exists().unwrap();
+        if second_child_lside2.is_some() && second_child_lside2.unwrap()[0] == *tbi1.rside() {
+            let new_item = tbi1.lside().inverse().unwrap().exists().unwrap();
 
-            let new_tbi_op = TbiDllite::new(new_item, tbi2.rside().clone(), level);
+            let new_tbi_op = TbiDllite::new(new_item, *tbi2.rside(), level);
 
             return output_new_tbis_for_rules(new_tbi_op, deduction_tree, CR::Third, tbi1, tbi2);
         }
 
         let third_child_rside2 = ItemDllite::child(Some(tbi2.rside()), 3);
 
-        if third_child_rside2.is_some() && third_child_rside2.unwrap()[0] == tbi1.rside() {
-            let new_item = tbi1.lside().clone().inverse().unwrap().exists().unwrap();
+        if third_child_rside2.is_some() && third_child_rside2.unwrap()[0] == *tbi1.rside() {
+            let new_item = tbi1.lside().inverse().unwrap().exists().unwrap();
 
-            let new_tbi_op = TbiDllite::new(new_item, tbi2.lside().clone().negate(), level);
+            let new_tbi_op = TbiDllite::new(new_item, tbi2.lside().negate(), level);
 
             return output_new_tbis_for_rules(new_tbi_op, deduction_tree, CR::Third, tbi1, tbi2);
         }
@@ -406,16 +406,16 @@ pub fn dl_lite_closure_negative_five(
         && tbi.lside().is_negation(tbi.rside())
         && !tbi.lside().is_purely_negated()
     {
-        let r_inv = tbi.lside().clone().inverse().unwrap();
+        let r_inv = tbi.lside().inverse().unwrap();
 
         let tbi1_op = TbiDllite::new(
-            r_inv.clone().exists().unwrap(),
+            r_inv.exists().unwrap(),
             r_inv.exists().unwrap().negate(),
             level,
         );
         let tbi2_op = TbiDllite::new(
-            tbi.lside().clone().exists().unwrap(),
-            tbi.lside().clone().exists().unwrap().negate(),
+            tbi.lside().exists().unwrap(),
+            tbi.lside().exists().unwrap().negate(),
             level,
         );
 
@@ -425,10 +425,10 @@ pub fn dl_lite_closure_negative_five(
 
         let real_r = ItemDllite::child(Some(tbi.lside()), 1).unwrap()[0];
 
-        let tbi1_op = TbiDllite::new(real_r.clone(), real_r.clone().negate(), level);
+        let tbi1_op = TbiDllite::new(real_r, real_r.negate(), level);
         let tbi2_op = TbiDllite::new(
-            real_r.clone().inverse().unwrap().exists().unwrap(),
-            real_r.clone().inverse().unwrap().exists().unwrap().negate(),
+            real_r.inverse().unwrap().exists().unwrap(),
+            real_r.inverse().unwrap().exists().unwrap().negate(),
             level,
         );
 
@@ -448,7 +448,7 @@ pub fn dl_lite_closure_positive_six(
     let (tbi1, tbi2, level) = dl_lite_closure_decompact_vec(&vec);
 
     if tbi1.rside() == tbi2.lside() {
-        let new_tbi_op = TbiDllite::new(tbi1.lside().clone(), tbi2.rside().clone(), level);
+        let new_tbi_op = TbiDllite::new(*tbi1.lside(), *tbi2.rside(), level);
 
         output_new_tbis_for_rules(new_tbi_op, deduction_tree, CR::Sixth, tbi1, tbi2)
     } else {
@@ -466,13 +466,9 @@ pub fn dl_lite_closure_positive_seven(
     if DLType::all_roles(tbi1.lside().t(), tbi1.lside().t())
         && !tbi1.rside().is_purely_negated()
         && tbi2.rside().t() == DLType::ExistsConcept
-        && ItemDllite::child(Some(tbi2.rside()), 1).unwrap()[0] == tbi1.lside()
+        && ItemDllite::child(Some(tbi2.rside()), 1).unwrap()[0] == *tbi1.lside()
     {
-        let new_tbi_op = TbiDllite::new(
-            tbi2.lside().clone(),
-            tbi1.rside().clone().exists().unwrap(),
-            level,
-        );
+        let new_tbi_op = TbiDllite::new(*tbi2.lside(), tbi1.rside().exists().unwrap(), level);
 
         output_new_tbis_for_rules(new_tbi_op, deduction_tree, CR::Seventh, tbi1, tbi2)
     } else {
@@ -492,8 +488,8 @@ pub fn dl_lite_closure_positive_eight(
         && tbi2.rside().t() == DLType::ExistsConcept
         && ItemDllite::child(Some(tbi2.rside()), 1).unwrap()[0].is_inverse(tbi1.lside())
     {
-        let new_item = tbi1.rside().clone().inverse().unwrap().exists().unwrap();
-        let new_tbi_op = TbiDllite::new(tbi2.lside().clone(), new_item, level);
+        let new_item = tbi1.rside().inverse().unwrap().exists().unwrap();
+        let new_tbi_op = TbiDllite::new(*tbi2.lside(), new_item, level);
 
         output_new_tbis_for_rules(new_tbi_op, deduction_tree, CR::Eight, tbi1, tbi2)
     } else {
@@ -523,11 +519,7 @@ pub fn dl_lite_closure_positive_ten(
         let r = tbi.lside();
         let s = tbi.rside();
 
-        let new_tbi_op = TbiDllite::new(
-            r.clone().inverse().unwrap(),
-            s.clone().inverse().unwrap(),
-            level,
-        );
+        let new_tbi_op = TbiDllite::new(r.inverse().unwrap(), s.inverse().unwrap(), level);
 
         match new_tbi_op {
             Some(mut new_tbi) => {
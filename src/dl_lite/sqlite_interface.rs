@@ -0,0 +1,1010 @@
+/*
+UMONS 2021
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+use std::collections::HashMap;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, Error, Result};
+
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::abox_item::AbiDllite;
+use crate::dl_lite::abox_item_quantum::AbiqDllite;
+use crate::dl_lite::node::ItemDllite;
+use crate::dl_lite::string_formatter::{node_to_string, string_to_node};
+use crate::dl_lite::tbox::TBDllite;
+use crate::dl_lite::tbox_item::TbiDllite;
+use crate::kb::knowledge_base::{
+    ABox, Implier, LeveledItem, SourceDict, SymbolDict, TBox, TBoxItem,
+};
+use crate::kb::types::{ConflictType, DLType, CR};
+
+/*
+   one database file holds one ontology:
+       - the symbols
+       - the tbox and, if they were computed, both closures, each tbi with its level
+         and its impliers
+       - any number of aboxes, identified by their name
+       - the rankings computed over those aboxes, each one with the parameters of the run
+   nodes are stored as strings in the native format, that way identifiers can change
+   between two sessions without breaking anything
+*/
+
+// index in the abox, value and conflict type of each ranked assertion
+type RankingValues = Vec<(usize, Option<f64>, Option<ConflictType>)>;
+
+/// Name under which the tbox of the ontology is stored.
+pub const DB_TBOX: &str = "tbox";
+/// Name under which the positive closure is stored.
+pub const DB_CLN_POSITIVE: &str = "cln_positive";
+/// Name under which the negative closure is stored.
+pub const DB_CLN_NEGATIVE: &str = "cln_negative";
+
+// the provenance of an assertion is stored in one column, 'source:credibility,...'
+const SOURCES_SEPARATOR: &str = ",";
+const CREDIBILITY_SEPARATOR: char = ':';
+
+/// Version of the tables, kept in the database with 'PRAGMA user_version':
+///   1: symbols, tboxes, aboxes and rankings
+///   2: the provenance of each assertion ('sources' column of 'abis')
+///   3: the trust of each source ('sources' table)
+/// Older databases are brought to this version when they are opened.
+pub const DB_SCHEMA_VERSION: i64 = 3;
+
+const DB_TABLES: &str = "
+CREATE TABLE IF NOT EXISTS ontology (
+    key     TEXT PRIMARY KEY,
+    value   TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS symbols (
+    name    TEXT PRIMARY KEY,
+    id      INTEGER NOT NULL,
+    type    TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS tbis (
+    tbox    TEXT NOT NULL,
+    id      INTEGER NOT NULL,
+    lside   TEXT NOT NULL,
+    rside   TEXT NOT NULL,
+    level   INTEGER NOT NULL,
+    PRIMARY KEY (tbox, id)
+);
+CREATE TABLE IF NOT EXISTS tbi_impliers (
+    tbox        TEXT NOT NULL,
+    tbi         INTEGER NOT NULL,
+    implier     INTEGER NOT NULL,
+    rule        INTEGER NOT NULL,
+    lside       TEXT NOT NULL,
+    rside       TEXT NOT NULL,
+    level       INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS sources (
+    name    TEXT PRIMARY KEY,
+    trust   REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS aboxes (
+    name        TEXT PRIMARY KEY,
+    created     INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS abis (
+    abox        TEXT NOT NULL,
+    id          INTEGER NOT NULL,
+    symbol      TEXT NOT NULL,
+    a           TEXT NOT NULL,
+    b           TEXT,
+    credibility REAL NOT NULL,
+    value       REAL,
+    level       INTEGER NOT NULL,
+    sources     TEXT NOT NULL DEFAULT '',
+    PRIMARY KEY (abox, id)
+);
+CREATE TABLE IF NOT EXISTS rankings (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    abox        TEXT NOT NULL,
+    aggr        TEXT NOT NULL,
+    tolerance   REAL NOT NULL,
+    m_scale     REAL NOT NULL,
+    b_translate REAL NOT NULL,
+    created     INTEGER NOT NULL,
+    length      INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS ranking_values (
+    ranking     INTEGER NOT NULL,
+    abi         INTEGER NOT NULL,
+    value       REAL,
+    conflict    TEXT NOT NULL
+);
+";
+
+/// The metadata of a ranking computed over an ABox: which aggregation
+/// function and which adjusters (tolerance, m_scale, b_translate) were used,
+/// when it was computed and over how many assertions.
+/// The id is given by the database, it is None for a run not yet stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankingRun {
+    id: Option<i64>,
+    abox: String,
+    aggr: String,
+    adjusters: (f64, f64, f64),
+    created: u64,
+    length: usize,
+}
+
+impl RankingRun {
+    /// A new run over the ABox 'abox', the creation time is now.
+    pub fn new(abox: &str, aggr: &str, adjusters: (f64, f64, f64), length: usize) -> RankingRun {
+        RankingRun {
+            id: Option::None,
+            abox: abox.to_string(),
+            aggr: aggr.to_string(),
+            adjusters,
+            created: now_in_seconds(),
+            length,
+        }
+    }
+
+    pub fn id(&self) -> Option<i64> {
+        self.id
+    }
+
+    pub fn abox(&self) -> &str {
+        &self.abox
+    }
+
+    pub fn aggr(&self) -> &str {
+        &self.aggr
+    }
+
+    pub fn adjusters(&self) -> (f64, f64, f64) {
+        self.adjusters
+    }
+
+    /// Seconds since the unix epoch.
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+}
+
+// ------------------------------------------------------------------------------------------
+// writing
+
+pub fn add_basic_tables_to_db(conn: &Connection, verbose: bool) -> Result<()> {
+    if verbose {
+        println!(" -- sqlite_interface::add_basic_tables_to_db: creating tables");
+    }
+
+    conn.execute_batch(DB_TABLES)?;
+    migrate_db(conn, verbose)
+}
+
+/// Opens a database written by the ontology, an older one is brought to the current
+/// version of the tables first.
+pub fn open_db(filename: &str, verbose: bool) -> Result<Connection> {
+    let conn = Connection::open(filename)?;
+
+    // a file without tables has nothing to migrate, reading it will fail later
+    if table_exists(&conn, "symbols")? {
+        migrate_db(&conn, verbose)?;
+    }
+
+    Ok(conn)
+}
+
+// each version only adds to the previous one: missing columns are added and missing
+// tables created
+fn migrate_db(conn: &Connection, verbose: bool) -> Result<()> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    if version > DB_SCHEMA_VERSION {
+        return Err(invalid_db_data(format!(
+            "the database has tables of version {}, this version only knows up to {}",
+            version, DB_SCHEMA_VERSION
+        )));
+    }
+
+    if version == DB_SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    if verbose {
+        println!(
+            " -- sqlite_interface::migrate_db: from version {} to {}",
+            version, DB_SCHEMA_VERSION
+        );
+    }
+
+    let tx = conn.unchecked_transaction()?;
+
+    if table_exists(&tx, "abis")? && !column_exists(&tx, "abis", "sources")? {
+        tx.execute_batch("ALTER TABLE abis ADD COLUMN sources TEXT NOT NULL DEFAULT '';")?;
+    }
+
+    tx.execute_batch(DB_TABLES)?;
+    tx.execute_batch(&format!("PRAGMA user_version = {};", DB_SCHEMA_VERSION))?;
+
+    tx.commit()
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
+pub fn add_ontology_name_to_db(name: &str, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO ontology (key, value) VALUES ('name', ?1)",
+        params![name],
+    )?;
+
+    Ok(())
+}
+
+/// Replace the symbols in the database by symbols.
+pub fn add_symbols_to_db(symbols: &SymbolDict, conn: &Connection, verbose: bool) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+
+    tx.execute("DELETE FROM symbols", [])?;
+
+    {
+        let mut stmt = tx.prepare("INSERT INTO symbols (name, id, type) VALUES (?1, ?2, ?3)")?;
+
+        for (name, (id, t)) in symbols {
+            if verbose {
+                println!(
+                    " -- sqlite_interface::add_symbols_to_db: adding {} ({})",
+                    name, id
+                );
+            }
+
+            stmt.execute(params![name, *id as i64, dltype_to_db(*t)])?;
+        }
+    }
+
+    tx.commit()
+}
+
+/// Stores the trust of each source, a source already stored is updated.
+pub fn add_sources_to_db(trust: &SourceDict, conn: &Connection, verbose: bool) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+
+    {
+        let mut stmt =
+            tx.prepare("INSERT OR REPLACE INTO sources (name, trust) VALUES (?1, ?2)")?;
+
+        for (name, value) in trust {
+            if verbose {
+                println!(
+                    " -- sqlite_interface::add_sources_to_db: adding source {} ({})",
+                    name, value
+                );
+            }
+
+            stmt.execute(params![name, value])?;
+        }
+    }
+
+    tx.commit()
+}
+
+/// Replace the TBox stored under the name 'tbox_name' by tbis, impliers included.
+pub fn add_tbis_to_db(
+    symbols: &SymbolDict,
+    tbis: &[TbiDllite],
+    tbox_name: &str,
+    conn: &Connection,
+    verbose: bool,
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+
+    tx.execute("DELETE FROM tbis WHERE tbox = ?1", params![tbox_name])?;
+    tx.execute(
+        "DELETE FROM tbi_impliers WHERE tbox = ?1",
+        params![tbox_name],
+    )?;
+
+    {
+        let mut stmt_tbi = tx.prepare(
+            "INSERT INTO tbis (tbox, id, lside, rside, level) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let mut stmt_implier = tx.prepare(
+            "INSERT INTO tbi_impliers (tbox, tbi, implier, rule, lside, rside, level)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+
+        for (id, tbi) in tbis.iter().enumerate() {
+            let (lside, rside) = tbi_to_db(tbi, symbols)?;
+
+            if verbose {
+                println!(
+                    " -- sqlite_interface::add_tbis_to_db: adding {} < {} to {}",
+                    &lside, &rside, tbox_name
+                );
+            }
+
+            stmt_tbi.execute(params![
+                tbox_name,
+                id as i64,
+                &lside,
+                &rside,
+                tbi.level() as i64
+            ])?;
+
+            for (implier_index, (rule, implier)) in tbi.implied_by().iter().enumerate() {
+                for implier_tbi in implier {
+                    let (implier_lside, implier_rside) = tbi_to_db(implier_tbi, symbols)?;
+
+                    stmt_implier.execute(params![
+                        tbox_name,
+                        id as i64,
+                        implier_index as i64,
+                        rule.to_usize() as i64,
+                        &implier_lside,
+                        &implier_rside,
+                        implier_tbi.level() as i64
+                    ])?;
+                }
+            }
+        }
+    }
+
+    tx.commit()
+}
+
+/// Replace the ABox stored with the same name as abq.
+pub fn add_abox_to_db(
+    symbols: &SymbolDict,
+    abq: &AbqDllite,
+    conn: &Connection,
+    verbose: bool,
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let name = abq.name();
+
+    tx.execute("DELETE FROM abis WHERE abox = ?1", params![&name])?;
+    tx.execute(
+        "INSERT OR REPLACE INTO aboxes (name, created) VALUES (?1, ?2)",
+        params![&name, now_in_seconds() as i64],
+    )?;
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO abis (abox, id, symbol, a, b, credibility, value, level, sources)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+
+        for (id, abiq) in abq.items().iter().enumerate() {
+            let (symbol, a, b) = match abiq.abi() {
+                AbiDllite::CA(c, a) => (node_to_db(c, symbols)?, node_to_db(a, symbols)?, None),
+                AbiDllite::RA(r, a, b) => (
+                    node_to_db(r, symbols)?,
+                    node_to_db(a, symbols)?,
+                    Some(node_to_db(b, symbols)?),
+                ),
+            };
+
+            if verbose {
+                println!(
+                    " -- sqlite_interface::add_abox_to_db: adding {} to {}",
+                    abiq, &name
+                );
+            }
+
+            stmt.execute(params![
+                &name,
+                id as i64,
+                &symbol,
+                &a,
+                &b,
+                abiq.credibility(),
+                abiq.value(),
+                abiq.level() as i64,
+                evidence_to_db(abiq)
+            ])?;
+        }
+    }
+
+    tx.commit()
+}
+
+/// Store a ranking: the metadata in run and the value of each item of abq,
+/// returns the id of the new run.
+pub fn add_ranking_to_db(
+    abq: &AbqDllite,
+    conflict_type: &HashMap<usize, ConflictType>,
+    run: &RankingRun,
+    conn: &Connection,
+    verbose: bool,
+) -> Result<i64> {
+    let tx = conn.unchecked_transaction()?;
+    let (tolerance, m_scale, b_translate) = run.adjusters();
+
+    tx.execute(
+        "INSERT INTO rankings (abox, aggr, tolerance, m_scale, b_translate, created, length)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            run.abox(),
+            run.aggr(),
+            tolerance,
+            m_scale,
+            b_translate,
+            run.created() as i64,
+            run.len() as i64
+        ],
+    )?;
+    let run_id = tx.last_insert_rowid();
+
+    if verbose {
+        println!(
+            " -- sqlite_interface::add_ranking_to_db: new ranking with id {} for {}",
+            run_id,
+            run.abox()
+        );
+    }
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO ranking_values (ranking, abi, value, conflict) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+
+        for (id, abiq) in abq.items().iter().enumerate() {
+            let conflict = conflict_type
+                .get(&id)
+                .map_or("unknown", |ct| conflict_type_to_db(*ct));
+
+            stmt.execute(params![run_id, id as i64, abiq.value(), conflict])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(run_id)
+}
+
+// ------------------------------------------------------------------------------------------
+// reading
+
+pub fn ontology_name_from_db(conn: &Connection) -> Result<String> {
+    conn.query_row("SELECT value FROM ontology WHERE key = 'name'", [], |row| {
+        row.get(0)
+    })
+}
+
+pub fn add_symbols_from_db(
+    symbols: &mut SymbolDict,
+    conn: &Connection,
+    verbose: bool,
+) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT name, id, type FROM symbols ORDER BY id")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(0)?;
+        let id: i64 = row.get(1)?;
+        let t: String = row.get(2)?;
+
+        Ok((name, id as usize, t))
+    })?;
+
+    for row in rows {
+        let (name, id, t) = row?;
+
+        match dltype_from_db(&t) {
+            Some(dltype) => {
+                if verbose {
+                    println!(
+                        " -- sqlite_interface::add_symbols_from_db: found {} ({})",
+                        &name, id
+                    );
+                }
+
+                symbols.insert(name, (id, dltype));
+            }
+            Option::None => {
+                return Err(invalid_db_data(format!("unknown symbol type: {}", t)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The trust of every source stored.
+pub fn sources_from_db(conn: &Connection) -> Result<SourceDict> {
+    let mut stmt = conn.prepare("SELECT name, trust FROM sources")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+    rows.collect()
+}
+
+/// Add to tb the tbis stored under 'tbox_name', impliers are rebuilt, for this
+/// tbis are read by increasing level: impliers of a tbi are always of lower level,
+/// they are added to tb in the order they were stored.
+pub fn add_tbis_from_db(
+    symbols: &SymbolDict,
+    tb: &mut TBDllite,
+    tbox_name: &str,
+    conn: &Connection,
+    verbose: bool,
+) -> Result<()> {
+    type ImplierRow = (usize, usize, String, String, usize);
+
+    // first the impliers, indexed by the tbi they imply
+    let mut impliers: HashMap<usize, Vec<ImplierRow>> = HashMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT tbi, implier, rule, lside, rside, level FROM tbi_impliers
+             WHERE tbox = ?1 ORDER BY tbi, implier",
+        )?;
+        let rows = stmt.query_map(params![tbox_name], |row| {
+            let tbi: i64 = row.get(0)?;
+            let implier: i64 = row.get(1)?;
+            let rule: i64 = row.get(2)?;
+            let lside: String = row.get(3)?;
+            let rside: String = row.get(4)?;
+            let level: i64 = row.get(5)?;
+
+            Ok((
+                tbi as usize,
+                (
+                    implier as usize,
+                    rule as usize,
+                    lside,
+                    rside,
+                    level as usize,
+                ),
+            ))
+        })?;
+
+        for row in rows {
+            let (tbi, implier_row) = row?;
+            impliers.entry(tbi).or_default().push(implier_row);
+        }
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT id, lside, rside, level FROM tbis WHERE tbox = ?1 ORDER BY level, id")?;
+    let rows = stmt.query_map(params![tbox_name], |row| {
+        let id: i64 = row.get(0)?;
+        let lside: String = row.get(1)?;
+        let rside: String = row.get(2)?;
+        let level: i64 = row.get(3)?;
+
+        Ok((id as usize, lside, rside, level as usize))
+    })?;
+
+    // already rebuilt tbis, to give impliers their own impliers
+    let mut built: HashMap<(String, String), TbiDllite> = HashMap::new();
+    let mut by_id: Vec<(usize, TbiDllite)> = Vec::new();
+
+    for row in rows {
+        let (id, lside, rside, level) = row?;
+        let mut tbi = tbi_from_db(&lside, &rside, level, symbols)?;
+
+        if let Some(tbi_impliers) = impliers.get(&id) {
+            let mut current_implier: Option<(usize, usize)> = Option::None;
+            let mut current_tbis: Vec<TbiDllite> = Vec::new();
+
+            for (implier, rule, implier_lside, implier_rside, implier_level) in tbi_impliers {
+                if let Some((old_implier, old_rule)) = current_implier {
+                    if old_implier != *implier {
+                        tbi.add_to_implied_by((rule_from_db(old_rule)?, current_tbis));
+                        current_tbis = Vec::new();
+                    }
+                }
+
+                current_implier = Some((*implier, *rule));
+
+                let key = (implier_lside.clone(), implier_rside.clone());
+                let implier_tbi = match built.get(&key) {
+                    Some(implier_tbi) => implier_tbi.clone(),
+                    Option::None => {
+                        tbi_from_db(implier_lside, implier_rside, *implier_level, symbols)?
+                    }
+                };
+
+                current_tbis.push(implier_tbi);
+            }
+
+            if let Some((_, rule)) = current_implier {
+                tbi.add_to_implied_by((rule_from_db(rule)?, current_tbis));
+            }
+        }
+
+        if verbose {
+            println!(
+                " -- sqlite_interface::add_tbis_from_db: found {} in {}",
+                &tbi, tbox_name
+            );
+        }
+
+        built.insert((lside, rside), tbi.clone());
+        by_id.push((id, tbi));
+    }
+
+    // the order of the stored tbox is kept
+    by_id.sort_by_key(|(id, _)| *id);
+
+    for (_, tbi) in by_id {
+        tb.add(tbi);
+    }
+
+    Ok(())
+}
+
+/// Names of the ABoxes in the database, by order of creation.
+pub fn abox_names_from_db(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM aboxes ORDER BY created, rowid")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+
+    rows.collect()
+}
+
+/// Builds the ABox stored under the name 'name'.
+pub fn abox_from_db(
+    symbols: &SymbolDict,
+    name: &str,
+    conn: &Connection,
+    verbose: bool,
+) -> Result<AbqDllite> {
+    let mut abq = AbqDllite::new(name);
+
+    let mut stmt = conn.prepare(
+        "SELECT symbol, a, b, credibility, value, level, sources FROM abis
+         WHERE abox = ?1 ORDER BY id",
+    )?;
+    let rows = stmt.query_map(params![name], |row| {
+        let symbol: String = row.get(0)?;
+        let a: String = row.get(1)?;
+        let b: Option<String> = row.get(2)?;
+        let credibility: f64 = row.get(3)?;
+        let value: Option<f64> = row.get(4)?;
+        let level: i64 = row.get(5)?;
+        let sources: String = row.get(6)?;
+
+        Ok((symbol, a, b, credibility, value, level as usize, sources))
+    })?;
+
+    for row in rows {
+        let (symbol, a, b, credibility, value, level, sources) = row?;
+
+        let symbol = node_from_db(&symbol, symbols)?;
+        let a = node_from_db(&a, symbols)?;
+
+        // stored aboxes may come from a completion, complex symbols are allowed
+        let for_completion = true;
+        let abi_op = match b {
+            Option::None => AbiDllite::new_ca(symbol, a, for_completion),
+            Some(b) => {
+                let b = node_from_db(&b, symbols)?;
+                AbiDllite::new_ra(symbol, a, b, for_completion)
+            }
+        };
+
+        match abi_op {
+            Some(abi) => {
+                let mut abiq = AbiqDllite::new(abi, Some(credibility), value, level);
+
+                for (source, source_credibility) in evidence_from_db(&sources, credibility) {
+                    abiq.add_evidence(source, source_credibility);
+                }
+
+                if verbose {
+                    println!(
+                        " -- sqlite_interface::abox_from_db: found {} in {}",
+                        &abiq, name
+                    );
+                }
+
+                abq.add(abiq);
+            }
+            Option::None => {
+                return Err(invalid_db_data(format!(
+                    "invalid abox item in abox {}",
+                    name
+                )));
+            }
+        }
+    }
+
+    Ok(abq)
+}
+
+/// All rankings stored for the ABox 'abox_name', oldest first.
+pub fn rankings_from_db(abox_name: &str, conn: &Connection) -> Result<Vec<RankingRun>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, abox, aggr, tolerance, m_scale, b_translate, created, length FROM rankings
+         WHERE abox = ?1 ORDER BY id",
+    )?;
+    let rows = stmt.query_map(params![abox_name], |row| {
+        let created: i64 = row.get(6)?;
+        let length: i64 = row.get(7)?;
+
+        Ok(RankingRun {
+            id: Some(row.get(0)?),
+            abox: row.get(1)?,
+            aggr: row.get(2)?,
+            adjusters: (row.get(3)?, row.get(4)?, row.get(5)?),
+            created: created as u64,
+            length: length as usize,
+        })
+    })?;
+
+    rows.collect()
+}
+
+/// The values of a stored ranking, one tuple (index in the abox, value, conflict type)
+/// for each assertion, the conflict type is None if it was not known.
+pub fn ranking_values_from_db(run_id: i64, conn: &Connection) -> Result<RankingValues> {
+    let mut stmt = conn.prepare(
+        "SELECT abi, value, conflict FROM ranking_values WHERE ranking = ?1 ORDER BY abi",
+    )?;
+    let rows = stmt.query_map(params![run_id], |row| {
+        let abi: i64 = row.get(0)?;
+        let value: Option<f64> = row.get(1)?;
+        let conflict: String = row.get(2)?;
+
+        Ok((abi as usize, value, conflict_type_from_db(&conflict)))
+    })?;
+
+    rows.collect()
+}
+
+// ------------------------------------------------------------------------------------------
+// same interface as the native and json parsers, a database is one more file type
+
+pub fn parse_symbols_sqlite(filename: &str, verbose: bool) -> io::Result<SymbolDict> {
+    let conn = open_db(filename, verbose).map_err(to_io_error)?;
+    let mut symbols: SymbolDict = HashMap::new();
+
+    add_symbols_from_db(&mut symbols, &conn, verbose).map_err(to_io_error)?;
+
+    Ok(symbols)
+}
+
+pub fn parse_tbox_sqlite(
+    filename: &str,
+    symbols: &SymbolDict,
+    verbose: bool,
+) -> io::Result<TBDllite> {
+    let conn = open_db(filename, verbose).map_err(to_io_error)?;
+    let mut tb = TBDllite::new();
+
+    add_tbis_from_db(symbols, &mut tb, DB_TBOX, &conn, verbose).map_err(to_io_error)?;
+
+    Ok(tb)
+}
+
+/// Reads the ABox named 'name' or the last stored one if None is given.
+/// Nominals of the database unknown to symbols are added to it, as when
+/// parsing a native abox file.
+pub fn parse_abox_sqlite(
+    filename: &str,
+    name: Option<&str>,
+    symbols: &mut SymbolDict,
+    verbose: bool,
+) -> io::Result<AbqDllite> {
+    let conn = open_db(filename, verbose).map_err(to_io_error)?;
+
+    let name = match name {
+        Some(n) => n.to_string(),
+        Option::None => match abox_names_from_db(&conn).map_err(to_io_error)?.pop() {
+            Some(n) => n,
+            Option::None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("no abox stored in {}", filename),
+                ))
+            }
+        },
+    };
+
+    let mut db_symbols: SymbolDict = HashMap::new();
+    add_symbols_from_db(&mut db_symbols, &conn, verbose).map_err(to_io_error)?;
+
+    let mut current_id = symbols.values().map(|(id, _)| *id).max().unwrap_or(1) + 1;
+
+    for (symbol_name, (_, t)) in db_symbols {
+        if t == DLType::Nominal && !symbols.contains_key(&symbol_name) {
+            symbols.insert(symbol_name, (current_id, DLType::Nominal));
+            current_id += 1;
+        }
+    }
+
+    abox_from_db(symbols, &name, &conn, verbose).map_err(to_io_error)
+}
+
+// ------------------------------------------------------------------------------------------
+// helpers
+
+fn to_io_error(e: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+fn now_in_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+// data that was read but makes no sense, the column is not known (rusqlite writes only the
+// message for 'usize::MAX')
+fn invalid_db_data(message: String) -> Error {
+    Error::FromSqlConversionFailure(usize::MAX, Type::Text, message.into())
+}
+
+fn evidence_to_db(abiq: &AbiqDllite) -> String {
+    abiq.evidence()
+        .iter()
+        .map(|(source, credibility)| format!("{}{}{}", source, CREDIBILITY_SEPARATOR, credibility))
+        .collect::<Vec<String>>()
+        .join(SOURCES_SEPARATOR)
+}
+
+// a source without its credibility gets the one of the assertion
+fn evidence_from_db(s: &str, credibility: f64) -> Vec<(&str, f64)> {
+    s.split(SOURCES_SEPARATOR)
+        .filter(|source| !source.is_empty())
+        .map(|source| match source.rsplit_once(CREDIBILITY_SEPARATOR) {
+            Some((name, value)) => match value.parse::<f64>() {
+                Ok(value) => (name, value),
+                Err(_) => (source, credibility),
+            },
+            Option::None => (source, credibility),
+        })
+        .collect()
+}
+
+fn dltype_to_db(t: DLType) -> &'static str {
+    match t {
+        DLType::Bottom => "bottom",
+        DLType::Top => "top",
+        DLType::BaseConcept => "concept",
+        DLType::BaseRole => "role",
+        DLType::Nominal => "nominal",
+        // only base types are symbols
+        _ => "complex",
+    }
+}
+
+fn dltype_from_db(s: &str) -> Option<DLType> {
+    match s {
+        "bottom" => Some(DLType::Bottom),
+        "top" => Some(DLType::Top),
+        "concept" => Some(DLType::BaseConcept),
+        "role" => Some(DLType::BaseRole),
+        "nominal" => Some(DLType::Nominal),
+        _ => Option::None,
+    }
+}
+
+fn conflict_type_to_db(ct: ConflictType) -> &'static str {
+    match ct {
+        ConflictType::Clean => "clean",
+        ConflictType::Conflict => "conflict",
+        ConflictType::SelfConflict => "self_conflict",
+    }
+}
+
+fn conflict_type_from_db(s: &str) -> Option<ConflictType> {
+    match s {
+        "clean" => Some(ConflictType::Clean),
+        "conflict" => Some(ConflictType::Conflict),
+        "self_conflict" => Some(ConflictType::SelfConflict),
+        _ => Option::None,
+    }
+}
+
+fn rule_from_db(rule: usize) -> Result<CR> {
+    CR::from_usize(rule).ok_or_else(|| invalid_db_data(format!("unknown rule: {}", rule)))
+}
+
+fn node_to_db(node: &ItemDllite, symbols: &SymbolDict) -> Result<String> {
+    node_to_string(node, symbols, String::new())
+        .ok_or_else(|| invalid_db_data(format!("node without symbol: {}", node)))
+}
+
+fn node_from_db(s: &str, symbols: &SymbolDict) -> Result<ItemDllite> {
+    string_to_node(s, symbols).map_err(|e| invalid_db_data(e.to_string()))
+}
+
+fn tbi_to_db(tbi: &TbiDllite, symbols: &SymbolDict) -> Result<(String, String)> {
+    Ok((
+        node_to_db(tbi.lside(), symbols)?,
+        node_to_db(tbi.rside(), symbols)?,
+    ))
+}
+
+fn tbi_from_db(lside: &str, rside: &str, level: usize, symbols: &SymbolDict) -> Result<TbiDllite> {
+    let lside = node_from_db(lside, symbols)?;
+    let rside = node_from_db(rside, symbols)?;
+
+    TbiDllite::new(lside, rside, level)
+        .ok_or_else(|| invalid_db_data(String::from("invalid tbox item")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the tables of a database written before the provenance and the trust of sources
+    const DB_TABLES_VERSION_1: &str = "
+    CREATE TABLE symbols (name TEXT PRIMARY KEY, id INTEGER NOT NULL, type TEXT NOT NULL);
+    CREATE TABLE abis (
+        abox TEXT NOT NULL, id INTEGER NOT NULL, symbol TEXT NOT NULL, a TEXT NOT NULL,
+        b TEXT, credibility REAL NOT NULL, value REAL, level INTEGER NOT NULL,
+        PRIMARY KEY (abox, id)
+    );
+    INSERT INTO abis VALUES ('university', 0, 'Student', 'John', NULL, 0.8, NULL, 0);
+    ";
+
+    #[test]
+    fn older_databases_are_migrated() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(DB_TABLES_VERSION_1).unwrap();
+
+        migrate_db(&conn, false).unwrap();
+
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, DB_SCHEMA_VERSION);
+        assert!(column_exists(&conn, "abis", "sources").unwrap());
+        assert!(table_exists(&conn, "sources").unwrap());
+
+        // the assertions already stored have no sources
+        let sources: String = conn
+            .query_row("SELECT sources FROM abis WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(sources, "");
+
+        // a second time does nothing
+        migrate_db(&conn, false).unwrap();
+    }
+
+    #[test]
+    fn newer_databases_are_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        add_basic_tables_to_db(&conn, false).unwrap();
+        conn.execute_batch(&format!("PRAGMA user_version = {};", DB_SCHEMA_VERSION + 1))
+            .unwrap();
+
+        assert!(migrate_db(&conn, false).is_err());
+    }
+}
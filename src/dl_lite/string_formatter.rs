@@ -20,7 +20,7 @@ along with this program.  If not, see https://www.gnu.org/licenses/.
 use crate::dl_lite::abox_item::AbiDllite;
 use crate::dl_lite::abox_item_quantum::AbiqDllite;
 use crate::dl_lite::json_filetype_utilities::{invalid_data_result, result_from_error};
-use crate::dl_lite::node::{ItemDllite, Mod};
+use crate::dl_lite::node::{ItemDllite, Mod, Unpacked};
 use crate::dl_lite::tbox_item::TbiDllite;
 use crate::kb::knowledge_base::LeveledItem;
 use crate::kb::types::DLType;
@@ -95,11 +95,11 @@ pub fn node_to_string(
     symbols: &SymbolDict,
     mut current: String,
 ) -> Option<String> {
-    match node {
-        ItemDllite::B => Some(String::from("Bottom")),
-        ItemDllite::T => Some(String::from("Top")),
-        ItemDllite::N(n) => {
-            let vec_of_s = find_keys_for_value(symbols, *n);
+    match node.unpack() {
+        Unpacked::B => Some(String::from("Bottom")),
+        Unpacked::T => Some(String::from("Top")),
+        Unpacked::N(n) => {
+            let vec_of_s = find_keys_for_value(symbols, n);
 
             if !vec_of_s.is_empty() {
                 current.push_str(vec_of_s[0].as_str());
@@ -108,8 +108,8 @@ pub fn node_to_string(
                 Option::None
             }
         }
-        ItemDllite::R(n) | ItemDllite::C(n) => {
-            let vec_of_s = find_keys_for_value(symbols, *n);
+        Unpacked::R(n) | Unpacked::C(n) => {
+            let vec_of_s = find_keys_for_value(symbols, n);
 
             if !vec_of_s.is_empty() {
                 current.push_str(vec_of_s[0].as_str()); // no space here, need to account for it when doing the modifiers
@@ -118,19 +118,19 @@ pub fn node_to_string(
                 Option::None
             }
         }
-        ItemDllite::X(m, bn) => {
+        Unpacked::X(m, bn) => {
             match m {
                 Mod::I => {
                     current.push_str("INV "); // space here
-                    node_to_string(bn, symbols, current)
+                    node_to_string(&bn, symbols, current)
                 }
                 Mod::E => {
                     current.push_str("EXISTS "); // space here
-                    node_to_string(bn, symbols, current)
+                    node_to_string(&bn, symbols, current)
                 }
                 Mod::N => {
                     current.push_str("NOT "); // space here
-                    node_to_string(bn, symbols, current)
+                    node_to_string(&bn, symbols, current)
                 }
             }
         }
@@ -233,7 +233,7 @@ pub fn string_to_tbi(s: &str, symbols: &SymbolDict) -> io::Result<Vec<TbiDllite>
                         }
                         (Ok(lside), Ok(rside)) => {
                             let level = 0; // newly created tbi level should be zero
-                            let new_tbi_op = TbiDllite::new(lside.clone(), rside.clone(), level);
+                            let new_tbi_op = TbiDllite::new(*lside, *rside, level);
 
                             match new_tbi_op {
                                 Some(new_tbi) => {
@@ -356,9 +356,8 @@ pub fn string_to_abi(
                                 node2 = ItemDllite::new(Some(id2), DLType::Nominal).unwrap();
                             }
 
-                            let abi =
-                                AbiDllite::new_ra(abi_symbol.clone(), node1, node2, for_completion)
-                                    .unwrap();
+                            let abi = AbiDllite::new_ra(*abi_symbol, node1, node2, for_completion)
+                                .unwrap();
 
                             (Ok((abi, to_be_added)), current_id)
                         }
@@ -380,8 +379,8 @@ pub fn string_to_abi(
                                 node1 = ItemDllite::new(Some(id1), DLType::Nominal).unwrap();
                             }
 
-                            let abi = AbiDllite::new_ca(abi_symbol.clone(), node1, for_completion)
-                                .unwrap();
+                            let abi =
+                                AbiDllite::new_ca(*abi_symbol, node1, for_completion).unwrap();
 
                             (Ok((abi, to_be_added)), current_id)
                         }
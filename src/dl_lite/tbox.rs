@@ -18,7 +18,9 @@ along with this program.  If not, see https://www.gnu.org/licenses/.
 */
 
 // external imports
-use std::collections::VecDeque;
+use rayon::prelude::*;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use std::sync::{Arc, Mutex};
 
@@ -26,6 +28,7 @@ use std::sync::{Arc, Mutex};
 use crate::dl_lite::helpers_and_utilities::{
     complete_helper_add_if_necessary_general, complete_helper_dump_from_mutex_temporal_to_current,
 };
+use crate::dl_lite::node::ItemDllite;
 use crate::dl_lite::rule::{
     dl_lite_closure_negative_five, dl_lite_closure_negative_four, dl_lite_closure_negative_one,
     dl_lite_closure_negative_three, dl_lite_closure_negative_two, dl_lite_closure_positive_eight,
@@ -34,7 +37,7 @@ use crate::dl_lite::rule::{
 };
 use crate::dl_lite::tbox_item::TbiDllite;
 use crate::dl_lite::utilities::get_max_level_abstract;
-use crate::kb::knowledge_base::{LeveledItem, TBox, TBoxItem, TbRule};
+use crate::kb::knowledge_base::{Implier, Item, LeveledItem, TBox, TBoxItem, TbRule};
 use crate::kb::types::CR;
 
 #[derive(PartialEq, Debug, Clone)]
@@ -108,6 +111,81 @@ impl fmt::Display for TBDllite {
     }
 }
 
+/*
+   indexes for the saturation: items are found by the base symbol of their left side and
+   of their right side ('EXISTS r^-' and 'NOT r' are both indexed under 'r'), every closure
+   rule combines two items sharing a base symbol:
+       - the right side of the first with any side of the second (X < Y with Y < Z,
+         Y < -Z, Z < -Y, E.Y < -Z, ...)
+       - the left side of the first with the right side of the second (r < s with X < E.r)
+*/
+#[derive(Debug, Default)]
+struct ClosureIndex {
+    by_lside: HashMap<ItemDllite, Vec<usize>>,
+    by_rside: HashMap<ItemDllite, Vec<usize>>,
+}
+
+impl ClosureIndex {
+    fn insert(&mut self, tbi: &TbiDllite, position: usize) {
+        self.by_lside
+            .entry(ItemDllite::base(tbi.lside()))
+            .or_default()
+            .push(position);
+        self.by_rside
+            .entry(ItemDllite::base(tbi.rside()))
+            .or_default()
+            .push(position);
+    }
+
+    fn get<'a>(map: &'a HashMap<ItemDllite, Vec<usize>>, item: &ItemDllite) -> &'a [usize] {
+        map.get(&ItemDllite::base(item))
+            .map_or(&[], |v| v.as_slice())
+    }
+
+    // positions of the items that can be combined with tbi by some rule (in any order),
+    // only the ones before 'before' are kept, sorted and without repetitions
+    fn candidates(&self, tbi: &TbiDllite, before: usize) -> Vec<usize> {
+        let mut candidates = [
+            ClosureIndex::get(&self.by_lside, tbi.rside()),
+            ClosureIndex::get(&self.by_rside, tbi.rside()),
+            ClosureIndex::get(&self.by_rside, tbi.lside()),
+            ClosureIndex::get(&self.by_lside, tbi.lside()),
+        ]
+        .iter()
+        .flat_map(|positions| positions.iter())
+        .filter(|position| **position < before)
+        .cloned()
+        .collect::<Vec<usize>>();
+
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        candidates
+    }
+}
+
+// the closure rules with their ordinal, the negative closure only uses the first five
+fn closure_rules(negative_closure: bool) -> Vec<(CR, TbRule<TbiDllite>)> {
+    let rules: [(CR, TbRule<TbiDllite>); 10] = [
+        (CR::First, dl_lite_closure_negative_one),
+        (CR::Second, dl_lite_closure_negative_two),
+        (CR::Third, dl_lite_closure_negative_three),
+        (CR::Fourth, dl_lite_closure_negative_four),
+        (CR::Fifth, dl_lite_closure_negative_five),
+        (CR::Sixth, dl_lite_closure_positive_six),
+        (CR::Seventh, dl_lite_closure_positive_seven),
+        (CR::Eight, dl_lite_closure_positive_eight),
+        (CR::Ninth, dl_lite_closure_positive_nine),
+        (CR::Tenth, dl_lite_closure_positive_ten),
+    ];
+
+    if negative_closure {
+        rules[..5].to_vec()
+    } else {
+        rules.to_vec()
+    }
+}
+
 // adding Default Implementation: thanks Clippy
 impl Default for TBDllite {
     fn default() -> Self {
@@ -141,6 +219,19 @@ impl TBDllite {
 
      */
 
+    /// Removes tbi from self, returns false if it was not present.
+    pub fn remove(&mut self, tbi: &TbiDllite) -> bool {
+        match self.items.iter().position(|present| present == tbi) {
+            Some(index) => {
+                self.items.remove(index);
+                self.length -= 1;
+                self.completed = false;
+                true
+            }
+            Option::None => false,
+        }
+    }
+
     pub fn levels(&self) -> Vec<usize> {
         let levels: Vec<usize> = self.items.iter().map(|x| (&x).level()).collect();
 
@@ -160,7 +251,108 @@ impl TBDllite {
         neg_tbi
     }
 
-    // TODO: this is maybe the most important function, to update !!!!
+    /// True if tbi follows from self: it is in the positive closure, or it is trivial
+    /// ('X < Top', 'Bottom < X') or redundant ('X < X').
+    pub fn entails(&self, tbi: &TbiDllite, verbose: bool) -> bool {
+        let negative_closure = false;
+        let deduction_tree = false;
+
+        tbi.is_trivial()
+            || tbi.is_redundant()
+            || self
+                .cln_completion(negative_closure, deduction_tree, verbose)
+                .contains(tbi)
+    }
+
+    /// A subset of self with the same closure: in the order of self, each item entailed
+    /// by the ones kept so far and the ones not yet seen is dropped. The dropped items
+    /// come with the items of the subset they follow from, none for trivial and
+    /// redundant items.
+    pub fn minimize(&self, verbose: bool) -> (TBDllite, Vec<(TbiDllite, Vec<TbiDllite>)>) {
+        let mut reduced = self.clone();
+        let mut dropped: Vec<TbiDllite> = Vec::new();
+
+        for tbi in &self.items {
+            reduced.remove(tbi);
+
+            if reduced.entails(tbi, verbose) {
+                if verbose {
+                    println!(" -- TBDllite::minimize: {} is entailed, dropped", tbi);
+                }
+
+                dropped.push(tbi.clone());
+            } else {
+                reduced.add(tbi.clone());
+            }
+        }
+
+        // the deduction tree of the reduced closure tells where each dropped item comes from
+        let negative_closure = false;
+        let deduction_tree = true;
+        let closure = reduced.cln_completion(negative_closure, deduction_tree, verbose);
+
+        let justified = dropped
+            .into_iter()
+            .map(|tbi| {
+                let mut grounds: Vec<TbiDllite> = Vec::new();
+
+                if !tbi.is_trivial() && !tbi.is_redundant() {
+                    closure.grounds(&tbi, &mut Vec::new(), &mut grounds);
+                }
+
+                (tbi, grounds)
+            })
+            .collect();
+
+        // 'add' puts kept items at the end, back to the order of self
+        reduced
+            .items
+            .sort_by_key(|kept| self.items.iter().position(|tbi| tbi == kept));
+
+        (reduced, justified)
+    }
+
+    // the level 0 items of a closure that tbi comes from, through the first (the
+    // smallest) implier of each item, 'path' avoids going around a cycle of impliers
+    fn grounds<'a>(
+        &'a self,
+        tbi: &TbiDllite,
+        path: &mut Vec<&'a TbiDllite>,
+        grounds: &mut Vec<TbiDllite>,
+    ) {
+        let known_op = self.items.iter().find(|known| *known == tbi);
+
+        match known_op {
+            Some(known)
+                if known.level() > 0
+                    && !known.implied_by().is_empty()
+                    && !path.contains(&known) =>
+            {
+                path.push(known);
+
+                for implier in &known.implied_by()[0].1 {
+                    self.grounds(implier, path, grounds);
+                }
+
+                path.pop();
+            }
+            _ => {
+                if !grounds.contains(tbi) {
+                    grounds.push(tbi.clone());
+                }
+            }
+        }
+    }
+
+    /// The negative closure of self (only negative inclusions) if 'negative_closure' is
+    /// true, the positive closure (every consequence) otherwise.
+    ///
+    /// Semi-naive evaluation: each round only combines the items found by the last round
+    /// (the delta, at first every item of self) with the items known before them, the
+    /// candidates come from indexes on the left and right sides instead of trying every
+    /// pair against every rule. The items of a delta are treated in parallel, reading
+    /// what is known, their consequences are merged at the end of the round: new items
+    /// make the next delta, already known ones only give their impliers (deduction tree).
     pub fn cln_completion(
         &self,
         negative_closure: bool,
@@ -169,6 +361,132 @@ impl TBDllite {
     ) -> TBDllite {
         let mut cln_tbox = TBDllite::new();
 
+        if self.items.is_empty() {
+            if verbose {
+                println!("the tbox is empty, no closure to complete");
+            }
+
+            return cln_tbox;
+        }
+
+        let rules = closure_rules(negative_closure);
+
+        /*
+           every item of self takes part, even in the negative closure: positive inclusions
+           are needed to deduce negative ones (X < Y and Y < -Z), but every negative rule
+           needs a negative inclusion and gives one, the negative closure is the negative
+           part of what is found here
+        */
+        let mut known: Vec<TbiDllite> = Vec::new();
+        let mut positions: HashMap<(ItemDllite, ItemDllite), usize> = HashMap::new();
+        let mut index = ClosureIndex::default();
+
+        for item in &self.items {
+            let sides = (*item.lside(), *item.rside());
+
+            if let Entry::Vacant(entry) = positions.entry(sides) {
+                entry.insert(known.len());
+                index.insert(item, known.len());
+                known.push(item.clone());
+            }
+        }
+
+        let mut delta_start: usize = 0;
+        let mut round: usize = 0;
+
+        while delta_start < known.len() {
+            let delta_end = known.len();
+
+            if verbose {
+                println!(
+                    " -- TBDllite::cln_completion: round {}, combining {} new items with {} known",
+                    round,
+                    delta_end - delta_start,
+                    delta_start
+                );
+            }
+
+            // each pair is combined once: by the item of the pair found last
+            let consequences = (delta_start..delta_end)
+                .into_par_iter()
+                .map(|position| {
+                    let current_item = &known[position];
+                    let mut new_items: Vec<(CR, TbiDllite)> = Vec::new();
+
+                    let mut apply = |tbis: [&TbiDllite; 2]| {
+                        for (rule_ord, rule) in &rules {
+                            if let Some(items) = TbiDllite::apply_rule(&tbis, rule, deduction_tree)
+                            {
+                                new_items.extend(items.into_iter().map(|item| (*rule_ord, item)));
+                            }
+                        }
+                    };
+
+                    // rules of arity one only look at the first item
+                    apply([current_item, current_item]);
+
+                    for other in index.candidates(current_item, position) {
+                        apply([current_item, &known[other]]);
+                        apply([&known[other], current_item]);
+                    }
+
+                    new_items
+                })
+                .collect::<Vec<Vec<(CR, TbiDllite)>>>();
+
+            for (rule_ord, new_item) in consequences.into_iter().flatten() {
+                let sides = (*new_item.lside(), *new_item.rside());
+
+                match positions.get(&sides) {
+                    Some(position) => {
+                        if deduction_tree {
+                            for implier in new_item.implied_by() {
+                                known[*position].add_to_implied_by(implier.clone());
+                            }
+                        }
+                    }
+                    Option::None => {
+                        if verbose {
+                            println!(
+                                " -- TBDllite::cln_completion: {} rule gives {}",
+                                rule_ord, &new_item
+                            );
+                        }
+
+                        positions.insert(sides, known.len());
+                        index.insert(&new_item, known.len());
+                        known.push(new_item);
+                    }
+                }
+            }
+
+            delta_start = delta_end;
+            round += 1;
+        }
+
+        // items are already unique, no need to go through 'add'
+        cln_tbox.items = known
+            .into_iter()
+            .filter(|item| !negative_closure || item.is_negative_inclusion())
+            .collect();
+        cln_tbox.length = cln_tbox.items.len();
+
+        // of course, set completed to 'true' in the new tbox
+        cln_tbox.completed = true;
+        cln_tbox
+    }
+
+    /// The former fixpoint for 'cln_completion': every item found is tried against every
+    /// other one with every rule, the queues are shared behind mutexes.
+    /// Kept as a reference to compare with, see 'bench_tbox_closure'.
+    pub fn cln_completion_pairwise(
+        &self,
+        negative_closure: bool,
+        deduction_tree: bool,
+        verbose: bool,
+    ) -> TBDllite {
+        let mut cln_tbox = TBDllite::new();
+
         // TESTING: for type constriction
         type T = TbiDllite;
 
@@ -572,3 +890,137 @@ impl TBDllite {
         get_max_level_abstract(self.items())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dl_lite::native_filetype_utilities::parse_tbox_native_checked;
+    use crate::dl_lite::string_formatter::string_to_tbi;
+    use crate::kb::knowledge_base::SymbolDict;
+    use crate::kb::types::DLType;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashSet;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const CONCEPTS: usize = 8;
+    const ROLES: usize = 4;
+
+    fn symbols() -> SymbolDict {
+        let mut symbols: SymbolDict = HashMap::new();
+
+        for c in 0..CONCEPTS {
+            symbols.insert(format!("C{}", c), (c + 2, DLType::BaseConcept));
+        }
+        for r in 0..ROLES {
+            symbols.insert(format!("r{}", r), (CONCEPTS + r + 2, DLType::BaseRole));
+        }
+
+        symbols
+    }
+
+    fn basic(rng: &mut StdRng) -> String {
+        if rng.gen::<f64>() < 0.6 {
+            format!("C{}", rng.gen_range(0..CONCEPTS))
+        } else {
+            let inverse = if rng.gen::<bool>() { "INV " } else { "" };
+            format!("EXISTS {}r{}", inverse, rng.gen_range(0..ROLES))
+        }
+    }
+
+    // concept and role inclusions, some of them negative
+    fn random_tbox(seed: u64, symbols: &SymbolDict) -> TBDllite {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut tbox = TBDllite::new();
+
+        for _ in 0..rng.gen_range(6..20) {
+            let negation = if rng.gen::<f64>() < 0.3 { "NOT " } else { "" };
+            let axiom = if rng.gen::<f64>() < 0.8 {
+                format!("{} < {}{}", basic(&mut rng), negation, basic(&mut rng))
+            } else {
+                let inverse = if rng.gen::<f64>() < 0.4 { "INV " } else { "" };
+                format!(
+                    "r{} < {}{}r{}",
+                    rng.gen_range(0..ROLES),
+                    negation,
+                    inverse,
+                    rng.gen_range(0..ROLES)
+                )
+            };
+
+            for tbi in string_to_tbi(&axiom, symbols).unwrap_or_default() {
+                tbox.add(tbi);
+            }
+        }
+
+        tbox
+    }
+
+    fn tbi(axiom: &str, symbols: &SymbolDict) -> TbiDllite {
+        string_to_tbi(axiom, symbols).unwrap().pop().unwrap()
+    }
+
+    fn sides(tbox: &TBDllite) -> HashSet<(ItemDllite, ItemDllite)> {
+        tbox.items()
+            .iter()
+            .map(|tbi| (*tbi.lside(), *tbi.rside()))
+            .collect()
+    }
+
+    #[test]
+    fn semi_naive_closure_is_the_pairwise_one() {
+        let symbols = symbols();
+
+        for seed in 0..50 {
+            let tbox = random_tbox(seed, &symbols);
+
+            for negative_closure in [true, false] {
+                let closure = tbox.cln_completion(negative_closure, false, false);
+                let pairwise = tbox.cln_completion_pairwise(negative_closure, false, false);
+
+                assert_eq!(closure.len(), sides(&closure).len(), "seed {}", seed);
+                assert_eq!(
+                    sides(&closure),
+                    sides(&pairwise),
+                    "seed {}, negative closure {}",
+                    seed,
+                    negative_closure
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn minimize_drops_the_chain_shortcut_and_the_copy() {
+        let symbols = symbols();
+        let mut file = NamedTempFile::new().unwrap();
+
+        write!(
+            file,
+            "BEGINTBOX\nC0 < C1\nC1 < C2\nC0 < C2\nC0 < C1\nENDTBOX\n"
+        )
+        .unwrap();
+
+        let (tbox, repeated) =
+            parse_tbox_native_checked(file.path().to_str().unwrap(), &symbols, false).unwrap();
+
+        // the copy on line 5 is only reported, with the line of the first one
+        assert_eq!(repeated, vec![(tbi("C0 < C1", &symbols), 2)]);
+        assert_eq!(tbox.len(), 3);
+
+        let (reduced, removed) = tbox.minimize(false);
+
+        assert_eq!(
+            reduced.items(),
+            &vec![tbi("C0 < C1", &symbols), tbi("C1 < C2", &symbols)]
+        );
+        assert_eq!(
+            removed,
+            vec![(
+                tbi("C0 < C2", &symbols),
+                vec![tbi("C0 < C1", &symbols), tbi("C1 < C2", &symbols)]
+            )]
+        );
+    }
+}
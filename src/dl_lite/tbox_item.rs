@@ -239,8 +239,8 @@ impl TbiDllite {
         be aware: this method creates a new item
          */
         if self.rside.is_negated() {
-            let lside = self.lside.clone();
-            let rside = self.rside.clone();
+            let lside = self.lside;
+            let rside = self.rside;
 
             let level: usize = if !add_level {
                 self.level
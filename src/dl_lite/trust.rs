@@ -0,0 +1,278 @@
+/*
+UMONS 2021
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::abox_item_quantum::AbiqDllite;
+use crate::kb::knowledge_base::{ABox, AggrFn, SourceDict};
+
+/*
+   assertions come from sources that are not equally reliable, each source has a trust
+   value and each assertion keeps the credibility each of its sources gave it,
+   the evidence of a source is its credibility times its trust, the evidences of all
+   the sources asserting the same fact are combined (max, noisy-or, sum) and the result
+   is the credibility used by the ranking
+   once ranked, sources get feedback: how well their assertions did
+*/
+
+/// Trust of a source that was never declared.
+pub const DEFAULT_TRUST: f64 = 1.0;
+
+/// Marks a source tag at the end of an assertion (e.g. 'John : Professor, 0.8 @registry').
+pub const SOURCE_TAG: char = '@';
+
+/// What a ranking says about a source: its declared trust (if any), how many
+/// assertions it gave, the mean value they got and an estimate of its trust,
+/// the mean value of its assertions divided by the highest value of the abox.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFeedback {
+    name: String,
+    trust: Option<f64>,
+    assertions: usize,
+    mean_value: f64,
+    estimate: f64,
+}
+
+impl fmt::Display for SourceFeedback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let trust = match self.trust {
+            Some(t) => format!("{}", t),
+            Option::None => String::from("undeclared"),
+        };
+
+        write!(
+            f,
+            "{}: trust {}, estimated {:.4} ({} assertions, mean value {:.4})",
+            self.name, trust, self.estimate, self.assertions, self.mean_value
+        )
+    }
+}
+
+impl SourceFeedback {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn trust(&self) -> Option<f64> {
+        self.trust
+    }
+
+    pub fn assertions(&self) -> usize {
+        self.assertions
+    }
+
+    pub fn mean_value(&self) -> f64 {
+        self.mean_value
+    }
+
+    pub fn estimate(&self) -> f64 {
+        self.estimate
+    }
+}
+
+/// The credibility of abiq once the evidence of all its sources is combined,
+/// an assertion without sources keeps its credibility.
+pub fn combined_credibility(abiq: &AbiqDllite, trust: &SourceDict, combine: AggrFn) -> f64 {
+    if abiq.evidence().is_empty() {
+        abiq.credibility()
+    } else {
+        let evidences: Vec<f64> = abiq
+            .evidence()
+            .iter()
+            .map(|(source, credibility)| {
+                credibility * trust.get(source).copied().unwrap_or(DEFAULT_TRUST)
+            })
+            .collect();
+
+        combine(evidences)
+    }
+}
+
+/// Replaces the credibility of each assertion of abq with sources by
+/// their combined credibility, returns false if no assertion has sources.
+pub fn apply_trust(abq: &mut AbqDllite, trust: &SourceDict, combine: AggrFn) -> bool {
+    let mut applied = false;
+
+    for abiq in abq.items_mut() {
+        if !abiq.evidence().is_empty() {
+            let credibility = combined_credibility(abiq, trust, combine);
+            abiq.set_credibility(credibility);
+            applied = true;
+        }
+    }
+
+    applied
+}
+
+/// Feedback for every source of a ranked abox, in order of appearance,
+/// assertions not yet ranked are not taken into account.
+pub fn source_feedback(abq: &AbqDllite, trust: &SourceDict) -> Vec<SourceFeedback> {
+    let max_value = abq
+        .items()
+        .iter()
+        .filter_map(|abiq| abiq.value())
+        .fold(0_f64, f64::max);
+
+    let mut values: HashMap<String, Vec<f64>> = HashMap::new();
+
+    for abiq in abq.items() {
+        if let Some(value) = abiq.value() {
+            for source in abiq.sources() {
+                values.entry(source).or_default().push(value);
+            }
+        }
+    }
+
+    abq.sources()
+        .into_iter()
+        .filter_map(|name| {
+            let source_values = values.get(&name)?;
+            let mean_value = source_values.iter().sum::<f64>() / (source_values.len() as f64);
+            let estimate = if max_value > 0. {
+                mean_value / max_value
+            } else {
+                0.
+            };
+
+            Some(SourceFeedback {
+                trust: trust.get(&name).copied(),
+                name,
+                assertions: source_values.len(),
+                mean_value,
+                estimate,
+            })
+        })
+        .collect()
+}
+
+/// Where the source tags at the end of an assertion line begin, a tag is a word
+/// starting with '@' after a whitespace, thus 'alice@example.org' is not a tag.
+/// Returns None if the line has no tag.
+pub fn source_tags_start(line: &str) -> Option<usize> {
+    let mut start = Option::None;
+    let mut rest = line.trim_end();
+
+    while let Some(index) = rest.rfind(char::is_whitespace) {
+        if !rest[index..].trim_start().starts_with(SOURCE_TAG) {
+            break;
+        }
+
+        start = Some(index);
+        rest = rest[..index].trim_end();
+    }
+
+    start
+}
+
+/// Splits the source tags from the end of an assertion line,
+/// 'John : Professor, 0.8 @registry @forum' gives ('John : Professor, 0.8', ['registry', 'forum']).
+pub fn split_source_tags(line: &str) -> (&str, Vec<&str>) {
+    match source_tags_start(line) {
+        Option::None => (line.trim(), Vec::new()),
+        Some(index) => {
+            let tags = line[index..]
+                .split_whitespace()
+                .map(|tag| tag.trim_start_matches(SOURCE_TAG))
+                .filter(|tag| !tag.is_empty())
+                .collect();
+
+            (line[..index].trim(), tags)
+        }
+    }
+}
+
+/// Parses the source declarations of a native file, a section of lines 'name: trust':
+/// BEGINSOURCES
+/// registry: 0.9
+/// forum: 0.3
+/// ENDSOURCES
+/// A file without this section declares no source.
+pub fn parse_sources_native(filename: &str, verbose: bool) -> io::Result<SourceDict> {
+    let file = File::open(filename)?;
+    let reader = BufReader::new(file);
+
+    let mut trust: SourceDict = HashMap::new();
+    let mut begin_sources_encountered = false;
+
+    for line in reader.lines() {
+        let line = line?;
+        let not_ignored = line.split("//").next().unwrap_or("").trim();
+
+        if not_ignored == "BEGINSOURCES" {
+            begin_sources_encountered = true;
+            continue;
+        }
+
+        if !begin_sources_encountered || not_ignored.is_empty() {
+            continue;
+        }
+
+        if not_ignored == "ENDSOURCES" {
+            break;
+        }
+
+        let parsed = not_ignored
+            .split_once(':')
+            .and_then(|(name, value)| Some((name.trim(), value.trim().parse::<f64>().ok()?)));
+
+        match parsed {
+            Some((name, value)) if !name.is_empty() && (0. ..=1.).contains(&value) => {
+                if verbose {
+                    println!(
+                        " -- trust::parse_sources_native: source {} with trust {}",
+                        name, value
+                    );
+                }
+
+                trust.insert(name.to_string(), value);
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "not a source declaration (name: trust in [0, 1]): {}",
+                        not_ignored
+                    ),
+                ))
+            }
+        }
+    }
+
+    Ok(trust)
+}
+
+/// Writes the source declarations in the native format, sorted by name.
+pub fn sources_to_native_string(trust: &SourceDict) -> String {
+    let mut names: Vec<&String> = trust.keys().collect();
+    names.sort();
+
+    let mut s = String::from("BEGINSOURCES\n");
+
+    for name in names {
+        s.push_str(format!("{}: {}\n", name, trust[name]).as_str());
+    }
+
+    s.push_str("ENDSOURCES\n");
+    s
+}
@@ -1,11 +1,14 @@
 use crate::dl_lite::abox::AbqDllite;
 use crate::dl_lite::abox_item_quantum::AbiqDllite;
-use crate::dl_lite::string_formatter::abi_to_string;
+use crate::dl_lite::explanation::find_assertion;
+use crate::dl_lite::string_formatter::{abi_to_string, string_to_node};
+use crate::graph_maker::{EdgeKind, GraphNode};
 use crate::kb::knowledge_base::{ABox, LeveledItem, SymbolDict};
 use crate::kb::types::ConflictType;
+use petgraph::graph::NodeIndex;
 use petgraph::{Directed, Graph};
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Helps ordering size of impliers both in abox items and tbox items.
 pub fn ordering_cmp_helper(len1: usize, len2: usize) -> (usize, Ordering) {
@@ -34,11 +37,28 @@ pub fn create_aboxq_graph_dot(
     conflict_type: &HashMap<usize, ConflictType>,
     only_conflicts: bool,
 ) -> Graph<String, bool, Directed, u32> {
-    let mut graph: Graph<String, bool> = Graph::new();
-    let mut string_op: Option<String>;
-    let mut string_node: String;
+    create_aboxq_graph(
+        abq,
+        symbols,
+        conflict_matrix,
+        real_to_virtual,
+        conflict_type,
+        only_conflicts,
+    )
+    .map(|_, n| n.to_string(), |_, e| *e == EdgeKind::Implication)
+}
+
+/// The conflict graph of the abox: an edge from j to i when j implies i or refutes i.
+pub fn create_aboxq_graph(
+    abq: &AbqDllite,
+    symbols: &SymbolDict,
+    conflict_matrix: &[i8],
+    real_to_virtual: &HashMap<usize, usize>,
+    conflict_type: &HashMap<usize, ConflictType>,
+    only_conflicts: bool,
+) -> Graph<GraphNode, EdgeKind, Directed, u32> {
+    let mut graph: Graph<GraphNode, EdgeKind> = Graph::new();
     let mut abiq: &AbiqDllite;
-    let _conft: ConflictType;
 
     // dict for index
     let mut index_dict = HashMap::new();
@@ -48,40 +68,24 @@ pub fn create_aboxq_graph_dot(
         abiq = abq.items().get(i).unwrap();
         let conft = conflict_type.get(&i).unwrap_or(&ConflictType::Conflict);
 
-        match conft {
-            ConflictType::SelfConflict => (),
-            ConflictType::Clean => {
-                if !only_conflicts {
-                    string_op = abi_to_string(abiq.abi(), symbols);
+        let add_node = match conft {
+            ConflictType::SelfConflict => false,
+            ConflictType::Clean => !only_conflicts,
+            ConflictType::Conflict => true,
+        };
 
-                    string_node = match string_op {
-                        Option::None => {
-                            format!("{}, v: {}", abiq.abi(), abiq.value().unwrap_or(1.))
-                        }
-                        Some(s) => {
-                            format!("{}, v: {}", s, abiq.value().unwrap_or(1.))
-                        }
-                    };
+        if add_node {
+            let text = match abi_to_string(abiq.abi(), symbols) {
+                Option::None => format!("{}", abiq.abi()),
+                Some(s) => s,
+            };
+            let label = format!("{}, v: {}", &text, abiq.value().unwrap_or(1.));
 
-                    let index = graph.add_node(string_node);
-                    index_dict.insert(i, index);
-                }
-            }
-            ConflictType::Conflict => {
-                string_op = abi_to_string(abiq.abi(), symbols);
-
-                string_node = match string_op {
-                    Option::None => {
-                        format!("{}, v: {}", abiq.abi(), abiq.value().unwrap_or(1.))
-                    }
-                    Some(s) => {
-                        format!("{}, v: {}", s, abiq.value().unwrap_or(1.))
-                    }
-                };
+            let mut node = GraphNode::abi(abiq, text, label, Some(*conft));
+            node.set_index(i);
 
-                let index = graph.add_node(string_node);
-                index_dict.insert(i, index);
-            }
+            let index = graph.add_node(node);
+            index_dict.insert(i, index);
         }
     }
 
@@ -105,11 +109,13 @@ pub fn create_aboxq_graph_dot(
                         match w_ij.cmp(&0) {
                             Ordering::Less => {
                                 // i is refuted by j
-                                graph.add_edge(*index_j, *index_i, false); // an arrow from j to i
+                                graph.add_edge(*index_j, *index_i, EdgeKind::Refutation);
+                                // an arrow from j to i
                             }
                             Ordering::Greater => {
                                 // i is implied by j
-                                graph.add_edge(*index_j, *index_i, true); // an arrow from j to i
+                                graph.add_edge(*index_j, *index_i, EdgeKind::Implication);
+                                // an arrow from j to i
                             }
                             _ => (),
                         }
@@ -122,3 +128,134 @@ pub fn create_aboxq_graph_dot(
 
     graph
 }
+
+/// The ways to cut a conflict graph down to the part of interest, for big aboxes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphFilter {
+    Nominal(String),              // assertions on this constant
+    Predicate(String),            // assertions of this concept or role
+    Neighbourhood(String, usize), // assertions at most k edges away from this one
+    Lowest(usize),                // the n assertions with the lowest value
+}
+
+/// Keeps the nodes of the conflict graph that pass every filter, filters are applied
+/// in order (a neighbourhood is taken in what the previous filters left) and an edge
+/// is kept when both its ends are.
+pub fn filter_aboxq_graph(
+    graph: &Graph<GraphNode, EdgeKind>,
+    abq: &AbqDllite,
+    symbols: &SymbolDict,
+    filters: &[GraphFilter],
+) -> Graph<GraphNode, EdgeKind> {
+    let mut graph = graph.clone();
+
+    for filter in filters {
+        let keep: HashSet<NodeIndex> = match filter {
+            GraphFilter::Nominal(name) | GraphFilter::Predicate(name) => {
+                match string_to_node(name, symbols) {
+                    Err(_) => {
+                        println!(" -- the symbol '{}' is not in the ontology", name);
+                        HashSet::new()
+                    }
+                    Ok(item) => graph
+                        .node_indices()
+                        .filter(|ni| match graph[*ni].index() {
+                            Option::None => false,
+                            Some(i) => {
+                                let abi = abq.items()[i].abi();
+
+                                match filter {
+                                    GraphFilter::Nominal(_) => {
+                                        abi.decompact_nominals_refs().contains(&&item)
+                                    }
+                                    _ => *abi.symbol() == item,
+                                }
+                            }
+                        })
+                        .collect(),
+                }
+            }
+            GraphFilter::Neighbourhood(assertion, hops) => {
+                let center = find_assertion(abq, assertion, symbols).and_then(|i| {
+                    graph
+                        .node_indices()
+                        .find(|ni| graph[*ni].index() == Some(i))
+                });
+
+                match center {
+                    Option::None => {
+                        println!(
+                            " -- the assertion '{}' is not in the conflict graph",
+                            assertion
+                        );
+                        HashSet::new()
+                    }
+                    Some(center) => neighbourhood(&graph, center, *hops),
+                }
+            }
+            GraphFilter::Lowest(n) => {
+                let mut indices: Vec<NodeIndex> = graph.node_indices().collect();
+
+                // unranked assertions have the neutral value
+                indices.sort_by(|ni, nj| {
+                    let vi = graph[*ni].value().unwrap_or(1.);
+                    let vj = graph[*nj].value().unwrap_or(1.);
+
+                    vi.partial_cmp(&vj).unwrap_or(Ordering::Equal)
+                });
+
+                indices.into_iter().take(*n).collect()
+            }
+        };
+
+        graph = graph.filter_map(
+            |ni, node| {
+                if keep.contains(&ni) {
+                    Some(node.clone())
+                } else {
+                    Option::None
+                }
+            },
+            |_, edge| Some(*edge),
+        );
+    }
+
+    graph
+}
+
+// the nodes at most 'hops' edges away from center, whatever the direction of the edges
+fn neighbourhood(
+    graph: &Graph<GraphNode, EdgeKind>,
+    center: NodeIndex,
+    hops: usize,
+) -> HashSet<NodeIndex> {
+    let mut seen: HashSet<NodeIndex> = HashSet::new();
+    let mut queue: VecDeque<(NodeIndex, usize)> = VecDeque::new();
+
+    seen.insert(center);
+    queue.push_back((center, 0));
+
+    while let Some((ni, distance)) = queue.pop_front() {
+        if distance == hops {
+            continue;
+        }
+
+        for nj in graph.neighbors_undirected(ni) {
+            if seen.insert(nj) {
+                queue.push_back((nj, distance + 1));
+            }
+        }
+    }
+
+    seen
+}
+
+/// The range of the values in the graph, to scale the nodes.
+pub fn value_range(graph: &Graph<GraphNode, EdgeKind>) -> (f64, f64) {
+    graph
+        .node_indices()
+        .filter_map(|ni| graph[ni].value())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        })
+}
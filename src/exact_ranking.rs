@@ -0,0 +1,420 @@
+/*
+ © - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   the ranking again, every step with rationals instead of floats:
+   - credibilities and the bound are read as the decimals they print to (0.1 is 1/10)
+   - normalization, aggregation matrix, system (bound*1 - A) x = (1,...,1) and upscaling
+     follow 'rank_abox_from_conflicts' step by step
+   - the system is solved by Gaussian elimination, without any rounding
+   the only float left is the bound, found by the float pipeline, and aggregations that
+   don't give back the credibility (an expression like 'x * exp(x)'), their result is read
+   as a decimal too
+   rationals grow fast, this is meant to audit small aboxes
+*/
+
+use std::collections::HashMap;
+
+use crate::alg_math::rational::Rational;
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::ontology::{ConflictMatrixDict, OntologyDllite};
+use crate::dl_lite::string_formatter::abi_to_string;
+use crate::kb::knowledge_base::{ABox, SymbolDict};
+
+// above this many facts in conflict the exact ranking can take a long time
+pub const EXACT_MAX_DIMENSION: usize = 60;
+// digits after the point shown for exact values
+const EXACT_DIGITS: usize = 20;
+// fractions longer than this are only shown as decimals
+const EXACT_FRACTION_LENGTH: usize = 40;
+
+/// The values of a ranking computed with rationals, one per assertion of the abox.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExactRanking {
+    bound: Option<Rational>,
+    values: Vec<Rational>,
+}
+
+impl ExactRanking {
+    /// exact value minus float value for each assertion of the ranked abox, the float is
+    /// read as the decimal it prints to
+    pub fn differences(&self, ranked: &AbqDllite) -> Vec<f64> {
+        self.values
+            .iter()
+            .zip(ranked.items())
+            .map(|(exact, abiq)| {
+                let float = abiq.value().unwrap_or(1.);
+
+                match Rational::from_f64_decimal(float) {
+                    Option::None => f64::NAN,
+                    Some(float) => (exact - &float).to_f64(),
+                }
+            })
+            .collect()
+    }
+
+    /// pairs of assertions ordered one way by the exact values and the other way (or tied)
+    /// by the floats, or tied by the exact values and not by the floats
+    pub fn order_changes(&self, ranked: &AbqDllite) -> usize {
+        let floats = ranked
+            .items()
+            .iter()
+            .map(|abiq| abiq.value().unwrap_or(1.))
+            .collect::<Vec<f64>>();
+        let mut changes: usize = 0;
+
+        for i in 0..self.values.len() {
+            for j in (i + 1)..self.values.len() {
+                let exact_order = self.values[i].cmp(&self.values[j]);
+                let float_order = floats[i].partial_cmp(&floats[j]);
+
+                if Some(exact_order) != float_order {
+                    changes += 1;
+                }
+            }
+        }
+
+        changes
+    }
+
+    pub fn to_string_with_symbols(&self, ranked: &AbqDllite, symbols: &SymbolDict) -> String {
+        let differences = self.differences(ranked);
+        let mut s = match &self.bound {
+            Option::None => String::from(" -- exact ranking, no conflicts:\n"),
+            Some(bound) => format!(
+                " -- exact ranking with bound {} ({}):\n",
+                bound,
+                bound.to_decimal_string(EXACT_DIGITS)
+            ),
+        };
+
+        for (i, exact) in self.values.iter().enumerate() {
+            let name = ranked
+                .get(i)
+                .and_then(|abiq| abi_to_string(abiq.abi(), symbols))
+                .unwrap_or_default();
+            let fraction = exact.to_string();
+
+            s.push_str(&format!(
+                "    {}: exact {}",
+                name,
+                exact.to_decimal_string(EXACT_DIGITS)
+            ));
+
+            if fraction.len() <= EXACT_FRACTION_LENGTH {
+                s.push_str(&format!(" ({})", fraction));
+            }
+
+            s.push_str(&format!(
+                ", float {}, difference {:e}\n",
+                ranked.get(i).and_then(|abiq| abiq.value()).unwrap_or(1.),
+                differences[i]
+            ));
+        }
+
+        let max_difference = differences.iter().fold(0_f64, |a, b| a.max(b.abs()));
+
+        s.push_str(&format!(
+            " -- float pipeline: max difference {:e}, {} pairs of assertions ordered differently\n",
+            max_difference,
+            self.order_changes(ranked)
+        ));
+
+        s
+    }
+}
+
+// x such that (bound*1 - A) x = (1,...,1), A by rows, None if the system is singular
+fn solve_exact(aggr_matrix: &[Rational], bound: &Rational, n: usize) -> Option<Vec<Rational>> {
+    // the augmented matrix, by rows
+    let mut rows: Vec<Vec<Rational>> = (0..n)
+        .map(|r| {
+            let mut row = (0..n)
+                .map(|c| {
+                    let a = &Rational::zero() - &aggr_matrix[r * n + c];
+
+                    if r == c {
+                        bound + &a
+                    } else {
+                        a
+                    }
+                })
+                .collect::<Vec<Rational>>();
+            row.push(Rational::one());
+            row
+        })
+        .collect();
+
+    for column in 0..n {
+        let pivot = (column..n).find(|r| !rows[*r][column].is_zero())?;
+        rows.swap(column, pivot);
+        let pivot_row = rows[column].clone();
+
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r != column && !row[column].is_zero() {
+                let factor = (&row[column] / &pivot_row[column])?;
+
+                for (target, value) in row.iter_mut().zip(&pivot_row).skip(column) {
+                    *target = &*target - &(&factor * value);
+                }
+            }
+        }
+    }
+
+    (0..n)
+        .map(|r| &rows[r][n] / &rows[r][r])
+        .collect::<Option<Vec<Rational>>>()
+}
+
+fn median_exact(values: &[Rational]) -> Option<Rational> {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+
+    match sorted.len() {
+        0 => Option::None,
+        even if even % 2 == 0 => {
+            let sum = &sorted[(even / 2) - 1] + &sorted[even / 2];
+            &sum / &Rational::from_integer(2)
+        }
+        odd => Some(sorted[odd / 2].clone()),
+    }
+}
+
+/// The ranking of abq (not ranked yet) with rationals, 'bound' is the one found by the float
+/// pipeline on the same conflicts and aggregation.
+/// None if some credibility is not a number or the system can't be solved.
+pub fn rank_abox_exact<F: FnMut(Vec<f64>) -> f64>(
+    abq: &AbqDllite,
+    conflicts: ConflictMatrixDict,
+    aggr: &mut F,
+    bound: f64,
+) -> Option<ExactRanking> {
+    let credibilities = abq
+        .items()
+        .iter()
+        .map(|abiq| Rational::from_f64_decimal(abiq.credibility()))
+        .collect::<Option<Vec<Rational>>>()?;
+
+    // the same normalization: by the biggest credibility
+    let float_scale = abq
+        .items()
+        .iter()
+        .map(|abiq| abiq.credibility())
+        .fold(f64::NEG_INFINITY, f64::max);
+    let normalization_scale = credibilities
+        .iter()
+        .cloned()
+        .max()
+        .unwrap_or_else(Rational::one);
+    let normalized = credibilities
+        .iter()
+        .map(|c| c / &normalization_scale)
+        .collect::<Option<Vec<Rational>>>()?;
+
+    // values already in the abox stay for the facts the ranking doesn't give one
+    let mut values = abq
+        .items()
+        .iter()
+        .map(|abiq| Rational::from_f64_decimal(abiq.value().unwrap_or(1.)))
+        .collect::<Option<Vec<Rational>>>()?;
+
+    let (before_matrix, _, virtual_to_real) = conflicts;
+    let (done_matrix, before_to_done_matrix, _, clean_index_tuple_op) =
+        OntologyDllite::from_conflict_to_clean_matrix(&before_matrix)?;
+
+    if done_matrix.is_empty() {
+        for real_index in virtual_to_real.values() {
+            if *real_index < values.len() {
+                values[*real_index] = Rational::one();
+            }
+        }
+
+        return Some(ExactRanking {
+            bound: Option::None,
+            values,
+        });
+    }
+
+    // the aggregation matrix, an aggregation giving back the credibility is kept exact
+    let n = (done_matrix.len() as f64).sqrt() as usize;
+    let mut aggregated: HashMap<usize, Rational> = HashMap::new();
+    let mut aggr_matrix: Vec<Rational> = vec![Rational::zero(); n * n];
+
+    for (index, entry) in done_matrix.iter().enumerate() {
+        let j = index % n;
+
+        if let Some(real_index) = virtual_to_real.get(&j) {
+            if *real_index >= normalized.len() {
+                continue;
+            }
+
+            if !aggregated.contains_key(real_index) {
+                let credibility = abq.items()[*real_index].credibility() / float_scale;
+                let result = aggr(vec![credibility]);
+
+                let exact = if result == credibility {
+                    normalized[*real_index].clone()
+                } else {
+                    Rational::from_f64_decimal(result)?
+                };
+
+                aggregated.insert(*real_index, exact);
+            }
+
+            aggr_matrix[index] = &aggregated[real_index] * &Rational::from_integer(*entry as i64);
+        }
+    }
+
+    let bound = Rational::from_f64_decimal(bound)?;
+    let mut rank = solve_exact(&aggr_matrix, &bound, n)?;
+
+    // the same upscaling: by a clean fact if there is one, the median otherwise
+    let some_clean_fact = (0..n).find(|i| (0..n).all(|j| done_matrix[i * n + j] == 0));
+
+    let divisor = match (clean_index_tuple_op, some_clean_fact) {
+        (Some((new_clean_index, _)), _) => rank[new_clean_index].clone(),
+        (Option::None, Some(clean_fact_index)) => rank[clean_fact_index].clone(),
+        (Option::None, Option::None) => median_exact(&rank)?,
+    };
+
+    rank = rank
+        .iter()
+        .map(|x| x / &divisor)
+        .collect::<Option<Vec<Rational>>>()?;
+
+    for (key, value_op) in &before_to_done_matrix {
+        if let Some(real_index) = virtual_to_real.get(key) {
+            values[*real_index] = match value_op {
+                Option::None => Rational::one(),
+                Some(value) => rank[*value].clone(),
+            };
+        }
+    }
+
+    let values = values
+        .iter()
+        .map(|value| value * &normalization_scale)
+        .collect::<Vec<Rational>>();
+
+    Some(ExactRanking {
+        bound: Some(bound),
+        values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alg_math::bounds::{BoundAlgorithm, BoundSettings};
+    use crate::alg_math::polynomial_roots::Method;
+    use crate::alg_math::rational::BigInt;
+    use crate::helper::rank_abox_with_system;
+    use crate::kb::aggr_functions::AGGR_SUM;
+    use crate::kb::types::FileType;
+    use crate::{B_TRANSLATE, M_SCALE, TOLERANCE};
+
+    // the worked example of the README (and of the paper): university_tbox and
+    // university_abox, in conflict are John's teaching and attending
+    fn university() -> (OntologyDllite, AbqDllite) {
+        let examples = format!("{}/examples", env!("CARGO_MANIFEST_DIR"));
+        let path_tbox = format!("{}/university_tbox", examples);
+
+        let mut onto = OntologyDllite::new(String::from("university_tbox"));
+        onto.add_symbols_from_file(&path_tbox, FileType::Native, false);
+        onto.add_tbis_from_file(&path_tbox, FileType::Native, false);
+        onto.new_abox_from_file_quantum(
+            &format!("{}/university_abox", examples),
+            FileType::Native,
+            false,
+        );
+        onto.generate_cln(false, false, 0);
+
+        let abq = onto.abox().unwrap().clone();
+        (onto, abq)
+    }
+
+    fn fraction(numerator: &str, denominator: &str) -> Rational {
+        Rational::new(
+            BigInt::parse_decimal(numerator).unwrap(),
+            BigInt::parse_decimal(denominator).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn university_values_are_exact() {
+        let (onto, abq) = university();
+        let conflicts = onto.conflict_matrix_refs_only(&abq, false);
+        let mut aggr = AGGR_SUM;
+
+        // with bound 20 the system is small enough to be solved by hand, the clean
+        // 'Ava, IA: attends' is 1/20 before upscaling
+        let exact = rank_abox_exact(&abq, conflicts, &mut aggr, 20.).unwrap();
+        let expected = vec![
+            fraction("380", "379"),
+            fraction("21", "20"),
+            fraction("8339", "7959"),
+            fraction("174319", "159180"),
+            fraction("7600", "7959"),
+            fraction("7180", "7959"),
+            Rational::one(),
+            Rational::one(),
+        ];
+
+        assert_eq!(exact.bound, Some(Rational::from_integer(20)));
+        assert_eq!(exact.values, expected);
+    }
+
+    #[test]
+    fn float_bound_is_read_as_a_decimal() {
+        let (onto, abq) = university();
+        let mut ranked = abq.clone();
+        let bound_settings: BoundSettings = (
+            (TOLERANCE, M_SCALE, B_TRANSLATE),
+            Method::CauchyCubic,
+            BoundAlgorithm::Interpolation,
+        );
+        let (_, system_op) = rank_abox_with_system(
+            &onto,
+            &mut ranked,
+            false,
+            AGGR_SUM,
+            bound_settings,
+            false,
+            false,
+        );
+        let bound = system_op.unwrap().bound();
+
+        let conflicts = onto.conflict_matrix_refs_only(&abq, false);
+        let mut aggr = AGGR_SUM;
+        let exact = rank_abox_exact(&abq, conflicts, &mut aggr, bound).unwrap();
+        let exact_bound = Rational::from_f64_decimal(bound).unwrap();
+
+        // 'Ava : Student' only gets the clean fact, its value is 1 + 1/bound
+        let ava = &Rational::one() + &(&Rational::one() / &exact_bound).unwrap();
+
+        assert_eq!(exact.bound, Some(exact_bound));
+        assert_eq!(exact.values[1], ava);
+        assert!(exact
+            .differences(&ranked)
+            .iter()
+            .all(|difference| difference.abs() < 1e-15));
+        assert_eq!(exact.order_changes(&ranked), 0);
+    }
+}
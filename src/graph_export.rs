@@ -0,0 +1,314 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   Exporters of the typed graphs of 'graph_maker.rs' (deduction graphs) and
+   'dl_lite/utilities.rs' (conflict graphs):
+       - graphml and gexf, for tools like gephi or yed
+       - a json node-link format (the one of networkx and d3)
+       - mermaid flowcharts, for markdown and web pages
+   Node data is written with its type: the text of the assertion or axiom, its level,
+   the rule, the credibility, the rank value and the conflict type, missing data
+   is not written.
+*/
+
+use crate::graph_maker::{EdgeKind, GraphNode, NodeKind};
+use crate::interface::utilities::escape_xml;
+use crate::kb::types::ConflictType;
+use petgraph::visit::EdgeRef;
+use petgraph::Graph;
+use serde_json::{json, Map, Value};
+
+// the attributes of nodes, with their graphml type (gexf uses the same names)
+const NODE_ATTRIBUTES: [(&str, &str); 7] = [
+    ("kind", "string"),
+    ("text", "string"),
+    ("level", "int"),
+    ("rule", "string"),
+    ("credibility", "double"),
+    ("value", "double"),
+    ("conflict_type", "string"),
+];
+
+/// The name of a conflict type in the exports, the report and the served json.
+pub fn conflict_type_name(conflict_type: &ConflictType) -> &'static str {
+    match conflict_type {
+        ConflictType::Clean => "clean",
+        ConflictType::Conflict => "conflict",
+        ConflictType::SelfConflict => "self_conflict",
+    }
+}
+
+// the values of the attributes of a node, in the order of NODE_ATTRIBUTES
+fn node_values(node: &GraphNode) -> Vec<Option<String>> {
+    vec![
+        Some(node.kind().name().to_string()),
+        Some(node.text().to_string()),
+        node.level().map(|l| l.to_string()),
+        node.rule_id().map(|r| r.to_string()),
+        node.credibility().map(|c| c.to_string()),
+        node.value().map(|v| v.to_string()),
+        node.conflict_type()
+            .map(|c| conflict_type_name(&c).to_string()),
+    ]
+}
+
+pub fn graph_to_graphml(graph: &Graph<GraphNode, EdgeKind>) -> String {
+    let mut graphml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+    );
+
+    for (name, attribute_type) in NODE_ATTRIBUTES.iter() {
+        graphml.push_str(&format!(
+            "  <key id=\"{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+            name, name, attribute_type
+        ));
+    }
+
+    graphml.push_str(
+        "  <key id=\"edge_kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+    );
+    graphml.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+
+    for ni in graph.node_indices() {
+        graphml.push_str(&format!("    <node id=\"n{}\">\n", ni.index()));
+
+        for ((name, _), value) in NODE_ATTRIBUTES.iter().zip(node_values(&graph[ni])) {
+            if let Some(value) = value {
+                graphml.push_str(&format!(
+                    "      <data key=\"{}\">{}</data>\n",
+                    name,
+                    escape_xml(&value)
+                ));
+            }
+        }
+
+        graphml.push_str("    </node>\n");
+    }
+
+    for e in graph.edge_references() {
+        graphml.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">\n      <data key=\"edge_kind\">{}</data>\n    </edge>\n",
+            e.id().index(),
+            e.source().index(),
+            e.target().index(),
+            e.weight().name()
+        ));
+    }
+
+    graphml.push_str("  </graph>\n</graphml>\n");
+    graphml
+}
+
+pub fn graph_to_gexf(graph: &Graph<GraphNode, EdgeKind>) -> String {
+    let mut gexf = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n  <graph mode=\"static\" defaultedgetype=\"directed\">\n    <attributes class=\"node\">\n",
+    );
+
+    for (i, (name, attribute_type)) in NODE_ATTRIBUTES.iter().enumerate() {
+        // gexf says 'integer' where graphml says 'int'
+        let attribute_type = if *attribute_type == "int" {
+            "integer"
+        } else {
+            attribute_type
+        };
+
+        gexf.push_str(&format!(
+            "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>\n",
+            i, name, attribute_type
+        ));
+    }
+
+    gexf.push_str("    </attributes>\n    <attributes class=\"edge\">\n      <attribute id=\"0\" title=\"kind\" type=\"string\"/>\n    </attributes>\n    <nodes>\n");
+
+    for ni in graph.node_indices() {
+        let node = &graph[ni];
+
+        gexf.push_str(&format!(
+            "      <node id=\"{}\" label=\"{}\">\n        <attvalues>\n",
+            ni.index(),
+            escape_xml(node.text())
+        ));
+
+        for (i, value) in node_values(node).into_iter().enumerate() {
+            if let Some(value) = value {
+                gexf.push_str(&format!(
+                    "          <attvalue for=\"{}\" value=\"{}\"/>\n",
+                    i,
+                    escape_xml(&value)
+                ));
+            }
+        }
+
+        gexf.push_str("        </attvalues>\n      </node>\n");
+    }
+
+    gexf.push_str("    </nodes>\n    <edges>\n");
+
+    for e in graph.edge_references() {
+        gexf.push_str(&format!(
+            "      <edge id=\"{}\" source=\"{}\" target=\"{}\">\n        <attvalues>\n          <attvalue for=\"0\" value=\"{}\"/>\n        </attvalues>\n      </edge>\n",
+            e.id().index(),
+            e.source().index(),
+            e.target().index(),
+            e.weight().name()
+        ));
+    }
+
+    gexf.push_str("    </edges>\n  </graph>\n</gexf>\n");
+    gexf
+}
+
+/// The node-link format: '{"directed": true, "nodes": [...], "links": [...]}'.
+pub fn graph_to_json(graph: &Graph<GraphNode, EdgeKind>) -> String {
+    let nodes: Vec<Value> = graph
+        .node_indices()
+        .map(|ni| {
+            let node = &graph[ni];
+            let mut object = Map::new();
+
+            object.insert(String::from("id"), json!(ni.index()));
+            object.insert(String::from("kind"), json!(node.kind().name()));
+            object.insert(String::from("text"), json!(node.text()));
+
+            if let Some(level) = node.level() {
+                object.insert(String::from("level"), json!(level));
+            }
+            if let Some(rule) = node.rule_id() {
+                object.insert(String::from("rule"), json!(rule));
+            }
+            if let Some(credibility) = node.credibility() {
+                object.insert(String::from("credibility"), json!(credibility));
+            }
+            if let Some(value) = node.value() {
+                object.insert(String::from("value"), json!(value));
+            }
+            if let Some(conflict_type) = node.conflict_type() {
+                object.insert(
+                    String::from("conflict_type"),
+                    json!(conflict_type_name(&conflict_type)),
+                );
+            }
+
+            Value::Object(object)
+        })
+        .collect();
+
+    let links: Vec<Value> = graph
+        .edge_references()
+        .map(|e| {
+            json!({
+                "source": e.source().index(),
+                "target": e.target().index(),
+                "kind": e.weight().name(),
+            })
+        })
+        .collect();
+
+    let graph_json = json!({
+        "directed": true,
+        "multigraph": true,
+        "nodes": nodes,
+        "links": links,
+    });
+
+    serde_json::to_string_pretty(&graph_json).unwrap()
+}
+
+/// A mermaid flowchart: rules are boxes, refutations are dashed red links,
+/// assertions show their rank value and conflicting ones are colored.
+pub fn graph_to_mermaid(graph: &Graph<GraphNode, EdgeKind>) -> String {
+    let mut mermaid = String::from("flowchart TD\n");
+
+    for ni in graph.node_indices() {
+        let node = &graph[ni];
+
+        let text = match node.value() {
+            Some(value) => format!("{}<br/>v: {}", escape_mermaid(node.text()), value),
+            Option::None => escape_mermaid(node.text()),
+        };
+
+        let shape = match node.kind() {
+            NodeKind::Rule => format!("[[\"{}\"]]", text),
+            NodeKind::RuleDescription => format!("[\"{}\"]", text),
+            NodeKind::Tbi | NodeKind::Abi => format!("([\"{}\"])", text),
+        };
+
+        mermaid.push_str(&format!("    n{}{}\n", ni.index(), shape));
+    }
+
+    let mut refutations: Vec<usize> = Vec::new();
+
+    for (i, e) in graph.edge_references().enumerate() {
+        let arrow = match e.weight() {
+            EdgeKind::Refutation => {
+                refutations.push(i);
+                "-.->"
+            }
+            _ => "-->",
+        };
+
+        mermaid.push_str(&format!(
+            "    n{} {} n{}\n",
+            e.source().index(),
+            arrow,
+            e.target().index()
+        ));
+    }
+
+    if !refutations.is_empty() {
+        let refutations = refutations
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        mermaid.push_str(&format!("    linkStyle {} stroke:red\n", refutations));
+    }
+
+    // classes by kind and by conflict type
+    mermaid.push_str("    classDef rule fill:#ffe0e0,stroke:red\n");
+    mermaid.push_str("    classDef conflict fill:#fff0c0\n");
+    mermaid.push_str("    classDef self_conflict fill:#ffc0c0\n");
+
+    for ni in graph.node_indices() {
+        let node = &graph[ni];
+
+        let class = match (node.kind(), node.conflict_type()) {
+            (NodeKind::Rule, _) | (NodeKind::RuleDescription, _) => Some("rule"),
+            (_, Some(conflict_type)) if conflict_type_name(&conflict_type) != "clean" => {
+                Some(conflict_type_name(&conflict_type))
+            }
+            (_, _) => Option::None,
+        };
+
+        if let Some(class) = class {
+            mermaid.push_str(&format!("    class n{} {}\n", ni.index(), class));
+        }
+    }
+
+    mermaid
+}
+
+// mermaid uses its own entity codes inside quoted labels
+fn escape_mermaid(s: &str) -> String {
+    s.replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
@@ -24,12 +24,13 @@ use crate::interface::format_constants::{
     UNICODE_EXISTS, UNICODE_NEG, UNICODE_SQSUBSETEQ, UNICODE_SUBSETEQ,
 };
 use crate::kb::knowledge_base::{ABox, Implier, Item, LeveledItem, SymbolDict, TBox, TBoxItem};
-use crate::kb::types::{DLType, CR};
+use crate::kb::types::{ConflictType, DLType, CR};
 use petgraph::graph::EdgeReference;
 use petgraph::prelude::NodeIndex;
 use petgraph::{Directed, Graph};
 
 use std::collections::HashMap;
+use std::fmt;
 
 use crate::dl_lite::abox::AbqDllite;
 use crate::dl_lite::abox_item_quantum::AbiqDllite;
@@ -50,13 +51,178 @@ const RULE_IDS: [CR; 10] = [
 
 const RULE_STR_IDS: [&str; 10] = ["R1", "R2", "R3", "R4", "R5", "R6", "R7", "R8", "R9", "10"];
 
+/// What a node of the graphs stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Tbi,
+    Abi,
+    Rule,
+    RuleDescription,
+}
+
+impl NodeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeKind::Tbi => "tbi",
+            NodeKind::Abi => "abi",
+            NodeKind::Rule => "rule",
+            NodeKind::RuleDescription => "rule_description",
+        }
+    }
+}
+
+/// What an edge of the graphs stands for: a premise or a conclusion of a rule in
+/// deduction graphs, an implication or a refutation in conflict graphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    Deduction,
+    Implication,
+    Refutation,
+}
+
+impl fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl EdgeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EdgeKind::Deduction => "deduction",
+            EdgeKind::Implication => "implication",
+            EdgeKind::Refutation => "refutation",
+        }
+    }
+}
+
+/// The data of a node, the label is only the one of the dot notation (and is what
+/// a node displays), exporters use the other fields.
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    kind: NodeKind,
+    text: String,
+    level: Option<usize>,
+    rule: Option<String>,
+    credibility: Option<f64>,
+    value: Option<f64>,
+    conflict_type: Option<ConflictType>,
+    index: Option<usize>, // the position of the assertion in its abox
+    label: String,
+}
+
+impl fmt::Display for GraphNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl GraphNode {
+    pub fn tbi(tbi: &TbiDllite, text: String, label: String) -> GraphNode {
+        GraphNode {
+            kind: NodeKind::Tbi,
+            text,
+            level: Some(tbi.level()),
+            rule: Option::None,
+            credibility: Option::None,
+            value: Option::None,
+            conflict_type: Option::None,
+            index: Option::None,
+            label,
+        }
+    }
+
+    pub fn abi(
+        abiq: &AbiqDllite,
+        text: String,
+        label: String,
+        conflict_type: Option<ConflictType>,
+    ) -> GraphNode {
+        GraphNode {
+            kind: NodeKind::Abi,
+            text,
+            level: Some(abiq.level()),
+            rule: Option::None,
+            credibility: Some(abiq.credibility()),
+            value: abiq.value(),
+            conflict_type,
+            index: Option::None,
+            label,
+        }
+    }
+
+    pub fn rule(r: &CR) -> GraphNode {
+        GraphNode {
+            kind: NodeKind::Rule,
+            text: r.identifier(),
+            level: Option::None,
+            rule: Some(r.identifier()),
+            credibility: Option::None,
+            value: Option::None,
+            conflict_type: Option::None,
+            index: Option::None,
+            label: r.identifier(),
+        }
+    }
+
+    pub fn rule_description(r: &CR, for_tbi: bool) -> GraphNode {
+        GraphNode {
+            kind: NodeKind::RuleDescription,
+            text: r.description(for_tbi),
+            level: Option::None,
+            rule: Some(r.identifier()),
+            credibility: Option::None,
+            value: Option::None,
+            conflict_type: Option::None,
+            index: Option::None,
+            label: r.description(for_tbi),
+        }
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn level(&self) -> Option<usize> {
+        self.level
+    }
+
+    pub fn rule_id(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+
+    pub fn credibility(&self) -> Option<f64> {
+        self.credibility
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    pub fn conflict_type(&self) -> Option<ConflictType> {
+        self.conflict_type
+    }
+
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn set_index(&mut self, index: usize) {
+        self.index = Some(index);
+    }
+}
+
 // first function: create graph from tbox with impliers
-// in this function true for edges stand for tbi and false stand for rule
+/// The deduction graph of the tbox: each rule node goes from the premises to the conclusion.
 pub fn create_graph_for_tbox_unraveling(
     tbox: &TBDllite,
     symbols: &SymbolDict,
-) -> Graph<String, (), Directed, u32> {
-    let mut graph: Graph<String, ()> = Graph::new();
+) -> Graph<GraphNode, EdgeKind, Directed, u32> {
+    let mut graph: Graph<GraphNode, EdgeKind> = Graph::new();
     let max_level = tbox.get_max_level();
 
     let only_conflicts = false;
@@ -79,37 +245,42 @@ pub fn create_graph_for_tbox_unraveling(
                     let tbi_string = tbi_to_string(tbi, symbols).unwrap();
 
                     // here we substitute for the right symbol
-                    let tbi_string = transform_tbi_for_graph(tbi, tbi_string, is_for_abox);
+                    let tbi_label = transform_tbi_for_graph(tbi, tbi_string.clone(), is_for_abox);
 
                     let impliers = tbi.implied_by();
 
-                    let tbi_index: NodeIndex<u32> =
-                        modify_hashmap_graph(tbi_string, &mut added, &mut graph);
+                    let tbi_index: NodeIndex<u32> = modify_hashmap_graph(
+                        GraphNode::tbi(tbi, tbi_string, tbi_label),
+                        &mut added,
+                        &mut graph,
+                    );
 
                     for (r, v) in impliers {
                         let rule_usize = r.to_usize();
 
                         // add rule to the graph if it is not in
                         if !rules_added.contains(&rule_usize) {
-                            graph.add_node(r.description(for_tbi));
+                            graph.add_node(GraphNode::rule_description(r, for_tbi));
                             rules_added.push(rule_usize);
                         }
 
-                        let rule_string = r.identifier();
-                        let index_rule = graph.add_node(rule_string);
+                        let index_rule = graph.add_node(GraphNode::rule(r));
 
-                        let _ = graph.add_edge(index_rule, tbi_index, ());
+                        let _ = graph.add_edge(index_rule, tbi_index, EdgeKind::Deduction);
 
                         for tbi_imp in v {
                             let tbi_string = tbi_to_string(tbi_imp, symbols).unwrap();
 
                             // here we substitute for the right symbol
-                            let tbi_string =
-                                transform_tbi_for_graph(tbi_imp, tbi_string, is_for_abox);
-
-                            let imp_index: NodeIndex<u32> =
-                                modify_hashmap_graph(tbi_string, &mut added, &mut graph);
-                            let _ = graph.add_edge(imp_index, index_rule, ());
+                            let tbi_label =
+                                transform_tbi_for_graph(tbi_imp, tbi_string.clone(), is_for_abox);
+
+                            let imp_index: NodeIndex<u32> = modify_hashmap_graph(
+                                GraphNode::tbi(tbi_imp, tbi_string, tbi_label),
+                                &mut added,
+                                &mut graph,
+                            );
+                            let _ = graph.add_edge(imp_index, index_rule, EdgeKind::Deduction);
                         }
                     }
                 }
@@ -120,12 +291,13 @@ pub fn create_graph_for_tbox_unraveling(
     graph
 }
 
+/// The deduction graph of the abox, premises are assertions and axioms of the tbox.
 pub fn create_graph_for_aboxq_unraveling(
     abox: &AbqDllite,
     _tbox: &TBDllite,
     symbols: &SymbolDict,
-) -> Graph<String, (), Directed, u32> {
-    let mut graph: Graph<String, ()> = Graph::new();
+) -> Graph<GraphNode, EdgeKind, Directed, u32> {
+    let mut graph: Graph<GraphNode, EdgeKind> = Graph::new();
 
     let max_level = abox.get_max_level();
 
@@ -152,49 +324,57 @@ pub fn create_graph_for_aboxq_unraveling(
                     let abi_string = abi_to_string(abiq.abi(), symbols).unwrap();
 
                     // here we substitute for the right symbol
-                    let abi_string = transform_abiq_for_graph(abiq, abi_string);
+                    let abi_label = transform_abiq_for_graph(abiq, abi_string.clone());
 
                     let impliers = abiq.implied_by();
 
-                    let abi_index: NodeIndex<u32> =
-                        modify_hashmap_graph(abi_string, &mut added, &mut graph);
+                    let abi_index: NodeIndex<u32> = modify_hashmap_graph(
+                        GraphNode::abi(abiq, abi_string, abi_label, Option::None),
+                        &mut added,
+                        &mut graph,
+                    );
 
                     for (r, v_tbis, v_abiqs) in impliers {
                         let rule_usize = r.to_usize();
 
                         // add rule to the graph if it is not in
                         if !rules_added.contains(&rule_usize) {
-                            graph.add_node(r.description(for_tbi));
+                            graph.add_node(GraphNode::rule_description(r, for_tbi));
                             rules_added.push(rule_usize);
                         }
 
-                        let rule_string = r.identifier();
-                        let index_rule = graph.add_node(rule_string);
+                        let index_rule = graph.add_node(GraphNode::rule(r));
 
-                        let _ = graph.add_edge(index_rule, abi_index, ());
+                        let _ = graph.add_edge(index_rule, abi_index, EdgeKind::Deduction);
 
                         for tbi_imp in v_tbis {
                             let tbi_string = tbi_to_string(tbi_imp, symbols).unwrap();
 
                             // here we substitute for the right symbol
-                            let tbi_string =
-                                transform_tbi_for_graph(tbi_imp, tbi_string, is_for_abox);
+                            let tbi_label =
+                                transform_tbi_for_graph(tbi_imp, tbi_string.clone(), is_for_abox);
 
-                            let imp_index: NodeIndex<u32> =
-                                modify_hashmap_graph(tbi_string, &mut added, &mut graph);
+                            let imp_index: NodeIndex<u32> = modify_hashmap_graph(
+                                GraphNode::tbi(tbi_imp, tbi_string, tbi_label),
+                                &mut added,
+                                &mut graph,
+                            );
 
-                            let _ = graph.add_edge(imp_index, index_rule, ());
+                            let _ = graph.add_edge(imp_index, index_rule, EdgeKind::Deduction);
                         }
 
                         for abi_imp in v_abiqs {
                             let abi_string = abi_to_string(abi_imp.abi(), symbols).unwrap();
 
                             // here we substitute for the right symbol
-                            let abi_string = transform_abiq_for_graph(abi_imp, abi_string);
-
-                            let imp_index: NodeIndex<u32> =
-                                modify_hashmap_graph(abi_string, &mut added, &mut graph);
-                            let _ = graph.add_edge(imp_index, index_rule, ());
+                            let abi_label = transform_abiq_for_graph(abi_imp, abi_string.clone());
+
+                            let imp_index: NodeIndex<u32> = modify_hashmap_graph(
+                                GraphNode::abi(abi_imp, abi_string, abi_label, Option::None),
+                                &mut added,
+                                &mut graph,
+                            );
+                            let _ = graph.add_edge(imp_index, index_rule, EdgeKind::Deduction);
                         }
                     }
                 }
@@ -287,16 +467,18 @@ pub fn transform_tbi_for_graph(tbi: &TbiDllite, tbi_string: String, is_for_abox:
 }
 
 pub fn modify_hashmap_graph(
-    abi_string: String,
+    node: GraphNode,
     added: &mut HashMap<String, NodeIndex<u32>>,
-    graph: &mut Graph<String, ()>,
+    graph: &mut Graph<GraphNode, EdgeKind>,
 ) -> NodeIndex<u32> {
+    // nodes are the same if they show the same
     let abi_index: NodeIndex;
-    if added.contains_key(&abi_string) {
-        abi_index = *added.get(&abi_string).unwrap();
+    if added.contains_key(&node.label) {
+        abi_index = *added.get(&node.label).unwrap();
     } else {
-        abi_index = graph.add_node(abi_string.clone());
-        added.insert(abi_string, abi_index);
+        let label = node.label.clone();
+        abi_index = graph.add_node(node);
+        added.insert(label, abi_index);
     }
 
     abi_index
@@ -0,0 +1,1105 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   A layered (Sugiyama) layout of the graphs of 'graph_maker.rs' and 'dl_lite/utilities.rs',
+   written to svg or pdf, thus graphviz is not needed for visual outputs.
+   The attributes of nodes and edges are the ones given to the dot notation
+   (e.g. 'shape=rectangle color=red'), the same getters are used for both.
+   The steps of the layout are the usual ones:
+       - cycles are broken by reversing the edges going back in a depth first search
+       - layers are the longest path from the sources
+       - edges spanning several layers go through dummy nodes
+       - crossings are reduced by barycenter sweeps, the best ordering is kept
+       - nodes are moved towards their neighbours, keeping the order and the spacing
+*/
+
+use petgraph::graph::EdgeReference;
+use petgraph::prelude::NodeIndex;
+use petgraph::visit::EdgeRef;
+use petgraph::Graph;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::interface::utilities::escape_xml;
+
+// sizes are in points, as in pdf
+const FONT_SIZE: f64 = 12.;
+const CHAR_WIDTH: f64 = 7.;
+const NODE_HEIGHT: f64 = 36.;
+const NODE_PADDING: f64 = 24.;
+const NODE_SPACING: f64 = 24.;
+const LAYER_SPACING: f64 = 64.;
+const MARGIN: f64 = 24.;
+const ARROW_LENGTH: f64 = 10.;
+const ARROW_WIDTH: f64 = 4.;
+const PARALLEL_OFFSET: f64 = 8.;
+const POINTS_PER_INCH: f64 = 72.;
+
+const ORDERING_SWEEPS: usize = 24;
+const POSITIONING_SWEEPS: usize = 8;
+
+// the getters of the attributes in dot notation, as given to 'Dot::with_attr_getters'
+pub type EdgeAttrGetter<'a, N, E> = &'a dyn Fn(&Graph<N, E>, EdgeReference<E>) -> String;
+pub type NodeAttrGetter<'a, N, E> = &'a dyn Fn(&Graph<N, E>, (NodeIndex, &N)) -> String;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Ellipse,
+    Rectangle,
+}
+
+/// A node once placed, the position is its center.
+#[derive(Debug, Clone)]
+pub struct PlacedNode {
+    label: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    shape: Shape,
+    color: String,
+    fill: Option<String>,
+    font_color: String,
+    pen_width: f64,
+    dashed: bool,
+}
+
+/// An edge once placed, from its source to its target.
+#[derive(Debug, Clone)]
+pub struct PlacedEdge {
+    points: Vec<(f64, f64)>,
+    color: String,
+    pen_width: f64,
+    dashed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Layout {
+    nodes: Vec<PlacedNode>,
+    edges: Vec<PlacedEdge>,
+    width: f64,
+    height: f64,
+}
+
+impl Layout {
+    /// Computes the layout of graph, the attributes of nodes and edges are given
+    /// in dot notation by the same getters used for 'Dot::with_attr_getters'.
+    pub fn new<N: Display, E>(
+        graph: &Graph<N, E>,
+        edge_attr: EdgeAttrGetter<N, E>,
+        node_attr: NodeAttrGetter<N, E>,
+    ) -> Layout {
+        let real = graph.node_count();
+
+        // the nodes with their attributes, dummy nodes are added after the real ones
+        let mut nodes: Vec<PlacedNode> = graph
+            .node_indices()
+            .map(|ni| {
+                let attributes = parse_attributes(&node_attr(graph, (ni, &graph[ni])));
+                PlacedNode::from_attributes(graph[ni].to_string(), &attributes)
+            })
+            .collect();
+
+        let edges: Vec<(usize, usize, HashMap<String, String>)> = graph
+            .edge_references()
+            .map(|e| {
+                (
+                    e.source().index(),
+                    e.target().index(),
+                    parse_attributes(&edge_attr(graph, e)),
+                )
+            })
+            .collect();
+
+        // break cycles, self loops are not drawn
+        let reversed = back_edges(real, &edges);
+        let acyclic: Vec<(usize, usize)> = edges
+            .iter()
+            .enumerate()
+            .filter(|(_, (s, t, _))| s != t)
+            .map(|(i, (s, t, _))| if reversed[i] { (*t, *s) } else { (*s, *t) })
+            .collect();
+
+        let mut layer = longest_path_layers(real, &acyclic);
+
+        // chains of dummy nodes for long edges, each edge is a path of consecutive nodes
+        let mut chains: Vec<Vec<usize>> = Vec::new();
+        let mut segments: Vec<(usize, usize)> = Vec::new();
+
+        for (s, t) in &acyclic {
+            let mut chain = vec![*s];
+
+            for l in (layer[*s] + 1)..layer[*t] {
+                let dummy = nodes.len();
+                nodes.push(PlacedNode::dummy());
+                layer.push(l);
+                segments.push((*chain.last().unwrap(), dummy));
+                chain.push(dummy);
+            }
+
+            segments.push((*chain.last().unwrap(), *t));
+            chain.push(*t);
+            chains.push(chain);
+        }
+
+        let layers_number = layer.iter().max().map_or(0, |m| m + 1);
+        let mut layers: Vec<Vec<usize>> = vec![Vec::new(); layers_number];
+
+        for (node, l) in layer.iter().enumerate() {
+            layers[*l].push(node);
+        }
+
+        let mut up: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+        let mut down: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+
+        for (s, t) in &segments {
+            down[*s].push(*t);
+            up[*t].push(*s);
+        }
+
+        order_layers(&mut layers, &up, &down);
+        position_nodes(&mut nodes, &layers, &up, &down);
+
+        // now the edges, following the chains
+        let mut placed_edges: Vec<PlacedEdge> = Vec::new();
+        let mut parallels: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut chains = chains.into_iter();
+
+        for (i, (s, t, attributes)) in edges.iter().enumerate() {
+            if s == t {
+                continue;
+            }
+
+            let chain = chains.next().unwrap();
+            let mut points: Vec<(f64, f64)> =
+                chain.iter().map(|n| (nodes[*n].x, nodes[*n].y)).collect();
+
+            if reversed[i] {
+                points.reverse();
+            }
+
+            // edges between the same nodes are spread apart
+            let key = (*s.min(t), *s.max(t));
+            let already = parallels.entry(key).or_insert(0);
+
+            if *already > 0 {
+                bend_path(&mut points, *already);
+            }
+
+            *already += 1;
+
+            // from the border of the source to the border of the target
+            let last = points.len() - 1;
+            points[0] = nodes[*s].border_towards(points[1]);
+            points[last] = nodes[*t].border_towards(points[last - 1]);
+
+            placed_edges.push(PlacedEdge::from_attributes(points, attributes));
+        }
+
+        nodes.truncate(real);
+
+        // everything is moved to fit in the margins
+        let min_x = nodes
+            .iter()
+            .map(|n| n.x - n.width / 2.)
+            .chain(
+                placed_edges
+                    .iter()
+                    .flat_map(|e| e.points.iter().map(|p| p.0)),
+            )
+            .fold(f64::INFINITY, f64::min);
+        let min_x = if min_x.is_finite() { min_x } else { 0. };
+
+        for node in nodes.iter_mut() {
+            node.x += MARGIN - min_x;
+        }
+
+        for edge in placed_edges.iter_mut() {
+            for point in edge.points.iter_mut() {
+                point.0 += MARGIN - min_x;
+            }
+        }
+
+        let width = nodes
+            .iter()
+            .map(|n| n.x + n.width / 2.)
+            .chain(
+                placed_edges
+                    .iter()
+                    .flat_map(|e| e.points.iter().map(|p| p.0)),
+            )
+            .fold(0., f64::max)
+            + MARGIN;
+        let height = nodes.iter().map(|n| n.y + n.height / 2.).fold(0., f64::max) + MARGIN;
+
+        Layout {
+            nodes,
+            edges: placed_edges,
+            width: width.max(2. * MARGIN),
+            height: height.max(2. * MARGIN),
+        }
+    }
+
+    /// The layout as a svg document.
+    pub fn to_svg(&self) -> String {
+        let mut svg = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}pt\" height=\"{:.0}pt\" viewBox=\"0 0 {:.2} {:.2}\">\n",
+            self.width, self.height, self.width, self.height
+        );
+
+        svg.push_str(&format!(
+            "<rect x=\"0\" y=\"0\" width=\"{:.2}\" height=\"{:.2}\" fill=\"#ffffff\"/>\n",
+            self.width, self.height
+        ));
+
+        for edge in &self.edges {
+            let color = color_to_hex(&edge.color);
+            let dash = if edge.dashed {
+                " stroke-dasharray=\"5,3\""
+            } else {
+                ""
+            };
+            let points = edge
+                .points
+                .iter()
+                .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            svg.push_str(&format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.2}\"{}/>\n",
+                points, color, edge.pen_width, dash
+            ));
+
+            let arrow = edge
+                .arrow_head()
+                .iter()
+                .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            svg.push_str(&format!(
+                "<polygon points=\"{}\" fill=\"{}\" stroke=\"{}\"/>\n",
+                arrow, color, color
+            ));
+        }
+
+        for node in &self.nodes {
+            let fill = node
+                .fill
+                .as_ref()
+                .map_or(String::from("#ffffff"), |f| color_to_hex(f));
+            let dash = if node.dashed {
+                " stroke-dasharray=\"5,3\""
+            } else {
+                ""
+            };
+
+            match node.shape {
+                Shape::Ellipse => svg.push_str(&format!(
+                    "<ellipse cx=\"{:.2}\" cy=\"{:.2}\" rx=\"{:.2}\" ry=\"{:.2}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{:.2}\"{}/>\n",
+                    node.x,
+                    node.y,
+                    node.width / 2.,
+                    node.height / 2.,
+                    fill,
+                    color_to_hex(&node.color),
+                    node.pen_width,
+                    dash
+                )),
+                Shape::Rectangle => svg.push_str(&format!(
+                    "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{:.2}\"{}/>\n",
+                    node.x - node.width / 2.,
+                    node.y - node.height / 2.,
+                    node.width,
+                    node.height,
+                    fill,
+                    color_to_hex(&node.color),
+                    node.pen_width,
+                    dash
+                )),
+            }
+
+            svg.push_str(&format!(
+                "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"middle\" dominant-baseline=\"central\" font-family=\"Helvetica,Arial,sans-serif\" font-size=\"{:.0}\" fill=\"{}\">{}</text>\n",
+                node.x,
+                node.y,
+                FONT_SIZE,
+                color_to_hex(&node.font_color),
+                escape_xml(&node.label)
+            ));
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// The layout as a pdf document of one page, with the standard Helvetica font
+    /// (characters outside of ascii are written as '?', thus the document is plain text).
+    pub fn to_pdf(&self) -> String {
+        let mut content = String::new();
+
+        // pdf coordinates go up
+        let y = |v: f64| self.height - v;
+
+        for edge in &self.edges {
+            let (r, g, b) = color_to_rgb(&edge.color);
+            content.push_str(&format!(
+                "{:.3} {:.3} {:.3} RG {:.3} {:.3} {:.3} rg {:.2} w {} d\n",
+                r,
+                g,
+                b,
+                r,
+                g,
+                b,
+                edge.pen_width,
+                if edge.dashed { "[5 3] 0" } else { "[] 0" }
+            ));
+
+            for (i, (px, py)) in edge.points.iter().enumerate() {
+                let operator = if i == 0 { "m" } else { "l" };
+                content.push_str(&format!("{:.2} {:.2} {}\n", px, y(*py), operator));
+            }
+
+            content.push_str("S\n");
+
+            for (i, (px, py)) in edge.arrow_head().iter().enumerate() {
+                let operator = if i == 0 { "m" } else { "l" };
+                content.push_str(&format!("{:.2} {:.2} {}\n", px, y(*py), operator));
+            }
+
+            content.push_str("f\n");
+        }
+
+        for node in &self.nodes {
+            let (r, g, b) = color_to_rgb(&node.color);
+            let (fr, fg, fb) = node.fill.as_ref().map_or((1., 1., 1.), |f| color_to_rgb(f));
+
+            content.push_str(&format!(
+                "{:.3} {:.3} {:.3} RG {:.3} {:.3} {:.3} rg {:.2} w {} d\n",
+                r,
+                g,
+                b,
+                fr,
+                fg,
+                fb,
+                node.pen_width,
+                if node.dashed { "[5 3] 0" } else { "[] 0" }
+            ));
+
+            match node.shape {
+                Shape::Rectangle => content.push_str(&format!(
+                    "{:.2} {:.2} {:.2} {:.2} re B\n",
+                    node.x - node.width / 2.,
+                    y(node.y + node.height / 2.),
+                    node.width,
+                    node.height
+                )),
+                Shape::Ellipse => {
+                    // four bezier curves, the control points are at 0.5523 of the radius
+                    let (cx, cy) = (node.x, y(node.y));
+                    let (rx, ry) = (node.width / 2., node.height / 2.);
+                    let (kx, ky) = (0.5523 * rx, 0.5523 * ry);
+
+                    content.push_str(&format!("{:.2} {:.2} m\n", cx + rx, cy));
+                    content.push_str(&format!(
+                        "{:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c\n",
+                        cx + rx,
+                        cy + ky,
+                        cx + kx,
+                        cy + ry,
+                        cx,
+                        cy + ry
+                    ));
+                    content.push_str(&format!(
+                        "{:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c\n",
+                        cx - kx,
+                        cy + ry,
+                        cx - rx,
+                        cy + ky,
+                        cx - rx,
+                        cy
+                    ));
+                    content.push_str(&format!(
+                        "{:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c\n",
+                        cx - rx,
+                        cy - ky,
+                        cx - kx,
+                        cy - ry,
+                        cx,
+                        cy - ry
+                    ));
+                    content.push_str(&format!(
+                        "{:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c\n",
+                        cx + kx,
+                        cy - ry,
+                        cx + rx,
+                        cy - ky,
+                        cx + rx,
+                        cy
+                    ));
+                    content.push_str("B\n");
+                }
+            }
+
+            let (tr, tg, tb) = color_to_rgb(&node.font_color);
+            let label = ascii_symbols(&node.label);
+            let text_width = label_width(&label);
+
+            content.push_str(&format!(
+                "BT {:.3} {:.3} {:.3} rg /F1 {:.0} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+                tr,
+                tg,
+                tb,
+                FONT_SIZE,
+                node.x - text_width / 2.,
+                y(node.y) - FONT_SIZE / 3.,
+                escape_pdf(&label)
+            ));
+        }
+
+        let objects = [
+            String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+            String::from("<< /Type /Pages /Kids [3 0 R] /Count 1 >>"),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>",
+                self.width, self.height
+            ),
+            format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ),
+            String::from("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>"),
+        ];
+
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets: Vec<usize> = Vec::new();
+
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+        }
+
+        let xref = pdf.len();
+        pdf.push_str(&format!(
+            "xref\n0 {}\n0000000000 65535 f \n",
+            objects.len() + 1
+        ));
+
+        for offset in offsets {
+            pdf.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+
+        pdf.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        ));
+
+        pdf
+    }
+}
+
+impl PlacedNode {
+    fn from_attributes(label: String, attributes: &HashMap<String, String>) -> PlacedNode {
+        let shape = match attributes.get("shape").map(|s| s.as_str()) {
+            Some("rectangle") | Some("box") | Some("rect") | Some("square") => Shape::Rectangle,
+            _ => Shape::Ellipse,
+        };
+
+        // dot sizes are in inches, they are minimal sizes
+        let inches = |key: &str| {
+            attributes
+                .get(key)
+                .and_then(|v| v.parse::<f64>().ok())
+                .map(|v| v * POINTS_PER_INCH)
+        };
+
+        let mut width = label_width(&label) + NODE_PADDING;
+        if shape == Shape::Ellipse {
+            // the text has to fit in the ellipse
+            width *= 1.2;
+        }
+        let width = width.max(inches("width").unwrap_or(0.));
+        let height = NODE_HEIGHT.max(inches("height").unwrap_or(0.));
+
+        let style = attributes.get("style").cloned().unwrap_or_default();
+        let color = attributes
+            .get("color")
+            .cloned()
+            .unwrap_or_else(|| String::from("black"));
+
+        // as in dot, a filled node without fill color is filled with its color
+        let fill = if style.contains("filled") {
+            Some(
+                attributes
+                    .get("fillcolor")
+                    .cloned()
+                    .unwrap_or_else(|| color.clone()),
+            )
+        } else {
+            attributes.get("fillcolor").cloned()
+        };
+
+        PlacedNode {
+            label,
+            x: 0.,
+            y: 0.,
+            width,
+            height,
+            shape,
+            color,
+            fill,
+            font_color: attributes
+                .get("fontcolor")
+                .cloned()
+                .unwrap_or_else(|| String::from("black")),
+            pen_width: pen_width(attributes, &style),
+            dashed: style.contains("dashed") || style.contains("dotted"),
+        }
+    }
+
+    fn dummy() -> PlacedNode {
+        PlacedNode {
+            label: String::new(),
+            x: 0.,
+            y: 0.,
+            width: 0.,
+            height: 0.,
+            shape: Shape::Rectangle,
+            color: String::from("black"),
+            fill: Option::None,
+            font_color: String::from("black"),
+            pen_width: 1.,
+            dashed: false,
+        }
+    }
+
+    // where the segment from the center to 'towards' leaves the node
+    fn border_towards(&self, towards: (f64, f64)) -> (f64, f64) {
+        let (dx, dy) = (towards.0 - self.x, towards.1 - self.y);
+
+        if (dx == 0. && dy == 0.) || self.width == 0. {
+            return (self.x, self.y);
+        }
+
+        let (a, b) = (self.width / 2., self.height / 2.);
+
+        let t = match self.shape {
+            Shape::Ellipse => 1. / ((dx / a).powi(2) + (dy / b).powi(2)).sqrt(),
+            Shape::Rectangle => {
+                let tx = if dx != 0. {
+                    a / dx.abs()
+                } else {
+                    f64::INFINITY
+                };
+                let ty = if dy != 0. {
+                    b / dy.abs()
+                } else {
+                    f64::INFINITY
+                };
+                tx.min(ty)
+            }
+        };
+
+        (self.x + t * dx, self.y + t * dy)
+    }
+}
+
+impl PlacedEdge {
+    fn from_attributes(
+        points: Vec<(f64, f64)>,
+        attributes: &HashMap<String, String>,
+    ) -> PlacedEdge {
+        let style = attributes.get("style").cloned().unwrap_or_default();
+
+        PlacedEdge {
+            points,
+            color: attributes
+                .get("color")
+                .cloned()
+                .unwrap_or_else(|| String::from("black")),
+            pen_width: pen_width(attributes, &style),
+            dashed: style.contains("dashed") || style.contains("dotted"),
+        }
+    }
+
+    // the triangle at the end of the edge, the edge is shortened to not go through it
+    fn arrow_head(&self) -> Vec<(f64, f64)> {
+        let n = self.points.len();
+        let (tx, ty) = self.points[n - 1];
+        let (sx, sy) = self.points[n - 2];
+        let length = ((tx - sx).powi(2) + (ty - sy).powi(2)).sqrt();
+
+        if length == 0. {
+            return vec![(tx, ty)];
+        }
+
+        let (ux, uy) = ((tx - sx) / length, (ty - sy) / length);
+        let (bx, by) = (tx - ARROW_LENGTH * ux, ty - ARROW_LENGTH * uy);
+
+        vec![
+            (tx, ty),
+            (bx - ARROW_WIDTH * uy, by + ARROW_WIDTH * ux),
+            (bx + ARROW_WIDTH * uy, by - ARROW_WIDTH * ux),
+        ]
+    }
+}
+
+fn pen_width(attributes: &HashMap<String, String>, style: &str) -> f64 {
+    attributes
+        .get("penwidth")
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(if style.contains("bold") { 2. } else { 1. })
+}
+
+fn label_width(label: &str) -> f64 {
+    label.chars().count() as f64 * CHAR_WIDTH
+}
+
+/// Reads attributes in dot notation: 'key=value' separated by spaces or commas,
+/// values can be quoted.
+pub fn parse_attributes(attributes: &str) -> HashMap<String, String> {
+    let mut parsed: HashMap<String, String> = HashMap::new();
+    let mut chars = attributes.chars().peekable();
+
+    loop {
+        // skip separators
+        while let Some(c) = chars.peek() {
+            if c.is_whitespace() || *c == ',' || *c == ';' {
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.peek() {
+            if *c == '=' || c.is_whitespace() || *c == ',' {
+                break;
+            }
+            key.push(*c);
+            chars.next();
+        }
+
+        if key.is_empty() {
+            break;
+        }
+
+        let mut value = String::new();
+        if chars.peek() == Some(&'=') {
+            chars.next();
+
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        _ => value.push(c),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.peek() {
+                    if c.is_whitespace() || *c == ',' || *c == ';' {
+                        break;
+                    }
+                    value.push(*c);
+                    chars.next();
+                }
+            }
+        }
+
+        parsed.insert(key.to_lowercase(), value);
+    }
+
+    parsed
+}
+
+// edges reversed to have an acyclic graph: the ones going back to a node being visited
+fn back_edges(n: usize, edges: &[(usize, usize, HashMap<String, String>)]) -> Vec<bool> {
+    let mut out: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+
+    for (i, (s, t, _)) in edges.iter().enumerate() {
+        if s != t {
+            out[*s].push((i, *t));
+        }
+    }
+
+    let mut reversed = vec![false; edges.len()];
+    // 0: not visited, 1: being visited, 2: done
+    let mut state = vec![0_u8; n];
+
+    for root in 0..n {
+        if state[root] != 0 {
+            continue;
+        }
+
+        // iterative to not overflow on long chains of deductions
+        let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+        state[root] = 1;
+
+        while let Some((node, next)) = stack.pop() {
+            if next < out[node].len() {
+                stack.push((node, next + 1));
+                let (edge, target) = out[node][next];
+
+                match state[target] {
+                    0 => {
+                        state[target] = 1;
+                        stack.push((target, 0));
+                    }
+                    1 => reversed[edge] = true,
+                    _ => (),
+                }
+            } else {
+                state[node] = 2;
+            }
+        }
+    }
+
+    reversed
+}
+
+fn longest_path_layers(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut incoming = vec![0_usize; n];
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); n];
+
+    for (s, t) in edges {
+        incoming[*t] += 1;
+        out[*s].push(*t);
+    }
+
+    let mut layer = vec![0_usize; n];
+    let mut ready: Vec<usize> = (0..n).filter(|i| incoming[*i] == 0).collect();
+    ready.reverse();
+
+    while let Some(node) = ready.pop() {
+        for target in &out[node] {
+            layer[*target] = layer[*target].max(layer[node] + 1);
+            incoming[*target] -= 1;
+
+            if incoming[*target] == 0 {
+                ready.push(*target);
+            }
+        }
+    }
+
+    layer
+}
+
+fn order_layers(layers: &mut [Vec<usize>], up: &[Vec<usize>], down: &[Vec<usize>]) {
+    let n = up.len();
+    let mut position = vec![0_usize; n];
+
+    let set_positions = |layers: &[Vec<usize>], position: &mut Vec<usize>| {
+        for layer in layers {
+            for (i, node) in layer.iter().enumerate() {
+                position[*node] = i;
+            }
+        }
+    };
+
+    set_positions(layers, &mut position);
+
+    let mut best = layers.to_vec();
+    let mut best_crossings = crossings(layers, down, &position);
+
+    for sweep in 0..ORDERING_SWEEPS {
+        let going_down = sweep % 2 == 0;
+        let indices: Vec<usize> = if going_down {
+            (1..layers.len()).collect()
+        } else {
+            (0..layers.len().saturating_sub(1)).rev().collect()
+        };
+
+        for l in indices {
+            let neighbours = if going_down { up } else { down };
+
+            let mut keyed: Vec<(f64, usize)> = layers[l]
+                .iter()
+                .map(|node| {
+                    let around = &neighbours[*node];
+
+                    let barycenter = if around.is_empty() {
+                        // nodes without neighbours keep their place
+                        position[*node] as f64
+                    } else {
+                        around.iter().map(|m| position[*m] as f64).sum::<f64>()
+                            / around.len() as f64
+                    };
+
+                    (barycenter, *node)
+                })
+                .collect();
+
+            // stable, equal barycenters keep their order
+            keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            layers[l] = keyed.into_iter().map(|(_, node)| node).collect();
+
+            for (i, node) in layers[l].iter().enumerate() {
+                position[*node] = i;
+            }
+        }
+
+        let current = crossings(layers, down, &position);
+
+        if current < best_crossings {
+            best_crossings = current;
+            best = layers.to_vec();
+        }
+    }
+
+    layers.clone_from_slice(&best);
+}
+
+// crossings between consecutive layers, counted as inversions with a fenwick tree
+fn crossings(layers: &[Vec<usize>], down: &[Vec<usize>], position: &[usize]) -> usize {
+    let mut total: usize = 0;
+
+    for l in 0..layers.len().saturating_sub(1) {
+        let mut pairs: Vec<(usize, usize)> = layers[l]
+            .iter()
+            .flat_map(|s| down[*s].iter().map(move |t| (position[*s], position[*t])))
+            .collect();
+        pairs.sort_unstable();
+
+        let size = layers[l + 1].len() + 1;
+        let mut tree = vec![0_usize; size + 1];
+        for (seen, (_, t)) in pairs.into_iter().enumerate() {
+            // number of edges already seen ending strictly after t
+            let mut at_most = 0;
+            let mut i = t + 1;
+            while i > 0 {
+                at_most += tree[i];
+                i -= i & i.wrapping_neg();
+            }
+
+            total += seen - at_most;
+
+            let mut i = t + 1;
+            while i <= size {
+                tree[i] += 1;
+                i += i & i.wrapping_neg();
+            }
+        }
+    }
+
+    total
+}
+
+fn position_nodes(
+    nodes: &mut [PlacedNode],
+    layers: &[Vec<usize>],
+    up: &[Vec<usize>],
+    down: &[Vec<usize>],
+) {
+    // vertical positions, each layer as high as its highest node
+    let mut y = MARGIN;
+
+    for layer in layers {
+        let height = layer.iter().map(|n| nodes[*n].height).fold(0., f64::max);
+
+        for node in layer {
+            nodes[*node].y = y + height / 2.;
+        }
+
+        y += height + LAYER_SPACING;
+    }
+
+    // first packed from the left
+    for layer in layers {
+        let mut x = 0.;
+
+        for node in layer {
+            nodes[*node].x = x + nodes[*node].width / 2.;
+            x += nodes[*node].width + NODE_SPACING;
+        }
+    }
+
+    for sweep in 0..POSITIONING_SWEEPS {
+        let neighbours = if sweep % 2 == 0 { up } else { down };
+
+        for layer in layers {
+            let wanted: Vec<f64> = layer
+                .iter()
+                .map(|node| {
+                    let around = &neighbours[*node];
+
+                    if around.is_empty() {
+                        nodes[*node].x
+                    } else {
+                        around.iter().map(|m| nodes[*m].x).sum::<f64>() / around.len() as f64
+                    }
+                })
+                .collect();
+
+            // as close as possible to the wanted place, in order and without overlaps:
+            // pushed to the right from the left, then to the left from the right,
+            // the mean of both is kept
+            let gap = |a: usize, b: usize| (nodes[a].width + nodes[b].width) / 2. + NODE_SPACING;
+
+            let mut from_left = wanted.clone();
+            for i in 1..layer.len() {
+                from_left[i] = from_left[i].max(from_left[i - 1] + gap(layer[i - 1], layer[i]));
+            }
+
+            let mut from_right = wanted.clone();
+            for i in (0..layer.len().saturating_sub(1)).rev() {
+                from_right[i] = from_right[i].min(from_right[i + 1] - gap(layer[i], layer[i + 1]));
+            }
+
+            let mut placed: Vec<f64> = from_left
+                .iter()
+                .zip(from_right.iter())
+                .map(|(a, b)| (a + b) / 2.)
+                .collect();
+
+            // the mean can still overlap, a last pass from the left
+            for i in 1..layer.len() {
+                placed[i] = placed[i].max(placed[i - 1] + gap(layer[i - 1], layer[i]));
+            }
+
+            for (node, x) in layer.iter().zip(placed) {
+                nodes[*node].x = x;
+            }
+        }
+    }
+}
+
+// the k-th edge between the same nodes goes through a point moved aside
+fn bend_path(points: &mut Vec<(f64, f64)>, k: usize) {
+    let (sx, sy) = points[0];
+    let (tx, ty) = points[points.len() - 1];
+    let length = ((tx - sx).powi(2) + (ty - sy).powi(2)).sqrt();
+
+    if length == 0. {
+        return;
+    }
+
+    // alternatively on each side
+    let side = if k % 2 == 1 { 1. } else { -1. };
+    let offset = side * PARALLEL_OFFSET * k.div_ceil(2) as f64;
+    let (nx, ny) = (-(ty - sy) / length, (tx - sx) / length);
+
+    if points.len() == 2 {
+        let middle = ((sx + tx) / 2. + offset * nx, (sy + ty) / 2. + offset * ny);
+        points.insert(1, middle);
+    } else {
+        let last = points.len() - 1;
+        for point in points[1..last].iter_mut() {
+            point.0 += offset * nx;
+            point.1 += offset * ny;
+        }
+    }
+}
+
+// the pdf font only has ascii, the logic symbols are written as in the native files
+fn ascii_symbols(s: &str) -> String {
+    let mut ascii = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let word = match c {
+            '⊑' => "<",
+            '¬' => "NOT",
+            '∃' => "EXISTS",
+            '⁻' => {
+                // 'r⁻' is 'INV r'
+                let start = ascii
+                    .rfind(|c: char| c.is_whitespace() || c == '(')
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
+                ascii.insert_str(start, "INV ");
+                continue;
+            }
+            c => {
+                ascii.push(c);
+                continue;
+            }
+        };
+
+        ascii.push_str(word);
+
+        if word != "<" && chars.peek().map(|next| !next.is_whitespace()) == Some(true) {
+            ascii.push(' ');
+        }
+    }
+
+    ascii
+}
+
+fn escape_pdf(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '(' => String::from("\\("),
+            ')' => String::from("\\)"),
+            '\\' => String::from("\\\\"),
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+            _ => String::from("?"),
+        })
+        .collect()
+}
+
+// the colors of dot (x11 names) used here, any '#rrggbb' is also accepted
+const COLORS: [(&str, &str); 16] = [
+    ("black", "#000000"),
+    ("white", "#ffffff"),
+    ("red", "#ff0000"),
+    ("green", "#00ff00"),
+    ("blue", "#0000ff"),
+    ("yellow", "#ffff00"),
+    ("orange", "#ffa500"),
+    ("purple", "#a020f0"),
+    ("gray", "#bebebe"),
+    ("grey", "#bebebe"),
+    ("brown", "#a52a2a"),
+    ("cyan", "#00ffff"),
+    ("magenta", "#ff00ff"),
+    ("pink", "#ffc0cb"),
+    ("darkgreen", "#006400"),
+    ("lightgray", "#d3d3d3"),
+];
+
+pub fn color_to_hex(color: &str) -> String {
+    let color = color.trim().to_lowercase();
+
+    if color.starts_with('#') && color.len() == 7 {
+        return color;
+    }
+
+    COLORS
+        .iter()
+        .find(|(name, _)| *name == color)
+        .map_or(String::from("#000000"), |(_, hex)| hex.to_string())
+}
+
+fn color_to_rgb(color: &str) -> (f64, f64, f64) {
+    let hex = color_to_hex(color);
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0) as f64 / 255.;
+
+    (channel(1), channel(3), channel(5))
+}
@@ -36,14 +36,16 @@ use petgraph::Graph;
 use petgraph::graph::EdgeReference;
 
 // to the rankab task, which is rank abox assertion
-use crate::alg_math::bounds::find_bound_complex_wrapper;
+use crate::alg_math::bounds::{find_bound_complex_wrapper, BoundAlgorithm};
+use crate::alg_math::spectral::find_bound_spectral_wrapper;
 use crate::alg_math::utilities::{median, solve_system_wrapper_only_id_mod};
 // Ontology and ABox (quantified) realizations for dl_lite
 use crate::dl_lite::abox::AbqDllite;
-use crate::dl_lite::ontology::OntologyDllite;
+use crate::dl_lite::ontology::{ConflictMatrixDict, OntologyDllite};
+use crate::graph_maker::{EdgeKind, GraphNode};
 // abstract structs and widely use types
-use crate::alg_math::bounds::Adjusters;
-use crate::kb::knowledge_base::{ABox, AggrFn};
+use crate::alg_math::bounds::BoundSettings;
+use crate::kb::knowledge_base::ABox;
 use crate::kb::types::ConflictType;
 
 // execute a command
@@ -53,20 +55,137 @@ use crate::kb::types::ConflictType;
 
 // the rank abox algorithm returns
 type RankRemainder = (Vec<i8>, HashMap<usize, usize>, HashMap<usize, ConflictType>);
+// and when asked, the system solved to find the values
+type RankWithSystem = (RankRemainder, Option<RankSystem>);
+
+/// The linear system (bound·I − A)x = 1 solved by the ranking, kept to explain
+/// the values: the value of the assertion of row i is 'scale · x_i'.
+pub struct RankSystem {
+    aggr_matrix: Vec<f64>,
+    bound: f64,
+    solution: Vec<f64>,
+    scale: f64,
+    done_to_real: HashMap<usize, usize>,
+}
+
+impl RankSystem {
+    pub fn bound(&self) -> f64 {
+        self.bound
+    }
+
+    /// the number of assertions in the system
+    pub fn dimension(&self) -> usize {
+        self.solution.len()
+    }
+
+    /// the value of an assertion (index in the abox) as a sum, from its row in the system
+    /// x_i = (1 + Σ_j A_ij·x_j) / bound: a base 'scale / bound' and the contribution
+    /// 'scale · A_ij · x_j / bound' of each other assertion j (index in the abox),
+    /// assertions outside the system (clean or self conflicting) give none
+    pub fn decomposition(&self, real_index: usize) -> Option<(f64, Vec<(usize, f64)>)> {
+        let dim = self.solution.len();
+        let row = self
+            .done_to_real
+            .iter()
+            .find(|(_, real)| **real == real_index)
+            .map(|(done, _)| *done)?;
+
+        let base = self.scale / self.bound;
+        let mut contributions: Vec<(usize, f64)> = Vec::new();
+
+        for column in 0..dim {
+            let coefficient = self.aggr_matrix[row * dim + column];
+
+            if column != row && coefficient != 0. {
+                if let Some(real) = self.done_to_real.get(&column) {
+                    contributions.push((*real, base * coefficient * self.solution[column]));
+                }
+            }
+        }
+
+        Some((base, contributions))
+    }
+
+    /// the values (index in the abox, value) of the assertions of the system when its
+    /// aggregation matrix is 'aggr_matrix', the bound and the scale stay the ones of the
+    /// ranking, sorted by index, None if the system can't be solved
+    pub fn values_with_matrix(&self, aggr_matrix: &[f64]) -> Option<Vec<(usize, f64)>> {
+        let mut solution: Vec<f64> = vec![0.; self.solution.len()];
+
+        if !solve_system_wrapper_only_id_mod(aggr_matrix, &mut solution, self.bound) {
+            return Option::None;
+        }
+
+        let mut values = self
+            .done_to_real
+            .iter()
+            .map(|(done, real)| (*real, self.scale * solution[*done]))
+            .collect::<Vec<(usize, f64)>>();
+        values.sort_by_key(|(real, _)| *real);
+
+        Some(values)
+    }
+}
 
-pub fn rank_abox(
+pub fn rank_abox<F: FnMut(Vec<f64>) -> f64>(
     onto: &OntologyDllite,
     abq: &mut AbqDllite,
-    _deduction_tree: bool,
-    aggr: AggrFn,
-    adjusters: Adjusters,
+    deduction_tree: bool,
+    aggr: F,
+    bound_settings: BoundSettings,
     verbose: bool,
     use_concurrency: bool,
 ) -> RankRemainder {
+    let (remainder, _) = rank_abox_with_system(
+        onto,
+        abq,
+        deduction_tree,
+        aggr,
+        bound_settings,
+        verbose,
+        use_concurrency,
+    );
+
+    remainder
+}
+
+/// same as 'rank_abox', the system is also returned when there was one to solve
+pub fn rank_abox_with_system<F: FnMut(Vec<f64>) -> f64>(
+    onto: &OntologyDllite,
+    abq: &mut AbqDllite,
+    _deduction_tree: bool,
+    aggr: F,
+    bound_settings: BoundSettings,
+    verbose: bool,
+    use_concurrency: bool,
+) -> RankWithSystem {
+    // the conflicts don't depend on the credibilities
+    let conflicts = onto.conflict_matrix_refs_only(abq, verbose);
+
+    rank_abox_from_conflicts(
+        abq,
+        conflicts,
+        aggr,
+        bound_settings,
+        verbose,
+        use_concurrency,
+    )
+}
+
+/// the ranking once the conflicts of the abox are known, several rankings of the same
+/// assertions with other credibilities can share them
+pub fn rank_abox_from_conflicts<F: FnMut(Vec<f64>) -> f64>(
+    abq: &mut AbqDllite,
+    conflicts: ConflictMatrixDict,
+    aggr: F,
+    bound_settings: BoundSettings,
+    verbose: bool,
+    use_concurrency: bool,
+) -> RankWithSystem {
     // before everything we need to normalize
 
-    // unpack the adjuster
-    let (tolerance, m_scale, b_translate) = adjusters;
+    // unpack the adjuster, the bound method and the algorithm
+    let ((tolerance, m_scale, b_translate), method, algorithm) = bound_settings;
 
     let mut prevalues = abq
         .items()
@@ -79,10 +198,9 @@ pub fn rank_abox(
         abqi.set_credibility(prevalues[i]);
     }
 
-    let (before_matrix, real_to_virtual, virtual_to_real) =
-        onto.conflict_matrix_refs_only(abq, verbose);
+    let (before_matrix, real_to_virtual, virtual_to_real) = conflicts;
 
-    let (done_matrix, before_to_done_matrix, _done_to_before_matrix, clean_index_tuple_op) =
+    let (done_matrix, before_to_done_matrix, done_to_before_matrix, clean_index_tuple_op) =
         OntologyDllite::from_conflict_to_clean_matrix(&before_matrix).unwrap();
 
     let mut conflict_type: HashMap<usize, ConflictType> = HashMap::new();
@@ -124,7 +242,10 @@ pub fn rank_abox(
         }
 
         // return the conflict listing
-        (before_matrix, virtual_to_real, conflict_type)
+        (
+            (before_matrix, virtual_to_real, conflict_type),
+            Option::None,
+        )
     } else {
         // the rank can be done on done matrix without differentiating cases
         let aggr_matrix = OntologyDllite::compute_aggregation_matrix(
@@ -136,16 +257,25 @@ pub fn rank_abox(
         );
 
         // compute the bound
-        let bound_op = find_bound_complex_wrapper(
-            aggr_matrix.clone(),
-            tolerance,
-            m_scale,
-            b_translate,
-            use_concurrency,
-        );
+        let bound_op = match algorithm {
+            BoundAlgorithm::Interpolation => find_bound_complex_wrapper(
+                aggr_matrix.clone(),
+                tolerance,
+                m_scale,
+                b_translate,
+                method,
+                use_concurrency,
+            ),
+            BoundAlgorithm::Spectral => {
+                find_bound_spectral_wrapper(aggr_matrix.clone(), m_scale, use_concurrency)
+            }
+        };
 
         match bound_op {
-            Option::None => (before_matrix, virtual_to_real, conflict_type),
+            Option::None => (
+                (before_matrix, virtual_to_real, conflict_type),
+                Option::None,
+            ),
             Some(bound) => {
                 let dim = (done_matrix.len() as f64).sqrt() as usize;
 
@@ -153,6 +283,8 @@ pub fn rank_abox(
 
                 // let aggr_matrix_clone = aggr_matrix.clone();
                 solve_system_wrapper_only_id_mod(&aggr_matrix, &mut rank, bound);
+                let solution = rank.clone();
+                let divisor: f64;
 
                 // now I have the rank, I can begin to put the information inside abq!!
 
@@ -163,6 +295,7 @@ pub fn rank_abox(
 
                     // get the rank of the clean fact
                     let clean_rank = rank[new_clean_index];
+                    divisor = clean_rank;
                     rank = rank.iter().map(|x| x / clean_rank).collect::<Vec<f64>>();
                     rank[new_clean_index] = 1.;
                 } else {
@@ -192,6 +325,7 @@ pub fn rank_abox(
                     // if we found some fact not implied nor contradict we take
                     if let Some(clean_fact_index) = some_clean_fact {
                         let clean_rank = rank[clean_fact_index];
+                        divisor = clean_rank;
 
                         for item in rank.iter_mut().take(dim) {
                             item.div_assign(clean_rank)
@@ -202,6 +336,7 @@ pub fn rank_abox(
                         // then all facts have some kind of implication or contradiction
                         let rank_for_median = rank.iter().copied().collect::<Vec<f64>>();
                         let median = median(&rank_for_median).unwrap_or(1.);
+                        divisor = median;
 
                         rank = rank.iter().map(|x| x / median).collect();
                     }
@@ -270,7 +405,26 @@ pub fn rank_abox(
                     }
                 }
 
-                (before_matrix, virtual_to_real, conflict_type)
+                // rows of the system are indices of the done matrix
+                let done_to_real = done_to_before_matrix
+                    .iter()
+                    .filter_map(|(done, before)| {
+                        virtual_to_real.get(before).map(|real| (*done, *real))
+                    })
+                    .collect::<HashMap<usize, usize>>();
+
+                let system = RankSystem {
+                    aggr_matrix,
+                    bound,
+                    solution,
+                    scale: normalization_scale / divisor,
+                    done_to_real,
+                };
+
+                (
+                    (before_matrix, virtual_to_real, conflict_type),
+                    Some(system),
+                )
             }
         }
     }
@@ -278,16 +432,37 @@ pub fn rank_abox(
 
 // function for the creation of graphs
 // create functions for att
-pub fn edge_attr(_g: &Graph<String, bool>, e: EdgeReference<bool>) -> String {
-    if *e.weight() {
-        String::from("color=\"green\"")
-    } else {
-        String::from("color=\"red\"")
+pub fn edge_attr(_g: &Graph<GraphNode, EdgeKind>, e: EdgeReference<EdgeKind>) -> String {
+    match e.weight() {
+        EdgeKind::Refutation => String::from("color=\"red\""),
+        _ => String::from("color=\"green\""),
     }
 }
 
-pub fn node_attr(_g: &Graph<String, bool>, _ni: (petgraph::prelude::NodeIndex, &String)) -> String {
-    String::from("")
+// the rank value is shown by the node: the lowest of the range is small and red,
+// the highest is big and green
+pub fn node_attr(node: &GraphNode, range: (f64, f64)) -> String {
+    let (min, max) = range;
+
+    let t = match node.value() {
+        Some(v) if max > min => (v - min) / (max - min),
+        _ => 0.5,
+    };
+
+    let mix = |low: f64, high: f64| (low + t * (high - low)).round() as u8;
+    let fill = format!(
+        "#{:02x}{:02x}{:02x}",
+        mix(255., 170.),
+        mix(150., 230.),
+        mix(150., 170.)
+    );
+
+    format!(
+        "style=filled fillcolor=\"{}\" width={:.2} height={:.2}",
+        fill,
+        1.5 + 1.5 * t,
+        0.5 + 0.5 * t
+    )
 }
 
 /*
@@ -325,3 +500,171 @@ pub fn command_exists(name: &str) -> bool {
         Err(e) => !(matches!(e.kind(), ErrorKind::NotFound)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alg_math::polynomial_roots::Method;
+    use crate::kb::aggr_functions::AGGR_SUM;
+    use crate::kb::types::FileType;
+    use crate::{B_TRANSLATE, M_SCALE, TOLERANCE};
+
+    // the example aboxes, all over the university tbox
+    const EXAMPLE_ABOXES: [&str; 10] = [
+        "university_abox",
+        "university_abox2",
+        "university_abox_scaled",
+        "testab",
+        "testab2",
+        "testab3",
+        "testab4",
+        "testab5",
+        "testab6",
+        "testab7",
+    ];
+
+    // the university tbox and one of the example aboxes, the closures are generated
+    fn example(abox_name: &str) -> (OntologyDllite, AbqDllite) {
+        let examples = format!("{}/examples", env!("CARGO_MANIFEST_DIR"));
+        let path_tbox = format!("{}/university_tbox", examples);
+
+        let mut onto = OntologyDllite::new(String::from("university_tbox"));
+        onto.add_symbols_from_file(&path_tbox, FileType::Native, false);
+        onto.add_tbis_from_file(&path_tbox, FileType::Native, false);
+        onto.new_abox_from_file_quantum(
+            &format!("{}/{}", examples, abox_name),
+            FileType::Native,
+            false,
+        );
+        onto.generate_cln(false, false, 0);
+
+        let abq = onto.abox().unwrap().clone();
+        (onto, abq)
+    }
+
+    // the aggregation matrix the ranking bounds, as 'rank_abox_from_conflicts' builds it,
+    // None when nothing conflicts
+    fn aggregation_matrix(onto: &OntologyDllite, abq: &AbqDllite) -> Option<Vec<f64>> {
+        let mut abq = abq.clone();
+        let mut credibilities = abq
+            .items()
+            .iter()
+            .map(|abiq| abiq.credibility())
+            .collect::<Vec<f64>>();
+        normalize_vector(&mut credibilities);
+
+        for (abiq, credibility) in abq.items_mut().iter_mut().zip(credibilities) {
+            abiq.set_credibility(credibility);
+        }
+
+        let (before_matrix, _, virtual_to_real) = onto.conflict_matrix_refs_only(&abq, false);
+        let (done_matrix, _, _, _) = OntologyDllite::from_conflict_to_clean_matrix(&before_matrix)?;
+
+        if done_matrix.is_empty() {
+            Option::None
+        } else {
+            Some(OntologyDllite::compute_aggregation_matrix(
+                &abq,
+                &done_matrix,
+                &virtual_to_real,
+                AGGR_SUM,
+                false,
+            ))
+        }
+    }
+
+    // the values of the ranking of abq with the given bound algorithm
+    fn ranked_values(
+        onto: &OntologyDllite,
+        abq: &AbqDllite,
+        algorithm: BoundAlgorithm,
+    ) -> Vec<f64> {
+        let mut abq = abq.clone();
+        let bound_settings: BoundSettings = (
+            (TOLERANCE, M_SCALE, B_TRANSLATE),
+            Method::CauchyCubic,
+            algorithm,
+        );
+        let use_concurrency = false;
+
+        rank_abox(
+            onto,
+            &mut abq,
+            false,
+            AGGR_SUM,
+            bound_settings,
+            false,
+            use_concurrency,
+        );
+
+        abq.items()
+            .iter()
+            .map(|abiq| abiq.value().unwrap_or(1.))
+            .collect()
+    }
+
+    #[test]
+    fn spectral_and_interpolation_rankings_agree() {
+        for abox_name in EXAMPLE_ABOXES {
+            let (onto, abq) = example(abox_name);
+            let interpolation = ranked_values(&onto, &abq, BoundAlgorithm::Interpolation);
+            let spectral = ranked_values(&onto, &abq, BoundAlgorithm::Spectral);
+
+            // pairs ordered in opposite ways, near ties are rounding errors
+            let tolerance = 0.000000000001;
+            let mut discordant: Vec<(usize, usize)> = Vec::new();
+
+            for i in 0..abq.len() {
+                for j in (i + 1)..abq.len() {
+                    let by_interpolation = interpolation[i] - interpolation[j];
+                    let by_spectral = spectral[i] - spectral[j];
+
+                    if by_interpolation.abs() > tolerance
+                        && by_spectral.abs() > tolerance
+                        && by_interpolation * by_spectral < 0.
+                    {
+                        discordant.push((i, j));
+                    }
+                }
+            }
+
+            assert!(
+                discordant.is_empty(),
+                "{}: the pairs {:?} are ordered differently",
+                abox_name,
+                discordant
+            );
+        }
+    }
+
+    #[test]
+    fn auto_bound_is_never_looser_than_the_default() {
+        for abox_name in EXAMPLE_ABOXES {
+            let (onto, abq) = example(abox_name);
+
+            if let Some(aggr_matrix) = aggregation_matrix(&onto, &abq) {
+                let bound = |method: Method| {
+                    let use_concurrency = false;
+                    find_bound_complex_wrapper(
+                        aggr_matrix.clone(),
+                        TOLERANCE,
+                        M_SCALE,
+                        B_TRANSLATE,
+                        method,
+                        use_concurrency,
+                    )
+                    .unwrap()
+                };
+
+                let (auto, cubic) = (bound(Method::Auto), bound(Method::CauchyCubic));
+                assert!(
+                    auto <= cubic,
+                    "{}: the automatic bound {} is looser than cauchy cubic {}",
+                    abox_name,
+                    auto,
+                    cubic
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   The report of a task ('vertb', 'verab' or 'rankab') as one html file that is read
+   offline, style and script are inside the file:
+       - a summary: sizes, counts of clean and conflicting assertions, aggregation, bound
+       - the table of the assertions, sorted by clicking a header
+       - the unravel trees, one folded block by axiom or assertion
+       - the conflict graph drawn inline as svg by the built-in layout
+*/
+
+use std::collections::HashMap;
+
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::string_formatter::abi_to_string;
+use crate::graph_export::conflict_type_name;
+use crate::interface::utilities::escape_xml;
+use crate::kb::knowledge_base::{ABox, SymbolDict};
+use crate::kb::types::ConflictType;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #202020; }
+h1 { font-size: 1.6em; }
+h2 { font-size: 1.3em; border-bottom: 1px solid #c0c0c0; margin-top: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d0d0d0; padding: 0.3em 0.8em; text-align: left; }
+th { background: #f0f0f0; }
+table.sortable th { cursor: pointer; }
+table.sortable th:after { content: ' \\2195'; color: #a0a0a0; }
+td.number { text-align: right; font-family: monospace; }
+tr.conflict td { background: #fff4d8; }
+tr.self_conflict td { background: #ffe0e0; }
+details { margin: 0.2em 0; }
+summary { cursor: pointer; font-family: monospace; }
+pre { background: #f8f8f8; padding: 0.5em; overflow-x: auto; }
+.graph { overflow: auto; border: 1px solid #d0d0d0; }
+";
+
+// sorts the rows of a table by the clicked column, numbers as numbers
+const SCRIPT: &str = "
+document.querySelectorAll('table.sortable th').forEach(function (th) {
+    th.addEventListener('click', function () {
+        var body = th.closest('table').tBodies[0];
+        var column = th.cellIndex;
+        var ascending = th.dataset.order !== 'ascending';
+        th.dataset.order = ascending ? 'ascending' : 'descending';
+
+        var rows = Array.from(body.rows);
+        rows.sort(function (a, b) {
+            var x = a.cells[column].textContent;
+            var y = b.cells[column].textContent;
+            var nx = parseFloat(x);
+            var ny = parseFloat(y);
+            var c = (isNaN(nx) || isNaN(ny)) ? x.localeCompare(y) : nx - ny;
+            return ascending ? c : -c;
+        });
+        rows.forEach(function (row) { body.appendChild(row); });
+    });
+});
+";
+
+/// A report built section by section, 'to_html' gives the whole document.
+#[derive(Debug, Clone)]
+pub struct HtmlReport {
+    title: String,
+    summary: Vec<(String, String)>,
+    sections: Vec<(String, String)>,
+}
+
+impl HtmlReport {
+    pub fn new(title: &str) -> HtmlReport {
+        HtmlReport {
+            title: title.to_string(),
+            summary: Vec::new(),
+            sections: Vec::new(),
+        }
+    }
+
+    pub fn add_summary(&mut self, name: &str, value: &str) {
+        self.summary.push((name.to_string(), value.to_string()));
+    }
+
+    /// The counts of each conflict type go to the summary, the assertions with their
+    /// credibility, value and conflict type to a sortable table.
+    pub fn add_assertions(
+        &mut self,
+        abq: &AbqDllite,
+        symbols: &SymbolDict,
+        conflict_type: &HashMap<usize, ConflictType>,
+    ) {
+        let count = |wanted: ConflictType| {
+            (0..abq.len())
+                .filter(|i| conflict_type.get(i) == Some(&wanted))
+                .count()
+        };
+
+        self.add_summary("assertions", &abq.len().to_string());
+        self.add_summary("clean", &count(ConflictType::Clean).to_string());
+        self.add_summary("conflict", &count(ConflictType::Conflict).to_string());
+        self.add_summary(
+            "self conflict",
+            &count(ConflictType::SelfConflict).to_string(),
+        );
+
+        let mut table = String::from(
+            "<table class=\"sortable\">\n<thead><tr><th>#</th><th>assertion</th><th>credibility</th><th>value</th><th>conflict type</th></tr></thead>\n<tbody>\n",
+        );
+
+        for (i, abiq) in abq.items().iter().enumerate() {
+            let text = match abi_to_string(abiq.abi(), symbols) {
+                Option::None => format!("{}", abiq.abi()),
+                Some(s) => s,
+            };
+            let value = abiq.value().map(|v| v.to_string()).unwrap_or_default();
+            let kind = conflict_type
+                .get(&i)
+                .map(conflict_type_name)
+                .unwrap_or_default();
+
+            table.push_str(&format!(
+                "<tr class=\"{}\"><td class=\"number\">{}</td><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td>{}</td></tr>\n",
+                kind,
+                i,
+                escape_xml(&text),
+                abiq.credibility(),
+                value,
+                kind
+            ));
+        }
+
+        table.push_str("</tbody>\n</table>\n");
+        self.sections.push((String::from("assertions"), table));
+    }
+
+    pub fn add_list(&mut self, title: &str, items: &[String]) {
+        let mut list = String::from("<ul>\n");
+
+        for item in items {
+            list.push_str(&format!("<li><code>{}</code></li>\n", escape_xml(item)));
+        }
+
+        list.push_str("</ul>\n");
+        self.sections.push((title.to_string(), list));
+    }
+
+    /// Takes the output of 'create_string_for_unravel_conflict_abox' (or of the tbox
+    /// one): each level is a heading and each axiom or assertion a folded block.
+    pub fn add_unravel(&mut self, title: &str, unravel: &str) {
+        let mut html = String::new();
+        let mut block: Option<Vec<&str>> = Option::None;
+
+        for line in unravel.lines() {
+            match (&mut block, line) {
+                // the blocks of the top items are the only ones with this indentation
+                (Option::None, "   {") => block = Some(Vec::new()),
+                (Some(lines), "   }") => {
+                    let head = lines
+                        .first()
+                        .map(|l| {
+                            l.trim()
+                                .trim_start_matches("abi: ")
+                                .trim_start_matches("tbi: ")
+                        })
+                        .unwrap_or_default();
+
+                    html.push_str(&format!(
+                        "<details><summary>{}</summary><pre>{}</pre></details>\n",
+                        escape_xml(head),
+                        escape_xml(&lines.join("\n"))
+                    ));
+                    block = Option::None;
+                }
+                (Some(lines), _) => lines.push(line),
+                (Option::None, _) => {
+                    let trimmed = line.trim();
+
+                    if trimmed.starts_with("level") {
+                        html.push_str(&format!(
+                            "<h3>{}</h3>\n",
+                            escape_xml(trimmed.trim_end_matches('{').trim().trim_end_matches(':'))
+                        ));
+                    }
+                }
+            }
+        }
+
+        self.sections.push((title.to_string(), html));
+    }
+
+    /// The svg document is put in the page, without its xml declaration.
+    pub fn add_graph(&mut self, title: &str, svg: &str) {
+        let svg = match svg.find("<svg") {
+            Some(start) => &svg[start..],
+            Option::None => svg,
+        };
+
+        self.sections.push((
+            title.to_string(),
+            format!("<div class=\"graph\">\n{}</div>\n", svg),
+        ));
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+            escape_xml(&self.title),
+            STYLE,
+            escape_xml(&self.title)
+        );
+
+        if !self.summary.is_empty() {
+            html.push_str("<h2>summary</h2>\n<table>\n");
+
+            for (name, value) in &self.summary {
+                html.push_str(&format!(
+                    "<tr><th>{}</th><td>{}</td></tr>\n",
+                    escape_xml(name),
+                    escape_xml(value)
+                ));
+            }
+
+            html.push_str("</table>\n");
+        }
+
+        for (title, body) in &self.sections {
+            html.push_str(&format!(
+                "<section>\n<h2>{}</h2>\n{}</section>\n",
+                escape_xml(title),
+                body
+            ));
+        }
+
+        html.push_str(&format!("<script>{}</script>\n</body>\n</html>\n", SCRIPT));
+        html
+    }
+}
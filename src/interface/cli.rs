@@ -21,18 +21,29 @@ use std::str::FromStr;
 use std::string::ParseError;
 use structopt::StructOpt;
 
+use crate::kb::aggr_expression::EXPRESSION_PREFIX;
+
 /// Every task the the binary dl_lite_r can do, each is explained as a comment.
 /// more to be added after
 #[derive(Debug, Copy, Clone)]
 pub enum Task {
     VerTB,    // verify tbox
     GenConTB, // generate consequence tree tbox
+    MinTB,    // drop the axioms entailed by the other ones
+    DiffTB,   // what changed in meaning between two versions of a tbox
     // CTB,      // complete tbox
     VerAB,    // verify abox
     CleanAB,  // clean from self conflicts
     GenConAB, // generate consequence tree abox
     // CAB,      // complete abox
-    RankAB, // rank assertions on abox
+    RankAB,   // rank assertions on abox
+    ChaseAB,  // materialize the canonical model of the abox
+    QueryAB,  // answer a query over a database through mappings
+    MergeAB,  // merge several aboxes keeping the provenance of each assertion
+    DiffRank, // compare two rankings of the same abox
+    Serve,    // answer requests over http, ontologies stay in memory
+    Fmt,      // write native files again, in the way the ontology writes them
+    Lint,     // find duplicate, entailed and trivial axioms, unused symbols and alike
     Undefined,
 }
 
@@ -44,10 +55,19 @@ impl FromStr for Task {
         match s.trim() {
             "vertb" => Ok(Task::VerTB),
             "gencontb" => Ok(Task::GenConTB),
+            "mintb" => Ok(Task::MinTB),
+            "difftb" => Ok(Task::DiffTB),
             "verab" => Ok(Task::VerAB),
             "cleanab" => Ok(Task::CleanAB),
             "genconab" => Ok(Task::GenConAB),
             "rankab" => Ok(Task::RankAB),
+            "chaseab" => Ok(Task::ChaseAB),
+            "queryab" => Ok(Task::QueryAB),
+            "mergeab" => Ok(Task::MergeAB),
+            "diffrank" => Ok(Task::DiffRank),
+            "serve" => Ok(Task::Serve),
+            "fmt" => Ok(Task::Fmt),
+            "lint" => Ok(Task::Lint),
             _ => Ok(Task::Undefined),
         }
     }
@@ -61,6 +81,8 @@ pub enum AggrName {
     Sum,
     Mean,
     Count,
+    // an aggregation expression (see kb::aggr_expression), without the 'expr:' prefix
+    Expression(String),
     Undefined,
 }
 
@@ -77,7 +99,118 @@ impl FromStr for AggrName {
             "min" => Ok(AggrName::Min),
             "count" => Ok(AggrName::Count),
             "mean" => Ok(AggrName::Mean),
-            _ => Ok(AggrName::Undefined),
+            _ => match s2.strip_prefix(EXPRESSION_PREFIX) {
+                Some(expression) => Ok(AggrName::Expression(expression.trim().to_string())),
+                Option::None => Ok(AggrName::Undefined),
+            },
+        }
+    }
+}
+
+/// how the credibility given by several sources to the same assertion is combined
+#[derive(Debug)]
+pub enum CombineName {
+    Max,
+    NoisyOr,
+    Sum,
+    Undefined,
+}
+
+/// to cast to enum from string
+impl FromStr for CombineName {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "max" => Ok(CombineName::Max),
+            "noisyor" | "noisy-or" => Ok(CombineName::NoisyOr),
+            "sum" => Ok(CombineName::Sum),
+            _ => Ok(CombineName::Undefined),
+        }
+    }
+}
+
+/// the bound on the roots of the polynomials used to solve the ranking system
+#[derive(Debug)]
+pub enum BoundName {
+    Cauchy,
+    CauchySquare,
+    CauchyCubic,
+    CauchyQuad,
+    Fujiwara,
+    Lagrange,
+    Kojima,
+    LagrangeZassenhaus,
+    Auto,
+    Spectral,
+    Undefined,
+}
+
+/// to cast to enum from string
+impl FromStr for BoundName {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "cauchy" => Ok(BoundName::Cauchy),
+            "cauchy-square" => Ok(BoundName::CauchySquare),
+            "cauchy-cubic" => Ok(BoundName::CauchyCubic),
+            "cauchy-quad" => Ok(BoundName::CauchyQuad),
+            "fujiwara" => Ok(BoundName::Fujiwara),
+            "lagrange" => Ok(BoundName::Lagrange),
+            "kojima" => Ok(BoundName::Kojima),
+            "lagrange-zassenhaus" | "zassenhaus" => Ok(BoundName::LagrangeZassenhaus),
+            "auto" => Ok(BoundName::Auto),
+            "spectral" => Ok(BoundName::Spectral),
+            _ => Ok(BoundName::Undefined),
+        }
+    }
+}
+
+/// how the visual output of graphs is produced
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderName {
+    Dot,
+    Svg,
+    Pdf,
+    Undefined,
+}
+
+/// to cast to enum from string
+impl FromStr for RenderName {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "dot" => Ok(RenderName::Dot),
+            "svg" => Ok(RenderName::Svg),
+            "pdf" => Ok(RenderName::Pdf),
+            _ => Ok(RenderName::Undefined),
+        }
+    }
+}
+
+/// the formats graphs can be exported to, besides the dot notation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportName {
+    GraphMl,
+    Gexf,
+    Json,
+    Mermaid,
+    Undefined,
+}
+
+/// to cast to enum from string
+impl FromStr for ExportName {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "graphml" => Ok(ExportName::GraphMl),
+            "gexf" => Ok(ExportName::Gexf),
+            "json" => Ok(ExportName::Json),
+            "mermaid" => Ok(ExportName::Mermaid),
+            _ => Ok(ExportName::Undefined),
         }
     }
 }
@@ -90,7 +223,7 @@ pub struct Cli {
     #[structopt(
         short = "t",
         long = "task",
-        help = "describes the wanted task, (vertb|gencontb|verab|genconab|cleanab|rankab)"
+        help = "describes the wanted task, (vertb|gencontb|mintb|difftb|verab|genconab|cleanab|rankab|chaseab|queryab|mergeab|diffrank|serve|fmt|lint)"
     )]
     pub task: Task,
 
@@ -103,8 +236,25 @@ pub struct Cli {
     #[structopt(parse(from_os_str), long = "tbox", help = "path to the tbox file")]
     pub path_tbox: Option<std::path::PathBuf>,
 
-    #[structopt(parse(from_os_str), long = "abox", help = "path to the abox file")]
-    pub path_abox: Option<std::path::PathBuf>,
+    #[structopt(
+        parse(from_os_str),
+        long = "tbox-after",
+        help = "the new version of the tbox, the 'difftb' task compares it with the one of the 'tbox' option, the 'diffrank' task classifies the second ranking with it"
+    )]
+    pub path_tbox_after: Option<std::path::PathBuf>,
+
+    #[structopt(
+        parse(from_os_str),
+        long = "abox",
+        help = "path to the abox file, can be given several times to work with several aboxes"
+    )]
+    pub path_abox: Vec<std::path::PathBuf>,
+
+    #[structopt(
+        long = "name",
+        help = "optional, the abox to work on: one of the aboxes given, one stored in a database or the name of the merge of all the aboxes given"
+    )]
+    pub abox_name: Option<String>,
 
     #[structopt(
         parse(from_os_str),
@@ -122,7 +272,121 @@ pub struct Cli {
 
     #[structopt(
         long = "aggr",
-        help = "choose a function to aggregate during conflict graph computing: (sum|min|max|count|mean)"
+        help = "choose a function to aggregate during conflict graph computing: (sum|min|max|count|mean) or an expression 'expr:...' (e.g. 'expr:p = 2; (mean(x^p))^(1/p)')"
     )]
     pub aggr: Option<AggrName>,
+
+    #[structopt(
+        long = "depth",
+        default_value = "3",
+        help = "maximal depth of the labelled nulls created by the 'chaseab' task"
+    )]
+    pub depth: usize,
+
+    #[structopt(
+        parse(from_os_str),
+        long = "mappings",
+        help = "optional, maps the symbols to sql queries over the abox database, the abox is then never loaded"
+    )]
+    pub path_mappings: Option<std::path::PathBuf>,
+
+    #[structopt(
+        long = "query",
+        help = "the concept or role asked by the 'queryab' task, (e.g. 'Person', 'EXISTS teaches', 'INV teaches')"
+    )]
+    pub query: Option<String>,
+
+    #[structopt(
+        long = "explain",
+        help = "optional, the 'rankab' task explains the value of this assertion (e.g. 'John : Student')"
+    )]
+    pub explain: Option<String>,
+
+    #[structopt(
+        long = "sensitivity",
+        help = "optional, the 'rankab' task also shows how the values move with the credibilities"
+    )]
+    pub sensitivity: bool,
+
+    #[structopt(
+        parse(from_os_str),
+        long = "sources",
+        help = "optional, a file declaring the trust of the sources of assertions, abox files can also declare them"
+    )]
+    pub path_sources: Option<std::path::PathBuf>,
+
+    #[structopt(
+        long = "combine",
+        help = "choose how the credibility given by several sources to an assertion is combined: (max|noisyor|sum)"
+    )]
+    pub combine: Option<CombineName>,
+
+    #[structopt(
+        long = "bound",
+        help = "choose the bound on polynomial roots used by 'rankab': (cauchy|cauchy-square|cauchy-cubic|cauchy-quad|fujiwara|lagrange|kojima|lagrange-zassenhaus|auto|spectral)"
+    )]
+    pub bound: Option<BoundName>,
+
+    #[structopt(
+        long = "exact",
+        help = "optional, the 'rankab' task also ranks with exact rationals and shows the difference with the floats (small aboxes)"
+    )]
+    pub exact: bool,
+
+    #[structopt(
+        long = "render",
+        help = "optional, how visual outputs of graphs are made: (dot|svg|pdf), 'svg' and 'pdf' use a built-in layout and don't need graphviz, by default dot is used if found and svg otherwise"
+    )]
+    pub render: Option<RenderName>,
+
+    #[structopt(
+        long = "export",
+        help = "optional, graphs are also exported to this format: (graphml|gexf|json|mermaid), can be given several times"
+    )]
+    pub export: Vec<ExportName>,
+
+    #[structopt(
+        parse(from_os_str),
+        long = "report",
+        help = "optional, the 'vertb', 'verab' and 'rankab' tasks also write a html report to this file"
+    )]
+    pub path_report: Option<std::path::PathBuf>,
+
+    #[structopt(
+        long = "graph-nominal",
+        help = "optional, the conflict graph of 'rankab' only has the assertions on this constant (e.g. 'John')"
+    )]
+    pub graph_nominal: Option<String>,
+
+    #[structopt(
+        long = "graph-predicate",
+        help = "optional, the conflict graph of 'rankab' only has the assertions of this concept or role (e.g. 'teaches')"
+    )]
+    pub graph_predicate: Option<String>,
+
+    #[structopt(
+        long = "graph-around",
+        help = "optional, the conflict graph of 'rankab' only has the neighbourhood of this assertion (e.g. 'John : Student')"
+    )]
+    pub graph_around: Option<String>,
+
+    #[structopt(
+        long = "graph-hops",
+        default_value = "1",
+        help = "the size of the neighbourhood asked by '--graph-around', in edges"
+    )]
+    pub graph_hops: usize,
+
+    #[structopt(
+        long = "graph-lowest",
+        help = "optional, the conflict graph of 'rankab' only has the n assertions with the lowest value"
+    )]
+    pub graph_lowest: Option<usize>,
+
+    #[structopt(
+        long = "port",
+        default_value = "7878",
+        help = "the port of the 'serve' task, it listens on localhost only"
+    )]
+    pub port: u16,
 }
@@ -31,11 +31,20 @@ pub fn is_json_file(filename: &str) -> bool {
     filename.ends_with(".json")
 }
 
+pub fn is_sqlite_file(filename: &str) -> bool {
+    // check if the file is a sqlite database
+
+    filename.ends_with(".db") || filename.ends_with(".sqlite")
+}
+
 pub fn get_filetype(filename: &str) -> FileType {
     // class ontology files in one of the known types to parse
-    match is_json_file(filename) {
-        true => FileType::Json,
-        false => FileType::Native,
+    if is_json_file(filename) {
+        FileType::Json
+    } else if is_sqlite_file(filename) {
+        FileType::Sqlite
+    } else {
+        FileType::Native
     }
 }
 
@@ -75,3 +84,11 @@ pub fn write_str_to_file(s: &str, filename: &str) -> bool {
         }
     }
 }
+
+/// Escapes the characters with a meaning in xml (also used for html and svg).
+pub fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
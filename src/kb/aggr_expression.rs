@@ -0,0 +1,593 @@
+/*
+ © - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/// This module defines a small language to write aggregation functions without
+/// touching the code, an expression is evaluated on the credibility values of a subset:
+/// - numbers and the arithmetic operators + - * / ^ with parentheses
+/// - n: the number of values
+/// - sum(e), prod(e), max(e), min(e), mean(e): evaluate e on each value and
+///   aggregate the results, inside e the variable x is the value and i its index
+/// - quantile(q): the q-quantile of the values (q in [0, 1])
+/// - exp, ln, sqrt, abs with one argument, max and min with several arguments
+/// - definitions before the expression, separated by ';' (e.g. 'p = 2; ...')
+///
+/// Some examples:
+/// - power mean: 'p = 3; (mean(x^p))^(1/p)'
+/// - softmax weighted sum: 'sum(x * exp(x)) / sum(exp(x))'
+/// - third quartile: 'quantile(0.75)'
+/// - product t-norm: 'prod(x)', Lukasiewicz t-norm: 'max(0, sum(x) - n + 1)'
+use std::fmt;
+use std::io;
+use std::io::{Error, ErrorKind};
+
+use crate::kb::knowledge_base::AggrBox;
+
+/// the prefix that marks an aggregation expression on the command line
+pub const EXPRESSION_PREFIX: &str = "expr:";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Aggregate {
+    Sum,
+    Prod,
+    Max,
+    Min,
+    Mean,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Exp,
+    Ln,
+    Sqrt,
+    Abs,
+    Max,
+    Min,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Number(f64),
+    Length,
+    Value,
+    Index,
+    Definition(usize),
+    Neg(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+    Aggregate(Aggregate, Box<Expression>),
+    Quantile(Box<Expression>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Symbol(char),
+}
+
+/// A parsed aggregation expression, evaluate it directly or turn it
+/// into a boxed aggregate operator for the ranking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggrExpression {
+    source: String,
+    definitions: Vec<(String, Expression)>,
+    body: Expression,
+}
+
+// the state of an evaluation: the values, the definitions already computed
+// and the current value and index inside an aggregate
+struct Context<'a> {
+    values: &'a [f64],
+    definitions: Vec<f64>,
+    value: f64,
+    index: f64,
+}
+
+impl fmt::Display for AggrExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &self.source)
+    }
+}
+
+impl AggrExpression {
+    /// parse an expression, the 'expr:' prefix is accepted and ignored
+    pub fn parse(s: &str) -> io::Result<AggrExpression> {
+        let source = s.trim();
+        let source = source
+            .strip_prefix(EXPRESSION_PREFIX)
+            .unwrap_or(source)
+            .trim();
+
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            definitions: Vec::new(),
+            inside_aggregate: 0,
+        };
+
+        // definitions come first: 'name = expression;'
+        while parser.is_definition() {
+            let name = match parser.next() {
+                Some(Token::Identifier(name)) => name,
+                _ => return invalid_expression("expected a name"),
+            };
+            parser.next(); // the '='
+
+            if is_reserved(&name) || parser.definitions.iter().any(|(d, _)| d == &name) {
+                return invalid_expression(&format!("the name '{}' can't be defined", &name));
+            }
+
+            let expression = parser.expression()?;
+            parser.expect(';')?;
+            parser.definitions.push((name, expression));
+        }
+
+        let body = parser.expression()?;
+
+        if let Some(token) = parser.peek() {
+            return invalid_expression(&format!("unexpected {:?} at the end", token));
+        }
+
+        Ok(AggrExpression {
+            source: source.to_string(),
+            definitions: parser.definitions,
+            body,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// the value of the expression on values, an error if it is not a finite number
+    /// (e.g. '1/0', 'ln(0)' or 'sqrt(-1)'), it would break the ranking
+    pub fn evaluate(&self, values: &[f64]) -> io::Result<f64> {
+        let mut context = Context {
+            values,
+            definitions: Vec::with_capacity(self.definitions.len()),
+            value: 0.,
+            index: 0.,
+        };
+
+        for (_, expression) in &self.definitions {
+            let value = evaluate(expression, &mut context);
+            context.definitions.push(value);
+        }
+
+        let result = evaluate(&self.body, &mut context);
+
+        if result.is_finite() {
+            Ok(result)
+        } else {
+            Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "the aggregation expression '{}' gives {} on {:?}",
+                    &self.source, result, values
+                ),
+            ))
+        }
+    }
+
+    /// the expression as an aggregate operator usable by the ranking, a value that is
+    /// not a finite number stops everything
+    pub fn to_aggregator(&self) -> AggrBox {
+        let expression = self.clone();
+
+        Box::new(move |v: Vec<f64>| match expression.evaluate(&v) {
+            Ok(result) => result,
+            Err(e) => {
+                println!("ERROR: {}", &e);
+                std::process::exit(exitcode::DATAERR);
+            }
+        })
+    }
+}
+
+fn invalid_expression<T>(message: &str) -> io::Result<T> {
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!("malformed aggregation expression: {}", message),
+    ))
+}
+
+fn is_reserved(name: &str) -> bool {
+    matches!(name, "n" | "x" | "i") || aggregate(name).is_some() || function(name).is_some()
+}
+
+fn aggregate(name: &str) -> Option<Aggregate> {
+    match name {
+        "sum" => Some(Aggregate::Sum),
+        "prod" => Some(Aggregate::Prod),
+        "max" => Some(Aggregate::Max),
+        "min" => Some(Aggregate::Min),
+        "mean" => Some(Aggregate::Mean),
+        _ => Option::None,
+    }
+}
+
+fn function(name: &str) -> Option<Function> {
+    match name {
+        "exp" => Some(Function::Exp),
+        "ln" => Some(Function::Ln),
+        "sqrt" => Some(Function::Sqrt),
+        "abs" => Some(Function::Abs),
+        "max" => Some(Function::Max),
+        "min" => Some(Function::Min),
+        _ => Option::None,
+    }
+}
+
+fn tokenize(s: &str) -> io::Result<Vec<Token>> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+
+        if c.is_whitespace() {
+            index += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = index;
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                index += 1;
+            }
+
+            // exponent notation: 1e-3
+            if index < chars.len() && (chars[index] == 'e' || chars[index] == 'E') {
+                let mut end = index + 1;
+                if end < chars.len() && (chars[end] == '-' || chars[end] == '+') {
+                    end += 1;
+                }
+                if end < chars.len() && chars[end].is_ascii_digit() {
+                    index = end;
+                    while index < chars.len() && chars[index].is_ascii_digit() {
+                        index += 1;
+                    }
+                }
+            }
+
+            let number: String = chars[start..index].iter().collect();
+            match number.parse::<f64>() {
+                Ok(value) => tokens.push(Token::Number(value)),
+                Err(_) => return invalid_expression(&format!("bad number '{}'", &number)),
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = index;
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+
+            tokens.push(Token::Identifier(chars[start..index].iter().collect()));
+        } else if "+-*/^(),;=".contains(c) {
+            tokens.push(Token::Symbol(c));
+            index += 1;
+        } else {
+            return invalid_expression(&format!("unexpected character '{}'", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+// a recursive descent parser, from the lowest to the highest precedence:
+// sums, products, unary minus, powers (right associative) and atoms
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    definitions: Vec<(String, Expression)>,
+    inside_aggregate: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn is_symbol(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Symbol(c))
+    }
+
+    fn expect(&mut self, c: char) -> io::Result<()> {
+        match self.next() {
+            Some(Token::Symbol(d)) if d == c => Ok(()),
+            Some(token) => invalid_expression(&format!("expected '{}', found {:?}", c, token)),
+            Option::None => invalid_expression(&format!("expected '{}' at the end", c)),
+        }
+    }
+
+    fn is_definition(&self) -> bool {
+        matches!(self.tokens.get(self.position), Some(Token::Identifier(_)))
+            && self.tokens.get(self.position + 1) == Some(&Token::Symbol('='))
+    }
+
+    fn expression(&mut self) -> io::Result<Expression> {
+        let mut left = self.term()?;
+
+        loop {
+            let operator = if self.is_symbol('+') {
+                Operator::Add
+            } else if self.is_symbol('-') {
+                Operator::Sub
+            } else {
+                return Ok(left);
+            };
+            self.next();
+
+            let right = self.term()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn term(&mut self) -> io::Result<Expression> {
+        let mut left = self.unary()?;
+
+        loop {
+            let operator = if self.is_symbol('*') {
+                Operator::Mul
+            } else if self.is_symbol('/') {
+                Operator::Div
+            } else {
+                return Ok(left);
+            };
+            self.next();
+
+            let right = self.unary()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> io::Result<Expression> {
+        if self.is_symbol('-') {
+            self.next();
+            Ok(Expression::Neg(Box::new(self.unary()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> io::Result<Expression> {
+        let base = self.atom()?;
+
+        if self.is_symbol('^') {
+            self.next();
+            let exponent = self.unary()?;
+            Ok(Expression::Binary(
+                Operator::Pow,
+                Box::new(base),
+                Box::new(exponent),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn arguments(&mut self) -> io::Result<Vec<Expression>> {
+        self.expect('(')?;
+        let mut arguments: Vec<Expression> = Vec::new();
+
+        if self.is_symbol(')') {
+            self.next();
+            return Ok(arguments);
+        }
+
+        loop {
+            arguments.push(self.expression()?);
+
+            if self.is_symbol(',') {
+                self.next();
+            } else {
+                self.expect(')')?;
+                return Ok(arguments);
+            }
+        }
+    }
+
+    fn atom(&mut self) -> io::Result<Expression> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::Symbol('(')) => {
+                let expression = self.expression()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some(Token::Identifier(name)) => {
+                if self.is_symbol('(') {
+                    self.call(&name)
+                } else {
+                    self.variable(&name)
+                }
+            }
+            Some(token) => invalid_expression(&format!("unexpected {:?}", token)),
+            Option::None => invalid_expression("unexpected end"),
+        }
+    }
+
+    fn variable(&self, name: &str) -> io::Result<Expression> {
+        match name {
+            "n" => Ok(Expression::Length),
+            "x" | "i" if self.inside_aggregate == 0 => invalid_expression(&format!(
+                "'{}' can only be used inside sum, prod, max, min or mean",
+                name
+            )),
+            "x" => Ok(Expression::Value),
+            "i" => Ok(Expression::Index),
+            _ => match self.definitions.iter().position(|(d, _)| d == name) {
+                Some(index) => Ok(Expression::Definition(index)),
+                Option::None => invalid_expression(&format!("unknown name '{}'", name)),
+            },
+        }
+    }
+
+    fn call(&mut self, name: &str) -> io::Result<Expression> {
+        // max and min with one argument aggregate the values, with more they
+        // are the usual functions, the argument of an aggregate can use x and i
+        let is_aggregate = aggregate(name).is_some() && self.one_argument();
+
+        if is_aggregate {
+            self.inside_aggregate += 1;
+        }
+        let arguments = self.arguments();
+        if is_aggregate {
+            self.inside_aggregate -= 1;
+        }
+        let mut arguments = arguments?;
+
+        if let (true, Some(aggregate), 1) = (is_aggregate, aggregate(name), arguments.len()) {
+            return Ok(Expression::Aggregate(
+                aggregate,
+                Box::new(arguments.remove(0)),
+            ));
+        }
+
+        let accepted = match (name, function(name)) {
+            ("quantile", _) => arguments.len() == 1,
+            (_, Some(Function::Max)) | (_, Some(Function::Min)) => arguments.len() >= 2,
+            (_, Some(_)) => arguments.len() == 1,
+            (_, Option::None) if aggregate(name).is_some() => false,
+            (_, Option::None) => {
+                return invalid_expression(&format!("unknown function '{}'", name))
+            }
+        };
+
+        match (accepted, name, function(name)) {
+            (false, _, _) => invalid_expression(&format!(
+                "'{}' doesn't take {} arguments",
+                name,
+                arguments.len()
+            )),
+            (true, "quantile", _) => Ok(Expression::Quantile(Box::new(arguments.remove(0)))),
+            (true, _, f) => Ok(Expression::Call(f.unwrap(), arguments)),
+        }
+    }
+
+    // look ahead: is the call at the current position made with exactly one argument
+    fn one_argument(&self) -> bool {
+        let mut depth = 0;
+
+        for token in &self.tokens[self.position..] {
+            match token {
+                Token::Symbol('(') => depth += 1,
+                Token::Symbol(')') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return true;
+                    }
+                }
+                Token::Symbol(',') if depth == 1 => return false,
+                _ => (),
+            }
+        }
+
+        true
+    }
+}
+
+fn evaluate(expression: &Expression, context: &mut Context) -> f64 {
+    match expression {
+        Expression::Number(value) => *value,
+        Expression::Length => context.values.len() as f64,
+        Expression::Value => context.value,
+        Expression::Index => context.index,
+        Expression::Definition(index) => context.definitions[*index],
+        Expression::Neg(e) => -evaluate(e, context),
+        Expression::Binary(operator, left, right) => {
+            let left = evaluate(left, context);
+            let right = evaluate(right, context);
+
+            match operator {
+                Operator::Add => left + right,
+                Operator::Sub => left - right,
+                Operator::Mul => left * right,
+                Operator::Div => left / right,
+                Operator::Pow => left.powf(right),
+            }
+        }
+        Expression::Call(f, arguments) => {
+            let arguments: Vec<f64> = arguments.iter().map(|e| evaluate(e, context)).collect();
+
+            match f {
+                Function::Exp => arguments[0].exp(),
+                Function::Ln => arguments[0].ln(),
+                Function::Sqrt => arguments[0].sqrt(),
+                Function::Abs => arguments[0].abs(),
+                Function::Max => arguments.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+                Function::Min => arguments.iter().cloned().fold(f64::INFINITY, f64::min),
+            }
+        }
+        Expression::Aggregate(aggregate, e) => {
+            // keep the outer value and index, aggregates can be nested
+            let (outer_value, outer_index) = (context.value, context.index);
+            let values = context.values;
+            let mut results: Vec<f64> = Vec::with_capacity(values.len());
+
+            for (index, value) in values.iter().enumerate() {
+                context.value = *value;
+                context.index = index as f64;
+                results.push(evaluate(e, context));
+            }
+
+            context.value = outer_value;
+            context.index = outer_index;
+
+            // as the usual aggregates an empty subset gives 0 (1 for the product)
+            match (aggregate, results.len()) {
+                (Aggregate::Prod, _) => results.iter().product::<f64>(),
+                (_, 0) => 0.,
+                (Aggregate::Sum, _) => results.iter().sum::<f64>(),
+                (Aggregate::Mean, l) => results.iter().sum::<f64>() / (l as f64),
+                (Aggregate::Max, _) => results.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+                (Aggregate::Min, _) => results.iter().cloned().fold(f64::INFINITY, f64::min),
+            }
+        }
+        Expression::Quantile(q) => {
+            let q = evaluate(q, context).clamp(0., 1.);
+            let mut values = context.values.to_vec();
+
+            if values.is_empty() {
+                return 0.;
+            }
+
+            // linear interpolation between the closest ranks
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let position = q * ((values.len() - 1) as f64);
+            let lower = position.floor() as usize;
+            let upper = position.ceil() as usize;
+
+            values[lower] + (values[upper] - values[lower]) * (position - lower as f64)
+        }
+    }
+}
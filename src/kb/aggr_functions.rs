@@ -23,7 +23,9 @@ along with this program.  If not, see https://www.gnu.org/licenses/.
 /// - max
 /// - min
 /// - avg (arithmetic mean)
-/// - count (cardinality).
+/// - count (cardinality)
+/// - noisy-or (probability that at least one of independent events happens,
+///   values are clamped to [0, 1]).
 extern crate itertools;
 
 use crate::kb::knowledge_base::AggrFn;
@@ -61,8 +63,13 @@ fn count(v: Vec<f64>) -> f64 {
     v.len() as f64
 }
 
+fn noisy_or(v: Vec<f64>) -> f64 {
+    1. - v.iter().map(|x| 1. - x.clamp(0., 1.)).product::<f64>()
+}
+
 pub const AGGR_SUM: AggrFn = sum;
 pub const AGGR_MAX: AggrFn = max;
 pub const AGGR_MIN: AggrFn = min;
 pub const AGGR_MEAN: AggrFn = arith_mean;
 pub const AGGR_COUNT: AggrFn = count;
+pub const AGGR_NOISY_OR: AggrFn = noisy_or;
@@ -36,8 +36,8 @@ use std::cmp::Ordering;
 /// each Item has a type defined by the DLType defined in the 'types.rs' file of this module.
 pub trait Item: PartialOrd + Ord + PartialEq + Eq + Debug + Hash + Display + Sized {
     fn t(&self) -> DLType;
-    fn base(node: &Self) -> &Self;
-    fn child(node: Option<&Self>, depth: usize) -> Option<Vec<&Self>>;
+    fn base(node: &Self) -> Self;
+    fn child(node: Option<&Self>, depth: usize) -> Option<Vec<Self>>;
     fn is_negated(&self) -> bool;
 }
 
@@ -192,6 +192,10 @@ pub trait TBox: PartialEq + Debug + Display {
 /// Note how 'Bottom' and 'Top' are automatically added.
 pub type SymbolDict = HashMap<String, (usize, DLType)>;
 
+/// The trust put in each source of assertions, a value in [0, 1]
+/// (e.g. 'registry' : 0.9, 'forum' : 0.3).
+pub type SourceDict = HashMap<String, f64>;
+
 /// Deduction rules are defined as functions that take the corresponding
 /// arguments.
 /// - TBox deduction rules take a variable number of TBox items as argument and
@@ -207,3 +211,8 @@ pub type AbRule<T, A> = fn(&[&A], &[&T], bool) -> Option<Vec<A>>;
 /// following each aggregation
 /// (e.g. Sum([1,2,3,1]) = 1 + 2 + 3 + 1 = 7).
 pub type AggrFn = fn(Vec<f64>) -> f64;
+
+/// Aggregate operators that are not plain functions: closures built at run time
+/// (e.g. from an aggregation expression) or operators keeping some state between
+/// calls. The ranking accepts both, an AggrFn converts with 'Box::new'.
+pub type AggrBox = Box<dyn FnMut(Vec<f64>) -> f64>;
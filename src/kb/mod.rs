@@ -23,6 +23,7 @@ along with this program.  If not, see https://www.gnu.org/licenses/.
 //! done by traits.
 //! Items, TBoxes, ABoxes, every one is implemented by a trait.
 
+pub mod aggr_expression;
 pub mod aggr_functions;
 pub mod knowledge_base;
 pub mod types;
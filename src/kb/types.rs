@@ -169,6 +169,23 @@ impl CR {
         }
     }
 
+    /// inverse of 'to_usize', None if n is not the number of a rule
+    pub fn from_usize(n: usize) -> Option<CR> {
+        match n {
+            1 => Some(CR::First),
+            2 => Some(CR::Second),
+            3 => Some(CR::Third),
+            4 => Some(CR::Fourth),
+            5 => Some(CR::Fifth),
+            6 => Some(CR::Sixth),
+            7 => Some(CR::Seventh),
+            8 => Some(CR::Eight),
+            9 => Some(CR::Ninth),
+            10 => Some(CR::Tenth),
+            _ => Option::None,
+        }
+    }
+
     // TODO: come back here to repairs the rules !!
     /// Pretty printer for rules, the 'for_tbi' bool says
     /// if rules is to be formatted for TBox rules or ABox rules
@@ -211,7 +228,8 @@ impl CR {
                     if for_tbi {
                         String::from("r < s, B < NOT EXISTS s^- -> X < NOT EXISTS r^-")
                     } else {
-                        String::from("R4: NONE")
+                        // if a:Er and no b with (a,b):r then (a,n):r, n a labelled null
+                        format!("A{}: a: Exists r -> (a,n): r, n new", self.identifier())
                     }
                 }
                 CR::Fifth => {
@@ -355,7 +373,13 @@ impl CR {
                             UNICODE_NEG
                         )
                     } else {
-                        String::from("AR4: NONE")
+                        // if a:Er and no b with (a,b):r then (a,n):r, n a labelled null
+                        format!(
+                            "A{}: a:{}r {} (a,n):r, n new",
+                            self.identifier(),
+                            UNICODE_EXISTS,
+                            UNICODE_RIGHTARROW,
+                        )
                     }
                 }
                 CR::Fifth => {
@@ -521,6 +545,7 @@ impl Display for CR {
 pub enum FileType {
     Json,
     Native,
+    Sqlite,
     // Xml to come
 }
 
@@ -0,0 +1,354 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   The 'lint' task: what a hand written tbox accumulates and the parser says nothing
+   about, each finding is about a line of a file:
+       - axioms written twice, axioms entailed by the others (the closure of the tbox
+         without them has them)
+       - trivial ('X < Top') and redundant ('X < X') axioms
+       - symbols declared and never used, symbols used and never declared
+       - cycles of inclusions, their members are all equivalent
+       - lines the parser ignores
+*/
+
+use std::collections::HashMap;
+use std::fmt;
+
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::NodeIndex;
+use petgraph::Graph;
+
+use crate::dl_lite::native_filetype_utilities::native_lines;
+use crate::dl_lite::node::ItemDllite;
+use crate::dl_lite::string_formatter::{
+    node_to_string, string_to_symbol, string_to_tbi, tbi_to_string,
+};
+use crate::dl_lite::tbox::TBDllite;
+use crate::dl_lite::tbox_item::TbiDllite;
+use crate::dl_lite::trust::split_source_tags;
+use crate::kb::knowledge_base::{SymbolDict, TBox, TBoxItem};
+use crate::kb::types::DLType;
+
+// the name of a file and its text
+pub type NamedText<'a> = (&'a str, &'a str);
+
+// words of the tbox that are not symbols
+const KEYWORDS: [&str; 3] = ["NOT", "INV", "EXISTS"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintKind {
+    Duplicate,
+    Entailed,
+    Trivial,
+    Redundant,
+    Unused,
+    Undeclared,
+    Cycle,
+    NotParsed,
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LintKind::Duplicate => "duplicate",
+            LintKind::Entailed => "entailed",
+            LintKind::Trivial => "trivial",
+            LintKind::Redundant => "redundant",
+            LintKind::Unused => "unused",
+            LintKind::Undeclared => "undeclared",
+            LintKind::Cycle => "cycle",
+            LintKind::NotParsed => "not parsed",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Something the linter found, lines are counted from 0 and written from 1.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    file: String,
+    line: usize,
+    kind: LintKind,
+    message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            &self.file,
+            self.line + 1,
+            self.kind,
+            &self.message
+        )
+    }
+}
+
+impl Finding {
+    fn new(file: &str, line: usize, kind: LintKind, message: String) -> Finding {
+        Finding {
+            file: file.to_string(),
+            line,
+            kind,
+            message,
+        }
+    }
+}
+
+/// Lints a tbox and the aboxes that go with it, 'symbols' are the ones the ontology
+/// parsed from the file 'declarations'. Findings come by file and line.
+pub fn lint_native(
+    tbox: NamedText,
+    declarations: NamedText,
+    aboxes: &[NamedText],
+    symbols: &SymbolDict,
+    verbose: bool,
+) -> Vec<Finding> {
+    let (tbox_name, tbox_text) = tbox;
+    let mut findings: Vec<Finding> = Vec::new();
+
+    // every axiom with its line, the lines of an equivalence give two
+    let mut asserted: Vec<(TbiDllite, usize)> = Vec::new();
+    let mut used: Vec<&str> = Vec::new();
+
+    for (number, block, content, _) in native_lines(tbox_text) {
+        if block != Some("TBOX") || content.trim().is_empty() {
+            continue;
+        }
+
+        let words: Vec<&str> = content
+            .split(|c: char| c.is_whitespace() || c == '<' || c == '=')
+            .filter(|word| !word.is_empty() && !KEYWORDS.contains(word))
+            .collect();
+        used.extend(words.iter());
+
+        match string_to_tbi(content, symbols) {
+            Ok(mut tbis) => {
+                while let Some(tbi) = tbis.pop() {
+                    asserted.push((tbi, number));
+                }
+            }
+            Err(e) => {
+                let undeclared: Vec<&&str> = words
+                    .iter()
+                    .filter(|word| !symbols.contains_key(**word))
+                    .collect();
+
+                for word in &undeclared {
+                    findings.push(Finding::new(
+                        tbox_name,
+                        number,
+                        LintKind::Undeclared,
+                        format!("'{}' is used but not declared", word),
+                    ));
+                }
+
+                if undeclared.is_empty() {
+                    findings.push(Finding::new(
+                        tbox_name,
+                        number,
+                        LintKind::NotParsed,
+                        format!("the parser ignores this line: {}", &e),
+                    ));
+                }
+            }
+        }
+    }
+
+    for (abox_name, abox_text) in aboxes {
+        for (number, block, content, _) in native_lines(abox_text) {
+            let (content, _) = split_source_tags(content);
+
+            // the concept or role is after the ':'
+            let symbol = match (block, content.split(':').nth(1)) {
+                (Some("ABOX"), Some(rest)) => rest.split(',').next().unwrap_or("").trim(),
+                _ => continue,
+            };
+
+            if symbols.contains_key(symbol) {
+                used.push(symbol);
+            } else if !symbol.is_empty() {
+                findings.push(Finding::new(
+                    abox_name,
+                    number,
+                    LintKind::Undeclared,
+                    format!("'{}' is used but not declared", symbol),
+                ));
+            }
+        }
+    }
+
+    findings.extend(lint_axioms(tbox_name, &asserted, symbols, verbose));
+    findings.extend(lint_cycles(tbox_name, &asserted, symbols));
+
+    // declared concepts and roles nobody uses
+    let (declarations_name, declarations_text) = declarations;
+
+    for (number, block, content, _) in native_lines(declarations_text) {
+        if block != Some("SYMBOL") {
+            continue;
+        }
+
+        if let Ok((name, t)) = string_to_symbol(content) {
+            if t != DLType::Nominal && !used.contains(&name) {
+                findings.push(Finding::new(
+                    declarations_name,
+                    number,
+                    LintKind::Unused,
+                    format!("'{}' is declared but never used", name),
+                ));
+            }
+        }
+    }
+
+    findings.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    findings
+}
+
+// duplicates, trivial, redundant and entailed axioms
+fn lint_axioms(
+    file: &str,
+    asserted: &[(TbiDllite, usize)],
+    symbols: &SymbolDict,
+    verbose: bool,
+) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut first_lines: HashMap<&TbiDllite, usize> = HashMap::new();
+    let mut tbox = TBDllite::new();
+
+    for (tbi, number) in asserted {
+        let tbi_string = tbi_to_string(tbi, symbols).unwrap_or_default();
+
+        if tbi.is_trivial() {
+            findings.push(Finding::new(
+                file,
+                *number,
+                LintKind::Trivial,
+                format!("'{}' always holds", tbi_string),
+            ));
+        } else if tbi.is_redundant() {
+            findings.push(Finding::new(
+                file,
+                *number,
+                LintKind::Redundant,
+                format!("'{}' says nothing", tbi_string),
+            ));
+        } else if let Some(first) = first_lines.get(tbi) {
+            findings.push(Finding::new(
+                file,
+                *number,
+                LintKind::Duplicate,
+                format!("'{}' is already at line {}", tbi_string, first + 1),
+            ));
+        } else {
+            first_lines.insert(tbi, *number);
+            tbox.add(tbi.clone());
+        }
+    }
+
+    // each axiom against the closure of the others
+    for tbi in tbox.items() {
+        let mut others = tbox.clone();
+        others.remove(tbi);
+
+        if others.entails(tbi, verbose) {
+            findings.push(Finding::new(
+                file,
+                first_lines[tbi],
+                LintKind::Entailed,
+                format!(
+                    "'{}' is entailed by the other axioms",
+                    tbi_to_string(tbi, symbols).unwrap_or_default()
+                ),
+            ));
+        }
+    }
+
+    findings
+}
+
+// strongly connected components of the positive inclusions, an equivalence alone on its
+// line is what its author meant
+fn lint_cycles(file: &str, asserted: &[(TbiDllite, usize)], symbols: &SymbolDict) -> Vec<Finding> {
+    let mut graph: Graph<ItemDllite, usize> = Graph::new();
+    let mut indexes: HashMap<ItemDllite, NodeIndex> = HashMap::new();
+
+    for (tbi, number) in asserted {
+        if tbi.is_negative_inclusion() || tbi.is_trivial() || tbi.is_redundant() {
+            continue;
+        }
+
+        let mut index_of = |node: &ItemDllite| {
+            *indexes
+                .entry(*node)
+                .or_insert_with(|| graph.add_node(*node))
+        };
+        let (lside, rside) = (index_of(tbi.lside()), index_of(tbi.rside()));
+
+        graph.add_edge(lside, rside, *number);
+    }
+
+    let mut findings: Vec<Finding> = Vec::new();
+
+    for component in tarjan_scc(&graph) {
+        if component.len() < 2 {
+            continue;
+        }
+
+        let mut lines: Vec<usize> = graph
+            .edge_indices()
+            .filter(|edge| {
+                let (source, target) = graph.edge_endpoints(*edge).unwrap();
+                component.contains(&source) && component.contains(&target)
+            })
+            .map(|edge| graph[edge])
+            .collect();
+        lines.sort_unstable();
+        lines.dedup();
+
+        if lines.len() < 2 {
+            continue;
+        }
+
+        let mut names: Vec<String> = component
+            .iter()
+            .filter_map(|index| node_to_string(&graph[*index], symbols, String::new()))
+            .map(|name| format!("'{}'", name))
+            .collect();
+        names.sort();
+
+        let lines_string: Vec<String> = lines.iter().map(|line| (line + 1).to_string()).collect();
+
+        findings.push(Finding::new(
+            file,
+            lines[0],
+            LintKind::Cycle,
+            format!(
+                "{} are equivalent through a cycle of inclusions (lines {})",
+                names.join(", "),
+                lines_string.join(", ")
+            ),
+        ));
+    }
+
+    findings
+}
@@ -0,0 +1,1005 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   A language server for the native files (BEGINSYMBOL, BEGINTBOX, BEGINABOX blocks),
+   over stdin and stdout. Each line is given to the parser of 'string_formatter.rs',
+   when it refuses a line the line is looked at again to say where and why:
+       - symbols used but not declared
+       - a role used as a concept (or the other way around), a nominal in a tbox
+       - a negation on the left side of an inclusion
+   Go to definition goes to the declaration of a symbol, hover shows the super-concepts
+   derived by the closure, completion gives the symbols and the keywords.
+   The symbols of a file are the ones it declares, a file without a 'BEGINSYMBOL'
+   block (an abox for example) uses the ones of the other open files, the same goes
+   for the tbox used by hover.
+   Nothing is written on stdout but the protocol.
+*/
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest, Request as RequestTrait};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents,
+    HoverParams, HoverProviderCapability, Location, MarkupContent, MarkupKind, OneOf, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::dl_lite::native_filetype_utilities::find_bound_of_symbols;
+use crate::dl_lite::node::ItemDllite;
+use crate::dl_lite::string_formatter::{
+    node_to_string, string_to_abiq, string_to_node, string_to_symbol, string_to_tbi, PS,
+};
+use crate::dl_lite::tbox::TBDllite;
+use crate::dl_lite::trust::source_tags_start;
+use crate::kb::knowledge_base::{Item, SymbolDict, TBox, TBoxItem};
+use crate::kb::types::DLType;
+
+type LspResult<T> = Result<T, Box<dyn Error + Sync + Send>>;
+
+const SOURCE: &str = "rustoner";
+
+// modifiers of the tbox, completed with the symbols
+const KEYWORDS: [&str; 3] = ["NOT", "INV", "EXISTS"];
+
+// a word ends at one of these
+const SEPARATORS: [char; 4] = [',', ':', '<', '='];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Block {
+    Symbols,
+    TBox,
+    ABox,
+    Sources,
+}
+
+impl Block {
+    fn from_marker(marker: &str) -> Option<(Block, bool)> {
+        match marker {
+            "BEGINSYMBOL" => Some((Block::Symbols, true)),
+            "ENDSYMBOL" => Some((Block::Symbols, false)),
+            "BEGINTBOX" => Some((Block::TBox, true)),
+            "ENDTBOX" => Some((Block::TBox, false)),
+            "BEGINABOX" => Some((Block::ABox, true)),
+            "ENDABOX" => Some((Block::ABox, false)),
+            "BEGINSOURCES" => Some((Block::Sources, true)),
+            "ENDSOURCES" => Some((Block::Sources, false)),
+            _ => Option::None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Block::Symbols => "SYMBOL",
+            Block::TBox => "TBOX",
+            Block::ABox => "ABOX",
+            Block::Sources => "SOURCES",
+        }
+    }
+}
+
+/// A line of a file without its comment, 'block' is the block it is in.
+struct Line<'a> {
+    number: usize,
+    text: &'a str,
+    content: &'a str,
+    block: Option<Block>,
+}
+
+/// Where a symbol is declared, columns are byte offsets in the line.
+#[derive(Debug, Clone)]
+struct Declaration {
+    name: String,
+    t: DLType,
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+// a problem in a line, (start, end, severity, message), offsets in bytes
+type Problem = (usize, usize, DiagnosticSeverity, String);
+
+/// An open file, the text is the last one sent by the editor.
+struct Document {
+    text: String,
+}
+
+impl Document {
+    /// The lines of the file and the problems of the blocks themselves: not closed,
+    /// closed without being opened, lines out of every block.
+    fn lines(&self) -> (Vec<Line<'_>>, Vec<(usize, Problem)>) {
+        let mut lines: Vec<Line> = Vec::new();
+        let mut problems: Vec<(usize, Problem)> = Vec::new();
+        let mut current: Option<(Block, usize)> = Option::None;
+
+        for (number, text) in self.text.lines().enumerate() {
+            let content = match text.find("//") {
+                Some(index) => &text[..index],
+                Option::None => text,
+            };
+            let (start, end) = trimmed(0, content);
+
+            match (Block::from_marker(content.trim()), current) {
+                (Some((block, true)), _) => {
+                    if let Some((open, open_line)) = current {
+                        problems.push((open_line, unclosed(open, lines[open_line].text)));
+                    }
+                    current = Some((block, number));
+                }
+                (Some((block, false)), Some((open, _))) if block == open => current = Option::None,
+                (Some((block, false)), _) => problems.push((
+                    number,
+                    (
+                        start,
+                        end,
+                        DiagnosticSeverity::ERROR,
+                        format!("'END{}' without 'BEGIN{}'", block.name(), block.name()),
+                    ),
+                )),
+                (Option::None, Option::None) if start < end => problems.push((
+                    number,
+                    (
+                        start,
+                        end,
+                        DiagnosticSeverity::WARNING,
+                        String::from("this line is out of every block, it is ignored"),
+                    ),
+                )),
+                (Option::None, _) => (),
+            }
+
+            let block = match Block::from_marker(content.trim()) {
+                Some(_) => Option::None,
+                Option::None => current.map(|(block, _)| block),
+            };
+
+            lines.push(Line {
+                number,
+                text,
+                content,
+                block,
+            });
+        }
+
+        if let Some((open, open_line)) = current {
+            problems.push((open_line, unclosed(open, lines[open_line].text)));
+        }
+
+        (lines, problems)
+    }
+
+    fn declares_symbols(&self) -> bool {
+        self.lines()
+            .0
+            .iter()
+            .any(|line| line.content.trim() == "BEGINSYMBOL")
+    }
+
+    fn declarations(&self) -> Vec<Declaration> {
+        self.lines()
+            .0
+            .iter()
+            .filter(|line| line.block == Some(Block::Symbols))
+            .filter_map(|line| match string_to_symbol(line.content) {
+                Ok((name, t)) => {
+                    let start = line.content.find(':').unwrap() + 1;
+                    let start = start + line.content[start..].find(name).unwrap_or(0);
+
+                    Some(Declaration {
+                        name: name.to_string(),
+                        t,
+                        line: line.number,
+                        start,
+                        end: start + name.len(),
+                    })
+                }
+                Err(_) => Option::None,
+            })
+            .collect()
+    }
+
+    /// The word under the position, with its offsets.
+    fn word_at(&self, position: &Position) -> Option<(usize, usize, &str)> {
+        let text = self.text.lines().nth(position.line as usize)?;
+        let offset = byte_offset(text, position.character);
+
+        words(text)
+            .into_iter()
+            .find(|(start, word)| *start <= offset && offset <= start + word.len())
+            .map(|(start, word)| (start, start + word.len(), word))
+    }
+}
+
+/// The open files, and what is asked about them.
+struct State {
+    documents: HashMap<Url, Document>,
+}
+
+impl State {
+    /// The files whose symbols and tbox are used for 'uri'.
+    fn context<'a>(&'a self, uri: &'a Url) -> Vec<&'a Url> {
+        match self.documents.get(uri) {
+            Some(document) if document.declares_symbols() => vec![uri],
+            _ => {
+                let mut others: Vec<&Url> = self
+                    .documents
+                    .iter()
+                    .filter(|(other, document)| *other != uri && document.declares_symbols())
+                    .map(|(other, _)| other)
+                    .collect();
+                others.sort();
+                others
+            }
+        }
+    }
+
+    fn declarations(&self, uri: &Url) -> Vec<(Url, Declaration)> {
+        self.context(uri)
+            .into_iter()
+            .flat_map(|other| {
+                self.documents[other]
+                    .declarations()
+                    .into_iter()
+                    .map(move |declaration| (other.clone(), declaration))
+            })
+            .collect()
+    }
+
+    /// The symbols as 'parse_symbols_native' would give them, the first declaration
+    /// of a name counts.
+    fn symbols(&self, uri: &Url) -> SymbolDict {
+        let mut unsorted_symbols: Vec<PS> = Vec::new();
+
+        for (_, declaration) in self.declarations(uri) {
+            if !unsorted_symbols
+                .iter()
+                .any(|ps| ps.name() == declaration.name)
+            {
+                unsorted_symbols.push(PS::new(declaration.name, declaration.t));
+            }
+        }
+        unsorted_symbols.sort();
+
+        let mut symbols: SymbolDict = HashMap::new();
+        symbols.insert(String::from("Bottom"), (0, DLType::Bottom));
+        symbols.insert(String::from("Top"), (1, DLType::Top));
+
+        for (i, ps) in unsorted_symbols.iter().enumerate() {
+            symbols.insert(String::from(ps.name()), (i + 2, ps.t()));
+        }
+
+        symbols
+    }
+
+    fn tbox(&self, uri: &Url, symbols: &SymbolDict) -> TBDllite {
+        let mut tbox = TBDllite::new();
+
+        for other in self.context(uri) {
+            for line in self.documents[other].lines().0 {
+                if line.block == Some(Block::TBox) {
+                    if let Ok(tbis) = string_to_tbi(line.content, symbols) {
+                        for tbi in tbis {
+                            tbox.add(tbi);
+                        }
+                    }
+                }
+            }
+        }
+
+        tbox
+    }
+
+    fn diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let document = &self.documents[uri];
+        let symbols = self.symbols(uri);
+        let (lines, mut problems) = document.lines();
+
+        // the first declaration of a name counts
+        let mut declared: HashMap<String, usize> = HashMap::new();
+
+        for declaration in document.declarations() {
+            match declared.get(&declaration.name) {
+                Some(first) => problems.push((
+                    declaration.line,
+                    (
+                        declaration.start,
+                        declaration.end,
+                        DiagnosticSeverity::WARNING,
+                        format!(
+                            "'{}' is already declared at line {}",
+                            &declaration.name,
+                            first + 1
+                        ),
+                    ),
+                )),
+                Option::None => {
+                    declared.insert(declaration.name.clone(), declaration.line);
+                }
+            }
+        }
+
+        for line in &lines {
+            let found = match line.block {
+                Some(Block::Symbols) => symbol_problems(line.content),
+                Some(Block::TBox) => tbi_problems(line.content, &symbols),
+                Some(Block::ABox) => abi_problems(line.content, &symbols),
+                _ => Vec::new(),
+            };
+
+            problems.extend(found.into_iter().map(|problem| (line.number, problem)));
+        }
+
+        problems
+            .into_iter()
+            .map(|(number, (start, end, severity, message))| {
+                let text = lines[number].text;
+
+                Diagnostic {
+                    range: Range::new(
+                        Position::new(number as u32, column(text, start)),
+                        Position::new(number as u32, column(text, end)),
+                    ),
+                    severity: Some(severity),
+                    source: Some(SOURCE.to_string()),
+                    message,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    fn definition(&self, uri: &Url, position: &Position) -> Option<Location> {
+        let (_, _, word) = self.documents.get(uri)?.word_at(position)?;
+
+        self.declarations(uri)
+            .into_iter()
+            .find(|(_, declaration)| declaration.name == word)
+            .map(|(other, declaration)| {
+                let text = self.documents[&other]
+                    .text
+                    .lines()
+                    .nth(declaration.line)
+                    .unwrap_or("");
+                let line = declaration.line as u32;
+
+                Location::new(
+                    other,
+                    Range::new(
+                        Position::new(line, column(text, declaration.start)),
+                        Position::new(line, column(text, declaration.end)),
+                    ),
+                )
+            })
+    }
+
+    fn hover(&self, uri: &Url, position: &Position) -> Option<Hover> {
+        let document = self.documents.get(uri)?;
+        let (start, end, word) = document.word_at(position)?;
+        let symbols = self.symbols(uri);
+        let (id, t) = *symbols.get(word)?;
+
+        // a role is described by its domain and its range
+        let base = ItemDllite::new(Some(id), t)?;
+        let (kind, nodes) = match t {
+            DLType::BaseConcept => ("concept", vec![base]),
+            DLType::BaseRole => (
+                "role",
+                vec![
+                    base,
+                    base.exists()?,
+                    base.inverse().and_then(|inverse| inverse.exists())?,
+                ],
+            ),
+            _ => return Option::None,
+        };
+
+        let closure = self.tbox(uri, &symbols).cln_completion(false, false, false);
+        let mut value = format!("{} **{}**\n", kind, word);
+
+        for node in nodes {
+            let name = node_to_string(&node, &symbols, String::new())?;
+            let (supers, disjoints) = super_concepts(&closure, &node, &symbols);
+
+            let label = if node.t().is_role_type() {
+                "super-roles"
+            } else {
+                "super-concepts"
+            };
+
+            value.push_str(&format!("\n`{}`", &name));
+            value.push_str(&format!("\n- {}: {}", label, listed(&supers)));
+
+            if !disjoints.is_empty() {
+                value.push_str(&format!("\n- disjoint with: {}", listed(&disjoints)));
+            }
+            value.push('\n');
+        }
+
+        let line = position.line;
+        let text = document.text.lines().nth(line as usize)?;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(Range::new(
+                Position::new(line, column(text, start)),
+                Position::new(line, column(text, end)),
+            )),
+        })
+    }
+
+    fn completion(&self, uri: &Url, position: &Position) -> Vec<CompletionItem> {
+        let prefix = match self.documents.get(uri).and_then(|d| d.word_at(position)) {
+            Some((start, _, word)) => {
+                let text = self.documents[uri]
+                    .text
+                    .lines()
+                    .nth(position.line as usize)
+                    .unwrap_or("");
+                let end = byte_offset(text, position.character);
+
+                word[..(end - start).min(word.len())].to_string()
+            }
+            Option::None => String::new(),
+        };
+
+        let mut items: Vec<CompletionItem> = Vec::new();
+
+        for (_, declaration) in self.declarations(uri) {
+            let kind = match declaration.t {
+                DLType::BaseConcept => CompletionItemKind::CLASS,
+                DLType::BaseRole => CompletionItemKind::PROPERTY,
+                _ => CompletionItemKind::CONSTANT,
+            };
+
+            if declaration.name.starts_with(&prefix)
+                && !items.iter().any(|item| item.label == declaration.name)
+            {
+                items.push(CompletionItem {
+                    label: declaration.name,
+                    kind: Some(kind),
+                    ..Default::default()
+                });
+            }
+        }
+
+        for keyword in KEYWORDS.iter().chain(["Top", "Bottom"].iter()) {
+            if keyword.starts_with(&prefix) {
+                items.push(CompletionItem {
+                    label: keyword.to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    ..Default::default()
+                });
+            }
+        }
+
+        items
+    }
+}
+
+// =================================================================================================
+// THE PROBLEMS OF A LINE
+
+fn symbol_problems(content: &str) -> Vec<Problem> {
+    let (start, end) = trimmed(0, content);
+
+    if start == end {
+        return Vec::new();
+    }
+
+    match string_to_symbol(content) {
+        Ok(("", _)) => vec![(
+            start,
+            end,
+            DiagnosticSeverity::ERROR,
+            String::from("the symbol has no name"),
+        )],
+        Ok(_) => Vec::new(),
+        Err(e) => vec![(start, end, DiagnosticSeverity::ERROR, e.to_string())],
+    }
+}
+
+// the symbols of a side or an assertion are declared and are not nominals
+fn undeclared(content: &str, offset: usize, symbols: &SymbolDict) -> Vec<Problem> {
+    words(content)
+        .into_iter()
+        .filter(|(_, word)| !KEYWORDS.contains(word))
+        .filter_map(|(start, word)| {
+            let message = match symbols.get(word) {
+                Option::None => format!("undeclared symbol '{}'", word),
+                Some((_, DLType::Nominal)) => {
+                    format!(
+                        "'{}' is a nominal, a tbox only has concepts and roles",
+                        word
+                    )
+                }
+                Some(_) => return Option::None,
+            };
+
+            Some((
+                offset + start,
+                offset + start + word.len(),
+                DiagnosticSeverity::ERROR,
+                message,
+            ))
+        })
+        .collect()
+}
+
+fn tbi_problems(content: &str, symbols: &SymbolDict) -> Vec<Problem> {
+    let (start, end) = trimmed(0, content);
+
+    if start == end {
+        return Vec::new();
+    }
+
+    let problems = undeclared(content, 0, symbols);
+
+    if !problems.is_empty() {
+        return problems;
+    }
+
+    let error = match string_to_tbi(content, symbols) {
+        Ok(_) => return Vec::new(),
+        Err(e) => e.to_string(),
+    };
+
+    let separator = match (content.find('<'), content.find('=')) {
+        (Some(index), Option::None) | (Option::None, Some(index))
+            if content.matches(['<', '=']).count() == 1 =>
+        {
+            index
+        }
+        _ => {
+            return vec![(
+                start,
+                end,
+                DiagnosticSeverity::ERROR,
+                String::from("an axiom is 'B1 < B2' or 'B1 = B2'"),
+            )]
+        }
+    };
+    let equivalence = content[separator..].starts_with('=');
+
+    let sides = [
+        (0, &content[..separator]),
+        (separator + 1, &content[separator + 1..]),
+    ];
+    let mut nodes: Vec<(usize, usize, ItemDllite)> = Vec::new();
+
+    for (offset, side) in sides.iter() {
+        let (side_start, side_end) = trimmed(*offset, side);
+
+        match string_to_node(side, symbols) {
+            Ok(node) => nodes.push((side_start, side_end, node)),
+            Err(e) => {
+                // EXISTS and INV need a role
+                let base = words(side).last().map(|(_, word)| *word).unwrap_or("");
+                let is_concept = matches!(symbols.get(base), Some((_, DLType::BaseConcept)));
+                let message = if is_concept && (side.contains("EXISTS") || side.contains("INV")) {
+                    format!(
+                        "a concept used as a role: '{}' is a concept, EXISTS and INV take a role",
+                        base
+                    )
+                } else {
+                    e.to_string()
+                };
+
+                return vec![(side_start, side_end, DiagnosticSeverity::ERROR, message)];
+            }
+        }
+    }
+
+    let (lstart, lend, lside) = nodes[0];
+    let (rstart, rend, rside) = nodes[1];
+
+    if lside.is_negated() {
+        return vec![(
+            lstart,
+            lend,
+            DiagnosticSeverity::ERROR,
+            String::from("negation on the left side, only the right side can be negated"),
+        )];
+    }
+
+    if equivalence && rside.is_negated() {
+        return vec![(
+            rstart,
+            rend,
+            DiagnosticSeverity::ERROR,
+            String::from(
+                "negation in an equivalence, it would be on the left side of an inclusion",
+            ),
+        )];
+    }
+
+    if !DLType::same_type(lside.t(), rside.t()) {
+        let ((role_start, role_end), concept) = if lside.t().is_role_type() {
+            ((lstart, lend), &content[rstart..rend])
+        } else {
+            ((rstart, rend), &content[lstart..lend])
+        };
+
+        return vec![(
+            role_start,
+            role_end,
+            DiagnosticSeverity::ERROR,
+            format!(
+                "a role used as a concept: '{}' is a role and '{}' a concept, did you mean 'EXISTS {}'?",
+                &content[role_start..role_end],
+                concept,
+                &content[role_start..role_end]
+            ),
+        )];
+    }
+
+    vec![(start, end, DiagnosticSeverity::ERROR, error)]
+}
+
+fn abi_problems(content: &str, symbols: &SymbolDict) -> Vec<Problem> {
+    // source tags are at the end
+    let content = match source_tags_start(content) {
+        Some(index) => &content[..index],
+        Option::None => content,
+    };
+    let (start, end) = trimmed(0, content);
+
+    if start == end {
+        return Vec::new();
+    }
+
+    let colon = match content.find(':') {
+        Some(colon) if content.matches(':').count() == 1 => colon,
+        _ => {
+            return vec![(
+                start,
+                end,
+                DiagnosticSeverity::ERROR,
+                String::from("an assertion is 'a: C' or 'a, b: r', with an optional credibility"),
+            )]
+        }
+    };
+
+    let individuals = words(&content[..colon]);
+    let (symbol_start, symbol) = match words(&content[colon + 1..]).first() {
+        Some((offset, symbol)) => (colon + 1 + offset, *symbol),
+        Option::None => {
+            return vec![(
+                start,
+                end,
+                DiagnosticSeverity::ERROR,
+                String::from("the assertion has no concept or role"),
+            )]
+        }
+    };
+    let symbol_end = symbol_start + symbol.len();
+
+    let message = match (symbols.get(symbol), individuals.len()) {
+        (Option::None, _) => Some(format!("undeclared symbol '{}'", symbol)),
+        (Some((_, DLType::BaseConcept)), 1) | (Some((_, DLType::BaseRole)), 2) => Option::None,
+        (Some((_, DLType::BaseConcept)), _) => Some(format!(
+            "'{}' is a concept, it takes one individual",
+            symbol
+        )),
+        (Some((_, DLType::BaseRole)), _) => {
+            Some(format!("'{}' is a role, it takes two individuals", symbol))
+        }
+        (Some(_), _) => Some(format!("'{}' is not a concept nor a role", symbol)),
+    };
+
+    if let Some(message) = message {
+        return vec![(symbol_start, symbol_end, DiagnosticSeverity::ERROR, message)];
+    }
+
+    // individuals are nominals, not concepts or roles
+    let problems: Vec<Problem> = individuals
+        .into_iter()
+        .filter_map(|(offset, individual)| match symbols.get(individual) {
+            Some((_, t)) if t.is_concept_type() || t.is_role_type() => Some((
+                offset,
+                offset + individual.len(),
+                DiagnosticSeverity::ERROR,
+                format!(
+                    "'{}' is a {}, it can't be an individual",
+                    individual,
+                    if t.is_role_type() { "role" } else { "concept" }
+                ),
+            )),
+            _ => Option::None,
+        })
+        .collect();
+
+    if !problems.is_empty() {
+        return problems;
+    }
+
+    // nominals are added to a copy, the abox has its own
+    let mut symbols = symbols.clone();
+    let (_, id_bound) = find_bound_of_symbols(&symbols);
+
+    match string_to_abiq(content, &mut symbols, id_bound + 1, false).0 {
+        Ok(_) => Vec::new(),
+        Err(e) => vec![(start, end, DiagnosticSeverity::ERROR, e.to_string())],
+    }
+}
+
+// END OF THE PROBLEMS OF A LINE
+// =================================================================================================
+
+// the derived super-concepts of a node, the negated ones apart
+fn super_concepts(
+    closure: &TBDllite,
+    node: &ItemDllite,
+    symbols: &SymbolDict,
+) -> (Vec<String>, Vec<String>) {
+    let mut supers: Vec<String> = Vec::new();
+    let mut disjoints: Vec<String> = Vec::new();
+
+    for tbi in closure.items() {
+        if tbi.lside() == node && !tbi.is_redundant() && !tbi.is_trivial() {
+            if tbi.rside().is_negated() {
+                if let Some(name) = node_to_string(&tbi.rside().negate(), symbols, String::new()) {
+                    disjoints.push(name);
+                }
+            } else if let Some(name) = node_to_string(tbi.rside(), symbols, String::new()) {
+                supers.push(name);
+            }
+        }
+    }
+
+    supers.sort();
+    supers.dedup();
+    disjoints.sort();
+    disjoints.dedup();
+
+    (supers, disjoints)
+}
+
+fn listed(names: &[String]) -> String {
+    if names.is_empty() {
+        String::from("none")
+    } else {
+        names
+            .iter()
+            .map(|name| format!("`{}`", name))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+fn unclosed(block: Block, text: &str) -> Problem {
+    let (start, end) = trimmed(0, text);
+
+    (
+        start,
+        end,
+        DiagnosticSeverity::ERROR,
+        format!("'END{}' not found before the end of the file", block.name()),
+    )
+}
+
+// the words of a line and their offsets
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words: Vec<(usize, &str)> = Vec::new();
+    let mut start: Option<usize> = Option::None;
+
+    for (index, c) in text.char_indices() {
+        let is_separator = c.is_whitespace() || SEPARATORS.contains(&c);
+
+        match (start, is_separator) {
+            (Option::None, false) => start = Some(index),
+            (Some(word_start), true) => {
+                words.push((word_start, &text[word_start..index]));
+                start = Option::None;
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(word_start) = start {
+        words.push((word_start, &text[word_start..]));
+    }
+
+    words
+}
+
+// offsets of the trimmed text
+fn trimmed(offset: usize, text: &str) -> (usize, usize) {
+    let start = offset + text.len() - text.trim_start().len();
+    let end = offset + text.trim_end().len();
+
+    (start, end.max(start))
+}
+
+// positions of the protocol count utf-16 units
+fn column(text: &str, byte: usize) -> u32 {
+    text[..byte.min(text.len())].encode_utf16().count() as u32
+}
+
+fn byte_offset(text: &str, character: u32) -> usize {
+    let mut units: u32 = 0;
+
+    for (index, c) in text.char_indices() {
+        if units >= character {
+            return index;
+        }
+        units += c.len_utf16() as u32;
+    }
+
+    text.len()
+}
+
+// =================================================================================================
+// THE PROTOCOL
+
+pub fn run() -> LspResult<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        ..Default::default()
+    })?;
+
+    connection.initialize(capabilities)?;
+
+    let mut state = State {
+        documents: HashMap::new(),
+    };
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+
+                let response = handle_request(&state, request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(closed) = handle_notification(&mut state, notification) {
+                    // nothing more to say about a closed file
+                    publish(&connection, closed, Vec::new())?;
+                }
+
+                // the symbols of a file can come from the others
+                for uri in state.documents.keys() {
+                    publish(&connection, uri.clone(), state.diagnostics(uri))?;
+                }
+            }
+            Message::Response(_) => (),
+        }
+    }
+
+    // the writer thread ends when the connection is dropped
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn handle_request(state: &State, request: Request) -> Response {
+    let id = request.id.clone();
+
+    let result = match request.method.as_str() {
+        GotoDefinition::METHOD => params::<GotoDefinitionParams>(request.params).map(|p| {
+            let position = p.text_document_position_params;
+            let location = state.definition(&position.text_document.uri, &position.position);
+
+            serde_json::to_value(location.map(GotoDefinitionResponse::Scalar))
+        }),
+        HoverRequest::METHOD => params::<HoverParams>(request.params).map(|p| {
+            let position = p.text_document_position_params;
+
+            serde_json::to_value(state.hover(&position.text_document.uri, &position.position))
+        }),
+        Completion::METHOD => params::<CompletionParams>(request.params).map(|p| {
+            let position = p.text_document_position;
+            let items = state.completion(&position.text_document.uri, &position.position);
+
+            serde_json::to_value(CompletionResponse::Array(items))
+        }),
+        method => Err(format!("unknown request {}", method)),
+    };
+
+    match result {
+        Ok(Ok(value)) => Response::new_ok(id, value),
+        Ok(Err(e)) => error_response(id, e.to_string()),
+        Err(e) => error_response(id, e),
+    }
+}
+
+// returns the file closed, if any
+fn handle_notification(state: &mut State, notification: Notification) -> Option<Url> {
+    match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            if let Ok(p) = params::<DidOpenTextDocumentParams>(notification.params) {
+                let text = p.text_document.text;
+                state
+                    .documents
+                    .insert(p.text_document.uri, Document { text });
+            }
+            Option::None
+        }
+        DidChangeTextDocument::METHOD => {
+            if let Ok(mut p) = params::<DidChangeTextDocumentParams>(notification.params) {
+                // the whole text is sent each time
+                if let Some(change) = p.content_changes.pop() {
+                    let text = change.text;
+                    state
+                        .documents
+                        .insert(p.text_document.uri, Document { text });
+                }
+            }
+            Option::None
+        }
+        DidCloseTextDocument::METHOD => params::<DidCloseTextDocumentParams>(notification.params)
+            .ok()
+            .and_then(|p| {
+                state
+                    .documents
+                    .remove(&p.text_document.uri)
+                    .map(|_| p.text_document.uri)
+            }),
+        _ => Option::None,
+    }
+}
+
+fn publish(connection: &Connection, uri: Url, diagnostics: Vec<Diagnostic>) -> LspResult<()> {
+    let params = PublishDiagnosticsParams::new(uri, diagnostics, Option::None);
+    let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+
+    connection
+        .sender
+        .send(Message::Notification(notification))?;
+    Ok(())
+}
+
+fn params<P: DeserializeOwned>(value: Value) -> Result<P, String> {
+    serde_json::from_value(value).map_err(|e| format!("bad parameters: {}", e))
+}
+
+fn error_response(id: RequestId, message: String) -> Response {
+    // -32602: invalid params, -32601: method not found
+    let code = if message.starts_with("unknown request") {
+        -32601
+    } else {
+        -32602
+    };
+
+    Response::new_err(id, code, message)
+}
+
+// END OF THE PROTOCOL
+// =================================================================================================
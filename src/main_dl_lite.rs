@@ -26,18 +26,31 @@ use std::path::PathBuf;
 use structopt::StructOpt;
 
 // from the interface module
-use crate::interface::cli::{AggrName, Cli, Task};
+use crate::dl_lite::utilities::GraphFilter;
+use crate::interface::cli::{AggrName, BoundName, Cli, CombineName, ExportName, RenderName, Task};
 // (for dot -args blabla, create a pdf image)
-use crate::tasks::{task_abox_related, task_tbox_related};
+use crate::serve::serve;
+use crate::tasks::{task_abox_related, task_diff_tbox, task_native_related, task_tbox_related};
 
 mod alg_math; // this module is for computing the rank, matrix manipulation and interpolation are
               // defined here
 mod dl_lite; // dl_lite reasoner is in this module
+mod exact_ranking; // the ranking with rationals, to audit the floats
+mod graph_export; // graphs to graphml, gexf, json and mermaid
 mod graph_maker; // a module that is only a file, creates a graph from ontologies and alike
+mod graph_render; // layered layout of the graphs, to svg or pdf without graphviz
 mod helper; // helper functions to parse files, update list and other
+mod html_report; // the report of a task as a html page
 mod interface; // module with the cli interface
 mod kb;
+mod lint; // duplicate, entailed and trivial axioms, unused symbols
+mod native_fmt; // native files written again in the way of the ontology
+mod ranking_diff; // what moved between two rankings
+mod repl; // an ontology kept in memory with its closures, the serve task uses it
+mod sensitivity; // how rankings move with the credibilities
+mod serve; // the serve task, json over http
 mod tasks;
+mod tbox_diff; // what changed in meaning between two versions of a tbox
 
 // END OF MODULE DECLARATION
 // =================================================================================================
@@ -54,13 +67,62 @@ type TBoxRelatedPaths<'a> = (
     &'a Option<PathBuf>,
     &'a Option<PathBuf>,
     &'a Option<PathBuf>,
+    &'a Option<PathBuf>,
 );
 type ABoxRelatedPaths<'a> = (
+    &'a Vec<PathBuf>,
+    &'a Option<PathBuf>,
+    &'a Option<PathBuf>,
+    &'a Option<PathBuf>,
     &'a Option<PathBuf>,
     &'a Option<PathBuf>,
     &'a Option<PathBuf>,
     &'a Option<PathBuf>,
 );
+// the two rankings, the new tbox (if it changed) and the output of the 'diffrank' task
+type RankingDiffPaths<'a> = (&'a Vec<PathBuf>, &'a Option<PathBuf>, &'a Option<PathBuf>);
+// the tbox, the symbols, the aboxes and the output of the tasks on native files
+type NativeRelatedPaths<'a> = (
+    &'a Option<PathBuf>,
+    &'a Option<PathBuf>,
+    &'a Vec<PathBuf>,
+    &'a Option<PathBuf>,
+);
+// the tbox before, the tbox after, the symbols, the aboxes and the output of the 'difftb' task
+type TBoxDiffPaths<'a> = (
+    &'a Option<PathBuf>,
+    &'a Option<PathBuf>,
+    &'a Option<PathBuf>,
+    &'a Vec<PathBuf>,
+    &'a Option<PathBuf>,
+);
+// the output file and the html report
+type OutputPaths<'a> = (&'a Option<PathBuf>, &'a Option<PathBuf>);
+// name of the abox, depth of the chase, query, the options of the ranking and of the graphs
+type ABoxRelatedOptions<'a> = (
+    &'a Option<String>,
+    usize,
+    &'a Option<String>,
+    RankOptions<'a>,
+    GraphOptions<'a>,
+);
+// how graphs are rendered, the formats they are exported to and the filters of the
+// conflict graph
+type GraphOptions<'a> = (
+    &'a Option<RenderName>,
+    &'a Vec<ExportName>,
+    &'a Vec<GraphFilter>,
+);
+// aggregation function, combination of sources, assertion to explain, sensitivity analysis,
+// bound on the roots and exact ranking
+type RankOptions<'a> = (
+    &'a Option<AggrName>,
+    &'a Option<CombineName>,
+    &'a Option<String>,
+    bool,
+    &'a Option<BoundName>,
+    bool,
+);
 
 // END OF TYPE DECLARATION
 // =================================================================================================
@@ -93,29 +155,115 @@ pub fn main() {
     // get all arguments regardless of the task
     let task: Task = args.task;
     let path_tbox_op: Option<std::path::PathBuf> = args.path_tbox;
-    let path_abox_op: Option<std::path::PathBuf> = args.path_abox;
+    let path_tbox_after_op: Option<std::path::PathBuf> = args.path_tbox_after;
+    let path_aboxes: Vec<std::path::PathBuf> = args.path_abox;
+    let abox_name_op: Option<String> = args.abox_name;
     let path_symbols_op: Option<std::path::PathBuf> = args.path_symbols;
     let path_output_op: Option<std::path::PathBuf> = args.path_output;
     let verbose: bool = args.verbose;
     let silent: bool = args.silent;
     let aggr_name_op: Option<AggrName> = args.aggr;
+    let chase_depth: usize = args.depth;
+    let path_mappings_op: Option<std::path::PathBuf> = args.path_mappings;
+    let query_op: Option<String> = args.query;
+    let path_sources_op: Option<std::path::PathBuf> = args.path_sources;
+    let combine_name_op: Option<CombineName> = args.combine;
+    let explain_op: Option<String> = args.explain;
+    let sensitivity: bool = args.sensitivity;
+    let bound_name_op: Option<BoundName> = args.bound;
+    let exact: bool = args.exact;
+    let path_report_op: Option<std::path::PathBuf> = args.path_report;
+    let render_op: Option<RenderName> = args.render;
+    let exports: Vec<ExportName> = args.export;
+    let port: u16 = args.port;
+
+    // the filters are applied in this order
+    let mut filters: Vec<GraphFilter> = Vec::new();
+    if let Some(nominal) = args.graph_nominal {
+        filters.push(GraphFilter::Nominal(nominal));
+    }
+    if let Some(predicate) = args.graph_predicate {
+        filters.push(GraphFilter::Predicate(predicate));
+    }
+    if let Some(assertion) = args.graph_around {
+        filters.push(GraphFilter::Neighbourhood(assertion, args.graph_hops));
+    }
+    if let Some(n) = args.graph_lowest {
+        filters.push(GraphFilter::Lowest(n));
+    }
+
+    let graph_options: GraphOptions = (&render_op, &exports, &filters);
 
     // now do what you are ask
     match task {
-        Task::VerTB | Task::GenConTB => {
-            let tbox_paths: TBoxRelatedPaths = (&path_tbox_op, &path_symbols_op, &path_output_op);
+        Task::VerTB | Task::GenConTB | Task::MinTB => {
+            let tbox_paths: TBoxRelatedPaths = (
+                &path_tbox_op,
+                &path_symbols_op,
+                &path_output_op,
+                &path_report_op,
+            );
+
+            task_tbox_related(tbox_paths, task, graph_options, verbose, silent);
+        }
+        Task::DiffTB => {
+            let diff_paths: TBoxDiffPaths = (
+                &path_tbox_op,
+                &path_tbox_after_op,
+                &path_symbols_op,
+                &path_aboxes,
+                &path_output_op,
+            );
 
-            task_tbox_related(tbox_paths, task, verbose, silent);
+            task_diff_tbox(diff_paths, verbose, silent);
         }
-        Task::VerAB | Task::CleanAB | Task::GenConAB | Task::RankAB => {
+        Task::VerAB
+        | Task::CleanAB
+        | Task::GenConAB
+        | Task::RankAB
+        | Task::ChaseAB
+        | Task::QueryAB
+        | Task::MergeAB
+        | Task::DiffRank => {
             let abox_paths: ABoxRelatedPaths = (
-                &path_abox_op,
+                &path_aboxes,
+                &path_tbox_op,
+                &path_symbols_op,
+                &path_output_op,
+                &path_mappings_op,
+                &path_sources_op,
+                &path_report_op,
+                &path_tbox_after_op,
+            );
+
+            let rank_options: RankOptions = (
+                &aggr_name_op,
+                &combine_name_op,
+                &explain_op,
+                sensitivity,
+                &bound_name_op,
+                exact,
+            );
+            let abox_options: ABoxRelatedOptions = (
+                &abox_name_op,
+                chase_depth,
+                &query_op,
+                rank_options,
+                graph_options,
+            );
+
+            task_abox_related(abox_paths, abox_options, task, verbose, silent);
+        }
+        Task::Serve => serve(port, verbose),
+        Task::Fmt | Task::Lint => {
+            let native_paths: NativeRelatedPaths = (
                 &path_tbox_op,
                 &path_symbols_op,
+                &path_aboxes,
                 &path_output_op,
             );
 
-            task_abox_related(abox_paths, &aggr_name_op, task, verbose, silent);
+            task_native_related(native_paths, task, verbose, silent);
         }
         _ => println!("NOT IMPLEMENTED !!!"),
     }
@@ -0,0 +1,39 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+// =================================================================================================
+// MODULE DECLARATION
+
+mod dl_lite; // dl_lite reasoner is in this module
+mod graph_maker; // dl_lite needs the graph nodes
+mod interface; // dl_lite needs the file utilities
+mod kb;
+mod lsp; // the language server
+
+// END OF MODULE DECLARATION
+// =================================================================================================
+
+// the main function, the editor starts it and talks to it over stdin and stdout
+pub fn main() {
+    if let Err(e) = lsp::run() {
+        // stdout belongs to the protocol
+        eprintln!("ERROR: {}", &e);
+        std::process::exit(exitcode::SOFTWARE);
+    }
+}
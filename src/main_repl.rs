@@ -0,0 +1,213 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+// =================================================================================================
+// MODULE DECLARATION
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+// for cli interface, need to import it so the interface module works
+use structopt::StructOpt;
+
+use crate::kb::knowledge_base::TBox;
+use crate::repl::{Reply, Session, COMMANDS, KEYWORDS};
+
+mod alg_math; // computing the rank, matrix manipulation and interpolation
+mod dl_lite; // dl_lite reasoner is in this module
+mod graph_maker; // dl_lite needs the graph nodes
+mod helper; // the ranking is wrapped here
+mod interface; // module with the cli interface
+mod kb;
+mod repl; // the session and its commands
+
+// END OF MODULE DECLARATION
+// =================================================================================================
+
+// =================================================================================================
+// SOME CONSTANTS
+
+// constants for the bound computing, the same as in 'main_dl_lite.rs'
+// this values are not random, DO NOT TWEAK THEM  if you don't know what you're doing
+const TOLERANCE: f64 = 0.0000000000000001; // below this value we can consider values are equal
+                                           // computing sinus introduces rounding errors
+const M_SCALE: f64 = 1.1; // avoid singular matrices
+const B_TRANSLATE: f64 = 1.; // superfluous for FFT interpolation, see 'main_dl_lite.rs'
+
+const PROMPT: &str = "rustoner> ";
+
+// END OF CONSTANTS DECLARATION
+// =================================================================================================
+
+/// Completes the commands at the start of the line, afterwards the symbols of the
+/// ontology and the keywords.
+struct ReplHelper {
+    symbols: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        // the word under the cursor begins after a space or a comma
+        let start = line[..pos].rfind([' ', ',']).map(|i| i + 1).unwrap_or(0);
+        let word = &line[start..pos];
+
+        let candidates: Vec<String> = if start == 0 {
+            COMMANDS.iter().map(|c| c.to_string()).collect()
+        } else {
+            self.symbols
+                .iter()
+                .cloned()
+                .chain(KEYWORDS.iter().map(|k| k.to_string()))
+                .collect()
+        };
+
+        Ok((
+            start,
+            candidates
+                .into_iter()
+                .filter(|c| c.starts_with(word))
+                .collect(),
+        ))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// The arguments of the binary rustoner_repl, the ontology is loaded once and then
+/// explored by commands.
+#[derive(StructOpt, Debug)]
+struct ReplCli {
+    #[structopt(long = "verbose")]
+    verbose: bool,
+
+    #[structopt(parse(from_os_str), long = "tbox", help = "path to the tbox file")]
+    path_tbox: Option<std::path::PathBuf>,
+
+    #[structopt(
+        parse(from_os_str),
+        long = "abox",
+        help = "optional, path to an abox file, can be given several times, the last one is the current abox"
+    )]
+    path_abox: Vec<std::path::PathBuf>,
+
+    #[structopt(
+        parse(from_os_str),
+        long = "symbols",
+        help = "optional, if present will parse symbols from this file instead on symbols in the tbox file"
+    )]
+    path_symbols: Option<std::path::PathBuf>,
+
+    #[structopt(
+        parse(from_os_str),
+        long = "history",
+        default_value = ".rustoner_history",
+        help = "the file where the commands are kept between sessions"
+    )]
+    path_history: std::path::PathBuf,
+}
+
+// the main function
+pub fn main() {
+    let args = ReplCli::from_args();
+    let verbose = args.verbose;
+
+    let path_tbox = match &args.path_tbox {
+        Some(path_tbox) => path_tbox.to_str().unwrap(),
+        Option::None => {
+            println!("ERROR: you must provide a tbox file");
+            std::process::exit(exitcode::USAGE);
+        }
+    };
+    let path_symbols_op = args.path_symbols.as_ref().map(|p| p.to_str().unwrap());
+    let path_aboxes: Vec<&str> = args.path_abox.iter().map(|p| p.to_str().unwrap()).collect();
+
+    let mut session = match Session::from_files(path_tbox, path_symbols_op, &path_aboxes, verbose) {
+        Ok(session) => session,
+        Err(e) => {
+            println!("ERROR: {}", &e);
+            std::process::exit(exitcode::NOINPUT);
+        }
+    };
+    let mut editor: Editor<ReplHelper> = Editor::new();
+
+    editor.set_helper(Some(ReplHelper {
+        symbols: session.symbol_names(),
+    }));
+
+    // there is no history the first time
+    let _ = editor.load_history(&args.path_history);
+
+    println!(
+        " -- {} axioms and {} abox(es) loaded, 'help' lists the commands",
+        session.onto().tbox().len(),
+        session.onto().aboxes().len()
+    );
+
+    loop {
+        match editor.readline(PROMPT) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    editor.add_history_entry(line.as_str());
+                }
+
+                match session.execute(&line) {
+                    Reply::Quit => break,
+                    Reply::Text(text) => {
+                        if !text.is_empty() {
+                            println!("{}", &text);
+                        }
+                    }
+                }
+
+                // new nominals or declared symbols are completed too
+                if let Some(helper) = editor.helper_mut() {
+                    helper.symbols = session.symbol_names();
+                }
+            }
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                println!("ERROR: {}", &e);
+                break;
+            }
+        }
+    }
+
+    if let Err(e) = editor.save_history(&args.path_history) {
+        println!(" -- couldn't save the history: {}", &e);
+    }
+}
@@ -0,0 +1,176 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   The 'fmt' task: a native file is written again as the ontology writes its files,
+   line by line so that comments and the order of the lines stay:
+       - a symbol is 'concept: Name', 'role: name'
+       - an axiom goes through 'tbox_to_native_string', an equivalence gives its two
+         inclusions
+       - an assertion goes through 'abox_to_native_string_quantum', with its
+         credibility and its source tags
+       - a comment is kept after its line, blank lines are kept once
+   A line that doesn't parse is only trimmed.
+*/
+
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::native_filetype_utilities::{
+    abox_to_native_string_quantum, find_bound_of_symbols, native_lines, tbox_to_native_string,
+};
+use crate::dl_lite::string_formatter::{string_to_abiq, string_to_symbol, string_to_tbi};
+use crate::dl_lite::tbox::TBDllite;
+use crate::dl_lite::trust::split_source_tags;
+use crate::kb::knowledge_base::{ABox, SymbolDict, TBox};
+use crate::kb::types::DLType;
+
+/// Formats the text of a native file, the numbers (from 0) of the lines that couldn't
+/// be parsed are given back. Nominals found in assertions are added to symbols.
+pub fn format_native(text: &str, symbols: &mut SymbolDict) -> (String, Vec<usize>) {
+    let mut formatted: Vec<String> = Vec::new();
+    let mut not_parsed: Vec<usize> = Vec::new();
+
+    let (_, id_bound) = find_bound_of_symbols(symbols);
+    let mut current_id = id_bound + 1;
+
+    for (number, block, content, comment) in native_lines(text) {
+        let content = content.trim();
+
+        let lines_op: Option<Vec<String>> = if content.is_empty() {
+            Some(Vec::new())
+        } else {
+            match block {
+                Some("SYMBOL") => format_symbol(content),
+                Some("TBOX") => format_tbi(content, symbols),
+                Some("ABOX") => {
+                    let (lines_op, id) = format_abi(content, symbols, current_id);
+                    current_id = id;
+                    lines_op
+                }
+                // markers, sources and lines out of blocks
+                _ => Some(vec![content.to_string()]),
+            }
+        };
+
+        let mut lines = match lines_op {
+            Some(lines) => lines,
+            Option::None => {
+                not_parsed.push(number);
+                vec![content.to_string()]
+            }
+        };
+
+        // the comment goes with the first line, or alone
+        if let Some(comment) = comment {
+            let comment = format!("//{}", comment.trim_end());
+
+            match lines.first_mut() {
+                Some(first) => {
+                    first.push(' ');
+                    first.push_str(&comment);
+                }
+                Option::None => lines.push(comment),
+            }
+        }
+
+        // blank lines are kept once
+        if lines.is_empty() {
+            if formatted.last().is_some_and(|last| !last.is_empty()) {
+                formatted.push(String::new());
+            }
+        } else {
+            formatted.extend(lines);
+        }
+    }
+
+    while formatted.last().is_some_and(|last| last.is_empty()) {
+        formatted.pop();
+    }
+
+    let mut res = formatted.join("\n");
+    res.push('\n');
+
+    (res, not_parsed)
+}
+
+fn format_symbol(content: &str) -> Option<Vec<String>> {
+    let (name, t) = string_to_symbol(content).ok()?;
+
+    let type_in_string = match t {
+        DLType::BaseConcept => "concept",
+        DLType::BaseRole => "role",
+        _ => "nominal",
+    };
+
+    Some(vec![format!("{}: {}", type_in_string, name)])
+}
+
+fn format_tbi(content: &str, symbols: &SymbolDict) -> Option<Vec<String>> {
+    let mut tbis = string_to_tbi(content, symbols).ok()?;
+    let mut tbox = TBDllite::new();
+
+    // in the order 'parse_tbox_native' adds them
+    while let Some(tbi) = tbis.pop() {
+        tbox.add(tbi);
+    }
+
+    let dont_write_trivial = false;
+    let written = tbox_to_native_string(&tbox, symbols, dont_write_trivial)?;
+
+    Some(without_markers(&written))
+}
+
+fn format_abi(
+    content: &str,
+    symbols: &mut SymbolDict,
+    current_id: usize,
+) -> (Option<Vec<String>>, usize) {
+    let (content, source_tags) = split_source_tags(content);
+
+    match string_to_abiq(content, symbols, current_id, false) {
+        (Err(_), id) => (Option::None, id),
+        (Ok((mut abi, to_be_added)), id) => {
+            for (name, value) in to_be_added {
+                symbols.insert(name, value);
+            }
+
+            for source in source_tags {
+                abi.add_source(source);
+            }
+
+            let mut abox = AbqDllite::new("");
+            abox.add(abi);
+
+            let dont_write_trivial = false;
+            let lines_op = abox_to_native_string_quantum(&abox, symbols, dont_write_trivial)
+                .map(|written| without_markers(&written));
+
+            (lines_op, id)
+        }
+    }
+}
+
+// the lines written between 'BEGIN...' and 'END...'
+fn without_markers(written: &str) -> Vec<String> {
+    let lines: Vec<&str> = written.lines().collect();
+
+    lines[1..lines.len() - 1]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
@@ -0,0 +1,313 @@
+/*
+ © - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   the same abox ranked twice (after a change in the tbox or in the credibilities),
+   what moved? assertions are matched between both rankings, then:
+   - kendall tau and spearman correlations of the values of the common assertions
+   - how many of the top k assertions are the same
+   - assertions whose conflict type changed
+   - the largest shifts of value
+*/
+
+use serde_json::{json, Value};
+
+use crate::alg_math::utilities::{kendall_tau, spearman_rho};
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::abox_item_quantum::AbiqDllite;
+use crate::dl_lite::ontology::OntologyDllite;
+use crate::dl_lite::string_formatter::abi_to_string;
+use crate::kb::knowledge_base::{ABox, SymbolDict};
+use crate::kb::types::ConflictType;
+
+/// How two rankings of the same assertions differ, 'before' is the first one.
+#[derive(Debug, Clone)]
+pub struct RankingDiff {
+    before_name: String,
+    after_name: String,
+    // common assertions: the one before, the one after and their conflict types
+    common: Vec<(AbiqDllite, AbiqDllite, ConflictType, ConflictType)>,
+    only_before: Vec<AbiqDllite>,
+    only_after: Vec<AbiqDllite>,
+    top_k: usize,
+}
+
+// a ranked assertion without value keeps its credibility
+fn value_of(abiq: &AbiqDllite) -> f64 {
+    abiq.value().unwrap_or_else(|| abiq.credibility())
+}
+
+/// The conflict type of each assertion of abq, as the ranking finds it,
+/// the closure of the ontology must have been generated.
+pub fn conflict_types(onto: &OntologyDllite, abq: &AbqDllite, verbose: bool) -> Vec<ConflictType> {
+    let (matrix, real_to_virtual, _) = onto.conflict_matrix_refs_only(abq, verbose);
+    let dim = (matrix.len() as f64).sqrt() as usize;
+
+    (0..abq.len())
+        .map(|index| match real_to_virtual.get(&index) {
+            Some(Some(i)) => {
+                let is_clean =
+                    (0..dim).all(|j| matrix[i * dim + j] == 0 && matrix[j * dim + i] == 0);
+
+                if is_clean {
+                    ConflictType::Clean
+                } else {
+                    ConflictType::Conflict
+                }
+            }
+            _ => ConflictType::SelfConflict,
+        })
+        .collect()
+}
+
+impl RankingDiff {
+    /// compare two rankings, assertions are matched by their content, the 'top_k'
+    /// first assertions of both rankings are compared, each ranking is classified with
+    /// the closure of its own ontology (the same one if the tbox didn't change)
+    pub fn new(
+        (onto_before, before): (&OntologyDllite, &AbqDllite),
+        (onto_after, after): (&OntologyDllite, &AbqDllite),
+        top_k: usize,
+        verbose: bool,
+    ) -> RankingDiff {
+        let types_before = conflict_types(onto_before, before, verbose);
+        let types_after = conflict_types(onto_after, after, verbose);
+
+        let mut common: Vec<(AbiqDllite, AbiqDllite, ConflictType, ConflictType)> = Vec::new();
+        let mut only_before: Vec<AbiqDllite> = Vec::new();
+
+        for (i, abiq) in before.items().iter().enumerate() {
+            match after.items().iter().position(|a| a.abi() == abiq.abi()) {
+                Some(j) => common.push((
+                    abiq.clone(),
+                    after.items()[j].clone(),
+                    types_before[i],
+                    types_after[j],
+                )),
+                Option::None => only_before.push(abiq.clone()),
+            }
+        }
+
+        let only_after = after
+            .items()
+            .iter()
+            .filter(|a| !before.items().iter().any(|b| b.abi() == a.abi()))
+            .cloned()
+            .collect::<Vec<AbiqDllite>>();
+
+        RankingDiff {
+            before_name: before.name(),
+            after_name: after.name(),
+            common,
+            only_before,
+            only_after,
+            top_k,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.common.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.common.is_empty()
+    }
+
+    fn values(&self) -> (Vec<f64>, Vec<f64>) {
+        self.common
+            .iter()
+            .map(|(b, a, _, _)| (value_of(b), value_of(a)))
+            .unzip()
+    }
+
+    pub fn kendall_tau(&self) -> Option<f64> {
+        let (before, after) = self.values();
+        kendall_tau(&before, &after)
+    }
+
+    pub fn spearman_rho(&self) -> Option<f64> {
+        let (before, after) = self.values();
+        spearman_rho(&before, &after)
+    }
+
+    // the k common assertions with the highest values
+    fn top(&self, values: &[f64]) -> Vec<usize> {
+        let mut indices = (0..values.len()).collect::<Vec<usize>>();
+        indices.sort_by(|a, b| {
+            values[*b]
+                .partial_cmp(&values[*a])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        indices.truncate(self.top_k);
+        indices
+    }
+
+    /// how many of the top k assertions before are in the top k after, and k
+    /// (smaller than asked if there are not enough common assertions)
+    pub fn top_k_overlap(&self) -> (usize, usize) {
+        let (before, after) = self.values();
+        let top_before = self.top(&before);
+        let top_after = self.top(&after);
+
+        let overlap = top_before.iter().filter(|i| top_after.contains(i)).count();
+        (overlap, top_before.len())
+    }
+
+    /// the common assertions whose conflict type changed
+    pub fn type_changes(&self) -> Vec<(&AbiqDllite, ConflictType, ConflictType)> {
+        self.common
+            .iter()
+            .filter(|(_, _, tb, ta)| tb != ta)
+            .map(|(b, _, tb, ta)| (b, *tb, *ta))
+            .collect()
+    }
+
+    /// the common assertions with their value before and after, largest shift first
+    pub fn shifts(&self) -> Vec<(&AbiqDllite, f64, f64)> {
+        let mut shifts = self
+            .common
+            .iter()
+            .map(|(b, a, _, _)| (b, value_of(b), value_of(a)))
+            .collect::<Vec<(&AbiqDllite, f64, f64)>>();
+
+        shifts.sort_by(|x, y| {
+            (y.2 - y.1)
+                .abs()
+                .partial_cmp(&(x.2 - x.1).abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        shifts
+    }
+
+    pub fn only_before(&self) -> &Vec<AbiqDllite> {
+        &self.only_before
+    }
+
+    pub fn only_after(&self) -> &Vec<AbiqDllite> {
+        &self.only_after
+    }
+
+    /// a readable report, only the 'limit' largest shifts are shown
+    pub fn to_string_with_symbols(&self, symbols: &SymbolDict, limit: usize) -> String {
+        let name = |abiq: &AbiqDllite| abi_to_string(abiq.abi(), symbols).unwrap_or_default();
+        let number = |x: Option<f64>| match x {
+            Some(x) => format!("{:.4}", x),
+            Option::None => String::from("undefined"),
+        };
+
+        let mut s = format!(
+            " -- comparing {} with {}: {} assertions in common\n",
+            &self.before_name,
+            &self.after_name,
+            self.len()
+        );
+
+        let (overlap, k) = self.top_k_overlap();
+        s.push_str(&format!(
+            "    kendall tau: {}\n",
+            number(self.kendall_tau())
+        ));
+        s.push_str(&format!("    spearman: {}\n", number(self.spearman_rho())));
+        s.push_str(&format!("    top {} overlap: {}/{}\n", k, overlap, k));
+
+        let type_changes = self.type_changes();
+        if !type_changes.is_empty() {
+            s.push_str(" -- conflict type changes:\n");
+
+            for (abiq, before, after) in type_changes {
+                s.push_str(&format!(
+                    "    {}: {:?} -> {:?}\n",
+                    name(abiq),
+                    before,
+                    after
+                ));
+            }
+        }
+
+        s.push_str(" -- largest value shifts:\n");
+        for (abiq, before, after) in self.shifts().into_iter().take(limit) {
+            s.push_str(&format!(
+                "    {}: {:.6} -> {:.6} ({:+.6})\n",
+                name(abiq),
+                before,
+                after,
+                after - before
+            ));
+        }
+
+        for (title, abiqs) in [
+            (&self.before_name, &self.only_before),
+            (&self.after_name, &self.only_after),
+        ] {
+            if !abiqs.is_empty() {
+                s.push_str(&format!(" -- only in {}:\n", title));
+
+                for abiq in abiqs {
+                    s.push_str(&format!("    {}\n", name(abiq)));
+                }
+            }
+        }
+
+        s
+    }
+
+    /// the whole report as json
+    pub fn to_json(&self, symbols: &SymbolDict) -> Value {
+        let name = |abiq: &AbiqDllite| abi_to_string(abiq.abi(), symbols).unwrap_or_default();
+        let (overlap, k) = self.top_k_overlap();
+
+        let type_changes = self
+            .type_changes()
+            .into_iter()
+            .map(|(abiq, before, after)| {
+                json!({
+                    "assertion": name(abiq),
+                    "before": format!("{:?}", before),
+                    "after": format!("{:?}", after),
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        let shifts = self
+            .shifts()
+            .into_iter()
+            .map(|(abiq, before, after)| {
+                json!({
+                    "assertion": name(abiq),
+                    "before": before,
+                    "after": after,
+                    "shift": after - before,
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        json!({
+            "before": &self.before_name,
+            "after": &self.after_name,
+            "common": self.len(),
+            "kendall_tau": self.kendall_tau(),
+            "spearman": self.spearman_rho(),
+            "top_k": { "k": k, "overlap": overlap },
+            "conflict_type_changes": type_changes,
+            "shifts": shifts,
+            "only_before": self.only_before.iter().map(name).collect::<Vec<String>>(),
+            "only_after": self.only_after.iter().map(name).collect::<Vec<String>>(),
+        })
+    }
+}
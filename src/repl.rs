@@ -0,0 +1,595 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   A session keeps an ontology in memory with its closures, each command is a line:
+       - the closures are computed when a command needs them and kept until the tbox
+         changes, then they are computed again
+       - an axiom is told from an assertion by its '<' or '=', both are written as in
+         a native file: 'Man < Human', 'Socrates : Man, 0.8'
+   The editor (history, completion) is in 'main_repl.rs', a session only reads lines
+   and gives back text.
+*/
+
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::alg_math::bounds::{Adjusters, BoundAlgorithm, BoundSettings};
+use crate::alg_math::polynomial_roots::Method;
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::abox_item_quantum::AbiqDllite;
+use crate::dl_lite::native_filetype_utilities::{
+    abox_to_native_string_quantum, symbols_to_native_string, tbox_to_native_string,
+};
+use crate::dl_lite::ontology::OntologyDllite;
+use crate::dl_lite::string_formatter::{
+    abiq_to_string, create_string_for_unravel_conflict_abiq,
+    create_string_for_unravel_conflict_tbi, pretty_print_abiq_conflict, string_to_tbi,
+    tbi_to_string,
+};
+use crate::dl_lite::tbox_item::TbiDllite;
+use crate::helper::rank_abox;
+use crate::interface::cli::AggrName;
+use crate::interface::utilities::{get_filetype, parse_name_from_filename, write_str_to_file};
+use crate::kb::aggr_expression::AggrExpression;
+use crate::kb::aggr_functions::{AGGR_COUNT, AGGR_MAX, AGGR_MEAN, AGGR_MIN, AGGR_SUM};
+use crate::kb::knowledge_base::{ABox, AggrBox, TBox, TBoxItem};
+use crate::kb::types::{DLType, FileType};
+use crate::{B_TRANSLATE, M_SCALE, TOLERANCE};
+
+// name of the abox created when an assertion is added and there is none
+const REPL_ABOX_NAME: &str = "repl";
+
+pub const COMMANDS: [&str; 15] = [
+    "help",
+    "symbols",
+    "tbox",
+    "abox",
+    "aboxes",
+    "declare",
+    "add",
+    "retract",
+    "entails",
+    "consistent",
+    "unravel",
+    "rank",
+    "save",
+    "quit",
+    "exit",
+];
+
+// completed after the commands, with the symbols
+pub const KEYWORDS: [&str; 5] = ["NOT", "INV", "EXISTS", "concept", "role"];
+
+const HELP: &str = " -- commands:
+    symbols                         the concepts, roles and nominals
+    tbox [closure]                  the tbox, or its closure
+    abox [name]                     the current abox, or make 'name' the current one
+    aboxes                          the names of the aboxes
+    declare concept|role <name>     add a symbol
+    add <axiom|assertion>           e.g. 'add Man < Human', 'add Socrates : Man, 0.8'
+    retract <axiom|assertion>       remove it from the tbox or the current abox
+    entails <axiom|assertion>       is it a consequence of the ontology
+    consistent                      contradictions of the tbox and of the current abox
+    unravel <axiom|assertion>       how it is deduced
+    rank [sum|max|min|mean|count]   rank the current abox, sum by default
+    save <file>                     symbols, tbox and current abox to a native file
+    quit";
+
+/// What the editor does after a command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    Text(String),
+    Quit,
+}
+
+pub struct Session {
+    onto: OntologyDllite,
+    // None when the tbox changed since the closures were computed, otherwise
+    // tells if they were computed with the deduction tree
+    closures: Option<bool>,
+    verbose: bool,
+}
+
+impl Session {
+    pub fn new(onto: OntologyDllite, verbose: bool) -> Session {
+        Session {
+            onto,
+            closures: Option::None,
+            verbose,
+        }
+    }
+
+    /// Loads the tbox, with the symbols of 'path_symbols_op' or else of the tbox file,
+    /// and the aboxes, the last one is the current abox. A database already has the
+    /// symbols and the tbox.
+    pub fn from_files(
+        path_tbox: &str,
+        path_symbols_op: Option<&str>,
+        path_aboxes: &[&str],
+        verbose: bool,
+    ) -> Result<Session, String> {
+        for path in path_aboxes
+            .iter()
+            .chain(path_symbols_op.iter())
+            .chain([path_tbox].iter())
+        {
+            if !Path::new(path).exists() {
+                return Err(format!("no file {}", path));
+            }
+        }
+
+        let tb_ft = get_filetype(path_tbox);
+
+        let mut onto = if tb_ft == FileType::Sqlite {
+            OntologyDllite::initiate_from_db(path_tbox, verbose)
+                .map_err(|e| format!("couldn't read the database {}: {}", path_tbox, &e))?
+        } else {
+            let mut onto = OntologyDllite::new(parse_name_from_filename(path_tbox).to_string());
+
+            match path_symbols_op {
+                Some(path_symbols) => {
+                    onto.add_symbols_from_file(path_symbols, get_filetype(path_symbols), verbose)
+                }
+                Option::None => onto.add_symbols_from_file(path_tbox, tb_ft, verbose),
+            }
+
+            onto.add_tbis_from_file(path_tbox, tb_ft, verbose);
+            onto
+        };
+
+        for path_abox in path_aboxes {
+            onto.new_abox_from_file_quantum(path_abox, get_filetype(path_abox), verbose);
+        }
+
+        Ok(Session::new(onto, verbose))
+    }
+
+    pub fn onto(&self) -> &OntologyDllite {
+        &self.onto
+    }
+
+    /// The ontology with its closures up to date.
+    pub fn onto_with_closures(&mut self, deduction_tree: bool) -> &OntologyDllite {
+        self.update_closures(deduction_tree);
+        &self.onto
+    }
+
+    /// The symbols of the ontology, for completion.
+    pub fn symbol_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .onto
+            .symbols()
+            .keys()
+            .filter(|name| name.as_str() != "Top" && name.as_str() != "Bottom")
+            .cloned()
+            .collect();
+        names.sort();
+
+        names
+    }
+
+    pub fn execute(&mut self, line: &str) -> Reply {
+        let line = line.trim();
+        let (command, rest) = match line.find(' ') {
+            Some(space) => (&line[..space], line[space..].trim()),
+            Option::None => (line, ""),
+        };
+
+        let text = match command {
+            "" => String::new(),
+            "help" => String::from(HELP),
+            "symbols" => self.symbols(),
+            "tbox" => self.tbox(rest == "closure"),
+            "abox" => self.abox(rest),
+            "aboxes" => self.onto.abox_names().join("\n"),
+            "declare" => self.declare(rest),
+            "add" => self.add(rest),
+            "retract" => self.retract(rest),
+            "entails" => self.entails(rest),
+            "consistent" => self.consistent(),
+            "unravel" => self.unravel(rest),
+            "rank" => self.rank(rest),
+            "save" => self.save(rest),
+            "quit" | "exit" => return Reply::Quit,
+            _ => format!(" -- unknown command '{}', try 'help'", command),
+        };
+
+        Reply::Text(text)
+    }
+
+    // computed again only if the tbox changed or the deduction tree is needed now
+    fn update_closures(&mut self, deduction_tree: bool) {
+        match self.closures {
+            Some(with_tree) if with_tree || !deduction_tree => (),
+            _ => {
+                self.onto.generate_cln(deduction_tree, self.verbose, 0_i8);
+                self.closures = Some(deduction_tree);
+            }
+        }
+    }
+
+    fn symbols(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        for name in self.symbol_names() {
+            let (_, t) = self.onto.symbols()[&name];
+
+            let type_in_string = match t {
+                DLType::BaseConcept => "concept",
+                DLType::BaseRole => "role",
+                DLType::Nominal => "nominal",
+                _ => continue,
+            };
+
+            lines.push(format!("{}: {}", type_in_string, name));
+        }
+
+        lines.join("\n")
+    }
+
+    fn tbox(&mut self, closure: bool) -> String {
+        if closure {
+            self.update_closures(false);
+            self.onto.tbox_to_string(self.onto.cln(true), true)
+        } else {
+            self.onto.tbox_to_string(self.onto.tbox(), false)
+        }
+    }
+
+    fn abox(&mut self, name: &str) -> String {
+        if !name.is_empty() && !self.onto.set_current_abox(name) {
+            return format!(" -- no abox named '{}'", name);
+        }
+
+        match self.onto.abox() {
+            Option::None => String::from(" -- there is no abox"),
+            Some(abox) => format!(
+                "{}:\n{}",
+                abox.name(),
+                self.onto.abox_to_string_quantum(abox)
+            ),
+        }
+    }
+
+    fn declare(&mut self, rest: &str) -> String {
+        let (t, name) = match rest.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["concept", name] => (DLType::BaseConcept, name),
+            ["role", name] => (DLType::BaseRole, name),
+            _ => return String::from(" -- usage: declare concept|role <name>"),
+        };
+
+        if self.onto.declare_symbol(name, t) {
+            format!(" -- '{}' declared", name)
+        } else {
+            format!(" -- '{}' is already a symbol", name)
+        }
+    }
+
+    fn add(&mut self, item: &str) -> String {
+        if is_axiom(item) {
+            match string_to_tbi(item, self.onto.symbols()) {
+                Err(e) => format!(" -- couldn't parse the axiom: {}", &e),
+                Ok(tbis) => {
+                    let before = self.onto.tbox().len();
+                    self.onto.add_tbis_from_vec(&tbis);
+                    let added = self.onto.tbox().len() - before;
+
+                    if added > 0 {
+                        self.closures = Option::None;
+                    }
+
+                    format!(" -- {} axiom(s) added", added)
+                }
+            }
+        } else {
+            let abiq = match self.onto.abiq_from_string(item) {
+                Err(e) => return format!(" -- couldn't parse the assertion: {}", &e),
+                Ok(abiq) => abiq,
+            };
+
+            if self.onto.abox().is_none() {
+                self.onto.add_abox(AbqDllite::new(REPL_ABOX_NAME));
+            }
+
+            let abox = self.onto.abox().unwrap();
+
+            if abox.contains(&abiq) {
+                format!(" -- the assertion is already in '{}'", abox.name())
+            } else {
+                let name = abox.name();
+                self.onto.add_abi(&abiq);
+
+                format!(" -- assertion added to '{}'", name)
+            }
+        }
+    }
+
+    fn retract(&mut self, item: &str) -> String {
+        if is_axiom(item) {
+            match string_to_tbi(item, self.onto.symbols()) {
+                Err(e) => format!(" -- couldn't parse the axiom: {}", &e),
+                Ok(tbis) => {
+                    let removed = tbis.iter().filter(|tbi| self.onto.remove_tbi(tbi)).count();
+
+                    if removed > 0 {
+                        self.closures = Option::None;
+                    }
+
+                    format!(" -- {} axiom(s) retracted", removed)
+                }
+            }
+        } else {
+            match self.onto.abiq_from_string(item) {
+                Err(e) => format!(" -- couldn't parse the assertion: {}", &e),
+                Ok(abiq) => {
+                    if self.onto.remove_abi(&abiq) {
+                        String::from(" -- assertion retracted")
+                    } else {
+                        String::from(" -- the assertion is not in the current abox")
+                    }
+                }
+            }
+        }
+    }
+
+    fn entails(&mut self, item: &str) -> String {
+        if is_axiom(item) {
+            let tbis = match string_to_tbi(item, self.onto.symbols()) {
+                Err(e) => return format!(" -- couldn't parse the axiom: {}", &e),
+                Ok(tbis) => tbis,
+            };
+
+            self.update_closures(false);
+            let closure = self.onto.cln(true);
+
+            let entailed = tbis.iter().all(|tbi| {
+                tbi.is_trivial()
+                    || tbi.lside() == tbi.rside()
+                    || closure.contains(tbi)
+                    // an unsatisfiable left side is included in everything
+                    || closure
+                        .items()
+                        .iter()
+                        .any(|c| c.is_contradiction() && c.lside() == tbi.lside())
+            });
+
+            yes_or_no(entailed)
+        } else {
+            let abiq = match self.onto.abiq_from_string(item) {
+                Err(e) => return format!(" -- couldn't parse the assertion: {}", &e),
+                Ok(abiq) => abiq,
+            };
+
+            match self.complete_abox(false) {
+                Option::None => String::from(" -- there is no abox"),
+                Some(completed) => yes_or_no(completed.contains(&abiq)),
+            }
+        }
+    }
+
+    fn consistent(&mut self) -> String {
+        self.update_closures(false);
+
+        let mut lines: Vec<String> = Vec::new();
+        let unsatisfiable: Vec<String> = self
+            .onto
+            .cln(true)
+            .items()
+            .iter()
+            .filter(|tbi| tbi.is_contradiction() && !tbi.is_trivial())
+            .filter_map(|tbi| tbi_to_string(tbi, self.onto.symbols()))
+            .collect();
+
+        if unsatisfiable.is_empty() {
+            lines.push(String::from(" -- no contradictions in the tbox"));
+        } else {
+            lines.push(String::from(" -- contradictions in the tbox:"));
+
+            for tbi in unsatisfiable {
+                lines.push(format!("  {}", tbi));
+            }
+        }
+
+        if let Some(abox) = self.onto.abox() {
+            let (is_inconsistent, contradictions_op) = AbqDllite::is_inconsistent_refs_only(
+                abox.items_by_ref(),
+                self.onto.cln(false),
+                true,
+            );
+
+            if is_inconsistent {
+                lines.push(format!(" -- '{}' is inconsistent:", abox.name()));
+
+                for (tbi_op, abi_vec) in contradictions_op.unwrap_or_default() {
+                    lines.push(pretty_print_abiq_conflict(
+                        &tbi_op,
+                        &abi_vec,
+                        self.onto.symbols(),
+                    ));
+                }
+            } else {
+                lines.push(format!(" -- '{}' is consistent", abox.name()));
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    fn unravel(&mut self, item: &str) -> String {
+        let deduction_tree = true;
+
+        if is_axiom(item) {
+            let tbis = match string_to_tbi(item, self.onto.symbols()) {
+                Err(e) => return format!(" -- couldn't parse the axiom: {}", &e),
+                Ok(tbis) => tbis,
+            };
+
+            self.update_closures(deduction_tree);
+
+            let mut s = String::new();
+
+            for tbi in &tbis {
+                match self.find_in_closure(tbi) {
+                    Option::None => s.push_str(" -- the axiom is not in the closure\n"),
+                    Some(found) => s.push_str(&create_string_for_unravel_conflict_tbi(
+                        found,
+                        self.onto.symbols(),
+                        0,
+                    )),
+                }
+            }
+
+            s.trim_end().to_string()
+        } else {
+            let abiq = match self.onto.abiq_from_string(item) {
+                Err(e) => return format!(" -- couldn't parse the assertion: {}", &e),
+                Ok(abiq) => abiq,
+            };
+
+            match self.complete_abox(deduction_tree) {
+                Option::None => String::from(" -- there is no abox"),
+                Some(completed) => match completed.items().iter().find(|a| *a == &abiq) {
+                    Option::None => String::from(" -- the assertion is not a consequence"),
+                    Some(found) => {
+                        create_string_for_unravel_conflict_abiq(found, self.onto.symbols(), 0)
+                            .trim_end()
+                            .to_string()
+                    }
+                },
+            }
+        }
+    }
+
+    fn rank(&mut self, aggr_name: &str) -> String {
+        let aggr = match aggr_from_name(aggr_name) {
+            Ok(aggr) => aggr,
+            Err(e) => return format!(" -- {}", &e),
+        };
+
+        self.update_closures(false);
+
+        // the abox of the session keeps its credibilities, a copy is ranked
+        let mut abox = match self.onto.abox() {
+            Option::None => return String::from(" -- there is no abox"),
+            Some(abox) => abox.clone(),
+        };
+
+        let bound_settings = default_bound_settings();
+        let deduction_tree = false;
+        let use_concurrency = true;
+
+        rank_abox(
+            &self.onto,
+            &mut abox,
+            deduction_tree,
+            aggr,
+            bound_settings,
+            self.verbose,
+            use_concurrency,
+        );
+
+        let mut ranked: Vec<&AbiqDllite> = abox.items().iter().collect();
+        ranked.sort_by(|a, b| b.value().partial_cmp(&a.value()).unwrap());
+
+        ranked
+            .iter()
+            .filter_map(|abiq| abiq_to_string(abiq, self.onto.symbols(), false))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn save(&self, filename: &str) -> String {
+        if filename.is_empty() {
+            return String::from(" -- usage: save <file>");
+        }
+
+        let dont_write_trivial = true;
+        let mut s = symbols_to_native_string(self.onto.symbols());
+
+        s.push('\n');
+        s.push_str(
+            &tbox_to_native_string(self.onto.tbox(), self.onto.symbols(), dont_write_trivial)
+                .unwrap_or_default(),
+        );
+
+        if let Some(abox) = self.onto.abox() {
+            s.push('\n');
+            s.push_str(
+                &abox_to_native_string_quantum(abox, self.onto.symbols(), dont_write_trivial)
+                    .unwrap_or_default(),
+            );
+        }
+
+        if write_str_to_file(&s, filename) {
+            format!(" -- saved to {}", filename)
+        } else {
+            format!(" -- couldn't write to {}", filename)
+        }
+    }
+
+    fn find_in_closure(&self, tbi: &TbiDllite) -> Option<&TbiDllite> {
+        self.onto.cln(true).items().iter().find(|c| *c == tbi)
+    }
+
+    fn complete_abox(&mut self, deduction_tree: bool) -> Option<AbqDllite> {
+        self.update_closures(deduction_tree);
+
+        self.onto
+            .abox()
+            .map(|abox| abox.complete(self.onto.cln(true), deduction_tree, self.verbose))
+    }
+}
+
+/// The aggregation named as in the 'aggr' option, the sum if the name is empty or unknown.
+pub fn aggr_from_name(aggr_name: &str) -> Result<AggrBox, String> {
+    match AggrName::from_str(aggr_name) {
+        Ok(AggrName::Max) => Ok(Box::new(AGGR_MAX)),
+        Ok(AggrName::Min) => Ok(Box::new(AGGR_MIN)),
+        Ok(AggrName::Mean) => Ok(Box::new(AGGR_MEAN)),
+        Ok(AggrName::Count) => Ok(Box::new(AGGR_COUNT)),
+        Ok(AggrName::Expression(expression)) => match AggrExpression::parse(&expression) {
+            Ok(aggr_expression) => Ok(aggr_expression.to_aggregator()),
+            Err(e) => Err(format!("couldn't use the aggregation: {}", &e)),
+        },
+        _ => Ok(Box::new(AGGR_SUM)),
+    }
+}
+
+/// The bound of the 'rankab' task when no other is asked.
+pub fn default_bound_settings() -> BoundSettings {
+    let adjusters: Adjusters = (TOLERANCE, M_SCALE, B_TRANSLATE);
+
+    (
+        adjusters,
+        Method::CauchyCubic,
+        BoundAlgorithm::Interpolation,
+    )
+}
+
+fn is_axiom(item: &str) -> bool {
+    item.contains('<') || item.contains('=')
+}
+
+fn yes_or_no(answer: bool) -> String {
+    match answer {
+        true => String::from(" -- yes"),
+        false => String::from(" -- no"),
+    }
+}
@@ -0,0 +1,404 @@
+/*
+ © - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   input credibilities are estimates, how much do the values of a ranking depend on them?
+   - the derivative of each value with respect to each credibility, by finite differences:
+     the system of the ranking is solved again with one credibility moved a little, the
+     bound and the scale of the ranking are kept, otherwise moving the highest credibility
+     would move every value and the bound search would add its own noise
+   - the stability of the ordering: the ranking is done again with every credibility
+     perturbed at random, each ordering is compared to the original one with kendall tau,
+     the random generator has a fixed seed so the same abox always gives the same report
+   the conflicts between assertions don't depend on credibilities, they are found once
+*/
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+
+use crate::alg_math::bounds::BoundSettings;
+use crate::alg_math::utilities::kendall_tau;
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::ontology::{ConflictMatrixDict, OntologyDllite};
+use crate::dl_lite::string_formatter::abi_to_string;
+use crate::helper::{normalize_vector, rank_abox_from_conflicts, RankSystem};
+use crate::kb::knowledge_base::{ABox, SymbolDict};
+
+// relative step of the finite differences
+const SENSITIVITY_STEP: f64 = 0.0001;
+// relative noise (standard deviation) put on credibilities for the ordering stability
+const SENSITIVITY_NOISE: f64 = 0.05;
+// how many perturbed rankings are done
+const SENSITIVITY_SAMPLES: usize = 50;
+// seed of the perturbations
+const SENSITIVITY_SEED: u64 = 2021;
+// below this difference two kendall tau are the same
+const SENSITIVITY_TOLERANCE: f64 = 0.000000001;
+
+/// How the values of a ranked abox move with its credibilities: the derivative of
+/// each value with respect to each credibility and the kendall tau between the
+/// original ordering and the orderings of randomly perturbed credibilities.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensitivity {
+    values: Vec<f64>,
+    derivatives: Vec<f64>,
+    taus: Vec<f64>,
+    noise: f64,
+}
+
+impl Sensitivity {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn values(&self) -> &Vec<f64> {
+        &self.values
+    }
+
+    /// derivative of the value of assertion 'i' with respect to the credibility of assertion 'k'
+    pub fn derivative(&self, i: usize, k: usize) -> f64 {
+        self.derivatives[i * self.values.len() + k]
+    }
+
+    /// how much the value of assertion 'i' moves when all the credibilities move
+    pub fn total(&self, i: usize) -> f64 {
+        (0..self.len()).map(|k| self.derivative(i, k).abs()).sum()
+    }
+
+    /// the assertion whose credibility moves the value of 'i' the most, 'i' aside
+    pub fn strongest_input(&self, i: usize) -> Option<usize> {
+        (0..self.len())
+            .filter(|k| *k != i && self.derivative(i, *k) != 0.)
+            .max_by(|a, b| {
+                self.derivative(i, *a)
+                    .abs()
+                    .partial_cmp(&self.derivative(i, *b).abs())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+
+    /// indices of the assertions, the most sensitive first
+    pub fn most_sensitive(&self) -> Vec<usize> {
+        let mut indices = (0..self.len()).collect::<Vec<usize>>();
+
+        indices.sort_by(|a, b| {
+            self.total(*b)
+                .partial_cmp(&self.total(*a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        indices
+    }
+
+    pub fn taus(&self) -> &Vec<f64> {
+        &self.taus
+    }
+
+    pub fn mean_tau(&self) -> Option<f64> {
+        match self.taus.len() {
+            0 => Option::None,
+            l => Some(self.taus.iter().sum::<f64>() / (l as f64)),
+        }
+    }
+
+    pub fn min_tau(&self) -> Option<f64> {
+        self.taus
+            .iter()
+            .cloned()
+            .fold(Option::None, |m, t| match m {
+                Option::None => Some(t),
+                Some(m) => Some(f64::min(m, t)),
+            })
+    }
+
+    /// part of the perturbed rankings that kept exactly the original ordering
+    pub fn unchanged_ratio(&self) -> Option<f64> {
+        match self.taus.len() {
+            0 => Option::None,
+            l => {
+                let unchanged = self
+                    .taus
+                    .iter()
+                    .filter(|t| (1. - **t).abs() < SENSITIVITY_TOLERANCE)
+                    .count();
+                Some(unchanged as f64 / (l as f64))
+            }
+        }
+    }
+
+    /// the sensitivity table, only the 'limit' most sensitive assertions are shown
+    pub fn to_string_with_symbols(
+        &self,
+        abq: &AbqDllite,
+        symbols: &SymbolDict,
+        limit: usize,
+    ) -> String {
+        let name = |index: usize| {
+            abq.get(index)
+                .and_then(|abiq| abi_to_string(abiq.abi(), symbols))
+                .unwrap_or_default()
+        };
+
+        let mut s = String::from(
+            " -- sensitivity of the values to the credibilities, most sensitive first:\n",
+        );
+
+        for i in self.most_sensitive().into_iter().take(limit) {
+            s.push_str(&format!(
+                "    {}: value {:.6}, own {:+.6}, total {:.6}",
+                name(i),
+                self.values[i],
+                self.derivative(i, i),
+                self.total(i)
+            ));
+
+            if let Some(k) = self.strongest_input(i) {
+                s.push_str(&format!(
+                    ", most by {} ({:+.6})",
+                    name(k),
+                    self.derivative(i, k)
+                ));
+            }
+            s.push('\n');
+        }
+
+        if let (Some(mean), Some(min), Some(unchanged)) =
+            (self.mean_tau(), self.min_tau(), self.unchanged_ratio())
+        {
+            s.push_str(&format!(
+                " -- ordering stability over {} rankings with credibilities moved by {}%: mean kendall tau {:.4}, min {:.4}, same ordering in {:.0}%\n",
+                self.taus.len(),
+                self.noise * 100.,
+                mean,
+                min,
+                unchanged * 100.
+            ));
+        }
+
+        s
+    }
+}
+
+// the values of a ranking of abq with other credibilities, and its system if there was one
+fn values_with_credibilities<F: FnMut(Vec<f64>) -> f64>(
+    abq: &AbqDllite,
+    credibilities: &[f64],
+    conflicts: &ConflictMatrixDict,
+    aggr: &mut F,
+    bound_settings: BoundSettings,
+    verbose: bool,
+    use_concurrency: bool,
+) -> (Vec<f64>, Option<RankSystem>) {
+    let mut abq = abq.clone();
+
+    for (abiq, credibility) in abq.items_mut().iter_mut().zip(credibilities) {
+        abiq.set_credibility(*credibility);
+    }
+
+    let (_, system_op) = rank_abox_from_conflicts(
+        &mut abq,
+        conflicts.clone(),
+        aggr,
+        bound_settings,
+        verbose,
+        use_concurrency,
+    );
+
+    let values = abq
+        .items()
+        .iter()
+        .map(|abiq| abiq.value().unwrap_or(1.))
+        .collect();
+
+    (values, system_op)
+}
+
+// the values of the assertions in the system with other credibilities, the system keeps
+// its bound and its scale, the credibilities are normalized as the ranking did
+fn values_in_system<F: FnMut(Vec<f64>) -> f64>(
+    abq: &AbqDllite,
+    credibilities: &[f64],
+    normalization_scale: f64,
+    conflicts: &ConflictMatrixDict,
+    system: &RankSystem,
+    aggr: &mut F,
+) -> Option<Vec<(usize, f64)>> {
+    let (before_matrix, _, virtual_to_real) = conflicts;
+    let (done_matrix, _, _, _) = OntologyDllite::from_conflict_to_clean_matrix(before_matrix)?;
+
+    let mut abq = abq.clone();
+
+    for (abiq, credibility) in abq.items_mut().iter_mut().zip(credibilities) {
+        abiq.set_credibility(credibility / normalization_scale);
+    }
+
+    let aggr_matrix = OntologyDllite::compute_aggregation_matrix(
+        &abq,
+        &done_matrix,
+        virtual_to_real,
+        aggr,
+        false,
+    );
+
+    system.values_with_matrix(&aggr_matrix)
+}
+
+/// Sensitivity of the ranking of abq (not ranked yet) to its credibilities,
+/// the closure of the ontology must have been generated.
+pub fn sensitivity_analysis<F: FnMut(Vec<f64>) -> f64>(
+    onto: &OntologyDllite,
+    abq: &AbqDllite,
+    aggr: &mut F,
+    bound_settings: BoundSettings,
+    verbose: bool,
+    use_concurrency: bool,
+) -> Sensitivity {
+    let conflicts = onto.conflict_matrix_refs_only(abq, verbose);
+    let credibilities = abq
+        .items()
+        .iter()
+        .map(|abiq| abiq.credibility())
+        .collect::<Vec<f64>>();
+    let length = credibilities.len();
+
+    let (values, system_op) = values_with_credibilities(
+        abq,
+        &credibilities,
+        &conflicts,
+        aggr,
+        bound_settings,
+        verbose,
+        use_concurrency,
+    );
+
+    // one credibility at a time, only the assertions in the system can move
+    let mut derivatives: Vec<f64> = vec![0.; length * length];
+
+    if let Some(system) = &system_op {
+        let normalization_scale = normalize_vector(&mut credibilities.clone());
+        let base_op = values_in_system(
+            abq,
+            &credibilities,
+            normalization_scale,
+            &conflicts,
+            system,
+            aggr,
+        );
+
+        if let Some(base) = base_op {
+            for k in 0..length {
+                let step = SENSITIVITY_STEP * f64::max(credibilities[k].abs(), 1.);
+                let mut moved = credibilities.clone();
+                moved[k] += step;
+
+                let moved_op =
+                    values_in_system(abq, &moved, normalization_scale, &conflicts, system, aggr);
+
+                if let Some(moved_values) = moved_op {
+                    for ((i, value), (_, moved_value)) in base.iter().zip(moved_values) {
+                        derivatives[i * length + k] = (moved_value - value) / step;
+                    }
+                }
+            }
+        }
+    }
+
+    // every credibility at once, at random
+    let mut taus: Vec<f64> = Vec::new();
+    let normal = Normal::new(0., SENSITIVITY_NOISE).unwrap();
+    let mut rng = StdRng::seed_from_u64(SENSITIVITY_SEED);
+
+    for _ in 0..SENSITIVITY_SAMPLES {
+        let perturbed = credibilities
+            .iter()
+            .map(|c| f64::max(c * (1. + normal.sample(&mut rng)), 0.))
+            .collect::<Vec<f64>>();
+
+        let (perturbed_values, _) = values_with_credibilities(
+            abq,
+            &perturbed,
+            &conflicts,
+            aggr,
+            bound_settings,
+            verbose,
+            use_concurrency,
+        );
+
+        // all values equal in both: the ordering didn't move
+        taus.push(kendall_tau(&values, &perturbed_values).unwrap_or(1.));
+    }
+
+    Sensitivity {
+        values,
+        derivatives,
+        taus,
+        noise: SENSITIVITY_NOISE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alg_math::bounds::BoundAlgorithm;
+    use crate::alg_math::polynomial_roots::Method;
+    use crate::kb::aggr_functions::AGGR_SUM;
+    use crate::kb::types::FileType;
+    use crate::{B_TRANSLATE, M_SCALE, TOLERANCE};
+
+    fn university() -> (OntologyDllite, AbqDllite) {
+        let examples = format!("{}/examples", env!("CARGO_MANIFEST_DIR"));
+        let path_tbox = format!("{}/university_tbox", examples);
+
+        let mut onto = OntologyDllite::new(String::from("university_tbox"));
+        onto.add_symbols_from_file(&path_tbox, FileType::Native, false);
+        onto.add_tbis_from_file(&path_tbox, FileType::Native, false);
+        onto.new_abox_from_file_quantum(
+            &format!("{}/university_abox", examples),
+            FileType::Native,
+            false,
+        );
+        onto.generate_cln(false, false, 0);
+
+        let abq = onto.abox().unwrap().clone();
+        (onto, abq)
+    }
+
+    #[test]
+    fn report_is_reproducible() {
+        let (onto, abq) = university();
+        let bound_settings: BoundSettings = (
+            (TOLERANCE, M_SCALE, B_TRANSLATE),
+            Method::CauchyCubic,
+            BoundAlgorithm::Interpolation,
+        );
+
+        let mut aggr = AGGR_SUM;
+
+        let first = sensitivity_analysis(&onto, &abq, &mut aggr, bound_settings, false, false);
+        let second = sensitivity_analysis(&onto, &abq, &mut aggr, bound_settings, false, false);
+
+        assert_eq!(first.taus().len(), SENSITIVITY_SAMPLES);
+        assert_eq!(first, second);
+    }
+}
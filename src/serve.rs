@@ -0,0 +1,408 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   The 'serve' task: a json api over http, on localhost only. Ontologies are loaded
+   once under a name and kept, with their closures, between requests (each one is a
+   'Session' of 'repl.rs'). Requests are answered one at a time.
+       GET  /ontologies                         the loaded ontologies
+       POST /load      {name, tbox, symbols?, aboxes?}
+       POST /unload    {name}
+       POST /verify    {name, abox?}            contradictions of the tbox and the abox
+       POST /clean     {name, abox?}            clean and self conflicting assertions
+       POST /rank      {name, abox?, aggr?}     the ranking, best first
+       POST /unravel   {name, item}             how an axiom or assertion is deduced
+       POST /query     {name, abox?, query}     answers to a basic concept or role
+       POST /shutdown
+   Paths are the ones of the files on the machine of the server, without 'abox' the
+   current abox of the ontology is used. Errors come as {"error": "..."}.
+*/
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::abox_item_quantum::AbiqDllite;
+use crate::dl_lite::ontology::OntologyDllite;
+use crate::dl_lite::string_formatter::{
+    abi_to_string, node_to_string, string_to_node, tbi_to_string,
+};
+use crate::graph_export::conflict_type_name;
+use crate::helper::rank_abox;
+use crate::kb::knowledge_base::{ABox, SymbolDict, TBox, TBoxItem};
+use crate::ranking_diff::conflict_types;
+use crate::repl::{aggr_from_name, default_bound_settings, Reply, Session};
+
+// (status, body) of a response
+type Answer = (u16, Value);
+
+pub fn serve(port: u16, verbose: bool) {
+    let address = format!("127.0.0.1:{}", port);
+
+    let server = match Server::http(&address) {
+        Ok(server) => server,
+        Err(e) => {
+            println!("ERROR: couldn't listen on {}: {}", &address, &e);
+            std::process::exit(exitcode::UNAVAILABLE);
+        }
+    };
+
+    println!(" -- listening on http://{}", &address);
+
+    let mut sessions: HashMap<String, Session> = HashMap::new();
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let _ = request.as_reader().read_to_string(&mut body);
+
+        let method = request.method().clone();
+        let url = request.url().to_string();
+
+        let (status, answer) = match (&method, url.as_str()) {
+            (Method::Post, "/shutdown") => {
+                respond(request, (200, json!({ "shutdown": true })));
+                break;
+            }
+            (Method::Get, "/ontologies") => (200, ontologies(&sessions)),
+            (Method::Post, url) => match serde_json::from_str::<Value>(&body) {
+                Err(e) => error(400, &format!("the body is not json: {}", &e)),
+                Ok(body) => handle_post(&mut sessions, url, &body, verbose),
+            },
+            (_, url) => error(404, &format!("no endpoint {}", url)),
+        };
+
+        if verbose {
+            println!(" -- {} {} -> {}", &method, &url, status);
+        }
+
+        respond(request, (status, answer));
+    }
+}
+
+fn handle_post(
+    sessions: &mut HashMap<String, Session>,
+    url: &str,
+    body: &Value,
+    verbose: bool,
+) -> Answer {
+    let name = match string_field(body, "name") {
+        Ok(name) => name,
+        Err(answer) => return answer,
+    };
+
+    // the only endpoint that doesn't need a loaded ontology
+    if url == "/load" {
+        return load(sessions, name, body, verbose);
+    }
+
+    let session = match sessions.get_mut(name) {
+        Some(session) => session,
+        Option::None => return error(404, &format!("no ontology named '{}'", name)),
+    };
+
+    match url {
+        "/unload" => {
+            sessions.remove(name);
+            (200, json!({ "unloaded": name }))
+        }
+        "/verify" => verify(session, body, verbose),
+        "/clean" => clean(session, body),
+        "/rank" => rank(session, body, verbose),
+        "/unravel" => unravel(session, body),
+        "/query" => query(session, body),
+        _ => error(404, &format!("no endpoint {}", url)),
+    }
+}
+
+fn load(
+    sessions: &mut HashMap<String, Session>,
+    name: &str,
+    body: &Value,
+    verbose: bool,
+) -> Answer {
+    let path_tbox = match string_field(body, "tbox") {
+        Ok(path_tbox) => path_tbox,
+        Err(answer) => return answer,
+    };
+    let path_symbols_op = body["symbols"].as_str();
+    let path_aboxes: Vec<&str> = match &body["aboxes"] {
+        Value::Array(paths) => paths.iter().filter_map(|p| p.as_str()).collect(),
+        _ => Vec::new(),
+    };
+
+    match Session::from_files(path_tbox, path_symbols_op, &path_aboxes, verbose) {
+        Err(e) => error(400, &e),
+        Ok(session) => {
+            let description = describe(name, session.onto());
+
+            // a name already used is loaded again
+            sessions.insert(name.to_string(), session);
+            (200, description)
+        }
+    }
+}
+
+fn ontologies(sessions: &HashMap<String, Session>) -> Value {
+    let mut names: Vec<&String> = sessions.keys().collect();
+    names.sort();
+
+    Value::Array(
+        names
+            .into_iter()
+            .map(|name| describe(name, sessions[name].onto()))
+            .collect(),
+    )
+}
+
+fn describe(name: &str, onto: &OntologyDllite) -> Value {
+    json!({
+        "name": name,
+        "axioms": onto.tbox().len(),
+        "symbols": onto.symbols().len(),
+        "aboxes": onto.abox_names(),
+        "current_abox": onto.abox().map(|abox| abox.name()),
+    })
+}
+
+fn verify(session: &mut Session, body: &Value, verbose: bool) -> Answer {
+    let onto = session.onto_with_closures(false);
+    let symbols = onto.symbols();
+
+    let tbox_contradictions: Vec<String> = onto
+        .cln(true)
+        .items()
+        .iter()
+        .filter(|tbi| tbi.is_contradiction() && !tbi.is_trivial())
+        .filter_map(|tbi| tbi_to_string(tbi, symbols))
+        .collect();
+
+    let mut answer = json!({ "tbox_contradictions": tbox_contradictions });
+
+    // an ontology without abox only has its tbox verified
+    if onto.aboxes().is_empty() && body["abox"].is_null() {
+        return (200, answer);
+    }
+
+    let abox = match chosen_abox(onto, body) {
+        Ok(abox) => abox,
+        Err(answer) => return answer,
+    };
+
+    let (is_inconsistent, contradictions_op) =
+        AbqDllite::is_inconsistent_refs_only(abox.items_by_ref(), onto.cln(false), true);
+
+    let conflicts: Vec<Value> = contradictions_op
+        .unwrap_or_default()
+        .iter()
+        .map(|(tbi_op, abis)| {
+            json!({
+                "axiom": tbi_op.and_then(|tbi| tbi_to_string(tbi, symbols)),
+                "assertions": abis.iter().map(|abiq| assertion_to_string(abiq, symbols)).collect::<Vec<String>>(),
+            })
+        })
+        .collect();
+
+    if verbose {
+        println!(" -- {} conflicts in '{}'", conflicts.len(), abox.name());
+    }
+
+    answer["abox"] = json!(abox.name());
+    answer["consistent"] = json!(!is_inconsistent);
+    answer["conflicts"] = Value::Array(conflicts);
+
+    (200, answer)
+}
+
+fn clean(session: &mut Session, body: &Value) -> Answer {
+    let onto = session.onto_with_closures(false);
+
+    let abox = match chosen_abox(onto, body) {
+        Ok(abox) => abox,
+        Err(answer) => return answer,
+    };
+
+    let mut clean: Vec<String> = Vec::new();
+    let mut self_conflicting: Vec<String> = Vec::new();
+
+    for abiq in abox.items() {
+        let (is_self_conflict, _) =
+            AbqDllite::is_inconsistent_refs_only(vec![abiq], onto.cln(false), false);
+
+        if is_self_conflict {
+            self_conflicting.push(assertion_to_string(abiq, onto.symbols()));
+        } else {
+            clean.push(assertion_to_string(abiq, onto.symbols()));
+        }
+    }
+
+    (
+        200,
+        json!({
+            "abox": abox.name(),
+            "clean": clean,
+            "self_conflicting": self_conflicting,
+        }),
+    )
+}
+
+fn rank(session: &mut Session, body: &Value, verbose: bool) -> Answer {
+    let aggr = match aggr_from_name(body["aggr"].as_str().unwrap_or("")) {
+        Ok(aggr) => aggr,
+        Err(e) => return error(400, &e),
+    };
+
+    let onto = session.onto_with_closures(false);
+
+    let abox = match chosen_abox(onto, body) {
+        Ok(abox) => abox,
+        Err(answer) => return answer,
+    };
+
+    // the abox of the session keeps its credibilities, a copy is ranked
+    let mut ranked = abox.clone();
+    let deduction_tree = false;
+    let use_concurrency = true;
+
+    rank_abox(
+        onto,
+        &mut ranked,
+        deduction_tree,
+        aggr,
+        default_bound_settings(),
+        verbose,
+        use_concurrency,
+    );
+
+    let conflict_type = conflict_types(onto, abox, verbose);
+
+    let mut order: Vec<usize> = (0..ranked.len()).collect();
+    order.sort_by(|i, j| {
+        let value = |index: usize| ranked.items()[index].value();
+        value(*j).partial_cmp(&value(*i)).unwrap()
+    });
+
+    let assertions: Vec<Value> = order
+        .into_iter()
+        .map(|index| {
+            json!({
+                "assertion": assertion_to_string(&ranked.items()[index], onto.symbols()),
+                "credibility": abox.items()[index].credibility(),
+                "value": ranked.items()[index].value(),
+                "conflict_type": conflict_type_name(&conflict_type[index]),
+            })
+        })
+        .collect();
+
+    (
+        200,
+        json!({ "abox": abox.name(), "assertions": assertions }),
+    )
+}
+
+fn unravel(session: &mut Session, body: &Value) -> Answer {
+    let item = match string_field(body, "item") {
+        Ok(item) => item,
+        Err(answer) => return answer,
+    };
+
+    // the answers of the command are messages that begin with ' -- '
+    match session.execute(&format!("unravel {}", item)) {
+        Reply::Text(text) if !text.starts_with(" -- ") => {
+            (200, json!({ "item": item, "unravel": text }))
+        }
+        Reply::Text(text) => error(400, text.trim_start_matches(" -- ")),
+        Reply::Quit => error(400, "nothing to unravel"),
+    }
+}
+
+fn query(session: &mut Session, body: &Value) -> Answer {
+    let query_s = match string_field(body, "query") {
+        Ok(query_s) => query_s,
+        Err(answer) => return answer,
+    };
+
+    let onto = session.onto();
+
+    let query = match string_to_node(query_s, onto.symbols()) {
+        Ok(query) => query,
+        Err(e) => return error(400, &format!("couldn't parse the query: {}", &e)),
+    };
+
+    let abox = match chosen_abox(onto, body) {
+        Ok(abox) => abox,
+        Err(answer) => return answer,
+    };
+
+    let mut answers: Vec<Vec<String>> = abox
+        .answer(&query, onto.tbox())
+        .iter()
+        .map(|answer| {
+            answer
+                .iter()
+                .filter_map(|node| node_to_string(node, onto.symbols(), String::new()))
+                .collect()
+        })
+        .collect();
+    answers.sort();
+
+    (
+        200,
+        json!({ "abox": abox.name(), "query": query_s, "answers": answers }),
+    )
+}
+
+// the abox named in the request or the current one
+fn chosen_abox<'a>(onto: &'a OntologyDllite, body: &Value) -> Result<&'a AbqDllite, Answer> {
+    match body["abox"].as_str() {
+        Some(name) => onto
+            .abox_by_name(name)
+            .ok_or_else(|| error(404, &format!("no abox named '{}'", name))),
+        Option::None => onto
+            .abox()
+            .ok_or_else(|| error(404, "the ontology has no abox")),
+    }
+}
+
+fn string_field<'a>(body: &'a Value, key: &str) -> Result<&'a str, Answer> {
+    body[key]
+        .as_str()
+        .ok_or_else(|| error(400, &format!("the field '{}' is missing", key)))
+}
+
+fn assertion_to_string(abiq: &AbiqDllite, symbols: &SymbolDict) -> String {
+    abi_to_string(abiq.abi(), symbols).unwrap_or_else(|| format!("{}", abiq.abi()))
+}
+
+fn error(status: u16, message: &str) -> Answer {
+    (status, json!({ "error": message }))
+}
+
+fn respond(request: Request, answer: Answer) {
+    let (status, body) = answer;
+    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header);
+
+    if let Err(e) = request.respond(response) {
+        println!(" -- couldn't answer a request: {}", &e);
+    }
+}
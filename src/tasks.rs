@@ -1,44 +1,98 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use petgraph::dot::{Config, Dot};
+use petgraph::graph::NodeIndex;
+use petgraph::Graph;
 use question::{Answer, Question};
+use rusqlite::Connection;
 use tempfile::NamedTempFile;
 
-use crate::alg_math::bounds::Adjusters;
+use crate::alg_math::bounds::{Adjusters, BoundAlgorithm, BoundSettings};
+use crate::alg_math::polynomial_roots::Method;
 
 use crate::alg_math::utilities::null_vector;
 use crate::dl_lite::abox::AbqDllite;
-use crate::dl_lite::native_filetype_utilities::abox_to_native_string_quantum;
+use crate::dl_lite::chase::CanonicalModelDllite;
+use crate::dl_lite::explanation::{find_assertion, RankExplanation};
+use crate::dl_lite::json_filetype_utilities::parse_tbox_json;
+use crate::dl_lite::native_filetype_utilities::{
+    abox_to_native_string_quantum, parse_tbox_native, parse_tbox_native_checked,
+    symbols_to_native_string, tbox_to_native_string,
+};
+use crate::dl_lite::obda::{parse_mappings_native, ObdaDllite};
 use crate::dl_lite::ontology::OntologyDllite;
+use crate::dl_lite::sqlite_interface::{parse_tbox_sqlite, RankingRun};
 use crate::dl_lite::string_formatter::create_string_for_unravel_conflict_abox;
 use crate::dl_lite::string_formatter::pretty_print_abiq_conflict;
+use crate::dl_lite::string_formatter::string_to_node;
 use crate::dl_lite::string_formatter::{
     create_string_for_gencontb, create_string_for_unravel_conflict_tbox, tbi_to_string,
 };
+use crate::dl_lite::tbox::TBDllite;
 use crate::dl_lite::tbox_item::TbiDllite;
-use crate::dl_lite::utilities::create_aboxq_graph_dot;
+use crate::dl_lite::trust::{apply_trust, source_feedback, sources_to_native_string};
+use crate::dl_lite::utilities::{create_aboxq_graph, filter_aboxq_graph, value_range, GraphFilter};
+use crate::exact_ranking::{rank_abox_exact, EXACT_MAX_DIMENSION};
+use crate::graph_export::{graph_to_gexf, graph_to_graphml, graph_to_json, graph_to_mermaid};
 use crate::graph_maker::{
     create_graph_for_aboxq_unraveling, create_graph_for_tbox_unraveling, edge_attr_tbox_unraveling,
-    node_attr_abox_unraveling, node_attr_tbox_unraveling,
+    node_attr_abox_unraveling, node_attr_tbox_unraveling, EdgeKind, GraphNode,
 };
-use crate::helper::{command_exists, edge_attr, node_attr, rank_abox};
+use crate::graph_render::{EdgeAttrGetter, Layout, NodeAttrGetter};
+use crate::helper::{command_exists, edge_attr, node_attr, rank_abox_with_system};
+use crate::html_report::HtmlReport;
 use crate::interface::cli::AggrName;
+use crate::interface::cli::BoundName;
+use crate::interface::cli::CombineName;
+use crate::interface::cli::ExportName;
+use crate::interface::cli::RenderName;
 use crate::interface::cli::Task;
 use crate::interface::utilities::{get_filetype, parse_name_from_filename, write_str_to_file};
-use crate::kb::aggr_functions::{AGGR_COUNT, AGGR_MAX, AGGR_MEAN, AGGR_MIN, AGGR_SUM};
+use crate::kb::aggr_expression::{AggrExpression, EXPRESSION_PREFIX};
+use crate::kb::aggr_functions::{
+    AGGR_COUNT, AGGR_MAX, AGGR_MEAN, AGGR_MIN, AGGR_NOISY_OR, AGGR_SUM,
+};
 use crate::kb::knowledge_base::ABox;
+use crate::kb::knowledge_base::AggrBox;
+use crate::kb::knowledge_base::{SourceDict, SymbolDict};
 use crate::kb::knowledge_base::{TBox, TBoxItem};
+use crate::kb::types::{ConflictType, FileType};
+use crate::lint::{lint_native, NamedText};
+use crate::native_fmt::format_native;
+use crate::ranking_diff::{conflict_types, RankingDiff};
+use crate::sensitivity::sensitivity_analysis;
+use crate::tbox_diff::TBoxDiff;
 use crate::{
-    ABoxRelatedPaths, TBoxRelatedPaths, B_TRANSLATE, COMMAND_SHELL_LINUX, COMMAND_SHELL_WINDOWS,
-    DOT_COMMAND_LINUX, DOT_COMMAND_WINDOWS, M_SCALE, TOLERANCE,
+    ABoxRelatedOptions, ABoxRelatedPaths, GraphOptions, NativeRelatedPaths, OutputPaths,
+    RankOptions, RankingDiffPaths, TBoxDiffPaths, TBoxRelatedPaths, B_TRANSLATE,
+    COMMAND_SHELL_LINUX, COMMAND_SHELL_WINDOWS, DOT_COMMAND_LINUX, DOT_COMMAND_WINDOWS, M_SCALE,
+    TOLERANCE,
 };
 
+// name of the abox made of all the aboxes given when none is chosen
+const MERGED_ABOX_NAME: &str = "merged";
+// how many influences an explanation shows
+const EXPLANATION_LENGTH: usize = 10;
+// how many assertions the sensitivity table shows
+const SENSITIVITY_LENGTH: usize = 20;
+// how many top assertions two rankings compare, and how many shifts are shown
+const DIFF_TOP_K: usize = 10;
+const DIFF_SHIFTS_LENGTH: usize = 10;
+
 // ===============================================================================================
 // THESE ARE THE TASKS RELATED TO TBOXES
 
-pub fn task_tbox_related(tbox_paths: TBoxRelatedPaths, task: Task, verbose: bool, silent: bool) {
-    let (path_to_tbox_op, path_to_symbols_op, path_output_op) = tbox_paths;
+pub fn task_tbox_related(
+    tbox_paths: TBoxRelatedPaths,
+    task: Task,
+    graph_options: GraphOptions,
+    verbose: bool,
+    silent: bool,
+) {
+    let (path_to_tbox_op, path_to_symbols_op, path_output_op, path_report_op) = tbox_paths;
 
     if let Some(path_tbox) = path_to_tbox_op {
         // get the information from the file: name and bla bla
@@ -46,34 +100,56 @@ pub fn task_tbox_related(tbox_paths: TBoxRelatedPaths, task: Task, verbose: bool
         let tb_ft = get_filetype(&path_tbox);
         let tb_name = parse_name_from_filename(&path_tbox);
 
-        // create a temporal ontology
-        let mut onto = OntologyDllite::new(String::from(tb_name));
+        // mintb also tells which axioms of a native file are written several times
+        let mut repeated: Vec<(TbiDllite, usize)> = Vec::new();
+
+        // create a temporal ontology, a database already has everything
+        let mut onto = if tb_ft == FileType::Sqlite {
+            ontology_from_db_or_exit(&path_tbox, verbose)
+        } else {
+            let mut onto = OntologyDllite::new(String::from(tb_name));
 
-        // get symbols from this file if possible
-        match path_to_symbols_op {
-            Some(path_symbols) => {
-                let path_symbols = path_symbols.to_str().unwrap();
-                let symbols_ft = get_filetype(path_symbols);
+            // get symbols from this file if possible
+            match path_to_symbols_op {
+                Some(path_symbols) => {
+                    let path_symbols = path_symbols.to_str().unwrap();
+                    let symbols_ft = get_filetype(path_symbols);
 
-                onto.add_symbols_from_file(path_symbols, symbols_ft, verbose);
+                    onto.add_symbols_from_file(path_symbols, symbols_ft, verbose);
+                }
+                Option::None => {
+                    // else add from the tbox file
+                    onto.add_symbols_from_file(&path_tbox, tb_ft, verbose);
+                }
             }
-            Option::None => {
-                // else add from the tbox file
-                onto.add_symbols_from_file(&path_tbox, tb_ft, verbose);
+
+            // now add tbis from the tbox file
+            if matches!(task, Task::MinTB) && tb_ft == FileType::Native {
+                let (tb, found) = tbox_native_checked_or_exit(&path_tbox, onto.symbols(), verbose);
+
+                onto.add_tbis_from_vec(tb.items());
+                repeated = found;
+            } else {
+                onto.add_tbis_from_file(&path_tbox, tb_ft, verbose);
             }
-        }
 
-        // now add tbis from the tbox file
-        onto.add_tbis_from_file(&path_tbox, tb_ft, verbose);
+            onto
+        };
 
         // now we can pass the necessary information to each function
         match task {
-            Task::VerTB => task_verify_tbox(&mut onto, verbose, silent),
-            Task::GenConTB => {
-                task_generate_consequences_tbox(&mut onto, path_output_op, tb_name, verbose, silent)
-            }
+            Task::VerTB => task_verify_tbox(&mut onto, path_report_op, verbose, silent),
+            Task::GenConTB => task_generate_consequences_tbox(
+                &mut onto,
+                path_output_op,
+                tb_name,
+                graph_options,
+                verbose,
+                silent,
+            ),
+            Task::MinTB => task_minimize_tbox(&onto, &repeated, path_output_op, verbose, silent),
             _ => {
-                println!("ERROR: you must provide a tbox related task: 'verify' or  'generate consequences'");
+                println!("ERROR: you must provide a tbox related task: 'verify', 'generate consequences' or 'minimize'");
                 std::process::exit(exitcode::USAGE);
             }
         }
@@ -84,7 +160,17 @@ pub fn task_tbox_related(tbox_paths: TBoxRelatedPaths, task: Task, verbose: bool
 }
 
 // this function seem good to me
-pub fn task_verify_tbox(onto: &mut OntologyDllite, verbose: bool, silent: bool) {
+pub fn task_verify_tbox(
+    onto: &mut OntologyDllite,
+    path_report_op: &Option<PathBuf>,
+    verbose: bool,
+    silent: bool,
+) {
+    // the report is written whatever the answers to the questions
+    if let Some(path_report) = path_report_op {
+        write_tbox_report(onto, path_report, verbose, silent);
+    }
+
     let mut deduction_tree = false;
 
     /*
@@ -170,6 +256,7 @@ pub fn task_generate_consequences_tbox(
     onto: &mut OntologyDllite,
     path_output_op: &Option<PathBuf>,
     tbox_name: &str,
+    graph_options: GraphOptions,
     verbose: bool,
     silent: bool,
 ) {
@@ -198,7 +285,8 @@ pub fn task_generate_consequences_tbox(
 
     if print_output == Answer::YES {
         // TODO: I'm here
-        let graph = create_graph_for_tbox_unraveling(full_closure, onto.symbols());
+        let typed_graph = create_graph_for_tbox_unraveling(full_closure, onto.symbols());
+        let graph = typed_graph.map(|_, n| n.to_string(), |_, _| ());
 
         let get_edge = edge_attr_tbox_unraveling;
         let get_node = node_attr_tbox_unraveling;
@@ -215,9 +303,13 @@ pub fn task_generate_consequences_tbox(
             println!(" -- dot file created: {}", &filename);
         }
 
-        //create graph here
-        // here verify that the command exists
-        let dot_command_name = find_and_verify_dot_command();
+        let (render_op, exports, _) = graph_options;
+
+        if !exports.is_empty() {
+            let filename = format!("{}_consequences", tbox_name);
+
+            write_graph_exports(&typed_graph, exports, &filename, silent);
+        }
 
         // now show graph
         let question_print = " -- do you want to generate a visual output?";
@@ -225,9 +317,10 @@ pub fn task_generate_consequences_tbox(
         let print_output = ask_question(question_print);
 
         if print_output == Answer::YES {
-            generate_visual_and_dot_output(
+            generate_visual_output(
+                (&graph, &get_edge, &get_node),
                 &dot_notation_output,
-                dot_command_name,
+                render_op,
                 tbox_name,
                 silent,
                 false,
@@ -241,6 +334,151 @@ pub fn task_generate_consequences_tbox(
     std::process::exit(exitcode::OK);
 }
 
+pub fn task_minimize_tbox(
+    onto: &OntologyDllite,
+    repeated: &[(TbiDllite, usize)],
+    path_output_op: &Option<PathBuf>,
+    verbose: bool,
+    silent: bool,
+) {
+    let (reduced, removed) = onto.tbox().minimize(verbose);
+
+    // the reduced tbox first, then why each axiom was removed, as comments
+    let dont_write_trivial = false;
+    let mut minimized_string = symbols_to_native_string(onto.symbols());
+
+    minimized_string.push('\n');
+    minimized_string.push_str(
+        &tbox_to_native_string(&reduced, onto.symbols(), dont_write_trivial).unwrap_or_default(),
+    );
+
+    if !removed.is_empty() || !repeated.is_empty() {
+        minimized_string.push_str("\n// removed, they follow from the axioms above:\n");
+    }
+
+    for (tbi, first) in repeated {
+        minimized_string.push_str(&format!(
+            "// {}\n//     already at line {}\n",
+            tbi_to_string(tbi, onto.symbols()).unwrap_or_default(),
+            first
+        ));
+    }
+
+    for (tbi, grounds) in &removed {
+        let tbi_string = tbi_to_string(tbi, onto.symbols()).unwrap_or_default();
+
+        let because = if tbi.is_trivial() {
+            String::from("always holds")
+        } else if tbi.is_redundant() {
+            String::from("says nothing")
+        } else {
+            let grounds_string: Vec<String> = grounds
+                .iter()
+                .filter_map(|ground| tbi_to_string(ground, onto.symbols()))
+                .collect();
+
+            format!("from: {}", grounds_string.join(", "))
+        };
+
+        minimized_string.push_str(&format!("// {}\n//     {}\n", tbi_string, because));
+    }
+
+    if !silent {
+        println!("{}", &minimized_string);
+        println!(
+            " -- {} axiom(s) removed, {} kept",
+            removed.len() + repeated.len(),
+            reduced.len()
+        );
+    }
+
+    write_output_op_to_file(path_output_op, &minimized_string);
+
+    std::process::exit(exitcode::OK);
+}
+
+pub fn task_diff_tbox(diff_paths: TBoxDiffPaths, verbose: bool, silent: bool) {
+    let (path_before_op, path_after_op, path_symbols_op, path_aboxes, path_output_op) = diff_paths;
+
+    let (path_before, path_after) = match (path_before_op, path_after_op) {
+        (Some(before), Some(after)) => (
+            before.to_str().unwrap().to_string(),
+            after.to_str().unwrap().to_string(),
+        ),
+        _ => {
+            println!("ERROR: the 'difftb' task compares two tboxes, give the 'tbox' and 'tbox-after' options");
+            std::process::exit(exitcode::USAGE);
+        }
+    };
+
+    // a missing tbox would be compared as an empty one
+    for path_tbox in [&path_before, &path_after] {
+        if !Path::new(path_tbox).is_file() {
+            println!("ERROR: the tbox file {} doesn't exist", path_tbox);
+            std::process::exit(exitcode::NOINPUT);
+        }
+    }
+
+    let before_name = parse_name_from_filename(&path_before);
+    let after_name = parse_name_from_filename(&path_after);
+
+    // both tboxes are read over the same symbols: the ones of both files merged
+    let mut onto = OntologyDllite::new(String::from(after_name));
+
+    match path_symbols_op {
+        Some(path_symbols) => {
+            let path_symbols = path_symbols.to_str().unwrap();
+            onto.add_symbols_from_file(path_symbols, get_filetype(path_symbols), verbose);
+        }
+        Option::None => {
+            onto.add_symbols_from_file(&path_before, get_filetype(&path_before), verbose);
+            onto.add_symbols_from_file(&path_after, get_filetype(&path_after), verbose);
+        }
+    }
+
+    // an axiom the parser skips would be left out of the comparison
+    let read_tbox = |path_tbox: &str| match get_filetype(path_tbox) {
+        FileType::Native => tbox_native_checked_or_exit(path_tbox, onto.symbols(), verbose).0,
+        _ => tbox_from_file_or_exit(path_tbox, onto.symbols(), verbose),
+    };
+
+    let before = read_tbox(&path_before);
+    let after = read_tbox(&path_after);
+
+    for path_abox in path_aboxes {
+        let path_abox = path_abox.to_str().unwrap();
+        onto.new_abox_from_file_quantum(path_abox, get_filetype(path_abox), verbose);
+    }
+
+    let diff = TBoxDiff::new(
+        (before_name, &before),
+        (after_name, &after),
+        onto.aboxes(),
+        verbose,
+    );
+
+    print!("{}", diff.to_string_with_symbols(onto.symbols()));
+
+    // the report goes to the output, as json for a '.json' file
+    if let Some(path_output) = path_output_op {
+        let path_output_str = path_output.to_str().unwrap();
+
+        let report = if get_filetype(path_output_str) == FileType::Json {
+            serde_json::to_string_pretty(&diff.to_json(onto.symbols())).unwrap()
+        } else {
+            diff.to_string_with_symbols(onto.symbols())
+        };
+
+        write_output_op_to_file(path_output_op, &report);
+
+        if !silent {
+            println!(" -- report written to {}", path_output_str);
+        }
+    }
+
+    std::process::exit(exitcode::OK);
+}
+
 // this function is not needed at the moment
 // pub fn task_complete_tbox(_onto: &mut OntologyDllite) {}
 
@@ -249,14 +487,25 @@ pub fn task_generate_consequences_tbox(
 
 pub fn task_abox_related(
     abox_paths: ABoxRelatedPaths,
-    aggr_name_op: &Option<AggrName>,
+    abox_options: ABoxRelatedOptions,
     task: Task,
     verbose: bool,
     silent: bool,
 ) {
-    let (path_abox_op, path_tbox_op, path_symbols_op, path_output_op) = abox_paths;
-
-    if let (Some(path_abox), Some(path_tbox)) = (path_abox_op, path_tbox_op) {
+    let (
+        path_aboxes,
+        path_tbox_op,
+        path_symbols_op,
+        path_output_op,
+        path_mappings_op,
+        path_sources_op,
+        path_report_op,
+        path_tbox_after_op,
+    ) = abox_paths;
+    let output_paths: OutputPaths = (path_output_op, path_report_op);
+    let (abox_name_op, chase_depth, query_op, rank_options, graph_options) = abox_options;
+
+    if let (Some(path_abox), Some(path_tbox)) = (path_aboxes.first(), path_tbox_op) {
         println!(" -- be sure to have an abox without self conflicting facts before going further, you can use the 'cleanab' task for this");
 
         // get information for the tbox
@@ -264,55 +513,163 @@ pub fn task_abox_related(
         let onto_name = parse_name_from_filename(&path_tbox);
         let tb_filetype = get_filetype(&path_tbox);
 
-        let mut onto = OntologyDllite::new(onto_name.to_string());
+        let mut onto = if tb_filetype == FileType::Sqlite {
+            ontology_from_db_or_exit(&path_tbox, verbose)
+        } else {
+            let mut onto = OntologyDllite::new(onto_name.to_string());
 
-        // add symbols from where you can
-        if let Some(some_symbols_path) = path_symbols_op {
-            let path_symbols = some_symbols_path.to_str().unwrap().to_string();
-            let symbols_filetype = get_filetype(&path_symbols);
+            // add symbols from where you can
+            if let Some(some_symbols_path) = path_symbols_op {
+                let path_symbols = some_symbols_path.to_str().unwrap().to_string();
+                let symbols_filetype = get_filetype(&path_symbols);
 
-            onto.add_symbols_from_file(&path_symbols, symbols_filetype, verbose);
-        } else {
-            onto.add_symbols_from_file(&path_tbox, tb_filetype, verbose);
-        }
+                onto.add_symbols_from_file(&path_symbols, symbols_filetype, verbose);
+            } else {
+                onto.add_symbols_from_file(&path_tbox, tb_filetype, verbose);
+            }
+
+            // add tbis
+            onto.add_tbis_from_file(&path_tbox, tb_filetype, verbose);
 
-        // add tbis
-        onto.add_tbis_from_file(&path_tbox, tb_filetype, verbose);
+            onto
+        };
 
-        // path and name of the abox
+        // path of the (first) abox
         let path_abox = path_abox.to_str().unwrap().to_string();
-        let ab_name = parse_name_from_filename(&path_abox).trim().to_string();
         let ab_ft = get_filetype(&path_abox);
 
         // reserved name
-        if ab_name == "temp_abox" {
-            println!("ERROR: the name 'temp_abox' is reserved, please use another one");
-            std::process::exit(exitcode::USAGE);
+        for name in path_aboxes
+            .iter()
+            .map(|p| parse_name_from_filename(p.to_str().unwrap()).trim())
+            .chain(abox_name_op.as_deref())
+        {
+            if name == "temp_abox" {
+                println!("ERROR: the name 'temp_abox' is reserved, please use another one");
+                std::process::exit(exitcode::USAGE);
+            }
+        }
+
+        // with mappings the abox stays in the database, nothing is loaded
+        if let Some(path_mappings) = path_mappings_op {
+            if ab_ft != FileType::Sqlite {
+                println!("ERROR: mappings are only supported over a sqlite database ('.db' or '.sqlite' file)");
+                std::process::exit(exitcode::USAGE);
+            }
+
+            let conn = match Connection::open(&path_abox) {
+                Ok(conn) => conn,
+                Err(e) => {
+                    println!("ERROR: couldn't open the database {}: {}", &path_abox, &e);
+                    std::process::exit(exitcode::NOINPUT);
+                }
+            };
+
+            let path_mappings = path_mappings.to_str().unwrap().to_string();
+            let obda = obda_from_file_or_exit(&path_mappings, onto.symbols(), &conn, verbose);
+
+            match task {
+                Task::VerAB => {
+                    task_verify_obda(&mut onto, &obda, &conn, path_output_op, verbose, silent)
+                }
+                Task::QueryAB => {
+                    task_query_obda(&onto, &obda, &conn, query_op, path_output_op, verbose)
+                }
+                _ => {
+                    println!(
+                        "ERROR: only the 'verab' and 'queryab' tasks can be done through mappings"
+                    );
+                    std::process::exit(exitcode::USAGE);
+                }
+            }
+        }
+
+        // add aboxes, the task is done on the current one
+        if path_aboxes.len() == 1 {
+            onto.new_named_abox_from_file_quantum(
+                &path_abox,
+                ab_ft,
+                abox_name_op.as_deref(),
+                verbose,
+            );
+        } else {
+            for some_path_abox in path_aboxes {
+                let some_path_abox = some_path_abox.to_str().unwrap().to_string();
+                let some_ab_ft = get_filetype(&some_path_abox);
+
+                onto.new_abox_from_file_quantum(&some_path_abox, some_ab_ft, verbose);
+            }
+
+            // either one of them is chosen or all of them are merged
+            let chosen = match abox_name_op.as_deref() {
+                Some(name) => onto.set_current_abox(name),
+                Option::None => false,
+            };
+
+            if !chosen {
+                let merged_name = abox_name_op.as_deref().unwrap_or(MERGED_ABOX_NAME);
+                let merged = onto.merge_aboxes(&[], merged_name).unwrap();
+                let given = onto.add_abox(merged);
+
+                if given != merged_name {
+                    println!(
+                        "warning: an abox named '{}' is already loaded, the merged abox is named '{}'",
+                        merged_name, given
+                    );
+                }
+            }
+        }
+
+        // trust declared apart from the aboxes
+        if let Some(path_sources) = path_sources_op {
+            let path_sources = path_sources.to_str().unwrap().to_string();
+            let sources_filetype = get_filetype(&path_sources);
+
+            onto.add_trust_from_file(&path_sources, sources_filetype, verbose);
         }
 
-        // add abox
-        onto.new_abox_from_file_quantum(&path_abox, ab_ft, verbose);
+        let ab_name = match onto.abox() {
+            Some(abox) => abox.name(),
+            Option::None => {
+                println!("ERROR: the abox was not created, maybe run with 'verbose' option to see what happened");
+                std::process::exit(exitcode::CANTCREAT);
+            }
+        };
 
         match task {
-            Task::VerAB => task_verify_abox(&mut onto, path_output_op, verbose, silent),
+            Task::VerAB => task_verify_abox(&mut onto, output_paths, verbose, silent),
             Task::CleanAB => task_clean_abox(&mut onto, &ab_name, verbose, silent),
             Task::GenConAB => task_generate_consequences_abox(
                 &mut onto,
                 path_output_op,
                 &ab_name,
+                graph_options,
                 verbose,
                 silent,
             ),
             Task::RankAB => task_rank_abox(
                 &mut onto,
-                path_output_op,
-                aggr_name_op,
+                output_paths,
+                rank_options,
                 &ab_name,
+                graph_options,
                 verbose,
                 silent,
             ),
+            Task::ChaseAB => {
+                task_chase_abox(&mut onto, path_output_op, chase_depth, verbose, silent)
+            }
+            Task::QueryAB => {
+                println!("ERROR: the 'queryab' task needs a database and its mappings, use the 'mappings' option");
+                std::process::exit(exitcode::USAGE);
+            }
+            Task::MergeAB => task_merge_abox(&mut onto, path_output_op, verbose, silent),
+            Task::DiffRank => {
+                let diff_paths = (path_aboxes, path_tbox_after_op, path_output_op);
+                task_diff_rankings(&mut onto, diff_paths, verbose, silent)
+            }
             _ => {
-                println!("ERROR: you must provide a abox related task: 'verify', 'clean', 'generate consequences' or 'rank'");
+                println!("ERROR: you must provide a abox related task: 'verify', 'clean', 'generate consequences', 'rank' or 'chase'");
                 std::process::exit(exitcode::USAGE);
             }
         }
@@ -324,10 +681,30 @@ pub fn task_abox_related(
 
 pub fn task_verify_abox(
     onto: &mut OntologyDllite,
-    path_output_op: &Option<PathBuf>,
+    output_paths: OutputPaths,
     verbose: bool,
     silent: bool,
 ) {
+    let (path_output_op, path_report_op) = output_paths;
+
+    // the report is written whatever the answers to the questions
+    if let Some(path_report) = path_report_op {
+        // the positive closure gives the implications of the conflict graph
+        onto.generate_cln(false, verbose, 0_i8);
+
+        let abox = onto.abox().unwrap();
+        let conflict_type: HashMap<usize, ConflictType> = conflict_types(onto, abox, verbose)
+            .into_iter()
+            .enumerate()
+            .collect();
+
+        let mut report = HtmlReport::new(&format!("verab: {}", abox.name()));
+        report.add_assertions(abox, onto.symbols(), &conflict_type);
+        add_conflicts_to_report(&mut report, onto, abox, &conflict_type, &[], verbose);
+
+        write_report(&report, path_report, silent);
+    }
+
     // first create the negative closure
     let deduction_tree = false;
     let negative_only = -1_i8;
@@ -348,6 +725,14 @@ pub fn task_verify_abox(
         } else {
             println!(" -- contradictions were found");
 
+            // a merge of aboxes knows where each assertion comes from
+            if !abox.sources().is_empty() {
+                println!(
+                    " -- {} of them have members coming from different aboxes",
+                    onto.cross_abox_conflicts(abox).len()
+                );
+            }
+
             if !silent {
                 // show contradictions
                 let question_print = " -- do you want to see them";
@@ -528,6 +913,7 @@ pub fn task_generate_consequences_abox(
     onto: &mut OntologyDllite,
     path_output_op: &Option<PathBuf>,
     ab_name: &str,
+    graph_options: GraphOptions,
     verbose: bool,
     silent: bool,
 ) {
@@ -567,7 +953,9 @@ pub fn task_generate_consequences_abox(
         // TODO: I'm here
         let new_tb = onto.tbox();
 
-        let graph = create_graph_for_aboxq_unraveling(&abox_completed, new_tb, onto.symbols());
+        let typed_graph =
+            create_graph_for_aboxq_unraveling(&abox_completed, new_tb, onto.symbols());
+        let graph = typed_graph.map(|_, n| n.to_string(), |_, _| ());
 
         let get_edge = edge_attr_tbox_unraveling;
         let get_node = node_attr_abox_unraveling;
@@ -584,16 +972,23 @@ pub fn task_generate_consequences_abox(
             println!(" -- dot file created: {}", &filename);
         }
 
-        let dot_command_name = find_and_verify_dot_command();
+        let (render_op, exports, _) = graph_options;
+
+        if !exports.is_empty() {
+            let filename = format!("{}_consequences", &ab_name);
+
+            write_graph_exports(&typed_graph, exports, &filename, silent);
+        }
 
         // now show graph
         let question_print = " -- do you want see a generate a visual output?";
         let print_output = ask_question(question_print);
 
         if print_output == Answer::YES {
-            generate_visual_and_dot_output(
+            generate_visual_output(
+                (&graph, &get_edge, &get_node),
                 &dot_notation_output,
-                dot_command_name,
+                render_op,
                 ab_name,
                 silent,
                 false,
@@ -609,26 +1004,46 @@ pub fn task_generate_consequences_abox(
 
 pub fn task_rank_abox(
     onto: &mut OntologyDllite,
-    path_output_op: &Option<PathBuf>,
-    aggr_name_op: &Option<AggrName>,
+    output_paths: OutputPaths,
+    rank_options: RankOptions,
     ab_name: &str,
+    graph_options: GraphOptions,
     verbose: bool,
     silent: bool,
 ) {
+    let (path_output_op, path_report_op) = output_paths;
+    let (aggr_name_op, combine_name_op, explain_op, sensitivity, bound_name_op, exact) =
+        rank_options;
+
     // the current abox is not the completed one
     let mut abox = onto.abox().unwrap().clone();
     let deduction_tree = false;
 
+    // assertions with sources get their credibility from the trust in them
+    let combine = match combine_name_op {
+        Option::None | Some(CombineName::Undefined) | Some(CombineName::Max) => AGGR_MAX,
+        Some(CombineName::NoisyOr) => AGGR_NOISY_OR,
+        Some(CombineName::Sum) => AGGR_SUM,
+    };
+    let trust_applied = apply_trust(&mut abox, onto.trust(), combine);
+
     // find aggregation function
-    let aggr = match aggr_name_op {
-        Option::None => AGGR_SUM,
+    let aggr: AggrBox = match aggr_name_op {
+        Option::None => Box::new(AGGR_SUM),
         Some(aggr_name) => match aggr_name {
-            AggrName::Undefined => AGGR_SUM,
-            AggrName::Sum => AGGR_SUM,
-            AggrName::Max => AGGR_MAX,
-            AggrName::Min => AGGR_MIN,
-            AggrName::Mean => AGGR_MEAN,
-            AggrName::Count => AGGR_COUNT,
+            AggrName::Undefined => Box::new(AGGR_SUM),
+            AggrName::Sum => Box::new(AGGR_SUM),
+            AggrName::Max => Box::new(AGGR_MAX),
+            AggrName::Min => Box::new(AGGR_MIN),
+            AggrName::Mean => Box::new(AGGR_MEAN),
+            AggrName::Count => Box::new(AGGR_COUNT),
+            AggrName::Expression(expression) => match AggrExpression::parse(expression) {
+                Ok(aggr_expression) => aggr_expression.to_aggregator(),
+                Err(e) => {
+                    println!("couldn't use the aggregation '{}': {}", expression, &e);
+                    std::process::exit(exitcode::DATAERR);
+                }
+            },
         },
     };
 
@@ -641,16 +1056,60 @@ pub fn task_rank_abox(
     // defined the adjusters
     let adjusters: Adjusters = (TOLERANCE, M_SCALE, B_TRANSLATE);
 
+    // and the bound on the roots, cauchy cubic unless asked otherwise, the spectral bound
+    // doesn't look at roots
+    let algorithm = match bound_name_op {
+        Some(BoundName::Spectral) => BoundAlgorithm::Spectral,
+        _ => BoundAlgorithm::Interpolation,
+    };
+    let method = match bound_name_op {
+        Option::None
+        | Some(BoundName::Undefined)
+        | Some(BoundName::CauchyCubic)
+        | Some(BoundName::Spectral) => Method::CauchyCubic,
+        Some(BoundName::Cauchy) => Method::CauchyOriginal,
+        Some(BoundName::CauchySquare) => Method::CauchySquare,
+        Some(BoundName::CauchyQuad) => Method::CauchyQuad,
+        Some(BoundName::Fujiwara) => Method::Fujiwara,
+        Some(BoundName::Lagrange) => Method::Lagrange,
+        Some(BoundName::Kojima) => Method::Kojima,
+        Some(BoundName::LagrangeZassenhaus) => Method::LagrangeZassenhaus,
+        Some(BoundName::Auto) => Method::Auto,
+    };
+    let bound_settings: BoundSettings = (adjusters, method, algorithm);
+
     // TODO: after the test of concurrency come back here and use concurrency if it is better
     let use_concurrency = false;
     let use_concurrency = true;
 
-    let (before_matrix, virtual_to_real, conflict_type) = rank_abox(
+    // the same ranking with other credibilities, before the abox is ranked
+    let mut aggr = aggr;
+    let sensitivity_op = if sensitivity {
+        Some(sensitivity_analysis(
+            onto,
+            &abox,
+            &mut aggr,
+            bound_settings,
+            verbose,
+            use_concurrency,
+        ))
+    } else {
+        Option::None
+    };
+
+    // the exact ranking starts from the abox as it is now
+    let unranked_op = if exact {
+        Some(abox.clone())
+    } else {
+        Option::None
+    };
+
+    let ((before_matrix, virtual_to_real, conflict_type), system_op) = rank_abox_with_system(
         &onto,
         &mut abox,
         deduction_tree,
-        aggr,
-        adjusters,
+        &mut aggr,
+        bound_settings,
         verbose,
         use_concurrency,
     );
@@ -666,15 +1125,122 @@ pub fn task_rank_abox(
         }
     }
 
-    // save to file the new abox
-    if path_output_op.is_some() {
-        let dont_write_trivial = true;
-        let abox_ranked_string_op =
-            abox_to_native_string_quantum(&abox, onto.symbols(), dont_write_trivial);
+    // why the asked assertion got its value
+    if let Some(explain) = explain_op {
+        match find_assertion(&abox, explain, onto.symbols()) {
+            Option::None => println!(" -- no assertion '{}' in the abox to explain", explain),
+            Some(index) => {
+                let decomposition_op = system_op.as_ref().and_then(|s| s.decomposition(index));
+                let kind = *conflict_type.get(&index).unwrap_or(&ConflictType::Clean);
+
+                if let Some(explanation) =
+                    RankExplanation::new(onto, &abox, index, kind, decomposition_op)
+                {
+                    print!(
+                        "{}",
+                        explanation.to_string_with_symbols(onto.symbols(), EXPLANATION_LENGTH)
+                    );
+                }
+            }
+        }
+    }
+
+    // how much the values depend on the credibilities
+    if let Some(sensitivity) = sensitivity_op {
+        print!(
+            "{}",
+            sensitivity.to_string_with_symbols(&abox, onto.symbols(), SENSITIVITY_LENGTH)
+        );
+    }
+
+    // the same ranking without rounding errors
+    if let Some(unranked) = unranked_op {
+        let conflicts = onto.conflict_matrix_refs_only(&unranked, verbose);
+        let dimension = system_op
+            .as_ref()
+            .map(|system| system.dimension())
+            .unwrap_or(0);
+        let bound = system_op
+            .as_ref()
+            .map(|system| system.bound())
+            .unwrap_or(1.);
+
+        if dimension > EXACT_MAX_DIMENSION {
+            println!(
+                " -- {} assertions in conflict, the exact ranking can take a long time",
+                dimension
+            );
+        }
+
+        match rank_abox_exact(&unranked, conflicts, &mut aggr, bound) {
+            Option::None => println!(" -- the exact ranking couldn't be done"),
+            Some(exact_ranking) => print!(
+                "{}",
+                exact_ranking.to_string_with_symbols(&abox, onto.symbols())
+            ),
+        }
+    }
+
+    // sources learn how their assertions did
+    if trust_applied && !silent {
+        println!(" -- source feedback:");
+
+        for feedback in source_feedback(&abox, onto.trust()) {
+            println!("    {}", &feedback);
+        }
+    }
+
+    // save to file the new abox, or to the database with the ranking run
+    if let Some(path_output) = path_output_op {
+        let path_output = path_output.to_str().unwrap();
+
+        if get_filetype(path_output) == FileType::Sqlite {
+            let aggr_name = aggr_name_to_string(aggr_name_op);
+            let run = RankingRun::new(&abox.name(), &aggr_name, adjusters, abox.len());
+
+            match Connection::open(path_output) {
+                Err(e) => println!("couldn't open the database {}: {}", path_output, &e),
+                Ok(conn) => {
+                    // the ontology first, then the ranked abox replaces the original
+                    if onto.populate_db(&conn, verbose) {
+                        let run_id_op =
+                            onto.ranking_to_db(&conn, &abox, &conflict_type, &run, verbose);
+
+                        if let (Some(run_id), false) = (run_id_op, silent) {
+                            println!(" -- ranking stored in {} with id {}", path_output, run_id);
+                        }
+                    }
+                }
+            }
+        } else {
+            let dont_write_trivial = true;
+            let abox_ranked_string_op =
+                abox_to_native_string_quantum(&abox, onto.symbols(), dont_write_trivial)
+                    .map(|abox_string| with_source_declarations(onto.trust(), abox_string));
+
+            if let Some(abox_ranked_string) = &abox_ranked_string_op {
+                write_output_op_to_file(path_output_op, abox_ranked_string);
+            }
+        }
+    }
+
+    // the report is written whatever the answers to the questions
+    if let Some(path_report) = path_report_op {
+        let (_, _, filters) = graph_options;
+        let mut report = HtmlReport::new(&format!("rankab: {}", abox.name()));
 
-        if let Some(abox_ranked_string) = &abox_ranked_string_op {
-            write_output_op_to_file(path_output_op, abox_ranked_string);
+        report.add_summary("aggregation", &aggr_name_to_string(aggr_name_op));
+        report.add_summary("bound method", &format!("{:?} ({:?})", method, algorithm));
+
+        if let Some(system) = &system_op {
+            report.add_summary("bound", &system.bound().to_string());
+            report.add_summary("assertions in conflict", &system.dimension().to_string());
         }
+
+        report.add_assertions(&abox, onto.symbols(), &conflict_type);
+        add_conflicts_to_report(&mut report, onto, &abox, &conflict_type, filters, verbose);
+
+        write_report(&report, path_report, silent);
     }
 
     // now create graph if necessary
@@ -690,7 +1256,9 @@ pub fn task_rank_abox(
         // here ask later for only conflicts or not
         let only_conflicts_graph = true;
 
-        let graph = create_aboxq_graph_dot(
+        let (render_op, exports, filters) = graph_options;
+
+        let graph = create_aboxq_graph(
             &abox,
             onto.symbols(),
             &before_matrix,
@@ -698,13 +1266,22 @@ pub fn task_rank_abox(
             &conflict_type,
             only_conflicts_graph,
         );
+        let graph = filter_aboxq_graph(&graph, &abox, onto.symbols(), filters);
+
+        if graph.node_count() == 0 {
+            println!(" -- no assertion is left by the filters, the conflict graph will be empty, passing");
+            std::process::exit(exitcode::OK)
+        }
 
         if !silent {
             println!(" -- conflict graph created");
         }
 
+        let range = value_range(&graph);
+
         let get_edge = edge_attr;
-        let get_node = node_attr;
+        let get_node =
+            |_g: &Graph<GraphNode, EdgeKind>, ni: (NodeIndex, &GraphNode)| node_attr(ni.1, range);
 
         let dot_notation =
             Dot::with_attr_getters(&graph, &[Config::EdgeNoLabel], &get_edge, &get_node);
@@ -724,17 +1301,21 @@ pub fn task_rank_abox(
             }
         }
 
-        // here verify that the command exists
-        let dot_command_name = find_and_verify_dot_command();
+        if !exports.is_empty() {
+            let filename = format!("{}_conflict_graph", abox.name());
+
+            write_graph_exports(&graph, exports, &filename, silent);
+        }
 
         // now show graph
         let question_print = " -- do you want see a generate a visual output?";
         let print_output = ask_question(question_print);
 
         if print_output == Answer::YES {
-            generate_visual_and_dot_output(
+            generate_visual_output(
+                (&graph, &get_edge, &get_node),
                 &dot_notation_output,
-                dot_command_name,
+                render_op,
                 ab_name,
                 silent,
                 true,
@@ -745,43 +1326,756 @@ pub fn task_rank_abox(
     std::process::exit(exitcode::OK);
 }
 
-// ===============================================================================================
-// these are utitlies for every task
+pub fn task_chase_abox(
+    onto: &mut OntologyDllite,
+    path_output_op: &Option<PathBuf>,
+    chase_depth: usize,
+    verbose: bool,
+    silent: bool,
+) {
+    // both closures are needed: the negative one to verify, the positive one to chase
+    let deduction_tree = false;
+    let both_closures = 0_i8;
 
-pub fn ask_question(question: &str) -> Answer {
-    let answer = Question::new(question)
-        .default(Answer::YES)
-        .show_defaults()
-        .confirm();
+    onto.generate_cln(deduction_tree, verbose, both_closures);
 
-    answer
-}
+    let abox = onto.abox().unwrap();
 
-pub fn write_output_op_to_file(output_path: &Option<PathBuf>, text: &str) {
-    match output_path {
-        Some(path_output) => {
-            let filename = path_output.to_str().unwrap().to_string();
+    // an inconsistent abox has no model at all
+    let (the_abox_is_inconsistent, _) =
+        AbqDllite::is_inconsistent_refs_only(abox.items_by_ref(), onto.cln(false), false);
 
-            write_str_to_file(text, &filename);
-        }
-        Option::None => (),
+    if the_abox_is_inconsistent {
+        println!(
+            " -- the abox is inconsistent, it has no model, use 'verab' to see the contradictions"
+        );
+        std::process::exit(exitcode::DATAERR);
     }
-}
 
-pub fn generate_visual_and_dot_output(
-    dot_notation_output: &str,
-    dot_command_name: &str,
-    tbox_name: &str,
-    silent: bool,
-    ranking: bool,
-) {
-    // here create a temporary file
-    let temp_dot_file_res = NamedTempFile::new();
+    let model = CanonicalModelDllite::chase(
+        abox,
+        onto.cln(true),
+        onto.symbols(),
+        chase_depth,
+        deduction_tree,
+        verbose,
+    );
 
-    match temp_dot_file_res {
-        Err(e) => {
-            if !silent {
-                println!("could not generate output: {}", e);
+    if !silent {
+        println!(
+            " -- {} assertions, {} labelled nulls created (depth bound: {})",
+            model.abox().len(),
+            model.nulls().len(),
+            model.max_depth()
+        );
+
+        if model.is_truncated() {
+            println!(
+                " -- the depth bound was reached, this is only a prefix of the canonical model"
+            );
+        }
+
+        let question_print = " -- do you want to see the output?";
+        let print_output = ask_question(question_print);
+
+        if print_output == Answer::YES {
+            println!("{}", &model.to_pretty_string());
+        }
+    }
+
+    write_output_op_to_file(path_output_op, &model.to_native_string());
+
+    std::process::exit(exitcode::OK);
+}
+
+pub fn task_verify_obda(
+    onto: &mut OntologyDllite,
+    obda: &ObdaDllite,
+    conn: &Connection,
+    path_output_op: &Option<PathBuf>,
+    verbose: bool,
+    silent: bool,
+) {
+    // the negative closure gives the violation queries, the tbox is enough to rewrite them
+    let deduction_tree = false;
+    let negative_only = -1_i8;
+    let which_closure = false;
+
+    onto.generate_cln(deduction_tree, verbose, negative_only);
+
+    let detailed = !silent;
+    let result = obda.is_inconsistent(
+        conn,
+        onto.tbox(),
+        onto.cln(which_closure),
+        detailed,
+        verbose,
+    );
+
+    match result {
+        Err(e) => {
+            println!("ERROR: a violation query failed: {}", &e);
+            std::process::exit(exitcode::DATAERR);
+        }
+        Ok((false, _)) => println!(" -- no contradictions were found"),
+        Ok((true, violations_op)) => {
+            println!(" -- contradictions were found");
+
+            if let Some(violations) = violations_op {
+                let question_print = " -- do you want to see them";
+                let print_output = ask_question(question_print);
+
+                if print_output == Answer::YES {
+                    let mut s = String::new();
+
+                    for (tbi, rows) in &violations {
+                        let tbi_s = tbi_to_string(tbi, onto.symbols())
+                            .unwrap_or_else(|| format!("{}", tbi));
+
+                        s.push_str(format!("{} broken by:\n", tbi_s).as_str());
+
+                        for row in rows {
+                            s.push_str(format!("    {}\n", row.join(", ")).as_str());
+                        }
+                    }
+
+                    println!("{}", &s);
+                    write_output_op_to_file(path_output_op, &s);
+                }
+            }
+        }
+    }
+
+    std::process::exit(exitcode::OK);
+}
+
+pub fn task_query_obda(
+    onto: &OntologyDllite,
+    obda: &ObdaDllite,
+    conn: &Connection,
+    query_op: &Option<String>,
+    path_output_op: &Option<PathBuf>,
+    verbose: bool,
+) {
+    let query = match query_op {
+        Some(query_s) => match string_to_node(query_s, onto.symbols()) {
+            Ok(query) => query,
+            Err(e) => {
+                println!("ERROR: couldn't parse the query {}: {}", query_s, &e);
+                std::process::exit(exitcode::USAGE);
+            }
+        },
+        Option::None => {
+            println!("ERROR: the 'queryab' task needs a query, use the 'query' option");
+            std::process::exit(exitcode::USAGE);
+        }
+    };
+
+    match obda.answer(conn, &query, onto.tbox(), verbose) {
+        Ok(rows) => {
+            println!(" -- {} answers found", rows.len());
+
+            let mut s = String::new();
+
+            for row in &rows {
+                s.push_str(format!("{}\n", row.join(", ")).as_str());
+            }
+
+            print!("{}", &s);
+            write_output_op_to_file(path_output_op, &s);
+
+            std::process::exit(exitcode::OK);
+        }
+        Err(e) => {
+            println!("ERROR: the query failed: {}", &e);
+            std::process::exit(exitcode::DATAERR);
+        }
+    }
+}
+
+pub fn task_diff_rankings(
+    onto: &mut OntologyDllite,
+    (path_aboxes, path_tbox_after_op, path_output_op): RankingDiffPaths,
+    verbose: bool,
+    silent: bool,
+) {
+    // each ranking is the abox named after its file
+    let names: Vec<&str> = path_aboxes
+        .iter()
+        .map(|p| parse_name_from_filename(p.to_str().unwrap()))
+        .collect();
+
+    if names.len() != 2 || names[0] == names[1] {
+        println!("ERROR: the 'diffrank' task compares two rankings, give the 'abox' option twice with files of different names");
+        std::process::exit(exitcode::USAGE);
+    }
+
+    // conflict types are found as the ranking does
+    let deduction_tree = false;
+    let negative_only = 0_i8;
+
+    onto.generate_cln(deduction_tree, verbose, negative_only);
+
+    let (before, after) = match (onto.abox_by_name(names[0]), onto.abox_by_name(names[1])) {
+        (Some(before), Some(after)) => (before, after),
+        _ => {
+            println!("ERROR: the rankings couldn't be read, maybe run with 'verbose' option to see what happened");
+            std::process::exit(exitcode::DATAERR);
+        }
+    };
+
+    // with a new tbox the second ranking is classified with the closure of that tbox
+    let onto_after = path_tbox_after_op.as_ref().map(|path_tbox_after| {
+        let path_tbox_after = path_tbox_after.to_str().unwrap();
+        let mut onto_after = onto.clone();
+
+        onto_after.add_symbols_from_file(path_tbox_after, get_filetype(path_tbox_after), verbose);
+        let tbox_after = tbox_from_file_or_exit(path_tbox_after, onto_after.symbols(), verbose);
+
+        for tbi in onto.tbox().items() {
+            onto_after.remove_tbi(tbi);
+        }
+
+        onto_after.add_tbis_from_vec(tbox_after.items());
+
+        onto_after.generate_cln(deduction_tree, verbose, negative_only);
+        onto_after
+    });
+
+    let diff = RankingDiff::new(
+        (onto, before),
+        (onto_after.as_ref().unwrap_or(onto), after),
+        DIFF_TOP_K,
+        verbose,
+    );
+
+    print!(
+        "{}",
+        diff.to_string_with_symbols(onto.symbols(), DIFF_SHIFTS_LENGTH)
+    );
+
+    // the whole report goes to the output, as json for a '.json' file
+    if let Some(path_output) = path_output_op {
+        let path_output_str = path_output.to_str().unwrap();
+
+        let report = if get_filetype(path_output_str) == FileType::Json {
+            serde_json::to_string_pretty(&diff.to_json(onto.symbols())).unwrap()
+        } else {
+            diff.to_string_with_symbols(onto.symbols(), diff.len())
+        };
+
+        write_output_op_to_file(path_output_op, &report);
+
+        if !silent {
+            println!(" -- report written to {}", path_output_str);
+        }
+    }
+
+    std::process::exit(exitcode::OK);
+}
+
+pub fn task_merge_abox(
+    onto: &mut OntologyDllite,
+    path_output_op: &Option<PathBuf>,
+    verbose: bool,
+    silent: bool,
+) {
+    // conflicts are found with the negative closure
+    let deduction_tree = false;
+    let negative_only = -1_i8;
+
+    onto.generate_cln(deduction_tree, verbose, negative_only);
+
+    let abox = onto.abox().unwrap();
+    let sources = abox.sources();
+
+    if sources.len() < 2 {
+        println!(
+            "ERROR: at least two aboxes are needed to merge, give several times the 'abox' option"
+        );
+        std::process::exit(exitcode::USAGE);
+    }
+
+    if !silent {
+        println!(
+            " -- {} assertions merged in '{}' from {} aboxes: {}",
+            abox.len(),
+            abox.name(),
+            sources.len(),
+            sources.join(", ")
+        );
+    }
+
+    let cross_conflicts = onto.cross_abox_conflicts(abox);
+
+    if cross_conflicts.is_empty() {
+        println!(" -- no conflicts across aboxes were found");
+    } else {
+        println!(
+            " -- {} conflicts across aboxes were found",
+            cross_conflicts.len()
+        );
+
+        if !silent {
+            let question_print = " -- do you want to see them";
+            let print_output = ask_question(question_print);
+
+            if print_output == Answer::YES {
+                println!("[");
+
+                for (tbi_op, members) in &cross_conflicts {
+                    println!(
+                        "{}",
+                        &pretty_print_abiq_conflict(tbi_op, members, onto.symbols())
+                    );
+
+                    let from: Vec<String> = members
+                        .iter()
+                        .map(|m| format!("({})", m.sources().join(", ")))
+                        .collect();
+                    println!("      from: {}", from.join(" "));
+                }
+
+                println!("]");
+            }
+        }
+    }
+
+    if let Some(path_output) = path_output_op {
+        let path_output = path_output.to_str().unwrap();
+
+        if get_filetype(path_output) == FileType::Sqlite {
+            // every abox is stored, the merge included
+            match Connection::open(path_output) {
+                Err(e) => println!("couldn't open the database {}: {}", path_output, &e),
+                Ok(conn) => {
+                    if onto.populate_db(&conn, verbose) && !silent {
+                        println!(" -- aboxes stored in {}", path_output);
+                    }
+                }
+            }
+        } else {
+            let dont_write_trivial = true;
+            let abox_string_op =
+                abox_to_native_string_quantum(abox, onto.symbols(), dont_write_trivial)
+                    .map(|abox_string| with_source_declarations(onto.trust(), abox_string));
+
+            if let Some(abox_string) = &abox_string_op {
+                write_output_op_to_file(path_output_op, abox_string);
+            }
+        }
+    }
+
+    std::process::exit(exitcode::OK);
+}
+
+// ===============================================================================================
+// THESE ARE THE TASKS ON THE NATIVE FILES THEMSELVES
+
+pub fn task_native_related(
+    native_paths: NativeRelatedPaths,
+    task: Task,
+    verbose: bool,
+    silent: bool,
+) {
+    let (path_tbox_op, path_symbols_op, path_aboxes, path_output_op) = native_paths;
+
+    let path_tbox = match path_tbox_op {
+        Some(path_tbox) => path_tbox.to_str().unwrap().to_string(),
+        Option::None => {
+            println!("ERROR: you must provide a tbox file, the symbols are declared there");
+            std::process::exit(exitcode::USAGE);
+        }
+    };
+    let path_symbols = match path_symbols_op {
+        Some(path_symbols) => path_symbols.to_str().unwrap().to_string(),
+        Option::None => path_tbox.clone(),
+    };
+    let path_aboxes: Vec<String> = path_aboxes
+        .iter()
+        .map(|p| p.to_str().unwrap().to_string())
+        .collect();
+
+    for path in path_aboxes.iter().chain([&path_tbox, &path_symbols]) {
+        if get_filetype(path) != FileType::Native {
+            println!(
+                "ERROR: only native files can be formatted or linted: {}",
+                path
+            );
+            std::process::exit(exitcode::USAGE);
+        }
+    }
+
+    let mut onto = OntologyDllite::new(parse_name_from_filename(&path_tbox).to_string());
+    onto.add_symbols_from_file(&path_symbols, FileType::Native, verbose);
+
+    match task {
+        Task::Fmt => {
+            // the tbox first, the symbols are in a file of their own or in the tbox
+            let mut paths: Vec<String> = vec![path_tbox.clone()];
+
+            if path_symbols != path_tbox {
+                paths.push(path_symbols);
+            }
+            paths.extend(path_aboxes);
+
+            task_format_native(&paths, onto.symbols(), path_output_op, silent);
+        }
+        Task::Lint => task_lint_native(
+            (&path_tbox, &path_symbols, &path_aboxes),
+            onto.symbols(),
+            path_output_op,
+            verbose,
+        ),
+        _ => {
+            println!("ERROR: you must provide a task on native files: 'fmt' or 'lint'");
+            std::process::exit(exitcode::USAGE);
+        }
+    }
+}
+
+pub fn task_format_native(
+    paths: &[String],
+    symbols: &SymbolDict,
+    path_output_op: &Option<PathBuf>,
+    silent: bool,
+) {
+    if path_output_op.is_some() && paths.len() > 1 {
+        println!("ERROR: the 'output' option is only for a single file, the files are otherwise formatted in place");
+        std::process::exit(exitcode::USAGE);
+    }
+
+    for path in paths {
+        let name = parse_name_from_filename(path);
+        let text = read_file_or_exit(path);
+
+        // nominals of an abox are not the ones of another
+        let mut symbols = symbols.clone();
+        let (formatted, not_parsed) = format_native(&text, &mut symbols);
+
+        for number in not_parsed {
+            println!(
+                " -- {}:{}: couldn't be parsed, the line is kept as it is",
+                name,
+                number + 1
+            );
+        }
+
+        if path_output_op.is_some() {
+            write_output_op_to_file(path_output_op, &formatted);
+        } else if formatted == text {
+            println!(" -- {} is already formatted", name);
+        } else {
+            let question_print = format!(
+                " -- do you want to write the formatted {} over the file?",
+                name
+            );
+
+            if silent {
+                write_str_to_file(&formatted, path);
+            } else if ask_question(&question_print) == Answer::YES
+                && write_str_to_file(&formatted, path)
+            {
+                println!(" -- {} formatted", name);
+            }
+        }
+    }
+}
+
+pub fn task_lint_native(
+    paths: (&str, &str, &[String]),
+    symbols: &SymbolDict,
+    path_output_op: &Option<PathBuf>,
+    verbose: bool,
+) {
+    let (path_tbox, path_symbols, path_aboxes) = paths;
+
+    let tbox_text = read_file_or_exit(path_tbox);
+    let symbols_text = read_file_or_exit(path_symbols);
+    let abox_texts: Vec<String> = path_aboxes.iter().map(|p| read_file_or_exit(p)).collect();
+
+    let aboxes: Vec<NamedText> = path_aboxes
+        .iter()
+        .zip(abox_texts.iter())
+        .map(|(path, text)| (parse_name_from_filename(path), text.as_str()))
+        .collect();
+
+    let findings = lint_native(
+        (parse_name_from_filename(path_tbox), &tbox_text),
+        (parse_name_from_filename(path_symbols), &symbols_text),
+        &aboxes,
+        symbols,
+        verbose,
+    );
+
+    let findings_string: Vec<String> = findings.iter().map(|f| f.to_string()).collect();
+
+    for finding in &findings_string {
+        println!("{}", finding);
+    }
+
+    if findings.is_empty() {
+        println!(" -- nothing found");
+    } else {
+        println!(" -- {} finding(s)", findings.len());
+    }
+
+    write_output_op_to_file(path_output_op, &findings_string.join("\n"));
+
+    // so that scripts can tell
+    if findings.is_empty() {
+        std::process::exit(exitcode::OK);
+    } else {
+        std::process::exit(exitcode::DATAERR);
+    }
+}
+
+// ===============================================================================================
+// these are utitlies for every task
+
+pub fn ask_question(question: &str) -> Answer {
+    let answer = Question::new(question)
+        .default(Answer::YES)
+        .show_defaults()
+        .confirm();
+
+    answer
+}
+
+pub fn read_file_or_exit(path: &str) -> String {
+    match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            println!("ERROR: couldn't read the file {}: {}", path, &e);
+            std::process::exit(exitcode::NOINPUT);
+        }
+    }
+}
+
+// the tbox of a file read over 'symbols', a file that is missing or can't be parsed stops here
+pub fn tbox_from_file_or_exit(path_tbox: &str, symbols: &SymbolDict, verbose: bool) -> TBDllite {
+    let tb_result = match get_filetype(path_tbox) {
+        FileType::Json => parse_tbox_json(path_tbox, symbols, verbose),
+        FileType::Native => parse_tbox_native(path_tbox, symbols, verbose),
+        FileType::Sqlite => parse_tbox_sqlite(path_tbox, symbols, verbose),
+    };
+
+    match tb_result {
+        Ok(tb) => tb,
+        Err(e) => {
+            println!("ERROR: couldn't read the tbox from {}: {}", path_tbox, &e);
+            std::process::exit(exitcode::NOINPUT);
+        }
+    }
+}
+
+/// The tbox of a native file and the axioms written again in it, see 'parse_tbox_native_checked'.
+pub fn tbox_native_checked_or_exit(
+    path_tbox: &str,
+    symbols: &SymbolDict,
+    verbose: bool,
+) -> (TBDllite, Vec<(TbiDllite, usize)>) {
+    match parse_tbox_native_checked(path_tbox, symbols, verbose) {
+        Ok(checked) => checked,
+        Err(e) => {
+            println!("ERROR: couldn't read the tbox from {}: {}", path_tbox, &e);
+            std::process::exit(exitcode::NOINPUT);
+        }
+    }
+}
+
+pub fn ontology_from_db_or_exit(path_db: &str, verbose: bool) -> OntologyDllite {
+    match OntologyDllite::initiate_from_db(path_db, verbose) {
+        Ok(onto) => onto,
+        Err(e) => {
+            println!("ERROR: couldn't read the ontology from {}: {}", path_db, &e);
+            std::process::exit(exitcode::DATAERR);
+        }
+    }
+}
+
+pub fn obda_from_file_or_exit(
+    path_mappings: &str,
+    symbols: &SymbolDict,
+    conn: &Connection,
+    verbose: bool,
+) -> ObdaDllite {
+    let obda = match parse_mappings_native(path_mappings, symbols, verbose) {
+        Ok(obda) => obda,
+        Err(e) => {
+            println!(
+                "ERROR: couldn't read the mappings from {}: {}",
+                path_mappings, &e
+            );
+            std::process::exit(exitcode::DATAERR);
+        }
+    };
+
+    if let Err(e) = obda.check(conn, verbose) {
+        println!("ERROR: the mappings don't fit the database: {}", &e);
+        std::process::exit(exitcode::DATAERR);
+    }
+
+    obda
+}
+
+// native abox files with sources declare their trust before the abox
+pub fn with_source_declarations(trust: &SourceDict, abox_string: String) -> String {
+    if trust.is_empty() {
+        abox_string
+    } else {
+        format!("{}\n{}", sources_to_native_string(trust), abox_string)
+    }
+}
+
+// the name the aggregation is stored with
+fn aggr_name_to_string(aggr_name_op: &Option<AggrName>) -> String {
+    match aggr_name_op {
+        Option::None | Some(AggrName::Undefined) => String::from("sum"),
+        Some(AggrName::Expression(expression)) => format!("{}{}", EXPRESSION_PREFIX, expression),
+        Some(aggr_name) => format!("{:?}", aggr_name).to_lowercase(),
+    }
+}
+
+pub fn write_tbox_report(
+    onto: &mut OntologyDllite,
+    path_report: &Path,
+    verbose: bool,
+    silent: bool,
+) {
+    // the deduction tree is needed for the unravel
+    let deduction_tree = true;
+    let negative_only = 1_i8;
+    let which_closure = true;
+
+    onto.generate_cln(deduction_tree, verbose, negative_only);
+    let full_closure = onto.cln(which_closure);
+
+    let contradictions: Vec<String> = full_closure
+        .items()
+        .iter()
+        .filter(|tbi| tbi.is_contradiction() && !tbi.is_trivial())
+        .filter_map(|tbi| tbi_to_string(tbi, onto.symbols()))
+        .collect();
+
+    let mut report = HtmlReport::new("vertb: tbox verification");
+    report.add_summary("axioms", &onto.tbox().len().to_string());
+    report.add_summary("axioms in the closure", &full_closure.len().to_string());
+    report.add_summary("contradictions", &contradictions.len().to_string());
+
+    if !contradictions.is_empty() {
+        let only_conflicts = true;
+
+        report.add_list("contradictions", &contradictions);
+        report.add_unravel(
+            "unravel for conflicts",
+            &create_string_for_unravel_conflict_tbox(full_closure, onto.symbols(), only_conflicts),
+        );
+    }
+
+    write_report(&report, path_report, silent);
+}
+
+// the contradictions of abox, their unravel trees and the conflict graph,
+// the closures must have been generated
+fn add_conflicts_to_report(
+    report: &mut HtmlReport,
+    onto: &OntologyDllite,
+    abox: &AbqDllite,
+    conflict_type: &HashMap<usize, ConflictType>,
+    filters: &[GraphFilter],
+    verbose: bool,
+) {
+    let (_, contradictions_op) =
+        AbqDllite::is_inconsistent_refs_only(abox.items_by_ref(), onto.cln(false), true);
+
+    if let Some(contradictions) = contradictions_op {
+        let conflicts: Vec<String> = contradictions
+            .iter()
+            .map(|(tbi_op, abi_vec)| {
+                pretty_print_abiq_conflict(tbi_op, abi_vec, onto.symbols())
+                    .lines()
+                    .map(|line| line.trim())
+                    .filter(|line| *line != "{" && *line != "},")
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+
+        let only_conflicts = true;
+
+        report.add_list("contradictions", &conflicts);
+        report.add_unravel(
+            "unravel for conflicts",
+            &create_string_for_unravel_conflict_abox(
+                abox,
+                onto.symbols(),
+                only_conflicts,
+                &contradictions,
+            ),
+        );
+    }
+
+    let (matrix, _, virtual_to_real) = onto.conflict_matrix_refs_only(abox, verbose);
+
+    if !null_vector(&matrix) {
+        let only_conflicts_graph = true;
+
+        let graph = create_aboxq_graph(
+            abox,
+            onto.symbols(),
+            &matrix,
+            &virtual_to_real,
+            conflict_type,
+            only_conflicts_graph,
+        );
+        let graph = filter_aboxq_graph(&graph, abox, onto.symbols(), filters);
+
+        if graph.node_count() > 0 {
+            let range = value_range(&graph);
+            let get_node = |_g: &Graph<GraphNode, EdgeKind>, ni: (NodeIndex, &GraphNode)| {
+                node_attr(ni.1, range)
+            };
+
+            let layout = Layout::new(&graph, &edge_attr, &get_node);
+            report.add_graph("conflict graph", &layout.to_svg());
+        }
+    }
+}
+
+fn write_report(report: &HtmlReport, path_report: &Path, silent: bool) {
+    let filename = path_report.to_str().unwrap();
+
+    if write_str_to_file(&report.to_html(), filename) && !silent {
+        println!(" -- report created: {}", filename);
+    }
+}
+
+pub fn write_output_op_to_file(output_path: &Option<PathBuf>, text: &str) {
+    match output_path {
+        Some(path_output) => {
+            let filename = path_output.to_str().unwrap().to_string();
+
+            write_str_to_file(text, &filename);
+        }
+        Option::None => (),
+    }
+}
+
+pub fn generate_visual_and_dot_output(
+    dot_notation_output: &str,
+    dot_command_name: &str,
+    tbox_name: &str,
+    silent: bool,
+    ranking: bool,
+) {
+    // here create a temporary file
+    let temp_dot_file_res = NamedTempFile::new();
+
+    match temp_dot_file_res {
+        Err(e) => {
+            if !silent {
+                println!("could not generate output: {}", e);
             }
         }
         Ok(temp_dot) => {
@@ -839,14 +2133,118 @@ pub fn generate_visual_and_dot_output(
     }
 }
 
-pub fn find_and_verify_dot_command() -> &'static str {
-    //create graph here
-    // here verify that the command exists
-    let dot_command_name = if cfg!(windows) {
+/// Writes the typed graph in each of the formats asked, to 'filename' with the extension
+/// of the format.
+pub fn write_graph_exports(
+    graph: &Graph<GraphNode, EdgeKind>,
+    exports: &[ExportName],
+    filename: &str,
+    silent: bool,
+) {
+    for export in exports {
+        let (output, extension) = match export {
+            ExportName::GraphMl => (graph_to_graphml(graph), "graphml"),
+            ExportName::Gexf => (graph_to_gexf(graph), "gexf"),
+            ExportName::Json => (graph_to_json(graph), "json"),
+            ExportName::Mermaid => (graph_to_mermaid(graph), "mmd"),
+            ExportName::Undefined => {
+                println!(" -- unknown export format, use one of (graphml|gexf|json|mermaid)");
+                continue;
+            }
+        };
+
+        let filename = format!("{}.{}", filename, extension);
+
+        if write_str_to_file(&output, &filename) && !silent {
+            println!(" -- {} file created: {}", extension, &filename);
+        }
+    }
+}
+
+// a graph with the getters of its attributes in dot notation
+pub type GraphWithAttributes<'a, N, E> = (
+    &'a Graph<N, E>,
+    EdgeAttrGetter<'a, N, E>,
+    NodeAttrGetter<'a, N, E>,
+);
+
+/// Visual output of a graph: through dot or with the built-in layout, when nothing is
+/// asked dot is used if it is found.
+pub fn generate_visual_output<N: Display, E>(
+    graph_with_attributes: GraphWithAttributes<N, E>,
+    dot_notation_output: &str,
+    render_op: &Option<RenderName>,
+    name: &str,
+    silent: bool,
+    ranking: bool,
+) {
+    let render = match render_op {
+        Some(RenderName::Dot) => RenderName::Dot,
+        Some(RenderName::Svg) => RenderName::Svg,
+        Some(RenderName::Pdf) => RenderName::Pdf,
+        Option::None | Some(RenderName::Undefined) => {
+            if command_exists(dot_command_name()) {
+                RenderName::Dot
+            } else {
+                if !silent {
+                    println!(
+                        " -- {} not found, the built-in layout is used",
+                        dot_command_name()
+                    );
+                }
+
+                RenderName::Svg
+            }
+        }
+    };
+
+    match render {
+        RenderName::Svg | RenderName::Pdf => {
+            let (graph, get_edge, get_node) = graph_with_attributes;
+            let layout = Layout::new(graph, get_edge, get_node);
+
+            let (output, extension) = if render == RenderName::Svg {
+                (layout.to_svg(), "svg")
+            } else {
+                (layout.to_pdf(), "pdf")
+            };
+
+            let name_output_file = if ranking {
+                format!("{}_ranking.{}", name, extension)
+            } else {
+                format!("{}_consequences.{}", name, extension)
+            };
+
+            if write_str_to_file(&output, &name_output_file) && !silent {
+                println!(" -- file generated: {}", &name_output_file);
+            }
+        }
+        _ => {
+            let dot_command_name = find_and_verify_dot_command();
+
+            generate_visual_and_dot_output(
+                dot_notation_output,
+                dot_command_name,
+                name,
+                silent,
+                ranking,
+            );
+        }
+    }
+}
+
+fn dot_command_name() -> &'static str {
+    if cfg!(windows) {
         DOT_COMMAND_WINDOWS
     } else {
         DOT_COMMAND_LINUX
-    };
+    }
+}
+
+pub fn find_and_verify_dot_command() -> &'static str {
+    //create graph here
+    // here verify that the command exists
+    let dot_command_name = dot_command_name();
     let dot_exists = command_exists(dot_command_name);
 
     if !dot_exists {
@@ -0,0 +1,379 @@
+/*
+ © - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   two versions of a tbox over the same symbols, what changed in meaning?
+   - axioms added and removed, as written
+   - new and lost entailments: the closures are compared, axioms written in the files
+     are already in the first part
+   - concepts that became unsatisfiable ('X < NOT X' is in the new closure only)
+   - assertions of the aboxes whose conflict type changed with the new tbox
+*/
+
+use serde_json::{json, Value};
+
+use crate::dl_lite::abox::AbqDllite;
+use crate::dl_lite::abox_item_quantum::AbiqDllite;
+use crate::dl_lite::node::ItemDllite;
+use crate::dl_lite::string_formatter::{abi_to_string, node_to_string, tbi_to_string};
+use crate::dl_lite::tbox::TBDllite;
+use crate::dl_lite::tbox_item::TbiDllite;
+use crate::kb::knowledge_base::{ABox, Item, SymbolDict, TBox, TBoxItem};
+use crate::kb::types::ConflictType;
+
+/// How two versions of a tbox differ, 'before' is the first one.
+#[derive(Debug, Clone)]
+pub struct TBoxDiff {
+    before_name: String,
+    after_name: String,
+    // the axioms written in one version only, and if the other version entails them
+    added: Vec<(TbiDllite, bool)>,
+    removed: Vec<(TbiDllite, bool)>,
+    new_entailments: Vec<TbiDllite>,
+    lost_entailments: Vec<TbiDllite>,
+    unsatisfiable: Vec<ItemDllite>,
+    // the name of the abox, the assertion and its conflict types before and after
+    type_changes: Vec<(String, AbiqDllite, ConflictType, ConflictType)>,
+}
+
+// 'X < NOT Y' and 'Y < NOT X' say the same, a closure only has the one it deduced
+fn says(tbis: &[TbiDllite], tbi: &TbiDllite) -> bool {
+    tbis.contains(tbi)
+        || tbi
+            .reverse_negation(false)
+            .is_some_and(|reversed| tbis.contains(&reversed))
+}
+
+// axioms of 'these' not written in 'those', trivial ones are left out
+fn not_written(these: &TBDllite, those: &TBDllite) -> Vec<TbiDllite> {
+    these
+        .items()
+        .iter()
+        .filter(|tbi| !tbi.is_trivial() && !tbi.is_redundant())
+        .filter(|tbi| !those.contains(tbi))
+        .cloned()
+        .collect()
+}
+
+// items of the closure 'these' that 'those' and 'already' don't say, trivial ones are left out
+fn missing(these: &TBDllite, those: &TBDllite, already: &[TbiDllite]) -> Vec<TbiDllite> {
+    these
+        .items()
+        .iter()
+        .filter(|tbi| !tbi.is_trivial() && !tbi.is_redundant())
+        .filter(|tbi| !says(those.items(), tbi) && !says(already, tbi))
+        .cloned()
+        .collect()
+}
+
+// the concepts 'X' with 'X < NOT X' in the closure, they can't have instances
+fn unsatisfiable(closure: &TBDllite) -> Vec<ItemDllite> {
+    closure
+        .items()
+        .iter()
+        .filter(|tbi| tbi.is_contradiction() && !tbi.is_trivial())
+        .map(|tbi| *tbi.lside())
+        .filter(|node| !node.t().is_role_type())
+        .collect()
+}
+
+// the conflict type of each assertion of abq under the negative closure 'negative',
+// an assertion alone in a contradiction conflicts with itself
+fn conflict_types_under(abq: &AbqDllite, negative: &TBDllite) -> Vec<ConflictType> {
+    let detailed = true;
+    let (_, contradictions_op) =
+        AbqDllite::is_inconsistent_refs_only(abq.items_by_ref(), negative, detailed);
+    let contradictions = contradictions_op.unwrap_or_default();
+
+    abq.items()
+        .iter()
+        .map(|abiq| {
+            let mut conflict_type = ConflictType::Clean;
+
+            for (_, members) in &contradictions {
+                if !members.iter().any(|member| member.abi() == abiq.abi()) {
+                    continue;
+                }
+
+                if members.iter().all(|member| member.abi() == abiq.abi()) {
+                    return ConflictType::SelfConflict;
+                }
+
+                conflict_type = ConflictType::Conflict;
+            }
+
+            conflict_type
+        })
+        .collect()
+}
+
+impl TBoxDiff {
+    /// compare two tboxes built over the same symbols, the assertions of 'aboxes' are
+    /// checked against both
+    pub fn new(
+        (before_name, before): (&str, &TBDllite),
+        (after_name, after): (&str, &TBDllite),
+        aboxes: &[AbqDllite],
+        verbose: bool,
+    ) -> TBoxDiff {
+        let deduction_tree = false;
+
+        let closure_before = before.cln_completion(false, deduction_tree, verbose);
+        let closure_after = after.cln_completion(false, deduction_tree, verbose);
+
+        let entailed_by = |closure: &TBDllite, tbis: Vec<TbiDllite>| {
+            tbis.into_iter()
+                .map(|tbi| {
+                    let entailed = says(closure.items(), &tbi);
+                    (tbi, entailed)
+                })
+                .collect::<Vec<(TbiDllite, bool)>>()
+        };
+
+        let added = entailed_by(&closure_before, not_written(after, before));
+        let removed = entailed_by(&closure_after, not_written(before, after));
+
+        // what is written in the files is already told
+        let new_entailments = missing(&closure_after, &closure_before, after.items());
+        let lost_entailments = missing(&closure_before, &closure_after, before.items());
+
+        let unsatisfiable_before = unsatisfiable(&closure_before);
+        let unsatisfiable = unsatisfiable(&closure_after)
+            .into_iter()
+            .filter(|node| !unsatisfiable_before.contains(node))
+            .collect();
+
+        let negative_before = before.cln_completion(true, deduction_tree, verbose);
+        let negative_after = after.cln_completion(true, deduction_tree, verbose);
+
+        let mut type_changes: Vec<(String, AbiqDllite, ConflictType, ConflictType)> = Vec::new();
+
+        for abq in aboxes {
+            let types_before = conflict_types_under(abq, &negative_before);
+            let types_after = conflict_types_under(abq, &negative_after);
+
+            for (index, abiq) in abq.items().iter().enumerate() {
+                if types_before[index] != types_after[index] {
+                    type_changes.push((
+                        abq.name(),
+                        abiq.clone(),
+                        types_before[index],
+                        types_after[index],
+                    ));
+                }
+            }
+        }
+
+        TBoxDiff {
+            before_name: before_name.to_string(),
+            after_name: after_name.to_string(),
+            added,
+            removed,
+            new_entailments,
+            lost_entailments,
+            unsatisfiable,
+            type_changes,
+        }
+    }
+
+    /// true if both tboxes say the same, for the assertions given too
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.new_entailments.is_empty()
+            && self.lost_entailments.is_empty()
+            && self.unsatisfiable.is_empty()
+            && self.type_changes.is_empty()
+    }
+
+    // each part of the report with its title and its lines
+    fn parts(&self, symbols: &SymbolDict) -> Vec<(&'static str, Vec<String>)> {
+        let tbis = |tbis: &Vec<TbiDllite>| {
+            tbis.iter()
+                .filter_map(|tbi| tbi_to_string(tbi, symbols))
+                .collect::<Vec<String>>()
+        };
+        let written = |tbis: &Vec<(TbiDllite, bool)>, note: &str| {
+            tbis.iter()
+                .filter_map(|(tbi, entailed)| {
+                    tbi_to_string(tbi, symbols).map(|tbi_string| match entailed {
+                        true => format!("{} ({})", tbi_string, note),
+                        false => tbi_string,
+                    })
+                })
+                .collect::<Vec<String>>()
+        };
+
+        let unsatisfiable = self
+            .unsatisfiable
+            .iter()
+            .filter_map(|node| node_to_string(node, symbols, String::new()))
+            .collect::<Vec<String>>();
+
+        let type_changes = self
+            .type_changes
+            .iter()
+            .map(|(abox_name, abiq, before, after)| {
+                format!(
+                    "{}: {}: {:?} -> {:?}",
+                    abox_name,
+                    abi_to_string(abiq.abi(), symbols).unwrap_or_default(),
+                    before,
+                    after
+                )
+            })
+            .collect::<Vec<String>>();
+
+        vec![
+            ("added", written(&self.added, "already entailed")),
+            ("removed", written(&self.removed, "still entailed")),
+            ("new entailments", tbis(&self.new_entailments)),
+            ("lost entailments", tbis(&self.lost_entailments)),
+            ("unsatisfiable concepts", unsatisfiable),
+            ("conflict type changes", type_changes),
+        ]
+    }
+
+    /// a readable report, empty parts are not shown
+    pub fn to_string_with_symbols(&self, symbols: &SymbolDict) -> String {
+        let mut s = format!(
+            " -- comparing {} with {}\n",
+            &self.before_name, &self.after_name
+        );
+
+        if self.is_empty() {
+            s.push_str(" -- they say the same\n");
+            return s;
+        }
+
+        for (title, lines) in self.parts(symbols) {
+            if !lines.is_empty() {
+                s.push_str(&format!(" -- {}:\n", title));
+
+                for line in lines {
+                    s.push_str(&format!("    {}\n", line));
+                }
+            }
+        }
+
+        s
+    }
+
+    /// the whole report as json
+    pub fn to_json(&self, symbols: &SymbolDict) -> Value {
+        let parts = self.parts(symbols);
+
+        let type_changes = self
+            .type_changes
+            .iter()
+            .map(|(abox_name, abiq, before, after)| {
+                json!({
+                    "abox": abox_name,
+                    "assertion": abi_to_string(abiq.abi(), symbols).unwrap_or_default(),
+                    "before": format!("{:?}", before),
+                    "after": format!("{:?}", after),
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        json!({
+            "before": &self.before_name,
+            "after": &self.after_name,
+            "added": &parts[0].1,
+            "removed": &parts[1].1,
+            "new_entailments": &parts[2].1,
+            "lost_entailments": &parts[3].1,
+            "unsatisfiable": &parts[4].1,
+            "conflict_type_changes": type_changes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dl_lite::string_formatter::string_to_tbi;
+    use crate::kb::types::DLType;
+    use std::collections::HashMap;
+
+    fn symbols() -> SymbolDict {
+        let mut symbols: SymbolDict = HashMap::new();
+
+        for (id, name) in ["A", "B", "C", "D"].iter().enumerate() {
+            symbols.insert(name.to_string(), (id + 2, DLType::BaseConcept));
+        }
+
+        symbols
+    }
+
+    fn tbi(axiom: &str, symbols: &SymbolDict) -> TbiDllite {
+        string_to_tbi(axiom, symbols).unwrap().pop().unwrap()
+    }
+
+    fn tbox(axioms: &[&str], symbols: &SymbolDict) -> TBDllite {
+        let mut tb = TBDllite::new();
+
+        for axiom in axioms {
+            tb.add(tbi(axiom, symbols));
+        }
+
+        tb
+    }
+
+    #[test]
+    fn axioms_are_classified() {
+        let symbols = symbols();
+        let before = tbox(&["A < B", "B < C", "D < NOT A"], &symbols);
+        let after = tbox(&["A < B", "A < NOT D", "C < D"], &symbols);
+
+        let diff = TBoxDiff::new(("before", &before), ("after", &after), &[], false);
+
+        // 'A < NOT D' says what 'D < NOT A' said, written the other way
+        assert_eq!(
+            diff.added,
+            vec![
+                (tbi("A < NOT D", &symbols), true),
+                (tbi("C < D", &symbols), false)
+            ]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![
+                (tbi("B < C", &symbols), false),
+                (tbi("D < NOT A", &symbols), true)
+            ]
+        );
+        assert!(diff.lost_entailments.contains(&tbi("A < C", &symbols)));
+        assert!(diff.unsatisfiable.is_empty());
+    }
+
+    #[test]
+    fn same_meaning_is_no_entailment_change() {
+        let symbols = symbols();
+        let before = tbox(&["A < B", "B < C", "A < C"], &symbols);
+        let after = tbox(&["B < C", "A < B"], &symbols);
+
+        let diff = TBoxDiff::new(("before", &before), ("after", &after), &[], false);
+
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed, vec![(tbi("A < C", &symbols), true)]);
+        assert!(diff.new_entailments.is_empty());
+        assert!(diff.lost_entailments.is_empty());
+    }
+}
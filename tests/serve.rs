@@ -0,0 +1,184 @@
+/*
+© - 2021 – UMONS
+Horacio Alejandro Tellez Perez
+
+LICENSE GPLV3+:
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see https://www.gnu.org/licenses/.
+*/
+
+/*
+   The 'serve' task against a local client: the binary is started on a free port, the
+   university example is loaded and every endpoint is asked once.
+*/
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn start_server(port: u16) -> Child {
+    let child = Command::new(env!("CARGO_BIN_EXE_rustoner_dlliter"))
+        .args(["--task", "serve", "--port", &port.to_string()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // wait until the server listens
+    for _ in 0..100 {
+        if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+            return child;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    panic!("the server didn't start on port {}", port);
+}
+
+// (status, body) of the answer
+fn request(port: u16, method: &str, url: &str, body: &str) -> (u16, Value) {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        url,
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut answer = String::new();
+    stream.read_to_string(&mut answer).unwrap();
+
+    let (head, body) = answer.split_once("\r\n\r\n").unwrap();
+    let status: u16 = head.split(' ').nth(1).unwrap().parse().unwrap();
+
+    (status, serde_json::from_str(body).unwrap())
+}
+
+fn post(port: u16, url: &str, body: Value) -> (u16, Value) {
+    request(port, "POST", url, &body.to_string())
+}
+
+fn example(name: &str) -> String {
+    format!("{}/examples/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn serve_answers_the_university_example() {
+    let port = free_port();
+    let mut child = start_server(port);
+
+    let (status, loaded) = post(
+        port,
+        "/load",
+        json!({
+            "name": "university",
+            "tbox": example("university_tbox"),
+            "aboxes": [example("university_abox")],
+        }),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(loaded["axioms"], 8);
+    assert_eq!(loaded["current_abox"], "university_abox");
+
+    let (status, ontologies) = request(port, "GET", "/ontologies", "");
+    assert_eq!(status, 200);
+    assert_eq!(ontologies[0]["name"], "university");
+
+    // John attends and teaches
+    let (status, verified) = post(port, "/verify", json!({ "name": "university" }));
+    assert_eq!(status, 200);
+    assert_eq!(verified["consistent"], false);
+    assert_eq!(verified["conflicts"].as_array().unwrap().len(), 3);
+    assert!(verified["tbox_contradictions"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let (status, cleaned) = post(port, "/clean", json!({ "name": "university" }));
+    assert_eq!(status, 200);
+    assert_eq!(cleaned["clean"].as_array().unwrap().len(), 8);
+    assert!(cleaned["self_conflicting"].as_array().unwrap().is_empty());
+
+    let (status, ranked) = post(
+        port,
+        "/rank",
+        json!({ "name": "university", "aggr": "sum" }),
+    );
+    assert_eq!(status, 200);
+    let assertions = ranked["assertions"].as_array().unwrap();
+    assert_eq!(assertions.len(), 8);
+    assert_eq!(assertions[7]["assertion"], "John, KR: attends");
+
+    let (status, unravelled) = post(
+        port,
+        "/unravel",
+        json!({ "name": "university", "item": "Professor < NOT Course" }),
+    );
+    assert_eq!(status, 200);
+    assert!(unravelled["unravel"]
+        .as_str()
+        .unwrap()
+        .contains("Person < NOT Course"));
+
+    let (status, answered) = post(
+        port,
+        "/query",
+        json!({ "name": "university", "query": "Person" }),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(answered["answers"], json!([["Ava"], ["Bob"], ["John"]]));
+
+    let (status, answered) = post(
+        port,
+        "/query",
+        json!({ "name": "university", "query": "teaches" }),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(answered["answers"], json!([["John", "DB2"]]));
+
+    // the errors
+    let (status, _) = post(port, "/verify", json!({ "name": "nobody" }));
+    assert_eq!(status, 404);
+
+    let (status, _) = post(port, "/nowhere", json!({ "name": "university" }));
+    assert_eq!(status, 404);
+
+    let (status, _) = request(port, "POST", "/verify", "not json");
+    assert_eq!(status, 400);
+
+    let (status, _) = post(port, "/unload", json!({ "name": "university" }));
+    assert_eq!(status, 200);
+
+    let (_, ontologies) = request(port, "GET", "/ontologies", "");
+    assert!(ontologies.as_array().unwrap().is_empty());
+
+    let (status, _) = post(port, "/shutdown", json!({}));
+    assert_eq!(status, 200);
+    assert!(child.wait().unwrap().success());
+}